@@ -1,4 +1,3 @@
-use crayon::*;
 use std::env::args;
 
 use clif::arg::*;
@@ -7,6 +6,8 @@ use clif::Cli;
 use clif::Error;
 use clif::ErrorKind;
 use clif::Help;
+use clif::output::Output;
+use clif::style::Theme;
 
 fn main() {
     std::process::exit(go() as i32)
@@ -26,10 +27,13 @@ fn go() -> u8 {
         // report cli error
         Err(err) => {
             match err.kind() {
-                ErrorKind::Help => println!("{}", &err),
-                _ => eprintln!("{}: {}", "error".red().bold(), &err),
+                // exit quietly if the help text is piped into a closed reader
+                ErrorKind::Help => {
+                    let _ = Output::stdout().println(&err);
+                }
+                _ => eprintln!("{}", err.styled(&Theme::default())),
             }
-            err.code()
+            err.exit_code()
         }
     }
 }
@@ -62,8 +66,7 @@ impl FromCli for Addrs {
         cli.check_help(
             Help::new()
                 .quick_text(HELP)
-                .flag(Flag::new("help").switch('h'))
-                .ref_usage(USAGE_LINE..USAGE_LINE + 2),
+                .flag(Flag::new("help").switch('h')),
         )?;
         let radd = Addrs {
             verbose: cli.check_flag(Flag::new("verbose"))?,
@@ -93,9 +96,6 @@ impl Command<()> for Addrs {
     }
 }
 
-// 0-indexed from `HELP` string
-const USAGE_LINE: usize = 2;
-
 const HELP: &str = "\
 Adds two numbers together.
 
@@ -120,7 +120,7 @@ mod test {
             lhs: 10,
             rhs: 9,
             verbose: false,
-            count: None,
+            count: vec![],
         };
 
         assert_eq!(app.run(), 19);
@@ -0,0 +1,322 @@
+//! A package manager with nested subcommands whose option defaults are layered
+//! from the environment and a configuration file.
+//!
+//! ```text
+//! pkg new my-lib --lang verilog
+//! PKG_JOBS=8 pkg inst serde rand
+//! pkg list --format json
+//! ```
+//!
+//! Completions come from the same description of the arguments, either from
+//! a script written with `Spec::to_completion` or answered at runtime.
+
+use std::env::args;
+
+use clif::arg::*;
+use clif::cmd::{Availability, Command, Context, FromCli, Runner};
+use clif::config::Config;
+use clif::spec::Spec;
+use clif::{Cli, Error, Help, ThresholdPolicy};
+
+fn main() {
+    // options fall back to `PKG_*` variables and then to the `[cli]` table of `pkg.toml`
+    let config = Config::load("pkg.toml").unwrap_or_default().env("PKG_");
+    let cli = Cli::new()
+        .threshold_policy(ThresholdPolicy::Auto)
+        .allow_command_prefixes()
+        .config(config)
+        .dynamic_completion(spec())
+        .tokenize(args());
+    cli.go::<Context, Pkg>(&Context::new())
+}
+
+/// Describes the arguments read by each command for generating completions.
+fn spec() -> Spec {
+    Spec::new("pkg")
+        .summary("Manages packages.")
+        .flag(Flag::new("verbose").switch('v'), "display more information")
+        .command(
+            Spec::new("new")
+                .summary("create a new package")
+                .option(
+                    Optional::new("lang")
+                        .value("hdl")
+                        .possible_values(&["vhdl", "verilog"]),
+                    "primary language",
+                )
+                .positional(Positional::new("name"), "name of the package"),
+        )
+        .command(
+            Spec::new("install")
+                .summary("download and install packages")
+                .option(
+                    Optional::new("jobs").switch('j').value("n"),
+                    "number of parallel downloads",
+                )
+                .positional(Positional::new("name"), "packages to install")
+                .arity(1, None),
+        )
+        .command(
+            Spec::new("list")
+                .summary("list the installed packages")
+                .availability(Availability::deprecated("0.2.0").note("use 'pkg search' instead"))
+                .option(
+                    Optional::new("format")
+                        .value("fmt")
+                        .possible_values(&["text", "json"]),
+                    "output format",
+                ),
+        )
+}
+
+#[derive(Debug, PartialEq)]
+struct Pkg {
+    verbose: bool,
+    command: Option<PkgCommand>,
+}
+
+impl Runner<Context> for Pkg {}
+
+impl FromCli for Pkg {
+    fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+        cli.check_help(Help::new().quick_text(HELP).ref_usage(2..4))?;
+        // subcommands also read the flag when it is given before their name
+        cli.global_flag(Flag::new("verbose").switch('v'));
+        let app = Pkg {
+            verbose: cli.check_flag(Flag::new("verbose").switch('v'))?,
+            command: cli.check_command(Positional::new("command"))?,
+        };
+        cli.is_empty()?;
+        Ok(app)
+    }
+}
+
+impl Command<Context> for Pkg {
+    type Status = ();
+
+    fn exec(&self, context: &Context) -> Self::Status {
+        match &self.command {
+            Some(command) => command.exec(context),
+            None => context.stdout().print(HELP).unwrap(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum PkgCommand {
+    New(New),
+    Install(Install),
+    List(List),
+}
+
+impl FromCli for PkgCommand {
+    fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+        match cli.match_command(&["new", "install", "list"])?.as_ref() {
+            "new" => Ok(Self::New(New::from_cli(cli)?)),
+            "install" => Ok(Self::Install(Install::from_cli(cli)?)),
+            "list" => Ok(Self::List(List::from_cli(cli)?)),
+            _ => panic!("an unimplemented command was passed through!"),
+        }
+    }
+}
+
+impl Command<Context> for PkgCommand {
+    type Status = ();
+
+    fn exec(&self, context: &Context) -> Self::Status {
+        match self {
+            Self::New(c) => c.exec(context),
+            Self::Install(c) => c.exec(context),
+            Self::List(c) => c.exec(context),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct New {
+    name: String,
+    lang: String,
+}
+
+impl FromCli for New {
+    fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+        cli.check_help(Help::new().quick_text(HELP_NEW).ref_usage(2..4))?;
+        Ok(New {
+            lang: cli
+                .check_option(
+                    Optional::new("lang")
+                        .value("hdl")
+                        .possible_values(&["vhdl", "verilog"]),
+                )?
+                .unwrap_or(String::from("vhdl")),
+            name: cli.require_positional(Positional::new("name"))?,
+        })
+    }
+}
+
+impl Command<Context> for New {
+    type Status = ();
+
+    fn exec(&self, context: &Context) -> Self::Status {
+        let mut out = context.stdout();
+        out.println(format!("created {} ({})", self.name, self.lang)).unwrap();
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Install {
+    jobs: usize,
+    names: Vec<String>,
+}
+
+impl FromCli for Install {
+    fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+        cli.check_help(Help::new().quick_text(HELP_INSTALL).ref_usage(2..4))?;
+        Ok(Install {
+            jobs: cli.check_option(Optional::new("jobs").switch('j').value("n"))?.unwrap_or(1),
+            names: cli.require_positional_all(Positional::new("name"))?,
+        })
+    }
+}
+
+impl Command<Context> for Install {
+    type Status = ();
+
+    fn exec(&self, context: &Context) -> Self::Status {
+        let mut out = context.stdout();
+        for name in &self.names {
+            out.println(format!("installed {} ({} jobs)", name, self.jobs)).unwrap();
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct List {
+    format: String,
+}
+
+impl FromCli for List {
+    fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+        cli.check_availability(Availability::deprecated("0.2.0").note("use 'pkg search' instead"))?;
+        cli.check_help(Help::new().quick_text(HELP_LIST).ref_usage(2..4))?;
+        Ok(List {
+            format: cli
+                .check_option(Optional::new("format").possible_values(&["text", "json"]))?
+                .unwrap_or(String::from("text")),
+        })
+    }
+}
+
+impl Command<Context> for List {
+    type Status = ();
+
+    fn exec(&self, context: &Context) -> Self::Status {
+        let mut out = context.stdout();
+        match self.format.as_ref() {
+            "json" => out.println("[]").unwrap(),
+            _ => out.println("no packages installed").unwrap(),
+        }
+    }
+}
+
+const HELP: &str = "\
+Manages packages.
+
+Usage:
+    pkg [options] <command>
+
+Commands:
+    new         create a new package
+    install     download and install packages
+    list        list the installed packages
+
+Options:
+    --verbose, -v   display more information
+";
+
+const HELP_NEW: &str = "\
+Creates a new package.
+
+Usage:
+    pkg new [options] <name>
+
+Options:
+    --lang <hdl>    primary language (vhdl, verilog)
+";
+
+const HELP_INSTALL: &str = "\
+Downloads and installs packages.
+
+Usage:
+    pkg install [options] <name>...
+
+Options:
+    --jobs, -j <n>  number of parallel downloads
+";
+
+const HELP_LIST: &str = "\
+Lists the installed packages.
+
+Usage:
+    pkg list [options]
+
+Options:
+    --format <fmt>  output format (text, json)
+";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clif::complete::Shell;
+
+    fn args<'a>(args: Vec<&'a str>) -> Box<dyn Iterator<Item = String> + 'a> {
+        Box::new(args.into_iter().map(|f| f.to_string()))
+    }
+
+    #[test]
+    fn layered_defaults() {
+        let config = Config::parse("[cli]\njobs = 4\n")
+            .unwrap()
+            .env_vars("PKG_", vec![(String::from("PKG_JOBS"), String::from("8"))]);
+        let mut cli = Cli::new()
+            .allow_command_prefixes()
+            .config(config)
+            .tokenize(args(vec!["pkg", "inst", "serde", "-v"]));
+        assert_eq!(
+            Pkg::from_cli(&mut cli).unwrap(),
+            Pkg {
+                verbose: true,
+                command: Some(PkgCommand::Install(Install {
+                    jobs: 8,
+                    names: vec![String::from("serde")],
+                })),
+            }
+        );
+    }
+
+    #[test]
+    fn complete_commands() {
+        let script = spec().to_completion(Shell::Bash);
+        assert!(script.contains("new install list"));
+        assert!(script.contains("vhdl verilog"));
+        assert!(spec().to_completion(Shell::Zsh).contains("--jobs"));
+    }
+
+    #[test]
+    fn run_commands() {
+        let context = Context::captured();
+        clif::test::run::<_, Pkg, _>(["pkg", "install", "serde", "rand", "-j", "2"], &context)
+            .unwrap();
+        assert_eq!(
+            context.take_stdout(),
+            "installed serde (2 jobs)\ninstalled rand (2 jobs)\n"
+        );
+
+        clif::test::run::<_, Pkg, _>(["pkg", "new", "my-lib", "--lang", "verilog"], &context)
+            .unwrap();
+        assert_eq!(context.take_stdout(), "created my-lib (verilog)\n");
+
+        let err = clif::test::run::<_, Pkg, _>(["pkg", "new"], &context).unwrap_err();
+        assert_eq!(err.kind(), clif::ErrorKind::MissingPositional);
+    }
+}
@@ -1,36 +1,15 @@
-use crayon::*;
 use std::env::args;
 
 use clif::cmd::{Command, FromCli, Runner};
 use clif::Cli;
 use clif::Error;
 use clif::Help;
-use clif::{arg::*, ErrorKind};
+use clif::arg::*;
 
 fn main() {
-    std::process::exit(go() as i32)
-}
-
-/// Glues the interface layer and backend logic for a smooth hand-off of data.
-fn go() -> u8 {
-    // parse the command-line arguments
-    let mut cli = Cli::new().threshold(2).tokenize(args());
-
-    match Sum::from_cli(&mut cli) {
-        // construct the application
-        Ok(app) => {
-            std::mem::drop(cli);
-            app.exec(&())
-        }
-        // report cli error
-        Err(err) => {
-            match err.kind() {
-                ErrorKind::Help => println!("{}", &err),
-                _ => eprintln!("{}: {}", "error".red().bold(), &err),
-            }
-            err.code()
-        }
-    }
+    // parse the command-line arguments, report any error, and run the application
+    let status = Cli::new().threshold(2).tokenize(args()).go::<(), Sum>(&());
+    std::process::exit(status as i32)
 }
 
 type Digit = f32;
@@ -112,10 +91,10 @@ mod test {
     #[test]
     fn backend_logic() {
         let app = Sum {
-            nums: vec![1, 2, 3],
+            nums: vec![1.0, 2.0, 3.0],
             verbose: false,
         };
 
-        assert_eq!(app.run(), 6);
+        assert_eq!(app.run(), 6.0);
     }
 }
@@ -0,0 +1,176 @@
+//! A service controller whose commands reach a remote host and are retried
+//! when the host is temporarily unreachable.
+//!
+//! ```text
+//! svc start web --retries 3 --retry-delay 2
+//! svc status db --timings
+//! ```
+
+use std::env::args;
+
+use clif::arg::*;
+use clif::cmd::{Command, Context, FromCli, Retry, Runner, Transient};
+use clif::spec::Spec;
+use clif::{Cli, Error, Help};
+
+/// The outcome of a request sent to the service host.
+#[derive(Debug, PartialEq)]
+enum Reply {
+    Done(String),
+    Unreachable,
+    Rejected(String),
+}
+
+fn main() {
+    let cli = Cli::new()
+        .threshold(2)
+        .dynamic_completion(spec())
+        .tokenize(args());
+    let code = match cli.go::<Context, Retry<Svc>>(&Context::new()) {
+        Reply::Done(msg) => {
+            println!("{}", msg);
+            0
+        }
+        Reply::Unreachable => {
+            eprintln!("error: the service host is unreachable");
+            69
+        }
+        Reply::Rejected(reason) => {
+            eprintln!("error: {}", reason);
+            1
+        }
+    };
+    std::process::exit(code)
+}
+
+/// Describes the arguments read by the controller for generating completions.
+fn spec() -> Spec {
+    Spec::new("svc")
+        .summary("Controls services on a host.")
+        .option(
+            Optional::new("retries").value("n"),
+            "attempts to make again when the host is unreachable",
+        )
+        .option(
+            Optional::new("retry-delay").value("seconds"),
+            "time to wait between attempts",
+        )
+        .option(Optional::new("host").value("addr"), "host running the service")
+        .positional(
+            Positional::new("action").possible_values(&["start", "stop", "status"]),
+            "start, stop, or status",
+        )
+        .positional(Positional::new("service"), "name of the service")
+}
+
+#[derive(Debug, PartialEq)]
+struct Svc {
+    action: String,
+    service: String,
+    host: String,
+}
+
+impl Runner<Context> for Svc {}
+
+impl FromCli for Svc {
+    fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+        cli.check_help(Help::new().quick_text(HELP).ref_usage(2..4))?;
+        let app = Svc {
+            host: cli
+                .check_option(Optional::new("host").value("addr"))?
+                .unwrap_or(String::from("localhost")),
+            action: cli.require_positional(
+                Positional::new("action").possible_values(&["start", "stop", "status"]),
+            )?,
+            service: cli.require_positional(Positional::new("service"))?,
+        };
+        cli.is_empty()?;
+        Ok(app)
+    }
+}
+
+impl Command<Context> for Svc {
+    type Status = Reply;
+
+    fn exec(&self, _: &Context) -> Self::Status {
+        // a real controller would send the request to the host here
+        match self.host.as_ref() {
+            "localhost" => Reply::Done(format!("{} {}", self.action, self.service)),
+            "offline" => Reply::Unreachable,
+            host => Reply::Rejected(format!("unknown host '{}'", host)),
+        }
+    }
+}
+
+impl Transient<Context> for Svc {
+    fn is_transient(&self, status: &Self::Status) -> bool {
+        status == &Reply::Unreachable
+    }
+}
+
+const HELP: &str = "\
+Controls services on a host.
+
+Usage:
+    svc [options] <action> <service>
+
+Args:
+    <action>        start, stop, or status
+    <service>       name of the service
+
+Options:
+    --host <addr>           host running the service
+    --retries <n>           attempts to make again when the host is unreachable
+    --retry-delay <seconds> time to wait between attempts
+    --timings               summarize how long each phase took
+";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clif::complete::Shell;
+
+    fn args<'a>(args: Vec<&'a str>) -> Box<dyn Iterator<Item = String> + 'a> {
+        Box::new(args.into_iter().map(|f| f.to_string()))
+    }
+
+    #[test]
+    fn retry_unreachable_hosts() {
+        let mut cli = Cli::new().tokenize(args(vec![
+            "svc", "--retries", "2", "--retry-delay", "0", "--host", "offline", "stop", "web",
+        ]));
+        let app = Retry::<Svc>::from_cli(&mut cli).unwrap();
+        assert_eq!(app.get_retries(), 2);
+        assert_eq!(app.exec(&Context::captured()), Reply::Unreachable);
+
+        let mut cli = Cli::new().tokenize(args(vec!["svc", "status", "db"]));
+        let app = Retry::<Svc>::from_cli(&mut cli).unwrap();
+        assert_eq!(app.exec(&Context::captured()), Reply::Done(String::from("status db")));
+    }
+
+    #[test]
+    fn complete_actions() {
+        let script = spec().to_completion(Shell::Bash);
+        assert!(script.contains("start stop status"));
+        assert!(script.contains("--retry-delay"));
+    }
+
+    #[test]
+    fn run_with_retries() {
+        let context = Context::captured();
+        let reply = clif::test::run::<_, Retry<Svc>, _>(
+            ["svc", "--retries=1", "--retry-delay=0", "--host=offline", "start", "web"],
+            &context,
+        );
+        assert_eq!(reply.unwrap(), Reply::Unreachable);
+        assert_eq!(
+            context.take_stderr(),
+            "warning: attempt 1 of 2 failed; retrying in 0s\n"
+        );
+
+        let reply = clif::test::run::<_, Retry<Svc>, _>(["svc", "stop", "web"], &context);
+        assert_eq!(reply.unwrap(), Reply::Done(String::from("stop web")));
+        let err = clif::test::run::<_, Retry<Svc>, _>(["svc", "restart", "web"], &context);
+        assert_eq!(err.unwrap_err().kind(), clif::ErrorKind::InvalidChoice);
+    }
+}
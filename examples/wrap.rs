@@ -0,0 +1,169 @@
+//! A wrapper tool that runs another program with extra environment variables,
+//! forwarding every argument after `--` untouched.
+//!
+//! ```text
+//! wrap --env RUST_LOG=debug -- cargo build --release
+//! wrap --dry-run -- make -j4
+//! ```
+
+use std::env::args;
+use std::process::Command as Process;
+
+use clif::arg::*;
+use clif::cmd::{Command, Context, FromCli, Runner};
+use clif::spec::Spec;
+use clif::{Cli, Error, Help};
+
+fn main() {
+    let cli = Cli::new()
+        .threshold(2)
+        .dynamic_completion(spec())
+        .tokenize(args());
+    std::process::exit(cli.go::<Context, Wrap>(&Context::new()) as i32)
+}
+
+/// Describes the arguments read by the wrapper for generating completions.
+fn spec() -> Spec {
+    Spec::new("wrap")
+        .summary("Runs a command with extra environment variables.")
+        .option(
+            Optional::new("env").value("key=value"),
+            "set a variable for the command",
+        )
+        .flag(Flag::new("dry-run"), "print the command instead of running it")
+}
+
+#[derive(Debug, PartialEq)]
+struct Wrap {
+    dry_run: bool,
+    env: Vec<(String, String)>,
+    command: Vec<String>,
+}
+
+impl Runner<Context> for Wrap {}
+
+impl FromCli for Wrap {
+    fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+        cli.check_help(Help::new().quick_text(HELP).ref_usage(2..4))?;
+        let env: Vec<String> = cli
+            .check_option_all(Optional::new("env").value("key=value").validator(|v: &str| {
+                match v.contains('=') {
+                    true => Ok(()),
+                    false => Err(String::from("expected a '=' between the key and value")),
+                }
+            }))?
+            .unwrap_or_default();
+        let app = Wrap {
+            dry_run: cli.check_flag(Flag::new("dry-run"))?,
+            env: env
+                .into_iter()
+                .filter_map(|kv| kv.split_once('=').map(|(k, v)| (k.to_string(), v.to_string())))
+                .collect(),
+            command: cli.check_remainder()?,
+        };
+        cli.is_empty()?;
+        Ok(app)
+    }
+}
+
+impl Command<Context> for Wrap {
+    type Status = u8;
+
+    fn exec(&self, context: &Context) -> Self::Status {
+        let (program, rest) = match self.command.split_first() {
+            Some(parts) => parts,
+            None => {
+                let _ = context.stderr().println("error: missing the command to run after '--'");
+                return 2;
+            }
+        };
+        if self.dry_run == true {
+            let _ = context.stdout().println(self.command.join(" "));
+            return 0;
+        }
+        let status = Process::new(program)
+            .args(rest)
+            .envs(self.env.iter().map(|(k, v)| (k, v)))
+            .status();
+        match status {
+            Ok(s) => s.code().unwrap_or(1) as u8,
+            Err(e) => {
+                let _ = context
+                    .stderr()
+                    .println(format!("error: failed to run '{}': {}", program, e));
+                127
+            }
+        }
+    }
+}
+
+const HELP: &str = "\
+Runs a command with extra environment variables.
+
+Usage:
+    wrap [options] -- <command>...
+
+Options:
+    --env <key=value>   set a variable for the command
+    --dry-run           print the command instead of running it
+";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clif::complete::Shell;
+
+    fn args<'a>(args: Vec<&'a str>) -> Box<dyn Iterator<Item = String> + 'a> {
+        Box::new(args.into_iter().map(|f| f.to_string()))
+    }
+
+    #[test]
+    fn forward_arguments() {
+        let mut cli = Cli::new().tokenize(args(vec![
+            "wrap", "--env", "A=1", "--dry-run", "--", "make", "--env", "-j4",
+        ]));
+        assert_eq!(
+            Wrap::from_cli(&mut cli).unwrap(),
+            Wrap {
+                dry_run: true,
+                env: vec![(String::from("A"), String::from("1"))],
+                command: vec![
+                    String::from("make"),
+                    String::from("--env"),
+                    String::from("-j4")
+                ],
+            }
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec!["wrap", "--env", "A", "--", "make"]));
+        assert_eq!(
+            Wrap::from_cli(&mut cli).unwrap_err().kind(),
+            clif::ErrorKind::InvalidValue
+        );
+    }
+
+    #[test]
+    fn complete_options() {
+        let script = spec().to_completion(Shell::Bash);
+        assert!(script.contains("--env"));
+        assert!(script.contains("--dry-run"));
+    }
+
+    #[test]
+    fn run_dry() {
+        let context = Context::captured();
+        let status = clif::test::run::<_, Wrap, _>(
+            ["wrap", "--dry-run", "--", "make", "-j4"],
+            &context,
+        );
+        assert_eq!(status.unwrap(), 0);
+        assert_eq!(context.take_stdout(), "make -j4\n");
+
+        let status = clif::test::run::<_, Wrap, _>(["wrap", "--dry-run"], &context);
+        assert_eq!(status.unwrap(), 2);
+        assert_eq!(
+            context.take_stderr(),
+            "error: missing the command to run after '--'\n"
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::fmt::Debug;
 use std::fmt::Display;
+use std::sync::Arc;
 
 mod symbol {
     pub const FLAG: &str = "--";
@@ -7,7 +8,7 @@ mod symbol {
     pub const POS_BRACKER_R: &str = ">";
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 pub enum Arg {
     Flag(Flag),
     Positional(Positional),
@@ -22,6 +23,129 @@ impl Arg {
             Arg::Positional(_) => None,
         }
     }
+
+    /// Returns the name identifying the argument, which is the flag's name for
+    /// flags and options.
+    pub fn get_name(&self) -> &str {
+        match self {
+            Arg::Flag(f) => f.get_name(),
+            Arg::Optional(o) => o.get_flag().get_name(),
+            Arg::Positional(p) => p.get_name(),
+        }
+    }
+
+    /// References the positional holding the argument's value, if it takes one.
+    pub fn as_positional(&self) -> Option<&Positional> {
+        match self {
+            Arg::Flag(_) => None,
+            Arg::Optional(o) => Some(o.get_positional()),
+            Arg::Positional(p) => Some(p),
+        }
+    }
+
+    /// Displays the flag of a flag or option behind `prefix` instead of `--`.
+    pub(crate) fn prefixed(self, prefix: &str) -> Self {
+        match self {
+            Arg::Flag(f) => Arg::Flag(f.prefixed(prefix)),
+            Arg::Optional(mut o) => {
+                o.option = o.option.prefixed(prefix);
+                Arg::Optional(o)
+            }
+            Arg::Positional(p) => Arg::Positional(p),
+        }
+    }
+}
+
+/// A user-defined rule checked against a value before it is parsed.
+pub type Validator = dyn Fn(&str) -> Result<(), String> + Send + Sync;
+
+/// Asks the user for the value of a missing argument, given the text to
+/// prompt with, returning `None` if no answer could be read.
+pub type Prompter = dyn Fn(&str) -> Option<String> + Send + Sync;
+
+/// Lists the candidates for completing the partial value given.
+pub type Completer = dyn Fn(&str) -> Vec<String> + Send + Sync;
+
+/// Shares a user-defined function among clones of an argument.
+pub(crate) struct Callback<F: ?Sized>(Arc<F>);
+
+impl<F: ?Sized> Clone for Callback<F> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<F: ?Sized> Callback<F> {
+    pub(crate) fn new(f: Arc<F>) -> Self {
+        Self(f)
+    }
+
+    pub(crate) fn get(&self) -> &F {
+        self.0.as_ref()
+    }
+}
+
+impl<F: ?Sized> Debug for Callback<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<fn>")
+    }
+}
+
+impl<F: ?Sized> PartialEq for Callback<F> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// The kind of value an argument accepts, which tells shell completions what
+/// to offer for it.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum ValueHint {
+    /// Nothing in particular is offered.
+    #[default]
+    Unknown,
+    /// A path to any file.
+    FilePath,
+    /// A path to a directory.
+    DirPath,
+    /// A path to an executable file.
+    ExecutablePath,
+    /// The name of a command found on the `PATH`.
+    CommandName,
+    /// The name of a network host.
+    Hostname,
+    /// The name of a user on the system.
+    Username,
+    /// A URL.
+    Url,
+}
+
+/// Additional properties attached to an argument.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct Meta {
+    pub(crate) validator: Option<Callback<Validator>>,
+    pub(crate) values: Vec<String>,
+    pub(crate) list_flag: Option<String>,
+    pub(crate) hyphen_values: bool,
+    pub(crate) aliases: Vec<(String, String)>,
+    pub(crate) localized: bool,
+    pub(crate) delimiter: Option<char>,
+    pub(crate) secret: bool,
+    pub(crate) trailing: bool,
+    pub(crate) terminator: bool,
+    pub(crate) prompt_default: Option<String>,
+    pub(crate) hint: ValueHint,
+    pub(crate) completer: Option<Callback<Completer>>,
+}
+
+impl Meta {
+    /// Translates `value` into its canonical name if it is a declared alias.
+    pub(crate) fn canonicalize(&self, value: String) -> String {
+        match self.aliases.iter().find(|(alias, _)| alias == &value) {
+            Some((_, canonical)) => canonical.to_string(),
+            None => value,
+        }
+    }
 }
 
 impl Display for Arg {
@@ -43,11 +167,157 @@ impl Debug for Arg {
 #[derive(Debug, PartialEq, Clone)]
 pub struct Positional {
     name: String,
+    meta: Meta,
 }
 
 impl Positional {
     pub fn new<T: AsRef<str>>(s: T) -> Self {
-        Self { name: s.as_ref().to_string() }
+        Self {
+            name: s.as_ref().to_string(),
+            meta: Meta::default(),
+        }
+    }
+
+    /// Checks every value with `f` before it is parsed.
+    ///
+    /// Values rejected by `f` produce an error citing this argument and the
+    /// returned message.
+    pub fn validator<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.meta.validator = Some(Callback(Arc::new(f)));
+        self
+    }
+
+    /// Restricts the accepted values to the list of `values`.
+    pub fn possible_values<T: AsRef<str>>(mut self, values: &[T]) -> Self {
+        self.meta.values = values.iter().map(|v| v.as_ref().to_string()).collect();
+        self
+    }
+
+    /// Names the flag that lists every possible value, such as `list-indices`,
+    /// when there are too many to show in an error.
+    ///
+    /// The flag is named `list-<name>s` by default, which suits most names but
+    /// not irregular plurals.
+    pub fn list_flag<T: AsRef<str>>(mut self, name: T) -> Self {
+        self.meta.list_flag = Some(name.as_ref().to_string());
+        self
+    }
+
+    /// Accepts `alias` in place of the value `canonical`.
+    ///
+    /// Aliases are translated before any validation or parsing, so only canonical
+    /// names need to be listed as possible values.
+    pub fn value_alias<T: AsRef<str>, U: AsRef<str>>(mut self, alias: T, canonical: U) -> Self {
+        self.meta
+            .aliases
+            .push((alias.as_ref().to_string(), canonical.as_ref().to_string()));
+        self
+    }
+
+    /// Reads numbers written in the [NumberFormat](crate::value::NumberFormat)
+    /// set on the [Cli](crate::Cli), such as `1.234,56`, before they are parsed.
+    pub fn localized(mut self) -> Self {
+        self.meta.localized = true;
+        self
+    }
+
+    /// Marks the values as sensitive, such as passwords or tokens.
+    ///
+    /// Secret values are replaced by the [redaction](crate::Cli::redaction)
+    /// placeholder wherever the command-line is reported, including
+    /// [explain](crate::Cli::explain), [audit](crate::Cli::audit), errors, and
+    /// serialized [Matches](crate::matches::Matches).
+    pub fn secret(mut self) -> Self {
+        self.meta.secret = true;
+        self
+    }
+
+    pub fn is_secret(&self) -> bool {
+        self.meta.secret
+    }
+
+    /// Captures every argument from this positional's first value onward
+    /// verbatim, flags included, for wrapper commands such as
+    /// `exec <program> <args>...` that forward them to another process.
+    ///
+    /// Read the values with [check_positional_all](crate::Cli::check_positional_all)
+    /// or [require_positional_all](crate::Cli::require_positional_all). Flags of
+    /// the wrapper queried after this positional are only recognized before the
+    /// first captured value. Call [stop_at_operand](crate::Cli::stop_at_operand)
+    /// first to keep the same cutoff for flags queried before it.
+    pub fn trailing(mut self) -> Self {
+        self.meta.trailing = true;
+        self
+    }
+
+    pub fn is_trailing(&self) -> bool {
+        self.meta.trailing
+    }
+
+    /// Accepts a literal `--` as the value when it appears where this
+    /// positional is expected, instead of reading it as the terminator.
+    ///
+    /// The arguments after the `--` are still left for
+    /// [check_remainder](crate::Cli::check_remainder). A lone `-`, which
+    /// conventionally names standard input, is read as a value for any
+    /// positional without opting in.
+    pub fn allow_terminator(mut self) -> Self {
+        self.meta.terminator = true;
+        self
+    }
+
+    pub fn is_terminator_allowed(&self) -> bool {
+        self.meta.terminator
+    }
+
+    /// Sets the value offered when the user is [prompted](crate::Cli::interactive)
+    /// for the missing argument, used if the answer is left blank.
+    pub fn prompt_default<T: AsRef<str>>(mut self, value: T) -> Self {
+        self.meta.prompt_default = Some(value.as_ref().to_string());
+        self
+    }
+
+    pub fn get_prompt_default(&self) -> Option<&str> {
+        self.meta.prompt_default.as_deref()
+    }
+
+    /// Describes the kind of value accepted, such as a file path, so generated
+    /// completions can offer matching candidates.
+    pub fn value_hint(mut self, hint: ValueHint) -> Self {
+        self.meta.hint = hint;
+        self
+    }
+
+    pub fn get_value_hint(&self) -> ValueHint {
+        self.meta.hint
+    }
+
+    /// Completes a partial value with the candidates returned by `f`, such as
+    /// names looked up at runtime, when the program answers a
+    /// [dynamic completion](crate::Cli::dynamic_completion) request.
+    ///
+    /// The candidates take precedence over the possible values and value hint.
+    pub fn candidates<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Vec<String> + Send + Sync + 'static,
+    {
+        self.meta.completer = Some(Callback(Arc::new(f)));
+        self
+    }
+
+    pub fn get_possible_values(&self) -> &Vec<String> {
+        &self.meta.values
+    }
+
+    pub fn get_name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    pub(crate) fn get_meta(&self) -> &Meta {
+        &self.meta
     }
 }
 
@@ -67,6 +337,12 @@ impl Display for Positional {
 pub struct Flag {
     name: String,
     switch: Option<char>,
+    requires: Vec<String>,
+    unstable: bool,
+    deprecated: Option<String>,
+    prefix: Option<String>,
+    category: Option<String>,
+    negatable: bool,
 }
 
 impl Flag {
@@ -74,9 +350,21 @@ impl Flag {
         Self {
             name: s.as_ref().to_string(),
             switch: None,
+            requires: Vec::new(),
+            unstable: false,
+            deprecated: None,
+            prefix: None,
+            category: None,
+            negatable: false,
         }
     }
 
+    /// Displays the flag behind `prefix` instead of `--`.
+    pub(crate) fn prefixed(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_string());
+        self
+    }
+
     pub fn switch(mut self, c: char) -> Self {
         self.switch = Some(c);
         self
@@ -86,18 +374,80 @@ impl Flag {
         self.name.as_ref()
     }
 
+    /// Requires the flag or option named `name` to be raised whenever this flag is.
+    pub fn requires<T: AsRef<str>>(mut self, name: T) -> Self {
+        self.requires.push(name.as_ref().to_string());
+        self
+    }
+
+    /// Marks the flag as experimental.
+    ///
+    /// Experimental flags are rejected unless unstable features are enabled and
+    /// are left out of generated help text.
+    pub fn unstable(mut self) -> Self {
+        self.unstable = true;
+        self
+    }
+
+    pub fn is_unstable(&self) -> bool {
+        self.unstable
+    }
+
+    /// Marks the flag as deprecated with a `note` explaining what to use instead.
+    ///
+    /// Parsing still succeeds when the flag is raised, but a warning is collected
+    /// in [Cli::warnings](crate::Cli::warnings).
+    pub fn deprecated<T: AsRef<str>>(mut self, note: T) -> Self {
+        self.deprecated = Some(note.as_ref().to_string());
+        self
+    }
+
+    pub fn get_deprecation(&self) -> Option<&str> {
+        self.deprecated.as_deref()
+    }
+
+    /// Lists the flag under the `heading` in generated help text instead of
+    /// with the other options.
+    pub fn category<T: AsRef<str>>(mut self, heading: T) -> Self {
+        self.category = Some(heading.as_ref().to_string());
+        self
+    }
+
+    pub fn get_category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
+    /// Accepts `--no-<name>` to explicitly turn the flag off, which is read as
+    /// `false` by [check_bool_flag](crate::Cli::check_bool_flag).
+    ///
+    /// A negatable flag is shown as `--[no-]<name>` in generated help. The other
+    /// flag checks read it as a boolean too, so it is raised at most once.
+    pub fn negatable(mut self) -> Self {
+        self.negatable = true;
+        self
+    }
+
+    pub fn is_negatable(&self) -> bool {
+        self.negatable
+    }
+
     pub fn get_switch(&self) -> Option<&char> {
         self.switch.as_ref()
     }
+
+    pub fn get_requirements(&self) -> &Vec<String> {
+        &self.requires
+    }
 }
 
 impl Display for Flag {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "{}{}", symbol::FLAG, self.get_name())
+        let prefix = self.prefix.as_deref().unwrap_or(symbol::FLAG);
+        write!(f, "{}{}", prefix, self.get_name())
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Optional {
     option: Flag,
     value: Positional,
@@ -121,6 +471,114 @@ impl Optional {
         self
     }
 
+    /// Checks every value with `f` before it is parsed.
+    ///
+    /// Values rejected by `f` produce an error citing this argument and the
+    /// returned message.
+    pub fn validator<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.value = self.value.validator(f);
+        self
+    }
+
+    /// Restricts the accepted values to the list of `values`.
+    pub fn possible_values<T: AsRef<str>>(mut self, values: &[T]) -> Self {
+        self.value = self.value.possible_values(values);
+        self
+    }
+
+    /// Names the flag that lists every possible value, as with
+    /// [Positional::list_flag].
+    pub fn list_flag<T: AsRef<str>>(mut self, name: T) -> Self {
+        self.value = self.value.list_flag(name);
+        self
+    }
+
+    /// Accepts a value that begins with a hyphen, such as `--offset -5` or
+    /// `--pattern --x`, instead of treating it as another flag.
+    pub fn allow_hyphen_values(mut self) -> Self {
+        self.value.meta.hyphen_values = true;
+        self
+    }
+
+    /// Reads numbers written in the [NumberFormat](crate::value::NumberFormat)
+    /// set on the [Cli](crate::Cli), such as `1.234,56`, before they are parsed.
+    pub fn localized(mut self) -> Self {
+        self.value = self.value.localized();
+        self
+    }
+
+    /// Marks the values as sensitive, as with [Positional::secret].
+    pub fn secret(mut self) -> Self {
+        self.value = self.value.secret();
+        self
+    }
+
+    /// Sets the value offered when prompted, as with [Positional::prompt_default].
+    pub fn prompt_default<T: AsRef<str>>(mut self, value: T) -> Self {
+        self.value = self.value.prompt_default(value);
+        self
+    }
+
+    /// Describes the kind of value accepted, as with [Positional::value_hint].
+    pub fn value_hint(mut self, hint: ValueHint) -> Self {
+        self.value = self.value.value_hint(hint);
+        self
+    }
+
+    /// Completes a partial value with the candidates returned by `f`, as with
+    /// [Positional::candidates].
+    pub fn candidates<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Vec<String> + Send + Sync + 'static,
+    {
+        self.value = self.value.candidates(f);
+        self
+    }
+
+    /// Splits each value on `c`, so `--features a,b,c` supplies three values when
+    /// queried with [check_option_all](crate::Cli::check_option_all).
+    pub fn delimiter(mut self, c: char) -> Self {
+        self.value.meta.delimiter = Some(c);
+        self
+    }
+
+    /// Requires the flag or option named `name` to be raised whenever this option is.
+    pub fn requires<T: AsRef<str>>(mut self, name: T) -> Self {
+        self.option = self.option.requires(name);
+        self
+    }
+
+    /// Accepts `alias` in place of the value `canonical`.
+    pub fn value_alias<T: AsRef<str>, U: AsRef<str>>(mut self, alias: T, canonical: U) -> Self {
+        self.value = self.value.value_alias(alias, canonical);
+        self
+    }
+
+    /// Marks the option as deprecated with a `note` explaining what to use instead.
+    pub fn deprecated<T: AsRef<str>>(mut self, note: T) -> Self {
+        self.option = self.option.deprecated(note);
+        self
+    }
+
+    /// Marks the option as experimental.
+    ///
+    /// Experimental options are rejected unless unstable features are enabled and
+    /// are left out of generated help text.
+    pub fn unstable(mut self) -> Self {
+        self.option = self.option.unstable();
+        self
+    }
+
+    /// Lists the option under the `heading` in generated help text instead of
+    /// with the other options.
+    pub fn category<T: AsRef<str>>(mut self, heading: T) -> Self {
+        self.option = self.option.category(heading);
+        self
+    }
+
     pub fn get_flag(&self) -> &Flag {
         &self.option
     }
@@ -143,10 +601,22 @@ mod test {
     #[test]
     fn positional_new() {
         let ip = Positional::new("ip");
-        assert_eq!(ip, Positional { name: String::from("ip") });
+        assert_eq!(
+            ip,
+            Positional {
+                name: String::from("ip"),
+                meta: Meta::default(),
+            }
+        );
 
         let version = Positional::new("version");
-        assert_eq!(version, Positional { name: String::from("version") });
+        assert_eq!(
+            version,
+            Positional {
+                name: String::from("version"),
+                meta: Meta::default(),
+            }
+        );
     }
 
     #[test]
@@ -166,6 +636,12 @@ mod test {
             Flag {
                 name: String::from("help"),
                 switch: Some('h'),
+                requires: Vec::new(),
+                unstable: false,
+                deprecated: None,
+                prefix: None,
+                category: None,
+                negatable: false,
             }
         );
         assert_eq!(help.get_switch(), Some(&'h'));
@@ -177,6 +653,12 @@ mod test {
             Flag {
                 name: String::from("version"),
                 switch: None,
+                requires: Vec::new(),
+                unstable: false,
+                deprecated: None,
+                prefix: None,
+                category: None,
+                negatable: false,
             }
         );
         assert_eq!(version.get_switch(), None);
@@ -190,6 +672,9 @@ mod test {
 
         let version = Flag::new("version");
         assert_eq!(version.to_string(), "--version");
+
+        let help = Flag::new("help").prefixed("/");
+        assert_eq!(help.to_string(), "/help");
     }
 
     #[test]
@@ -260,4 +745,18 @@ mod test {
         let command = Arg::Positional(Positional::new("command"));
         assert_eq!(command.as_flag(), None);
     }
+
+    #[test]
+    fn positional_validator() {
+        let port = Positional::new("port").validator(|s| match s.len() < 6 {
+            true => Ok(()),
+            false => Err(String::from("too long")),
+        });
+        let check = port.get_meta().validator.as_ref().unwrap();
+        assert_eq!(check.get()("8080"), Ok(()));
+        assert_eq!(check.get()("123456"), Err(String::from("too long")));
+        // clones share the same validator
+        assert_eq!(port.clone(), port);
+        assert_ne!(port, Positional::new("port"));
+    }
 }
@@ -1,18 +1,60 @@
 use crate::arg::*;
-use crate::command::FromCli;
-use crate::error::{Error, ErrorContext, ErrorKind};
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::command::{Availability, FromCli, Runner};
+use crate::complete;
+use crate::crash::Crash;
+#[cfg(feature = "async")]
+use crate::crash::Guarded;
+#[cfg(feature = "async")]
+use crate::command::AsyncRunner;
+use crate::error::{Deferred, Error, ErrorContext, ErrorKind, Span};
 use crate::help::Help;
+use crate::i18n::{Catalog, English};
+use crate::limits::Limits;
+use crate::metrics::{Metric, MetricsSink};
+use crate::matches::{Consumption, Matches, Partial, RemainingToken, Source, TokenKind};
 use crate::seqalin;
 use crate::seqalin::Cost;
+use crate::source::{self, ArgSource};
+use crate::output::{self, Output, PipePolicy, Table};
+use crate::report::Timings;
+use crate::spec::{HelpLayout, Spec};
+use crate::style::{self, ColorChoice};
+use crate::suggest::{Class, SuggestionConfig, ThresholdPolicy};
+use crate::value::{Count, NumberFormat, Occurrences, Reason, ValueParse};
+use crate::workspace::Workspace;
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::str::FromStr;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
 
 mod symbol {
     // series of characters to denote flags and switches
     pub const SWITCH: &str = "-";
-    // @note: tokenizing depends on flag having the first character be the switch character
     pub const FLAG: &str = "--";
+    // prefix of flags reserved for the framework's own tooling
+    pub const INTERNAL: &str = "--clif-";
+    // flag that opts into experimental features
+    pub const UNSTABLE: &str = "enable-unstable";
+    pub const TIMINGS: &str = "timings";
+    // pseudo-subcommand that shows the help of the command path following it
+    pub const HELP: &str = "help";
+}
+
+/// Number of possible values at which errors show only the nearest choices and
+/// a listing flag, `--list-<name>s` unless [named](Positional::list_flag)
+/// otherwise, becomes available to see them all.
+const CHOICE_LIST_LIMIT: usize = 20;
+
+/// Number of nearest choices shown when the possible values are not all listed.
+const NEAREST_CHOICES: usize = 3;
+
+/// Names of the internal flags handled by the framework.
+mod internal {
+    pub const EXPLAIN: &str = "explain";
+    pub const CHECK: &str = "check";
 }
 
 #[derive(Debug, Eq, Hash, PartialEq)]
@@ -64,6 +106,27 @@ impl Token {
     }
 }
 
+/// The role a token played once it was consumed from the token stream.
+#[derive(Debug, PartialEq, Clone)]
+enum Role {
+    Flag,
+    Value,
+    Positional,
+    Subcommand,
+    Terminator,
+    Remainder,
+    Unknown,
+}
+
+/// Records which argument consumed a token.
+#[derive(Debug, PartialEq, Clone)]
+struct Binding {
+    role: Role,
+    arg: Option<String>,
+    fragment: Option<String>,
+    secret: bool,
+}
+
 #[derive(Debug, PartialEq)]
 struct Slot {
     pointers: Vec<usize>,
@@ -82,6 +145,26 @@ impl Slot {
         self.pointers.push(i);
     }
 
+    fn remove(&mut self, i: usize) -> () {
+        self.pointers.retain(|p| *p != i);
+    }
+
+    /// Forgets the occurrences within `range`, which was removed from the token
+    /// stream, and moves the ones after it back to fill the gap.
+    fn cut(&mut self, range: std::ops::Range<usize>) -> () {
+        let n = range.len();
+        self.pointers.retain(|p| range.contains(p) == false);
+        for p in self.pointers.iter_mut().filter(|p| **p >= range.end) {
+            *p -= n;
+        }
+    }
+
+    /// Moves the occurrences of `other` into this slot, keeping them in order.
+    fn absorb(&mut self, other: Slot) -> () {
+        self.pointers.extend(other.pointers);
+        self.pointers.sort();
+    }
+
     fn is_visited(&self) -> bool {
         self.visited
     }
@@ -99,16 +182,144 @@ impl Slot {
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// The command-line parser.
+///
+/// A `Cli` owns all of its parsing state, so separate instances can be used
+/// concurrently across threads.
+///
+/// Tokenizing and parsing only read the arguments and settings given to the
+/// `Cli`. The process's environment and terminal are consulted only by the
+/// features that ask for them: [from_env](Cli::from_env) reads the process's
+/// arguments, [interactive](Cli::interactive) prompts on the terminal for
+/// missing arguments, [Config::env] captures environment variables, and printing
+/// errors or help checks `NO_COLOR` and related variables and may measure the
+/// terminal to page the help.
+#[derive(PartialEq)]
 pub struct Cli {
     tokens: Vec<Option<Token>>,
+    program: Option<String>,
+    args: Vec<String>,
+    lexemes: Vec<(usize, String)>,
+    spans: Vec<Span>,
+    bindings: Vec<Option<Binding>>,
     opt_store: HashMap<Tag<String>, Slot>,
     known_args: Vec<Arg>,
+    fragment: Option<String>,
+    forwards: bool,
+    operands: bool,
+    scope: usize,
+    boundary: Option<usize>,
     help: Option<Help>,
     asking_for_help: bool,
+    long_help: bool,
     prioritize_help: bool,
-    threshold: Cost,
+    threshold: ThresholdPolicy,
     use_color: bool,
+    negative_numbers: bool,
+    posix: bool,
+    command_prefixes: bool,
+    escapes: bool,
+    escaped: Option<usize>,
+    switch_prefix: String,
+    flag_prefix: String,
+    response_files: bool,
+    invalid_unicode: Option<String>,
+    bug_url: Option<String>,
+    catalog: Callback<dyn Catalog>,
+    internals: Vec<(String, Option<String>)>,
+    raised: HashSet<String>,
+    together: Vec<Vec<String>>,
+    unstable: bool,
+    commands: Vec<String>,
+    warnings: Vec<String>,
+    limits: Limits,
+    exceeded: Option<(String, usize)>,
+    suggestions: SuggestionConfig,
+    matches: Matches,
+    clock: Clock,
+    config: Config,
+    frozen: Vec<(String, Option<String>)>,
+    number_format: NumberFormat,
+    metrics: Option<Callback<dyn MetricsSink>>,
+    prompter: Option<Callback<Prompter>>,
+    hidden_prompter: Option<Callback<Prompter>>,
+    timings: Option<Timings>,
+    workspace: Option<(Workspace, PathBuf)>,
+    collected: Option<Vec<Deferred>>,
+    parse_known: bool,
+    completion: Option<Spec>,
+}
+
+// guarantee a `Cli` can be handed off to other threads
+const _: fn() = || {
+    fn is_thread_safe<T: Send + Sync>() {}
+    is_thread_safe::<Cli>();
+};
+
+impl std::fmt::Debug for Cli {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // secret values must not leak into debug dumps
+        let lexemes: Vec<(usize, &str)> = self
+            .lexemes
+            .iter()
+            .zip(self.bindings.iter())
+            .map(|((index, text), binding)| (*index, self.redact(text, binding.as_ref())))
+            .collect();
+        f.debug_struct("Cli")
+            .field("tokens", &self.tokens)
+            .field("program", &self.program)
+            .field("args", &self.redacted_args())
+            .field("lexemes", &lexemes)
+            .field("spans", &self.spans)
+            .field("bindings", &self.bindings)
+            .field("opt_store", &self.opt_store)
+            .field("known_args", &self.known_args)
+            .field("fragment", &self.fragment)
+            .field("forwards", &self.forwards)
+            .field("operands", &self.operands)
+            .field("scope", &self.scope)
+            .field("boundary", &self.boundary)
+            .field("help", &self.help)
+            .field("asking_for_help", &self.asking_for_help)
+            .field("long_help", &self.long_help)
+            .field("prioritize_help", &self.prioritize_help)
+            .field("threshold", &self.threshold)
+            .field("use_color", &self.use_color)
+            .field("negative_numbers", &self.negative_numbers)
+            .field("posix", &self.posix)
+            .field("command_prefixes", &self.command_prefixes)
+            .field("escapes", &self.escapes)
+            .field("escaped", &self.escaped)
+            .field("switch_prefix", &self.switch_prefix)
+            .field("flag_prefix", &self.flag_prefix)
+            .field("response_files", &self.response_files)
+            .field("invalid_unicode", &self.invalid_unicode)
+            .field("bug_url", &self.bug_url)
+            .field("catalog", &self.catalog)
+            .field("internals", &self.internals)
+            .field("raised", &self.raised)
+            .field("together", &self.together)
+            .field("unstable", &self.unstable)
+            .field("commands", &self.commands)
+            .field("warnings", &self.warnings)
+            .field("limits", &self.limits)
+            .field("exceeded", &self.exceeded)
+            .field("suggestions", &self.suggestions)
+            .field("matches", &self.matches)
+            .field("clock", &self.clock)
+            .field("config", &self.config)
+            .field("frozen", &self.frozen)
+            .field("number_format", &self.number_format)
+            .field("metrics", &self.metrics)
+            .field("prompter", &self.prompter)
+            .field("hidden_prompter", &self.hidden_prompter)
+            .field("timings", &self.timings)
+            .field("workspace", &self.workspace)
+            .field("collected", &self.collected)
+            .field("parse_known", &self.parse_known)
+            .field("completion", &self.completion)
+            .finish()
+    }
 }
 
 impl Cli {
@@ -116,29 +327,161 @@ impl Cli {
     pub fn new() -> Self {
         Self {
             tokens: Vec::new(),
+            program: None,
+            args: Vec::new(),
+            lexemes: Vec::new(),
+            spans: Vec::new(),
+            bindings: Vec::new(),
             opt_store: HashMap::new(),
             known_args: Vec::new(),
+            fragment: None,
+            forwards: false,
+            operands: false,
+            scope: 0,
+            boundary: None,
             help: None,
             asking_for_help: false,
+            long_help: false,
             prioritize_help: true,
-            threshold: 0,
+            threshold: ThresholdPolicy::default(),
             use_color: true,
+            negative_numbers: false,
+            posix: false,
+            command_prefixes: false,
+            escapes: false,
+            escaped: None,
+            switch_prefix: symbol::SWITCH.to_string(),
+            flag_prefix: symbol::FLAG.to_string(),
+            response_files: false,
+            invalid_unicode: None,
+            bug_url: None,
+            catalog: Callback::new(Arc::new(English)),
+            internals: Vec::new(),
+            raised: HashSet::new(),
+            together: Vec::new(),
+            unstable: false,
+            commands: Vec::new(),
+            warnings: Vec::new(),
+            limits: Limits::default(),
+            exceeded: None,
+            suggestions: SuggestionConfig::default(),
+            matches: Matches::new(),
+            clock: Clock::system(),
+            config: Config::new(),
+            frozen: Vec::new(),
+            number_format: NumberFormat::default(),
+            metrics: None,
+            prompter: None,
+            hidden_prompter: None,
+            timings: None,
+            workspace: None,
+            collected: None,
+            parse_known: false,
+            completion: None,
         }
     }
 
     /// Builds the `Cli` struct by perfoming lexical analysis on the vector of
     /// `String`.
-    pub fn tokenize<T: Iterator<Item = String>>(mut self, args: T) -> Self {
+    pub fn tokenize<T: Iterator<Item = String>>(self, mut args: T) -> Self {
+        let program = args.next();
+        self.lex(program, args)
+    }
+
+    /// Builds a `Cli` with the default settings from the arguments the process
+    /// was started with, taking the program name from the first.
+    ///
+    /// Settings that apply while tokenizing, such as [posix_mode](Cli::posix_mode),
+    /// must be set on [new](Cli::new) before calling [read_args](Cli::read_args)
+    /// with [Env](crate::source::Env) instead.
+    pub fn from_env() -> Self {
+        Self::new().read_args(source::Env)
+    }
+
+    /// Builds the `Cli` struct from the arguments taken from `source`.
+    ///
+    /// The program name is the first argument if the source has one. An
+    /// argument that is not valid Unicode is read lossily and reported as an
+    /// `InvalidUnicode` error by the first check. With
+    /// [response files](Cli::allow_response_files) allowed, each `@path`
+    /// argument is replaced by the arguments written in the file.
+    pub fn read_args<S: ArgSource>(mut self, mut source: S) -> Self {
+        let mut args = source.read().into_iter();
+        let program = match source.has_program() {
+            true => args.next().map(|p| p.to_string_lossy().into_owned()),
+            false => None,
+        };
+        let mut words = Vec::new();
+        for arg in args {
+            let arg = match arg.into_string() {
+                Ok(s) => s,
+                Err(os) => {
+                    let text = os.to_string_lossy().into_owned();
+                    self.invalid_unicode.get_or_insert(text.clone());
+                    text
+                }
+            };
+            match self.response_files {
+                true => words.extend(Self::expand_response_file(arg)),
+                false => words.push(arg),
+            }
+        }
+        self.lex(program, words.into_iter())
+    }
+
+    /// Reads the arguments written in the file named by `arg` if it begins with
+    /// `@`, keeping `arg` as it is when it does not or the file cannot be read.
+    fn expand_response_file(arg: String) -> Vec<String> {
+        match arg.strip_prefix('@').filter(|path| path.is_empty() == false) {
+            Some(path) => match std::fs::read_to_string(path) {
+                Ok(text) => source::split_words(&text),
+                Err(_) => vec![arg],
+            },
+            None => vec![arg],
+        }
+    }
+
+    /// Performs lexical analysis on the arguments following the `program` name.
+    fn lex<T: Iterator<Item = String>>(mut self, program: Option<String>, args: T) -> Self {
         let mut tokens = Vec::<Option<Token>>::new();
         let mut store = HashMap::new();
         let mut terminated = false;
-        let mut args = args.skip(1).enumerate();
+        let mut raw = Vec::<String>::new();
+        let mut internals = Vec::new();
+        let (switch_prefix, flag_prefix) = (self.switch_prefix.clone(), self.flag_prefix.clone());
+        self.program = program;
+        let mut args = args.enumerate();
         while let Some((i, mut arg)) = args.next() {
+            // stop reading input larger than allowed
+            if let Some(limit) = self
+                .limits
+                .check_arg(i + 1, arg.len())
+                .or(self.limits.check_tokens(tokens.len()))
+            {
+                self.exceeded = Some(limit);
+                return self;
+            }
+            raw.push(arg.clone());
             // ignore all input after detecting the terminator
             if terminated == true {
-                tokens.push(Some(Token::Ignore(i, arg)));
+                match self.escapes {
+                    true => tokens.push(Some(Token::UnattachedArgument(i, arg))),
+                    false => tokens.push(Some(Token::Ignore(i, arg))),
+                }
+            // set aside flags reserved for the framework
+            } else if let Some(name) = arg.strip_prefix(symbol::INTERNAL) {
+                internals.push(match name.split_once('=') {
+                    Some((name, val)) => (name.to_string(), Some(val.to_string())),
+                    None => (name.to_string(), None),
+                });
+            // keep negative numbers as arguments when requested
+            } else if self.negative_numbers == true && Self::is_negative_number(&arg) == true {
+                tokens.push(Some(Token::UnattachedArgument(i, arg)));
+            // a lone hyphen is an argument conventionally naming standard input
+            } else if arg == switch_prefix {
+                tokens.push(Some(Token::UnattachedArgument(i, arg)));
             // handle an option
-            } else if arg.starts_with(symbol::SWITCH) == true {
+            } else if arg.starts_with(&switch_prefix) == true || arg.starts_with(&flag_prefix) == true {
                 // try to separate from '=' sign
                 let mut value: Option<String> = None;
                 let mut option: Option<String> = None;
@@ -153,8 +496,8 @@ impl Cli {
                     arg = opt;
                 }
                 // handle long flag signal
-                if arg.starts_with(symbol::FLAG) == true {
-                    arg.replace_range(0..=1, "");
+                if arg.starts_with(&flag_prefix) == true {
+                    arg.replace_range(0..flag_prefix.len(), "");
                     // caught the terminator (purely "--")
                     if arg.is_empty() == true {
                         tokens.push(Some(Token::Terminator(i)));
@@ -169,8 +512,8 @@ impl Cli {
                     }
                 // handle short flag signal
                 } else {
-                    // skip the initial switch character/symbol (1 char)
-                    let mut arg = arg.chars().skip(1);
+                    // skip the initial switch symbol
+                    let mut arg = arg[switch_prefix.len()..].chars();
                     // check if the switch is empty by evaulating the first possible switch position
                     if let Some(c) = arg.next() {
                         store
@@ -204,1535 +547,6698 @@ impl Cli {
             }
         }
 
+        if let Some(limit) = self.limits.check_tokens(tokens.len()) {
+            self.exceeded = Some(limit);
+            return self;
+        }
+
+        self.lexemes = tokens
+            .iter()
+            .map(|t| {
+                let t = t.as_ref().unwrap();
+                (*t._get_index_ref(), self.lexeme(t, &raw))
+            })
+            .collect();
+        self.spans = self.locate(&tokens, &raw);
+        self.bindings = vec![None; tokens.len()];
         self.tokens = tokens;
+        self.args = raw;
+        self.internals = internals;
         self.opt_store = store;
+        // the first operand is only known once the options are queried
+        self.operands = self.posix;
+        self.record(Metric::Tokens, self.tokens.len());
+        self.take_unstable_gate();
+        self.take_timings_flag();
+        self.take_escape();
         self
     }
 
-    /// Sets the maximum threshold value when comparing strings for character similiarity.
-    pub fn threshold(mut self, cost: Cost) -> Self {
-        self.threshold = cost;
-        self
+    /// Parses `T` from the token stream and executes it with `context`.
+    ///
+    /// Any warnings collected while parsing are written to stderr before
+    /// executing. If parsing fails, the error is printed and the process exits
+    /// with the error's [exit code](Error::exit_code). The same happens when the
+    /// command panics while panics are [caught](Cli::catch_panics).
+    ///
+    /// Use [launch](Cli::launch) to handle the error instead of exiting.
+    pub fn go<C, T: Runner<C>>(self, context: &C) -> T::Status {
+        self.answer_completion();
+        let catalog = self.catalog.clone();
+        match self.launch::<C, T>(context) {
+            Ok(status) => status,
+            Err(err) => {
+                err.print_in(catalog.get());
+                std::process::exit(err.exit_code() as i32)
+            }
+        }
     }
 
-    /// Enables the coloring for error messages.
+    /// Parses `T` from the token stream and executes it with `context` after
+    /// writing any warnings to stderr, returning any error instead of printing
+    /// it and exiting as [go](Cli::go) does.
     ///
-    /// This is enabled by default. Note this function is not able to override
-    /// the crayon crate's color variable.
-    #[cfg(feature = "color")]
-    pub fn use_color(&mut self) -> () {
-        self.use_color = true;
+    /// With the `--timings` flag, a summary of the durations of each phase is
+    /// written to stderr after executing.
+    pub fn launch<C, T: Runner<C>>(self, context: &C) -> Result<T::Status, Error> {
+        // phases are always timed but only summarized when requested
+        let requested = self.timings.is_some();
+        let timings = self
+            .timings
+            .clone()
+            .unwrap_or_else(|| Timings::new().clock(self.clock.clone()));
+        let (app, crash) = timings.time("parse", || self.prepare::<T>())?;
+        let status = timings.time("exec", || match &crash {
+            Some(crash) => crash.guard(|| app.exec(context)),
+            None => Ok(app.exec(context)),
+        })?;
+        if requested == true {
+            Self::report_timings(&timings);
+        }
+        Ok(status)
     }
 
-    /// Disables the coloring for error messages.
+    /// Executes the [AsyncRunner] parsed from the token stream with `context`.
     ///
-    /// Note this function is only effective if the overall crayon crate's color is enabled.
-    #[cfg(feature = "color")]
-    pub fn disable_color(&mut self) -> () {
-        self.use_color = false;
+    /// This behaves like [go](Cli::go) but awaits the command's execution.
+    #[cfg(feature = "async")]
+    pub async fn go_async<C: Sync, T: AsyncRunner<C>>(self, context: &C) -> T::Status {
+        self.answer_completion();
+        let catalog = self.catalog.clone();
+        let clock = self.clock.clone();
+        let requested = self.timings.is_some();
+        let timings = self
+            .timings
+            .clone()
+            .unwrap_or_else(|| Timings::new().clock(self.clock.clone()));
+        let prepared = timings.time("parse", || self.prepare::<T>());
+        let status = match prepared {
+            Ok((app, crash)) => {
+                let watch = clock.stopwatch();
+                let status = match crash {
+                    Some(crash) => Guarded::new(crash, app.exec(context)).await,
+                    None => Ok(app.exec(context).await),
+                };
+                timings.record("exec", watch.elapsed());
+                status
+            }
+            Err(err) => Err(err),
+        };
+        match status {
+            Ok(status) => {
+                if requested == true {
+                    Self::report_timings(&timings);
+                }
+                status
+            }
+            Err(err) => {
+                err.print_in(catalog.get());
+                std::process::exit(err.exit_code() as i32)
+            }
+        }
     }
 
-    /// Disables the coloring for error messages.
+    /// Writes the summary of `timings` to stderr once the command has run,
+    /// ignoring a closed pipe so the command's status is still returned.
+    fn report_timings(timings: &Timings) -> () {
+        let _ = Output::stderr()
+            .policy(PipePolicy::Ignore)
+            .println(timings.to_table());
+    }
+
+    /// Writes the candidates and exits the process if the program was invoked by
+    /// a dynamic completion script.
     ///
-    /// Note this function is only effective if the overall crayon crate's color is enabled.
-    #[cfg(feature = "color")]
-    pub fn no_color(mut self) -> Self {
-        self.disable_color();
-        self
+    /// A script that closes the pipe early ends the process as the default
+    /// [PipePolicy] of [Output] does.
+    fn answer_completion(&self) -> () {
+        if let Some(text) = self.completion_response() {
+            let mut out = Output::stdout();
+            let _ = out.print(text).and_then(|_| out.flush());
+            std::process::exit(0)
+        }
     }
 
-    /// Enables the coloring for error messages.
+    /// Returns the answer to a dynamic completion request, which is given as the
+    /// hidden `__complete` subcommand followed by the words being completed.
     ///
-    /// This is enabled by default. Note this function is not able to override
-    /// the crayon crate's color variable.
-    #[cfg(feature = "color")]
-    pub fn color(mut self) -> Self {
-        self.use_color();
-        self
+    /// Programs that do not run through [go](Cli::go) write the answer to stdout
+    /// and exit instead of parsing the command. Returns `None` if the program
+    /// was not invoked by the completion script or
+    /// [dynamic completion](Cli::dynamic_completion) is not enabled.
+    pub fn completion_response(&self) -> Option<String> {
+        let spec = self.completion.as_ref()?;
+        match self.args.split_first() {
+            Some((first, words)) if first == complete::COMPLETE => {
+                Some(complete::respond(spec, words))
+            }
+            _ => None,
+        }
     }
 
-    /// Sets the [Help] attribute to display and checks if help has already been raised in the token stream.
-    pub fn check_help(&mut self, help: Help) -> Result<(), Error> {
-        self.help = Some(help);
-        // check for flag if not already raised
-        if self.asking_for_help == false && self.is_help_enabled() == true {
-            self.asking_for_help =
-                self.check_flag(self.help.as_ref().unwrap().get_flag().clone())?;
+    /// Parses `T` from the token stream and writes any warnings to stderr, then
+    /// enters the [Workspace] declared while parsing if requested.
+    ///
+    /// The `Cli` is consumed so its state is released before `T` executes. The
+    /// details to report if `T` panics are kept when panics are caught.
+    fn prepare<T: FromCli>(mut self) -> Result<(T, Option<Crash>), Error> {
+        // errors set aside while collecting are reported with any that stopped parsing
+        let app = T::from_cli(&mut self);
+        let app = self.gather(app).map_err(|e| self.record_error(e))?;
+        self.record(Metric::Warnings, self.warnings.len());
+        for w in self.warnings() {
+            style::print_warning(w);
+        }
+        let crash = self.bug_url.take().map(|url| {
+            let invocation: Vec<String> =
+                self.program.iter().cloned().chain(self.redacted_args()).collect();
+            Crash::new(url, invocation.join(" "), self.use_color)
+        });
+        if let Some((ws, root)) = &self.workspace {
+            if ws.is_entered() == true {
+                ws.enter_root(root)?;
+            }
+        }
+        Ok((app, crash))
+    }
+
+    /// Parses the arguments described by `spec` and returns the values read for
+    /// each, without defining a type for the command.
+    ///
+    /// This suits scripting-style tools and command trees generated at runtime.
+    /// Flags and options are read at every level before the command's positionals,
+    /// which take the [arity](Spec::arity) and [required-ness](Spec::required)
+    /// recorded in the spec. The name of each subcommand reached is recorded
+    /// under `command`, so its values form the path to the innermost command.
+    /// Help text is generated from the spec and stacked when the terminal is
+    /// narrower than [HelpLayout::NARROW_WIDTH].
+    pub fn check_spec(&mut self, spec: &Spec) -> Result<Matches, Error> {
+        self.read_spec(spec, &mut Vec::new())?;
+        self.is_empty()?;
+        Ok(self.matches.clone())
+    }
+
+    /// Reads the arguments of `spec`, followed by the subcommand it matches.
+    fn read_spec<'s>(&mut self, spec: &'s Spec, path: &mut Vec<&'s str>) -> Result<(), Error> {
+        self.check_help(
+            spec.get_help()
+                .cloned()
+                .unwrap_or_else(Help::new)
+                .quick_text(spec.to_help(path))
+                .long_text(spec.to_long_help(path)),
+        )?;
+        // the terminal is only measured when the help is going to be shown
+        if self.asking_for_help == true {
+            let layout = HelpLayout::for_width(output::terminal_width());
+            if layout != HelpLayout::Columns {
+                self.help = self.help.take().map(|h| {
+                    h.quick_text(spec.to_help_with(path, layout))
+                        .long_text(spec.to_long_help_with(path, layout))
+                });
+            }
+        }
+        self.check_availability(spec.get_availability().clone())?;
+        for e in spec.get_args() {
+            match e.get_arg() {
+                Arg::Flag(f) => {
+                    self.check_flag_all(f.clone())?;
+                }
+                Arg::Optional(o) => {
+                    self.check_option_all::<String>(o.clone())?;
+                }
+                Arg::Positional(_) => (),
+            }
+        }
+        for e in spec.get_args() {
+            if let Arg::Positional(p) = e.get_arg() {
+                match (e.is_required(), e.get_arity()) {
+                    (true, (_, Some(1))) => {
+                        self.require_positional::<String>(p.clone())?;
+                    }
+                    (false, (_, Some(1))) => {
+                        self.check_positional::<String>(p.clone())?;
+                    }
+                    (required, (min, max)) => {
+                        let min = if required == true { min.max(1) } else { 0 };
+                        self.require_positional_n::<String>(p.clone(), min, max)?;
+                    }
+                }
+            }
+        }
+        if spec.get_commands().is_empty() == true {
+            return Ok(());
+        }
+        self.check_limits()?;
+        self.known_args.push(Arg::Positional(Positional::new("command")));
+        let command_exists = self
+            .tokens
+            .iter()
+            .any(|t| matches!(t, Some(Token::UnattachedArgument(_, _))));
+        if command_exists == false {
+            return Ok(());
+        }
+        let names: Vec<&str> = spec.get_commands().iter().map(|c| c.get_name()).collect();
+        let name = self.match_command(&names)?;
+        let sub = spec
+            .get_commands()
+            .iter()
+            .find(|c| c.get_name() == name)
+            .unwrap();
+        path.push(spec.get_name());
+        self.read_spec(sub, path)
+    }
+
+    /// Parses `T` from the token stream, returning the arguments read before
+    /// the error alongside it if parsing fails.
+    pub fn parse_partial<T: FromCli>(&mut self) -> Result<T, Partial> {
+        T::from_cli(self).map_err(|e| Partial::new(self.record_error(e), self.matches.clone()))
+    }
+
+    /// Parses `T` as one fragment of a command line composed from several
+    /// independent [FromCli] structs.
+    ///
+    /// A library crate can ship its own arguments, such as logging flags, as a
+    /// fragment that the host application reads alongside its own. The fragment
+    /// should not call [is_empty](Cli::is_empty); the host checks for unused
+    /// arguments once every fragment is read. Errors if a flag or option in `T`
+    /// shares a name or switch with an argument declared before it.
+    ///
+    /// Tokens consumed by `T` are attributed to its type name in
+    /// [audit](Cli::audit) and [explain](Cli::explain).
+    pub fn check_fragment<T: FromCli>(&mut self) -> Result<T, Error> {
+        let start = self.known_args.len();
+        let outer = self.fragment.replace(std::any::type_name::<T>().to_string());
+        let result = T::from_cli(self);
+        self.fragment = outer;
+        self.check_fragment_conflicts(start)?;
+        result
+    }
+
+    /// Errors if a flag or option declared at or after `start` was already declared
+    /// before it by name or by switch.
+    fn check_fragment_conflicts(&self, start: usize) -> Result<(), Error> {
+        let start = start.min(self.known_args.len());
+        let (earlier, fragment) = self.known_args.split_at(start);
+        for arg in fragment {
+            let f = match arg.as_flag() {
+                Some(f) => f,
+                None => continue,
+            };
+            for other in earlier {
+                let g = match other.as_flag() {
+                    Some(g) => g,
+                    None => continue,
+                };
+                if g.get_name() == f.get_name() {
+                    return Err(Error::new(
+                        self.help.clone(),
+                        ErrorKind::DuplicateArg,
+                        ErrorContext::DuplicateArg(arg.clone()),
+                        self.use_color,
+                    ));
+                }
+                match f.get_switch() {
+                    Some(c) if g.get_switch() == Some(c) => {
+                        return Err(Error::new(
+                            self.help.clone(),
+                            ErrorKind::DuplicateSwitch,
+                            ErrorContext::DuplicateSwitch(*c, other.clone(), arg.clone()),
+                            self.use_color,
+                        ))
+                    }
+                    _ => (),
+                }
+            }
         }
         Ok(())
     }
 
-    /// Clears the `asking_for_help` status flag.
-    pub fn clear_help(&mut self) -> () {
-        self.asking_for_help = false;
+    /// Returns the arguments read successfully so far.
+    pub fn matches(&self) -> &Matches {
+        &self.matches
     }
 
-    /// Directly calls the help error if asking for help is enabled.
-    pub fn raise_help(&self) -> Result<(), Error> {
-        self.prioritize_help()
+    /// Removes the `--enable-unstable` flag from the token stream, enabling
+    /// experimental features if it was raised.
+    fn take_unstable_gate(&mut self) -> () {
+        if self.take_builtin_flag(symbol::UNSTABLE) == true {
+            self.unstable = true;
+        }
     }
 
-    /// Removes the current help text set for the command-line argument parser.
-    pub fn disable_help(&mut self) -> () {
-        self.help = None;
+    /// Removes the `--timings` flag from the token stream, timing the phases of
+    /// the command if it was raised.
+    fn take_timings_flag(&mut self) -> () {
+        if self.take_builtin_flag(symbol::TIMINGS) == true {
+            self.timings = Some(Timings::new().clock(self.clock.clone()));
+        }
     }
 
-    /// Downplays the help action to not become a priority error over other errors in the parsing.
+    /// Removes the occurrences of the built-in flag `name` from the token stream,
+    /// returning if any were found.
     ///
-    /// Help is prioritized by default.
-    pub fn downplay_help(mut self) -> Self {
-        self.prioritize_help = false;
-        self
+    /// Occurrences given a value, as in `--timings=1`, are left in the stream for
+    /// [is_empty](Cli::is_empty) to reject.
+    fn take_builtin_flag(&mut self, name: &str) -> bool {
+        let mut raised = false;
+        for i in self.take_flag_locs(name) {
+            if let Some(Some(Token::AttachedArgument(_, _))) = self.tokens.get(i + 1) {
+                continue;
+            }
+            self.tokens[i] = None;
+            self.bindings[i] = Some(Binding {
+                role: Role::Flag,
+                arg: Some(self.flag_word(name)),
+                fragment: None,
+                secret: false,
+            });
+            raised = true;
+        }
+        raised
     }
 
-    /// Prioritizes the help action over other errors during parsing.
+    /// Returns the handle for recording the durations of a command's own steps,
+    /// if the `--timings` flag was supplied.
     ///
-    /// This is enabled by default.
-    pub fn emphasize_help(mut self) -> Self {
-        self.prioritize_help = true;
-        self
+    /// The recorded steps are included in the summary printed by [go](Cli::go).
+    pub fn timings(&self) -> Option<&Timings> {
+        self.timings.as_ref()
     }
 
-    /// Checks if help is enabled and is some value.
-    fn is_help_enabled(&self) -> bool {
-        self.help.is_some()
+    /// Removes the terminator from the token stream when it escapes the values
+    /// after it.
+    fn take_escape(&mut self) -> () {
+        if self.escapes == false {
+            return;
+        }
+        if let Some(i) = self.tokens.iter().position(|t| matches!(t, Some(Token::Terminator(_)))) {
+            self.tokens[i] = None;
+            self.bindings[i] = Some(Binding {
+                role: Role::Terminator,
+                arg: None,
+                fragment: None,
+                secret: false,
+            });
+            self.escaped = Some(i + 1);
+        }
     }
 
-    /// Checks if help has been raised and will return its own error for displaying
-    /// help.
-    fn prioritize_help(&self) -> Result<(), Error> {
-        if self.prioritize_help == true
-            && self.asking_for_help == true
-            && self.is_help_enabled() == true
-        {
-            Err(Error::new(
-                self.help.clone(),
-                ErrorKind::Help,
-                ErrorContext::Help,
-                self.use_color,
-            ))
-        } else {
-            Ok(())
+    /// Checks if the token at `loc` was escaped by the terminator.
+    fn is_escaped(&self, loc: usize) -> bool {
+        self.escaped.is_some_and(|start| loc >= start)
+    }
+
+    /// Checks if `arg` is a hyphen followed by a number, such as `-5` or `-0.25`.
+    fn is_negative_number(arg: &str) -> bool {
+        match arg.strip_prefix(symbol::SWITCH) {
+            // require a leading digit so switches like `-inf` are left alone
+            Some(num) => {
+                num.starts_with(|c: char| c.is_ascii_digit() || c == '.') && num.parse::<f64>().is_ok()
+            }
+            None => false,
         }
     }
 
-    /// Pulls the next `UnattachedArg` token from the token stream.
+    /// Displays the flag of `arg` with the chosen flag prefix in errors.
+    fn prefixed(&self, arg: Arg) -> Arg {
+        // keep learned arguments equal to the ones given when using the defaults
+        match self.flag_prefix == symbol::FLAG {
+            true => arg,
+            false => arg.prefixed(&self.flag_prefix),
+        }
+    }
+
+    /// Writes the flag called `name` as it would appear on the command-line.
+    fn flag_word(&self, name: &str) -> String {
+        format!("{}{}", self.flag_prefix, name)
+    }
+
+    /// Reconstructs the text a token was lexed from within the original arguments.
+    fn lexeme(&self, token: &Token, raw: &[String]) -> String {
+        match token {
+            Token::UnattachedArgument(_, s) | Token::AttachedArgument(_, s) | Token::Ignore(_, s) => {
+                s.to_string()
+            }
+            Token::Flag(i) => match raw[*i].split_once('=') {
+                Some((flag, _)) => flag.to_string(),
+                None => raw[*i].to_string(),
+            },
+            Token::Switch(_, c) => format!("{}{}", self.switch_prefix, c),
+            Token::EmptySwitch(_) => self.switch_prefix.clone(),
+            Token::Terminator(_) => self.flag_prefix.clone(),
+        }
+    }
+
+    /// Returns the span of each token within the command-line argument it was
+    /// lexed from.
+    fn locate(&self, tokens: &[Option<Token>], raw: &[String]) -> Vec<Span> {
+        let mut spans: Vec<Span> = Vec::with_capacity(tokens.len());
+        for t in tokens {
+            let t = t.as_ref().unwrap();
+            let i = *t._get_index_ref();
+            let len = raw[i].len();
+            let (start, end) = match t {
+                Token::Flag(_) => (0, raw[i].find('=').unwrap_or(len)),
+                Token::Switch(_, c) => {
+                    // a switch within a cluster begins where the previous one ends
+                    let start = match spans.last() {
+                        Some(prev) if prev.get_arg() == i + 1 => prev.get_range().end,
+                        _ => self.switch_prefix.len(),
+                    };
+                    (start, start + c.len_utf8())
+                }
+                Token::EmptySwitch(_) => (0, self.switch_prefix.len()),
+                Token::AttachedArgument(_, s) => (len - s.len(), len),
+                _ => (0, len),
+            };
+            spans.push(Span::new(i + 1, start, end));
+        }
+        spans
+    }
+
+    /// Returns the span of the token at index `i`.
+    fn span(&self, i: usize) -> Option<Span> {
+        self.spans.get(i).copied()
+    }
+
+    /// Returns the span of `word` read as a value by the most recently learned
+    /// argument.
     ///
-    /// If no more `UnattachedArg` tokens are left, it will return none.
-    fn next_uarg(&mut self) -> Option<String> {
-        if let Some(p) = self.tokens.iter_mut().find(|s| match s {
-            Some(Token::UnattachedArgument(_, _)) | Some(Token::Terminator(_)) => true,
-            _ => false,
-        }) {
-            if let Some(Token::Terminator(_)) = p {
-                None
-            } else {
-                Some(p.take().unwrap().take_str())
+    /// The span covers only `word` when it is one piece of a delimited value.
+    fn value_span(&self, word: &str) -> Option<Span> {
+        let arg = self.known_args.last().map(|a| a.to_string());
+        self.bindings.iter().enumerate().find_map(|(i, b)| {
+            let b = b.as_ref()?;
+            if b.arg != arg || matches!(b.role, Role::Value | Role::Positional) == false {
+                return None;
             }
-        } else {
-            None
+            let span = self.spans[i];
+            let text = &self.args[span.get_arg() - 1];
+            let start = span.get_range().start + text.get(span.get_range().start..)?.find(word)?;
+            Some(Span::new(span.get_arg(), start, start + word.len()))
+        })
+    }
+
+    /// Marks the token at index `i` as consumed by the most recently learned argument.
+    fn bind(&mut self, i: usize, role: Role) -> () {
+        let arg = self.known_args.last().map(|a| a.to_string());
+        let fragment = self.fragment.clone();
+        let secret = match role {
+            Role::Value | Role::Positional => self.is_secret(),
+            _ => false,
+        };
+        if let Some(b) = self.bindings.get_mut(i) {
+            *b = Some(Binding {
+                role,
+                arg,
+                fragment,
+                secret,
+            });
         }
     }
 
-    /// Determines if an `UnattachedArg` exists to be served as a subcommand.
+    /// Describes how each token in the command-line was classified against the
+    /// arguments learned so far.
     ///
-    /// If so, it will call `from_cli` on the type defined. If not, it will return none.
-    pub fn check_command<'a, T: FromCli>(
-        &mut self,
-        p: Positional,
-    ) -> Result<Option<T>, Error> {
-        self.known_args.push(Arg::Positional(p));
-        // check but do not remove if an unattached arg exists
-        let command_exists = self
-            .tokens
+    /// Each line lists the argument's position, the text that was lexed, and what
+    /// consumed it. Tokens that were never consumed are reported as unused. This
+    /// is intended to be called after parsing (successful or not) to help debug
+    /// quoting and ordering issues without executing the command.
+    pub fn explain(&self) -> String {
+        let width = self
+            .lexemes
             .iter()
-            .find(|f| match f {
-                Some(Token::UnattachedArgument(_, _)) => true,
-                _ => false,
+            .zip(self.bindings.iter())
+            .map(|(l, b)| self.redact(&l.1, b.as_ref()).len())
+            .max()
+            .unwrap_or(0);
+        self.lexemes
+            .iter()
+            .zip(self.bindings.iter())
+            .map(|((index, text), binding)| {
+                let arg = binding
+                    .as_ref()
+                    .and_then(|b| b.arg.clone())
+                    .unwrap_or(String::new());
+                let about = match binding.as_ref().map(|b| &b.role) {
+                    Some(Role::Flag) => format!("flag '{}'", arg),
+                    Some(Role::Value) => format!("value for '{}'", arg),
+                    Some(Role::Positional) => format!("positional '{}'", arg),
+                    Some(Role::Subcommand) => format!("subcommand for '{}'", arg),
+                    Some(Role::Terminator) => String::from("terminator"),
+                    Some(Role::Remainder) => String::from("remainder after terminator"),
+                    Some(Role::Unknown) => String::from("unknown, passed along"),
+                    None => String::from("unused"),
+                };
+                let about = match binding.as_ref().and_then(|b| b.fragment.as_ref()) {
+                    Some(fragment) => format!("{} in fragment '{}'", about, fragment),
+                    None => about,
+                };
+                let text = self.redact(text, binding.as_ref());
+                format!("{:>3}  {:<width$}  {}\n", index, text, about, width = width)
             })
-            .is_some();
-        if command_exists {
-            Ok(Some(T::from_cli(self)?))
-        } else {
-            return Ok(None);
+            .collect()
+    }
+
+    /// Returns `text` lexed from the command-line, or the placeholder when it was
+    /// consumed as a secret value.
+    fn redact<'s>(&'s self, text: &'s str, binding: Option<&Binding>) -> &'s str {
+        match binding.map(|b| b.secret).unwrap_or(false) {
+            true => self.matches.get_placeholder(),
+            false => text,
         }
     }
 
-    /// Tries to match the next `UnattachedArg` with a list of given `words`.
+    /// Checks if the most recently learned argument holds secret values.
+    fn is_secret(&self) -> bool {
+        self.known_args
+            .last()
+            .and_then(|a| a.as_positional())
+            .map(|p| p.is_secret())
+            .unwrap_or(false)
+    }
+
+    /// Sets the text shown in place of [secret](Positional::secret) values, which
+    /// is `***` by default.
+    pub fn redaction<T: AsRef<str>>(mut self, placeholder: T) -> Self {
+        self.matches.set_placeholder(placeholder);
+        self
+    }
+
+    /// Returns the command-line arguments, excluding the program name, with the
+    /// values consumed by secret arguments replaced by the placeholder.
     ///
-    /// If fails, it will attempt to offer a spelling suggestion if the name is close.
+    /// This is the form of the invocation to record in logs or history files.
+    pub fn redacted_args(&self) -> Vec<String> {
+        let mut result = self.args.clone();
+        for (i, binding) in self.bindings.iter().enumerate() {
+            if binding.as_ref().map(|b| b.secret).unwrap_or(false) == false {
+                continue;
+            }
+            let argv = self.lexemes[i].0;
+            let placeholder = self.matches.get_placeholder();
+            // keep the flag of a value attached with an equal sign
+            let attached = i > 0 && self.lexemes[i - 1].0 == argv;
+            result[argv] = match (attached, self.args[argv].split_once('=')) {
+                (true, Some((flag, _))) => format!("{}={}", flag, placeholder),
+                _ => placeholder.to_string(),
+            };
+        }
+        result
+    }
+
+    /// Reports which argument, and which fragment read with
+    /// [check_fragment](Cli::check_fragment), consumed each token in the
+    /// command-line.
     ///
-    /// Panics if there is not a next `UnattachedArg`. It is recommended to not directly call
-    /// this command, but through a `from_cli` call after `check_command` has been issued.
-    pub fn match_command<T: AsRef<str> + std::cmp::PartialEq>(
-        &mut self,
-        words: &[T],
-    ) -> Result<String, Error> {
-        // find the unattached arg's index before it is removed from the token stream
-        let i: usize = self
-            .tokens
+    /// This helps find out why a library's fragment took a token the application
+    /// expected to read itself, such as a positional.
+    pub fn audit(&self) -> Vec<Consumption> {
+        self.lexemes
             .iter()
-            .find_map(|f| match f {
-                Some(Token::UnattachedArgument(i, _)) => Some(*i),
-                _ => None,
+            .zip(self.bindings.iter())
+            .map(|((index, text), binding)| {
+                Consumption::new(
+                    index + 1,
+                    self.redact(text, binding.as_ref()).to_string(),
+                    binding.as_ref().and_then(|b| b.arg.clone()),
+                    binding.as_ref().and_then(|b| b.fragment.clone()),
+                    binding.is_some(),
+                )
             })
-            .expect("an unattached argument must exist before calling `match_command`");
-        let command = self
-            .next_uarg()
-            .expect("`check_command` must be called before this function");
-        // perform partial clean to ensure no arguments are remaining behind the command (uncaught options)
-        let ooc_arg = self.capture_bad_flag(i)?;
+            .collect()
+    }
 
-        if words.iter().find(|p| p.as_ref() == command).is_some() {
-            if let Some((prefix, key, pos)) = ooc_arg {
-                if pos < i {
-                    self.prioritize_help()?;
-                    return Err(Error::new(
-                        self.help.clone(),
-                        ErrorKind::OutOfContextArgSuggest,
-                        ErrorContext::OutofContextArgSuggest(format!("{}{}", prefix, key), command),
-                        self.use_color,
-                    ));
-                }
-            }
-            Ok(command)
-        // try to offer a spelling suggestion otherwise say we've hit an unexpected argument
-        } else {
-            // bypass sequence alignment algorithm if threshold == 0
-            if let Some(w) = if self.threshold > 0 {
-                seqalin::sel_min_edit_str(&command, &words, self.threshold)
-            } else {
-                None
-            } {
-                Err(Error::new(
-                    self.help.clone(),
-                    ErrorKind::SuggestSubcommand,
-                    ErrorContext::SuggestWord(command, w.to_string()),
-                    self.use_color,
-                ))
-            } else {
-                self.prioritize_help()?;
-                Err(Error::new(
-                    self.help.clone(),
-                    ErrorKind::UnknownSubcommand,
-                    ErrorContext::UnknownSubcommand(
-                        self.known_args.pop().expect("requires positional argument"),
-                        command,
-                    ),
-                    self.use_color,
-                ))
-            }
+    /// Lists the tokens of the command-line that no argument has consumed yet,
+    /// in the order they were supplied.
+    ///
+    /// This helps find out why an argument was not read as expected, such as a
+    /// value lexed as a switch. Print the list with `{:#?}` to see one token per
+    /// line.
+    pub fn dump_tokens(&self) -> Vec<RemainingToken> {
+        self.tokens
+            .iter()
+            .zip(self.lexemes.iter())
+            .filter_map(|(token, (index, text))| {
+                let kind = match token.as_ref()? {
+                    Token::UnattachedArgument(_, _) => TokenKind::Argument,
+                    Token::AttachedArgument(_, _) => TokenKind::AttachedArgument,
+                    Token::Flag(_) => TokenKind::Flag,
+                    Token::Switch(_, _) => TokenKind::Switch,
+                    Token::EmptySwitch(_) => TokenKind::EmptySwitch,
+                    Token::Terminator(_) => TokenKind::Terminator,
+                    Token::Ignore(_, _) => TokenKind::Remainder,
+                };
+                Some(RemainingToken::new(index + 1, kind, text.clone()))
+            })
+            .collect()
+    }
+
+    /// Classifies arguments that look like negative numbers (`-5`, `-0.25`) as
+    /// values instead of switches.
+    ///
+    /// This must be set before calling [tokenize](Cli::tokenize).
+    pub fn allow_negative_numbers(mut self) -> Self {
+        self.negative_numbers = true;
+        self
+    }
+
+    /// Writes the errors printed by [go](Cli::go) in the language of `catalog`.
+    ///
+    /// Messages the catalog does not translate are written in English.
+    pub fn catalog<C: Catalog + 'static>(mut self, catalog: C) -> Self {
+        self.catalog = Callback::new(Arc::new(catalog));
+        self
+    }
+
+    /// Reports a panic raised while executing the command as an error instead of
+    /// a raw message and backtrace, asking the user to file a bug at `url`.
+    ///
+    /// The report includes the panic's message and the invocation, with
+    /// [secret](Positional::secret) values redacted. The process exits with
+    /// code 70. Panics are caught by [go](Cli::go), [launch](Cli::launch),
+    /// and `go_async`, on the thread running or polling the command.
+    pub fn catch_panics<T: AsRef<str>>(mut self, url: T) -> Self {
+        self.bug_url = Some(url.as_ref().to_string());
+        self
+    }
+
+    /// Answers the requests of the script written by
+    /// [Spec::to_dynamic_completion] using the arguments described by `spec`.
+    ///
+    /// When the program is invoked with the hidden `__complete` subcommand,
+    /// [go](Cli::go) writes the candidates for the last argument and exits
+    /// instead of parsing the command. Other programs answer the request with
+    /// [completion_response](Cli::completion_response).
+    pub fn dynamic_completion(mut self, spec: Spec) -> Self {
+        self.completion = Some(spec);
+        self
+    }
+
+    /// Sets aside the errors raised by the checks for optional arguments instead
+    /// of returning them, so the user sees every problem with the command-line in
+    /// one run. These are [check_flag](Cli::check_flag),
+    /// [check_flag_all](Cli::check_flag_all), [check_flag_n](Cli::check_flag_n),
+    /// [check_flag_count](Cli::check_flag_count),
+    /// [check_bool_flag](Cli::check_bool_flag), [check_option](Cli::check_option),
+    /// [check_option_all](Cli::check_option_all), [check_option_n](Cli::check_option_n),
+    /// [check_option_occurrences](Cli::check_option_occurrences),
+    /// [check_option_greedy](Cli::check_option_greedy),
+    /// [check_option_positional](Cli::check_option_positional),
+    /// [check_positional](Cli::check_positional), and
+    /// [check_positional_all](Cli::check_positional_all). The `require_` forms
+    /// return their errors right away.
+    ///
+    /// A check that fails returns as if the argument was not supplied. Each error
+    /// keeps its [context](Error::context) and [span](Error::span). The
+    /// errors are reported by [finish](Cli::finish), or by [go](Cli::go) after
+    /// reading the command, along with any error that stops parsing, such as a
+    /// missing required positional. More than one is reported as a `Multiple`
+    /// error listing each of them.
+    pub fn collect_errors(mut self) -> Self {
+        self.collected = Some(Vec::new());
+        self
+    }
+
+    /// Leaves unknown flags and extra positionals for [unknown](Cli::unknown)
+    /// instead of reporting them from [is_empty](Cli::is_empty), for wrappers
+    /// that forward the arguments they do not recognize to another program.
+    ///
+    /// An unknown flag's value given as a separate argument cannot be told apart
+    /// from a positional, so it may be read by one.
+    pub fn parse_known(mut self) -> Self {
+        self.parse_known = true;
+        self
+    }
+
+    /// Replaces each `@path` argument with the arguments written in the file at
+    /// `path` when reading them with [read_args](Cli::read_args), as in
+    /// `build @flags.txt`.
+    ///
+    /// Arguments in the file are separated by whitespace and may be quoted as in
+    /// a shell. An argument naming a file that cannot be read is kept as it is.
+    pub fn allow_response_files(mut self) -> Self {
+        self.response_files = true;
+        self
+    }
+
+    /// Replaces the `-` and `--` that introduce switches and flags with `switch`
+    /// and `flag`, such as `+` and `++` for tools with toggles like `+opt`.
+    ///
+    /// The `flag` prefix given alone is the terminator. When both prefixes are
+    /// the same, as with `/` for Windows-flavored tools, every option is read as
+    /// a flag, so `/v` raises the flag named `v` and there is no terminator.
+    /// Errors and suggestions name flags with the chosen prefix.
+    ///
+    /// This must be set before calling [tokenize](Cli::tokenize). Panics if
+    /// either prefix is empty.
+    pub fn option_prefixes<T: AsRef<str>, U: AsRef<str>>(mut self, switch: T, flag: U) -> Self {
+        if switch.as_ref().is_empty() == true || flag.as_ref().is_empty() == true {
+            panic!("option prefixes cannot be empty");
         }
+        self.switch_prefix = switch.as_ref().to_string();
+        self.flag_prefix = flag.as_ref().to_string();
+        self
     }
 
-    /// Serves the next `Positional` value in the token stream parsed as `T`.
+    /// Stops reading flags and options at the first positional of each command,
+    /// so every argument after it is read as a positional, as POSIX utilities
+    /// do, instead of allowing flags to be interspersed.
     ///
-    /// Errors if parsing fails. If the next argument is not a positional, it will
-    /// not move forward in the token stream.
-    pub fn check_positional<'a, T: FromStr>(
-        &mut self,
-        p: Positional,
-    ) -> Result<Option<T>, Error>
-    where
-        <T as FromStr>::Err: 'static + std::error::Error,
-    {
-        self.known_args.push(Arg::Positional(p));
-        self.try_positional()
+    /// An argument directly after a flag or option that was not queried yet may
+    /// be that option's value, as in `-f out.tar`, so it does not end the
+    /// options. Query a command's flags and options before its positionals so
+    /// the first operand is known when they are read.
+    ///
+    /// This must be set before calling [tokenize](Cli::tokenize).
+    pub fn posix_mode(mut self, enabled: bool) -> Self {
+        self.posix = enabled;
+        self
     }
 
-    /// Attempts to extract the next unattached argument to get a positional with valid parsing.
+    /// Accepts an abbreviated subcommand in [match_command](Cli::match_command)
+    /// when exactly one of its words begins with it, so `orbit inst` runs
+    /// `install`.
     ///
-    /// Assumes the [Positional] argument is already added as the last element to the `known_args` vector.
-    fn try_positional<'a, T: FromStr>(&mut self) -> Result<Option<T>, Error>
-    where
-        <T as FromStr>::Err: 'static + std::error::Error,
-    {
-        match self.next_uarg() {
-            Some(word) => match word.parse::<T>() {
-                Ok(r) => Ok(Some(r)),
-                Err(err) => {
-                    self.prioritize_help()?;
-                    self.prioritize_suggestion()?;
-                    Err(Error::new(
-                        self.help.clone(),
-                        ErrorKind::BadType,
-                        ErrorContext::FailedCast(
-                            self.known_args.pop().unwrap(),
-                            word,
-                            Box::new(err),
-                        ),
-                        self.use_color,
-                    ))
-                }
-            },
-            None => Ok(None),
+    /// An abbreviation shared by several words is an `AmbiguousSubcommand` error.
+    pub fn allow_command_prefixes(mut self) -> Self {
+        self.command_prefixes = true;
+        self
+    }
+
+    /// Reads the arguments after the `--` terminator as values for positionals
+    /// instead of forwarding them as the remainder.
+    ///
+    /// Escaped values are never read as flags, switches, or subcommands, so
+    /// `remove -- --force-named-file` removes the file named `--force-named-file`.
+    /// A single value can also be escaped without this by writing it as a
+    /// path, such as `./--weird-name`.
+    ///
+    /// This must be set before calling [tokenize](Cli::tokenize).
+    pub fn escape_positionals(mut self) -> Self {
+        self.escapes = true;
+        self
+    }
+
+    /// Checks if the internal flag `--clif-<name>` was raised.
+    ///
+    /// Flags under the `--clif-` prefix are reserved for tooling provided by this
+    /// crate. They are removed during tokenization, so they are never seen by
+    /// [FromCli] implementations and cannot collide with an application's flags.
+    pub fn is_internal_raised(&self, name: &str) -> bool {
+        self.internals.iter().any(|(n, _)| n == name)
+    }
+
+    /// Returns the value attached to the internal flag `--clif-<name>=<value>`.
+    pub fn get_internal_value(&self, name: &str) -> Option<&str> {
+        self.internals
+            .iter()
+            .find(|(n, _)| n == name)
+            .and_then(|(_, v)| v.as_deref())
+    }
+
+    /// Enables experimental features as if `--enable-unstable` was supplied.
+    ///
+    /// This allows an application to opt in from a source of its own, such as an
+    /// environment variable.
+    pub fn enable_unstable(mut self, enabled: bool) -> Self {
+        self.unstable = self.unstable || enabled;
+        self
+    }
+
+    /// Adds a warning to be written before the command executes.
+    pub(crate) fn warn<T: AsRef<str>>(&mut self, message: T) -> () {
+        self.warnings.push(message.as_ref().to_string());
+    }
+
+    /// Returns the warnings collected while parsing, such as uses of deprecated
+    /// subcommands or arguments.
+    pub fn warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
+
+    /// Reports counters such as the number of tokens lexed and values parsed to
+    /// `sink`.
+    ///
+    /// This must be set before calling [tokenize](Cli::tokenize) for the tokens
+    /// to be counted. Errors and warnings are counted when parsing through
+    /// [go](Cli::go) or [parse_partial](Cli::parse_partial).
+    pub fn metrics<M: MetricsSink + 'static>(mut self, sink: M) -> Self {
+        self.metrics = Some(Callback::new(Arc::new(sink)));
+        self
+    }
+
+    fn record(&self, metric: Metric, n: usize) -> () {
+        if let Some(sink) = &self.metrics {
+            sink.get().record(metric, n);
         }
     }
 
-    /// Forces the next [Positional] to exist from token stream.
+    /// Counts `err` as a failure unless it is informational, such as a request
+    /// for help, and attaches the command-line it was raised for.
+    fn record_error(&self, err: Error) -> Error {
+        if err.is_informational() == false {
+            self.record(Metric::Errors, 1);
+        }
+        // the command-line is echoed to point out the offending token
+        match err.span().is_some() {
+            true => {
+                let program = self.program.clone().unwrap_or(String::new());
+                err.with_line(std::iter::once(program).chain(self.redacted_args()).collect())
+            }
+            false => err,
+        }
+    }
+
+    /// Prompts on the terminal for required arguments that are missing instead
+    /// of erroring.
     ///
-    /// Errors if parsing fails or if no unattached argument is left in the token stream.
-    pub fn require_positional<'a, T: FromStr>(&mut self, p: Positional) -> Result<T, Error>
-    where
-        <T as FromStr>::Err: 'static + std::error::Error,
-    {
-        if let Some(value) = self.check_positional(p)? {
-            Ok(value)
-        } else {
-            self.prioritize_help()?;
-            self.is_empty()?;
-            Err(Error::new(
-                self.help.clone(),
-                ErrorKind::MissingPositional,
-                ErrorContext::FailedArg(self.known_args.pop().unwrap()),
-                self.use_color,
-            ))
+    /// This applies to [require_positional](Cli::require_positional) and
+    /// [require_option_positional](Cli::require_option_positional), and only when
+    /// standard input is a terminal, so scripts and pipelines still fail fast.
+    /// Answers for [secret](Positional::secret) arguments are read with echo
+    /// disabled, which is only possible on Unix terminals.
+    pub fn interactive(mut self, enabled: bool) -> Self {
+        if enabled == true && std::io::stdin().is_terminal() == true {
+            self.prompter = Some(Callback::new(Arc::new(Self::read_tty)));
+            self.hidden_prompter = Some(Callback::new(Arc::new(Self::read_tty_hidden)));
         }
+        self
     }
 
-    /// Forces all the next [Positional] to be captured from the token stream.
+    /// Asks `f` for the values of missing required arguments, as with
+    /// [interactive](Cli::interactive).
     ///
-    /// Errors if parsing fails or if zero unattached arguments are left in the token stream to begin.
+    /// Secret arguments are only prompted for with [prompt_hidden](Cli::prompt_hidden).
+    pub fn prompter<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.prompter = Some(Callback::new(Arc::new(f)));
+        self
+    }
+
+    /// Asks `f` for the values of missing required [secret](Positional::secret)
+    /// arguments, such as passwords, which it must read without echoing them.
     ///
-    /// The resulting vector is guaranteed to have `.len() >= 1`.
-    pub fn require_positional_all<'a, T: FromStr>(
-        &mut self,
-        p: Positional,
-    ) -> Result<Vec<T>, Error>
+    /// Values read this way are redacted like any other secret value.
+    pub fn prompt_hidden<F>(mut self, f: F) -> Self
     where
-        <T as FromStr>::Err: 'static + std::error::Error,
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
     {
-        let mut result = Vec::<T>::new();
-        result.push(self.require_positional(p)?);
-        while let Some(v) = self.try_positional()? {
-            result.push(v);
+        self.hidden_prompter = Some(Callback::new(Arc::new(f)));
+        self
+    }
+
+    /// Writes `text` to stderr and reads a line from stdin.
+    fn read_tty(text: &str) -> Option<String> {
+        eprint!("{}", text);
+        std::io::stderr().flush().ok()?;
+        let mut line = String::new();
+        match std::io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line),
         }
-        Ok(result)
     }
 
-    /// Iterates through the list of tokens to find the first suggestion against a flag to return.
+    /// Writes `text` to stderr and reads a line from stdin with echo disabled.
     ///
-    /// Returns ok if cannot make a suggestion.
-    fn prioritize_suggestion(&self) -> Result<(), Error> {
-        let mut kv: Vec<(&String, &Vec<usize>)> = self
-            .opt_store
-            .iter()
-            .map(|(tag, slot)| (tag.as_ref(), slot.get_indices()))
-            .collect::<Vec<(&String, &Vec<usize>)>>();
-        kv.sort_by(|a, b| a.1.first().unwrap().cmp(b.1.first().unwrap()));
-        let bank: Vec<&str> = self.known_args_as_flag_names().into_iter().collect();
-        let r = kv
-            .iter()
-            .find_map(|f| match self.tokens.get(*f.1.first().unwrap()).unwrap() {
-                Some(Token::Flag(_)) => {
-                    if let Some(word) = if self.threshold > 0 {
-                        seqalin::sel_min_edit_str(f.0, &bank, self.threshold)
-                    } else {
-                        None
-                    } {
-                        Some(Error::new(
-                            self.help.clone(),
-                            ErrorKind::SuggestArg,
-                            ErrorContext::SuggestWord(
-                                format!("{}{}", symbol::FLAG, f.0),
-                                format!("{}{}", symbol::FLAG, word),
-                            ),
-                            self.use_color,
-                        ))
-                    } else {
-                        None
-                    }
-                }
-                _ => None,
-            });
-        if self.asking_for_help == true {
-            Ok(())
-        } else if let Some(e) = r {
-            Err(e)
-        } else {
-            Ok(())
+    /// Nothing is read if echo cannot be disabled.
+    fn read_tty_hidden(text: &str) -> Option<String> {
+        #[cfg(unix)]
+        {
+            let stty = |mode: &str| {
+                std::process::Command::new("stty")
+                    .arg(mode)
+                    .stdin(std::fs::File::open("/dev/tty").ok()?)
+                    .status()
+                    .ok()
+                    .filter(|s| s.success())
+            };
+            stty("-echo")?;
+            let line = Self::read_tty(text);
+            stty("echo");
+            // the newline typed by the user was not echoed either
+            eprintln!();
+            line
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = text;
+            None
         }
     }
 
-    /// Queries for a value of `Optional`.
+    /// Prompts for the value of the most recently learned argument, falling
+    /// back to its default when the answer is blank.
     ///
-    /// Errors if there are multiple values or if parsing fails.
-    pub fn check_option<'a, T: FromStr>(&mut self, o: Optional) -> Result<Option<T>, Error>
-    where
-        <T as FromStr>::Err: 'static + std::error::Error,
-    {
-        // collect information on where the flag can be found
-        let mut locs = self.take_flag_locs(o.get_flag().get_name());
-        if let Some(c) = o.get_flag().get_switch() {
-            locs.extend(self.take_switch_locs(c));
+    /// Returns `None` if prompting is disabled or no value was given.
+    fn prompt_value<T: ValueParse>(&mut self) -> Result<Option<T>, Error> {
+        let arg = self.known_args.last().unwrap();
+        let secret = arg.as_positional().map(|p| p.is_secret()).unwrap_or(false);
+        let prompter = match secret {
+            true => &self.hidden_prompter,
+            false => &self.prompter,
+        };
+        let prompter = match prompter {
+            Some(p) => p.clone(),
+            None => return Ok(None),
+        };
+        let default = arg
+            .as_positional()
+            .and_then(|p| p.get_prompt_default())
+            .map(|d| d.to_string());
+        let text = match (&default, secret) {
+            (Some(_), true) => format!("{} [{}]: ", arg.get_name(), self.matches.get_placeholder()),
+            (Some(d), false) => format!("{} [{}]: ", arg.get_name(), d),
+            (None, _) => format!("{}: ", arg.get_name()),
+        };
+        let answer = prompter.get()(&text).map(|a| a.trim().to_string());
+        match answer.filter(|a| a.is_empty() == false).or(default) {
+            Some(word) => Ok(Some(self.parse_value(word)?)),
+            None => Ok(None),
         }
-        self.known_args.push(Arg::Optional(o));
-        // pull values from where the option flags were found (including switch)
-        let mut values = self.pull_flag(locs, true);
-        match values.len() {
-            1 => {
-                if let Some(word) = values.pop().unwrap() {
-                    let result = word.parse::<T>();
-                    match result {
-                        Ok(r) => Ok(Some(r)),
-                        Err(err) => {
-                            self.prioritize_help()?;
-                            Err(Error::new(
-                                self.help.clone(),
-                                ErrorKind::BadType,
-                                ErrorContext::FailedCast(
-                                    self.known_args.pop().unwrap(),
-                                    word,
-                                    Box::new(err),
-                                ),
-                                self.use_color,
-                            ))
-                        }
-                    }
-                } else {
+    }
+
+    /// Enforces the [Availability] of the most recently matched subcommand.
+    ///
+    /// Call this at the start of the subcommand's `from_cli`. Errors if the
+    /// subcommand is experimental and unstable features are not enabled, or if it
+    /// was removed. Deprecated subcommands add to the [warnings](Cli::warnings).
+    pub fn check_availability(&mut self, a: Availability) -> Result<(), Error> {
+        let command = self.commands.last().cloned().unwrap_or(String::new());
+        match a {
+            Availability::Stable => Ok(()),
+            Availability::Experimental => match self.unstable {
+                true => Ok(()),
+                false => {
                     self.prioritize_help()?;
                     Err(Error::new(
                         self.help.clone(),
-                        ErrorKind::ExpectingValue,
-                        ErrorContext::FailedArg(self.known_args.pop().unwrap()),
+                        ErrorKind::UnstableCommand,
+                        ErrorContext::UnstableCommand(
+                            command,
+                            self.flag_word(symbol::UNSTABLE),
+                        ),
                         self.use_color,
                     ))
                 }
+            },
+            Availability::Deprecated { since, note } => {
+                self.warnings.push(match note {
+                    Some(n) => format!("subcommand '{}' is deprecated since {}: {}", command, since, n),
+                    None => format!("subcommand '{}' is deprecated since {}", command, since),
+                });
+                Ok(())
             }
-            0 => Ok(None),
-            _ => {
+            Availability::Removed { since, note } => {
                 self.prioritize_help()?;
                 Err(Error::new(
                     self.help.clone(),
-                    ErrorKind::DuplicateOptions,
-                    ErrorContext::FailedArg(self.known_args.pop().unwrap()),
+                    ErrorKind::RemovedCommand,
+                    ErrorContext::RemovedCommand(command, since, note),
                     self.use_color,
                 ))
             }
         }
     }
 
-    /// Queries for up to `n` values behind an `Optional`.
+    /// Stops reading the current command's flags and options at its first
+    /// operand, leaving every argument from there on to its positionals.
     ///
-    /// Errors if a parsing fails from string or if the number of detected optionals is > n.
-    pub fn check_option_n<'a, T: FromStr>(
-        &mut self,
-        o: Optional,
-        n: usize,
-    ) -> Result<Option<Vec<T>>, Error>
-    where
-        <T as FromStr>::Err: 'static + std::error::Error,
-    {
-        let values = self.check_option_all::<T>(o)?;
-        match values {
-            // verify the size of the vector does not exceed `n`
-            Some(r) => match r.len() <= n {
-                true => Ok(Some(r)),
-                false => Err(Error::new(
-                    self.help.clone(),
-                    ErrorKind::ExceedingMaxCount,
-                    ErrorContext::ExceededThreshold(self.known_args.pop().unwrap(), r.len(), n),
-                    self.use_color,
-                )),
-            },
-            None => Ok(None),
-        }
+    /// Call this at the start of the `from_cli` of a command with a
+    /// [trailing](Positional::trailing) positional, so the flags it forwards are
+    /// not read as its own even when they are queried before it. A word directly
+    /// after a flag that was not queried yet may be that flag's value, so it does
+    /// not end the flags. The setting ends at the next subcommand.
+    pub fn stop_at_operand(&mut self) -> () {
+        self.operands = true;
     }
 
-    /// Queries for all values behind an `Optional`.
+    /// Requires the most recently matched subcommand to run inside the
+    /// [Workspace] `ws`, returning the discovered root.
     ///
-    /// Errors if a parsing fails from string.
-    pub fn check_option_all<'a, T: FromStr>(
-        &mut self,
-        o: Optional,
-    ) -> Result<Option<Vec<T>>, Error>
-    where
-        <T as FromStr>::Err: 'static + std::error::Error,
-    {
-        // collect information on where the flag can be found
-        let mut locs = self.take_flag_locs(o.get_flag().get_name());
-        if let Some(c) = o.get_flag().get_switch() {
-            locs.extend(self.take_switch_locs(c));
-        }
-        self.known_args.push(Arg::Optional(o));
-        // pull values from where the option flags were found (including switch)
-        let values = self.pull_flag(locs, true);
-        if values.is_empty() == true {
-            return Ok(None);
+    /// Call this in the `from_cli` of each command that works on a project and
+    /// keep the root to locate its files. The root is discovered from the current
+    /// directory while parsing and, if the workspace is
+    /// [entered](Workspace::enter), becomes the current directory after parsing
+    /// succeeds. A workspace declared by a nested subcommand replaces one
+    /// declared by its parent.
+    ///
+    /// Errors if neither the current directory nor any of its parents contains
+    /// the marker.
+    pub fn check_workspace(&mut self, ws: Workspace) -> Result<PathBuf, Error> {
+        let watch = self.clock.stopwatch();
+        let root = ws.resolve();
+        if let Some(timings) = &self.timings {
+            timings.record("workspace", watch.elapsed());
         }
-        // try to convert each value into the type T
-        let mut transform = Vec::<T>::with_capacity(values.len());
-        for val in values {
-            if let Some(word) = val {
-                let result = word.parse::<T>();
-                match result {
-                    Ok(r) => transform.push(r),
-                    Err(err) => {
-                        self.prioritize_help()?;
-                        return Err(Error::new(
-                            self.help.clone(),
-                            ErrorKind::BadType,
-                            ErrorContext::FailedCast(
-                                self.known_args.pop().unwrap(),
-                                word,
-                                Box::new(err),
-                            ),
-                            self.use_color,
-                        ));
-                    }
-                }
-            } else {
-                self.prioritize_help()?;
-                return Err(Error::new(
-                    self.help.clone(),
-                    ErrorKind::ExpectingValue,
-                    ErrorContext::FailedArg(self.known_args.pop().unwrap()),
-                    self.use_color,
-                ));
+        match root {
+            Ok(root) => {
+                self.workspace = Some((ws, root.clone()));
+                Ok(root)
             }
-        }
-        Ok(Some(transform))
-    }
-
-    /// Queries if a flag was raised once and only once.
-    ///
-    /// Errors if the flag has an attached value or was raised multiple times.
-    pub fn check_flag<'a>(&mut self, f: Flag) -> Result<bool, Error> {
-        let occurences = self.check_flag_all(f)?;
-        match occurences > 1 {
-            true => {
+            Err(context) => {
                 self.prioritize_help()?;
                 Err(Error::new(
                     self.help.clone(),
-                    ErrorKind::DuplicateOptions,
-                    ErrorContext::FailedArg(self.known_args.pop().unwrap()),
+                    ErrorKind::MissingWorkspace,
+                    context,
                     self.use_color,
                 ))
             }
-            // the flag was either raised once or not at all
-            false => Ok(occurences == 1),
         }
     }
 
-    /// Queries for the number of times a flag was raised.
+    /// Bounds the size of the input and the effort spent on spelling suggestions.
     ///
-    /// Errors if the flag has an attached value. Returning a zero indicates the flag was never raised.
-    pub fn check_flag_all<'a>(&mut self, f: Flag) -> Result<usize, Error> {
-        // collect information on where the flag can be found
-        let mut locs = self.take_flag_locs(f.get_name());
-        // try to find the switch locations
-        if let Some(c) = f.get_switch() {
-            locs.extend(self.take_switch_locs(c));
-        };
-        self.known_args.push(Arg::Flag(f));
-        let mut occurences = self.pull_flag(locs, false);
-        // verify there are no values attached to this flag
-        if let Some(val) = occurences.iter_mut().find(|p| p.is_some()) {
-            self.prioritize_help()?;
+    /// This must be set before calling [tokenize](Cli::tokenize). Input exceeding
+    /// the [Limits] is discarded and the next query returns a `LimitsExceeded` error.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns the error for input discarded for exceeding the [Limits].
+    fn check_limits(&self) -> Result<(), Error> {
+        if let Some(text) = &self.invalid_unicode {
             return Err(Error::new(
                 self.help.clone(),
-                ErrorKind::UnexpectedValue,
-                ErrorContext::UnexpectedValue(self.known_args.pop().unwrap(), val.take().unwrap()),
+                ErrorKind::InvalidUnicode,
+                ErrorContext::InvalidUnicode(text.clone()),
                 self.use_color,
             ));
-        } else {
-            let raised = occurences.len() != 0;
-            // check if the user is asking for help by raising the help flag
-            if let Some(hp) = &self.help {
-                if raised == true
-                    && hp.get_flag().get_name()
-                        == self
-                            .known_args
-                            .last()
-                            .unwrap()
-                            .as_flag()
-                            .unwrap()
-                            .get_name()
-                {
-                    self.asking_for_help = true;
-                }
-            }
-            // return the number of times the flag was raised
-            Ok(occurences.len())
         }
-    }
-
-    /// Queries for the number of times a flag was raised up until `n` times.
-    ///
-    /// Errors if the flag has an attached value. Returning a zero indicates the flag was never raised.
-    pub fn check_flag_n<'a>(&mut self, f: Flag, n: usize) -> Result<usize, Error> {
-        let occurences = self.check_flag_all(f)?;
-        // verify the size of the vector does not exceed `n`
-        match occurences <= n {
-            true => Ok(occurences),
-            false => Err(Error::new(
+        match &self.exceeded {
+            Some((unit, max)) => Err(Error::new(
                 self.help.clone(),
-                ErrorKind::ExceedingMaxCount,
-                ErrorContext::ExceededThreshold(self.known_args.pop().unwrap(), occurences, n),
+                ErrorKind::LimitsExceeded,
+                ErrorContext::LimitsExceeded(unit.to_string(), *max),
                 self.use_color,
             )),
+            None => Ok(()),
         }
     }
 
-    /// Transforms the list of `known_args` into a list of the names for every available
-    /// flag.
+    /// Sets the [NumberFormat] of arguments marked as
+    /// [localized](Optional::localized), such as [NumberFormat::detect] for the
+    /// user's locale.
+    pub fn number_format(mut self, format: NumberFormat) -> Self {
+        self.number_format = format;
+        self
+    }
+
+    /// Forces the flag called `name` to be raised once, rejecting any attempt by
+    /// the user to supply it.
     ///
-    /// This method is useful for acquiring a word bank to offer a flag spelling suggestion.
-    fn known_args_as_flag_names(&self) -> HashSet<&str> {
-        // note: collect into a `std::collections::HashSet` to avoid dupe
-        self.known_args
-            .iter()
-            .filter_map(|f| match f {
-                Arg::Flag(f) => Some(f.get_name()),
-                Arg::Optional(o) => Some(o.get_flag().get_name()),
-                _ => None,
-            })
-            .collect()
+    /// An embedding application freezes the arguments its policy depends on, such
+    /// as a server that always runs commands with `--sandbox`.
+    pub fn freeze_flag<T: AsRef<str>>(mut self, name: T) -> Self {
+        self.frozen.push((name.as_ref().to_string(), None));
+        self
     }
 
-    /// Returns the first index where a flag/switch still remains in the token stream.
+    /// Forces the option called `name` to take `value`, rejecting any attempt by
+    /// the user to supply or override it.
+    pub fn freeze_option<T: AsRef<str>, U: AsRef<str>>(mut self, name: T, value: U) -> Self {
+        self.frozen
+            .push((name.as_ref().to_string(), Some(value.as_ref().to_string())));
+        self
+    }
+
+    /// Sets the [Config] supplying defaults for options not given on the command
+    /// line.
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the [Clock] supplying the time and random numbers to time-dependent
+    /// features.
     ///
-    /// The flag must occur in the token stream before the `breakpoint` index. If
-    /// the `opt_store` hashmap is empty, it will return none.
-    fn find_first_flag_left(&self, breakpoint: usize) -> Option<(&str, usize)> {
-        let mut min_i: Option<(&str, usize)> = None;
-        let mut opt_it = self
-            .opt_store
-            .iter()
-            .filter(|(_, slot)| slot.is_visited() == false);
-        while let Some((key, val)) = opt_it.next() {
-            // check if this flag's index comes before the currently known minimum index
-            min_i = if *val.first().unwrap() < breakpoint
-                && (min_i.is_none() || min_i.unwrap().1 > *val.first().unwrap())
-            {
-                Some((key.as_ref(), *val.first().unwrap()))
-            } else {
-                min_i
-            };
+    /// The clock also measures the phases summarized by the `--timings` flag.
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.timings = self.timings.map(|t| t.clock(clock.clone()));
+        self.clock = clock;
+        self
+    }
+
+    /// Freezes the time and random sources when `enabled`, so runs are
+    /// reproducible in tests and replays, or restores the system sources
+    /// otherwise.
+    pub fn deterministic(self, enabled: bool) -> Self {
+        match enabled {
+            true => self.clock(Clock::frozen()),
+            false => self.clock(Clock::system()),
         }
-        min_i
     }
 
-    /// Verifies there are no uncaught flags behind a given index.
-    fn capture_bad_flag<'a>(&self, i: usize) -> Result<Option<(&str, &str, usize)>, Error> {
-        if let Some((key, val)) = self.find_first_flag_left(i) {
-            self.prioritize_help()?;
-            // check what type of token it was to determine if it was called with '-' or '--'
-            if let Some(t) = self.tokens.get(val).unwrap() {
-                let prefix = match t {
-                    Token::Switch(_, _) | Token::EmptySwitch(_) => symbol::SWITCH,
-                    Token::Flag(_) => {
-                        // try to match it with a valid flag from word bank
-                        let bank: Vec<&str> = self.known_args_as_flag_names().into_iter().collect();
-                        if let Some(closest) = if self.threshold > 0 {
-                            seqalin::sel_min_edit_str(key, &bank, self.threshold)
-                        } else {
-                            None
-                        } {
-                            return Err(Error::new(
-                                self.help.clone(),
-                                ErrorKind::SuggestArg,
-                                ErrorContext::SuggestWord(
-                                    format!("{}{}", symbol::FLAG, key),
-                                    format!("{}{}", symbol::FLAG, closest),
-                                ),
-                                self.use_color,
-                            ));
-                        }
-                        symbol::FLAG
-                    }
-                    _ => panic!("no other tokens are allowed in hashmap"),
-                };
-                Ok(Some((prefix, key, val)))
-            } else {
-                panic!("this token's values have been removed")
+    pub fn get_clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Selects which classes of spelling suggestions are offered in errors.
+    pub fn suggestions(mut self, config: SuggestionConfig) -> Self {
+        self.suggestions = config;
+        self
+    }
+
+    /// Checks if suggestions for the `class` of `word` are enabled and comparing
+    /// it against `bank` stays within the suggestion budget of the [Limits].
+    fn allows_suggestion<T: AsRef<str>>(&self, class: Class, word: &str, bank: &[T]) -> bool {
+        self.suggestions.is_enabled(class) == true
+            && self.limits.allows_suggestion(word, bank) == true
+    }
+
+    /// Selects the word in `bank` closest to `word` within the threshold.
+    ///
+    /// Bypasses the sequence alignment algorithm if the threshold is 0 or the
+    /// suggestion is not allowed for the `class` of word.
+    fn suggest<'a, T: AsRef<str>>(
+        &self,
+        class: Class,
+        word: &str,
+        bank: &'a [T],
+    ) -> Option<&'a str> {
+        if self.threshold.is_disabled() == true || self.allows_suggestion(class, word, bank) == false {
+            return None;
+        }
+        self.record(Metric::Suggestions, 1);
+        match self.suggestions.is_ignoring_case() {
+            true => {
+                let folded = Self::fold_case(bank);
+                let word = word.to_lowercase();
+                let closest = seqalin::sel_min_edit_str_within(&word, &folded, |w| {
+                    self.threshold.threshold(&word, w)
+                })?;
+                let i = folded.iter().position(|w| w == closest)?;
+                Some(bank[i].as_ref())
             }
-        } else {
-            Ok(None)
+            false => seqalin::sel_min_edit_str_within(word, bank, |w| self.threshold.threshold(word, w)),
         }
     }
 
-    /// Verifies there are no more tokens remaining in the stream.
-    ///
-    /// Note this mutates the referenced self only if an error is found.
-    pub fn is_empty<'a>(&'a self) -> Result<(), Error> {
-        self.prioritize_help()?;
-        // check if map is empty, and return the minimum found index.
-        if let Some((prefix, key, _)) = self.capture_bad_flag(self.tokens.len())? {
-            Err(Error::new(
-                self.help.clone(),
-                ErrorKind::UnexpectedArg,
-                ErrorContext::UnexpectedArg(format!("{}{}", prefix, key)),
-                self.use_color,
-            ))
-        // find first non-none token
-        } else if let Some(t) = self.tokens.iter().find(|p| p.is_some()) {
-            match t {
-                Some(Token::UnattachedArgument(_, word)) => Err(Error::new(
-                    self.help.clone(),
-                    ErrorKind::UnexpectedArg,
-                    ErrorContext::UnexpectedArg(word.to_string()),
-                    self.use_color,
-                )),
-                Some(Token::Terminator(_)) => Err(Error::new(
-                    self.help.clone(),
-                    ErrorKind::UnexpectedArg,
-                    ErrorContext::UnexpectedArg(symbol::FLAG.to_string()),
-                    self.use_color,
-                )),
-                _ => panic!("no other tokens types should be left"),
+    /// Selects up to `n` words in `bank` closest to `word`, ignoring case if
+    /// requested by the [SuggestionConfig].
+    fn nearest<'a, T: AsRef<str>>(&self, word: &str, bank: &'a [T], n: usize) -> Vec<&'a str> {
+        match self.suggestions.is_ignoring_case() {
+            true => {
+                let folded = Self::fold_case(bank);
+                seqalin::sel_nearest_strs(&word.to_lowercase(), &folded, n)
+                    .into_iter()
+                    .filter_map(|w| folded.iter().position(|f| f == w))
+                    .map(|i| bank[i].as_ref())
+                    .collect()
             }
-        } else {
-            Ok(())
+            false => seqalin::sel_nearest_strs(word, bank, n),
         }
     }
 
-    /// Grabs the flag/switch from the token stream, and collects.
+    fn fold_case<T: AsRef<str>>(bank: &[T]) -> Vec<String> {
+        bank.iter().map(|w| w.as_ref().to_lowercase()).collect()
+    }
+
+    /// Sets the maximum threshold value when comparing strings for character similiarity.
+    pub fn threshold(mut self, cost: Cost) -> Self {
+        self.threshold = ThresholdPolicy::Fixed(cost);
+        self
+    }
+
+    /// Scales the threshold with the length of the misspelled word, allowing
+    /// one edit for every `n` characters instead of a fixed cost.
     ///
-    /// If an argument were to follow it will be in the vector.
-    fn pull_flag(&mut self, locations: Vec<usize>, with_uarg: bool) -> Vec<Option<String>> {
-        // remove all flag instances located at each index `i` in the vector `locations`
-        locations
-            .iter()
-            .map(|i| {
-                // remove the flag instance from the token stream
-                self.tokens.get_mut(*i).unwrap().take();
-                // check the next position for a value
-                if let Some(t_next) = self.tokens.get_mut(*i + 1) {
-                    match t_next {
-                        Some(Token::AttachedArgument(_, _)) => {
-                            Some(t_next.take().unwrap().take_str())
-                        }
-                        Some(Token::UnattachedArgument(_, _)) => {
-                            // do not take unattached arguments unless told by parameter
-                            match with_uarg {
-                                true => Some(t_next.take().unwrap().take_str()),
-                                false => None,
-                            }
-                        }
-                        _ => None,
-                    }
-                } else {
-                    None
-                }
-            })
-            .collect()
+    /// With `n` set to 4, `ad` is only matched exactly while `--verbsoe` may be
+    /// two edits away from its suggestion. This replaces any fixed
+    /// [threshold](Cli::threshold).
+    pub fn relative_threshold(self, n: usize) -> Self {
+        self.threshold_policy(ThresholdPolicy::Relative(n))
     }
 
-    /// Removes the ignored tokens from the stream, if they exist.
+    /// Decides how close a known word must be to a misspelled word to be
+    /// suggested, replacing any fixed [threshold](Cli::threshold).
+    pub fn threshold_policy(mut self, policy: ThresholdPolicy) -> Self {
+        self.threshold = policy;
+        self
+    }
+
+    /// Enables the coloring for error messages.
     ///
-    /// Errors if an `AttachedArg` is found (could only be immediately after terminator)
-    /// after the terminator.
-    pub fn check_remainder(&mut self) -> Result<Vec<String>, Error> {
-        self.tokens
-            .iter_mut()
-            .skip_while(|tkn| match tkn {
-                Some(Token::Terminator(_)) => false,
-                _ => true,
-            })
-            .filter_map(|tkn| {
-                match tkn {
-                    // remove the terminator from the stream
-                    Some(Token::Terminator(_)) => {
-                        tkn.take().unwrap();
-                        None
-                    }
-                    Some(Token::Ignore(_, _)) => Some(Ok(tkn.take().unwrap().take_str())),
-                    Some(Token::AttachedArgument(_, _)) => Some(Err(Error::new(
-                        self.help.clone(),
-                        ErrorKind::UnexpectedValue,
-                        ErrorContext::UnexpectedValue(
-                            Arg::Flag(Flag::new("")),
-                            tkn.take().unwrap().take_str(),
-                        ),
-                        self.use_color,
-                    ))),
-                    _ => panic!("no other tokens should exist beyond terminator {:?}", tkn),
+    /// This is enabled by default. Colors are still only written when
+    /// [should_color](crate::style::should_color) allows them for the stream the
+    /// error is written to. Errors rendered with [Error::styled] decide on colors
+    /// separately.
+    #[cfg(feature = "color")]
+    pub fn use_color(&mut self) -> () {
+        self.use_color = true;
+    }
+
+    /// Disables the coloring for error messages.
+    ///
+    /// Errors rendered with [Error::styled] decide on colors separately.
+    #[cfg(feature = "color")]
+    pub fn disable_color(&mut self) -> () {
+        self.use_color = false;
+    }
+
+    /// Disables the coloring for error messages.
+    ///
+    /// Errors rendered with [Error::styled] decide on colors separately.
+    #[cfg(feature = "color")]
+    pub fn no_color(mut self) -> Self {
+        self.disable_color();
+        self
+    }
+
+    /// Enables the coloring for error messages.
+    ///
+    /// This is enabled by default. Colors are still only written when
+    /// [should_color](crate::style::should_color) allows them for the stream the
+    /// error is written to. Errors rendered with [Error::styled] decide on colors
+    /// separately.
+    #[cfg(feature = "color")]
+    pub fn color(mut self) -> Self {
+        self.use_color();
+        self
+    }
+
+    /// Checks for the conventional `--color <when>` option and applies the
+    /// [ColorChoice] it selects, which is `auto` when not supplied.
+    ///
+    /// The choice overrides the color decision of the [style] module for
+    /// everything written afterward, and `never` also disables the coloring for
+    /// error messages.
+    pub fn check_color(&mut self) -> Result<ColorChoice, Error> {
+        let choice = self.read_color()?;
+        choice.apply();
+        Ok(choice)
+    }
+
+    /// Reads the `--color <when>` option as with [check_color](Cli::check_color)
+    /// without overriding the color decision of the [style] module.
+    fn read_color(&mut self) -> Result<ColorChoice, Error> {
+        let choice = self
+            .check_option::<ColorChoice>(ColorChoice::arg())?
+            .unwrap_or_default();
+        if choice == ColorChoice::Never {
+            self.use_color = false;
+        }
+        Ok(choice)
+    }
+
+    /// Sets the [Help] attribute to display and checks if help has already been raised in the token stream.
+    pub fn check_help(&mut self, help: Help) -> Result<(), Error> {
+        let help = match self.flag_prefix == symbol::FLAG {
+            true => help,
+            false => {
+                let flag = help.get_flag().clone().prefixed(&self.flag_prefix);
+                help.flag(flag)
+            }
+        };
+        self.help = Some(help);
+        // check for flag if not already raised
+        if self.asking_for_help == false && self.is_help_enabled() == true {
+            let flag = self.help.as_ref().unwrap().get_flag().clone();
+            // the long flag asks for the full help and the switch for the quick text
+            let long = self
+                .opt_store
+                .get(&Tag::Flag(flag.get_name().to_string()))
+                .and_then(|slot| slot.first())
+                .is_some();
+            self.asking_for_help = self.check_flag(flag)?;
+            self.long_help = self.asking_for_help == true && long == true;
+        }
+        Ok(())
+    }
+
+    /// Clears the `asking_for_help` status flag.
+    pub fn clear_help(&mut self) -> () {
+        self.asking_for_help = false;
+    }
+
+    /// Directly calls the help error if asking for help is enabled.
+    pub fn raise_help(&self) -> Result<(), Error> {
+        self.prioritize_help()
+    }
+
+    /// Removes the current help text set for the command-line argument parser.
+    pub fn disable_help(&mut self) -> () {
+        self.help = None;
+    }
+
+    /// Downplays the help action to not become a priority error over other errors in the parsing.
+    ///
+    /// Help is prioritized by default.
+    pub fn downplay_help(mut self) -> Self {
+        self.prioritize_help = false;
+        self
+    }
+
+    /// Prioritizes the help action over other errors during parsing.
+    ///
+    /// This is enabled by default.
+    pub fn emphasize_help(mut self) -> Self {
+        self.prioritize_help = true;
+        self
+    }
+
+    /// Checks if help is enabled and is some value.
+    fn is_help_enabled(&self) -> bool {
+        self.help.is_some()
+    }
+
+    /// Checks if help has been raised and will return its own error for displaying
+    /// help.
+    fn prioritize_help(&self) -> Result<(), Error> {
+        if self.prioritize_help == true
+            && self.asking_for_help == true
+            && self.is_help_enabled() == true
+        {
+            Err(self.help_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the error displaying the current help, in full if it was
+    /// requested with the long flag.
+    fn help_error(&self) -> Error {
+        Error::new(
+            self.help.clone().map(|h| h.detailed(self.long_help)),
+            ErrorKind::Help,
+            ErrorContext::Help,
+            self.use_color,
+        )
+    }
+
+    /// Returns the names of the subcommands matched so far, from the outermost
+    /// to the command being read.
+    pub fn get_commands(&self) -> &[String] {
+        &self.commands
+    }
+
+    /// Returns the program's file name followed by the subcommands matched so
+    /// far, such as `orbit new ip`, for the usage and help of a nested command.
+    pub fn command_path(&self) -> String {
+        let program = self
+            .program
+            .as_ref()
+            .map(|p| match std::path::Path::new(p).file_name() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => p.clone(),
+            });
+        program
+            .into_iter()
+            .chain(self.commands.iter().cloned())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// Composes the usage line from the arguments learned within the current
+    /// command, such as `orbit add [options] <lhs> <rhs>`, beginning with the
+    /// [command path](Cli::command_path).
+    ///
+    /// Only the arguments read so far are known, so the line ends at the last
+    /// positional the command asked for.
+    pub fn usage(&self) -> String {
+        let mut words = vec![self.command_path()];
+        let args = &self.known_args[self.scope.min(self.known_args.len())..];
+        if args.iter().any(|a| a.as_positional().is_none()) == true {
+            words.push(String::from("[options]"));
+        }
+        args.iter()
+            .filter_map(|a| a.as_positional())
+            .for_each(|p| match p.is_trailing() {
+                true => words.push(format!("{}...", p)),
+                false => words.push(p.to_string()),
+            });
+        format!("Usage:\n    {}", words.join(" ").trim_start())
+    }
+
+    /// Returns the help for a missing positional, falling back to a usage
+    /// composed from the arguments read when the help does not give one.
+    fn usage_help(&self) -> Option<Help> {
+        self.help.clone().map(|h| match h.get_usage() {
+            Some(_) => h,
+            None => h.usage(self.usage()),
+        })
+    }
+
+    /// Pulls the next `UnattachedArg` token from the token stream.
+    ///
+    /// If no more `UnattachedArg` tokens are left, it will return none.
+    fn next_uarg(&mut self) -> Option<String> {
+        if let Some(i) = self.tokens.iter().position(|s| match s {
+            Some(Token::UnattachedArgument(_, _)) | Some(Token::Terminator(_)) => true,
+            _ => false,
+        }) {
+            if let Some(Token::Terminator(_)) = self.tokens[i] {
+                // read the terminator as a literal value only when requested
+                let allowed = self
+                    .known_args
+                    .last()
+                    .and_then(|a| a.as_positional())
+                    .map(|p| p.is_terminator_allowed())
+                    .unwrap_or(false);
+                match allowed {
+                    true => {
+                        self.bind(i, Role::Positional);
+                        self.tokens[i] = None;
+                        Some(self.flag_prefix.clone())
+                    }
+                    false => None,
                 }
+            } else {
+                self.bind(i, Role::Positional);
+                Some(self.tokens[i].take().unwrap().take_str())
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Determines if an `UnattachedArg` exists to be served as a subcommand.
+    ///
+    /// If so, it will call `from_cli` on the type defined. If not, it will return none.
+    pub fn check_command<'a, T: FromCli>(
+        &mut self,
+        p: Positional,
+    ) -> Result<Option<T>, Error> {
+        self.check_limits()?;
+        self.known_args.push(Arg::Positional(p));
+        // check but do not remove if an unattached arg exists
+        let command_exists = self
+            .tokens
+            .iter()
+            .position(|f| match f {
+                Some(Token::UnattachedArgument(_, _)) => true,
+                _ => false,
             })
-            .collect()
+            // escaped values are never subcommands
+            .filter(|loc| self.is_escaped(*loc) == false)
+            .is_some();
+        if command_exists {
+            Ok(Some(T::from_cli(self)?))
+        } else {
+            return Ok(None);
+        }
+    }
+
+    /// Resolves `command` to the one word in `words` it is a prefix of, if
+    /// [command prefixes](Cli::allow_command_prefixes) are allowed.
+    ///
+    /// Returns `command` unchanged if no word begins with it. Errors if more
+    /// than one does, pointing to the token at `loc`.
+    fn expand_command_prefix<T: AsRef<str>>(
+        &self,
+        command: String,
+        words: &[T],
+        loc: usize,
+    ) -> Result<String, Error> {
+        if self.command_prefixes == false || command.is_empty() == true {
+            return Ok(command);
+        }
+        let candidates: Vec<String> = words
+            .iter()
+            .map(|w| w.as_ref())
+            .filter(|w| w.starts_with(command.as_str()))
+            .map(|w| w.to_string())
+            .collect();
+        match candidates.len() {
+            0 => Ok(command),
+            1 => Ok(candidates.into_iter().next().unwrap()),
+            _ => {
+                self.prioritize_help()?;
+                Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::AmbiguousSubcommand,
+                    ErrorContext::Ambiguous(command, candidates),
+                    self.use_color,
+                )
+                .with_span(self.span(loc)))
+            }
+        }
+    }
+
+    /// Tries to match the next `UnattachedArg` with a list of given `words`.
+    ///
+    /// If fails, it will attempt to offer a spelling suggestion if the name is close.
+    ///
+    /// Unless `help` is one of the `words`, it is read as a pseudo-subcommand
+    /// when help is [enabled](Cli::check_help): `orbit help new ip` shows the
+    /// help of `ip` as `orbit new ip --help` does, and `orbit help` shows the
+    /// current command's help.
+    ///
+    /// Panics if there is not a next `UnattachedArg`. It is recommended to not directly call
+    /// this command, but through a `from_cli` call after `check_command` has been issued.
+    pub fn match_command<T: AsRef<str> + std::cmp::PartialEq>(
+        &mut self,
+        words: &[T],
+    ) -> Result<String, Error> {
+        // find the unattached arg's index before it is removed from the token stream
+        let (loc, i): (usize, usize) = self
+            .tokens
+            .iter()
+            .enumerate()
+            .find_map(|(loc, f)| match f {
+                Some(Token::UnattachedArgument(i, _)) => Some((loc, *i)),
+                _ => None,
+            })
+            .expect("an unattached argument must exist before calling `match_command`");
+        let command = self
+            .next_uarg()
+            .expect("`check_command` must be called before this function");
+        self.bind(loc, Role::Subcommand);
+        // read `help <command>...` as the command path with the help flag raised
+        if command == symbol::HELP
+            && self.is_help_enabled() == true
+            && words.iter().any(|w| w.as_ref() == symbol::HELP) == false
+        {
+            self.asking_for_help = true;
+            self.long_help = true;
+            let next = self
+                .tokens
+                .iter()
+                .position(|t| matches!(t, Some(Token::UnattachedArgument(_, _))))
+                .filter(|loc| self.is_escaped(*loc) == false);
+            return match next {
+                Some(_) => self.match_command(words),
+                None => Err(self.help_error()),
+            };
+        }
+        // perform partial clean to ensure no arguments are remaining behind the command (uncaught options)
+        let ooc_arg = self.capture_bad_flag(i)?;
+        // expand an abbreviated command to the single word it begins
+        let command = match words.iter().any(|p| p.as_ref() == command) {
+            true => command,
+            false => self.expand_command_prefix(command, words, loc)?,
+        };
+
+        if words.iter().find(|p| p.as_ref() == command).is_some() {
+            if let Some((prefix, key, pos)) = ooc_arg {
+                if pos < i {
+                    self.prioritize_help()?;
+                    return Err(Error::new(
+                        self.help.clone(),
+                        ErrorKind::OutOfContextArgSuggest,
+                        ErrorContext::OutofContextArgSuggest(format!("{}{}", prefix, key), command),
+                        self.use_color,
+                    )
+                    .with_span(self.span(pos)));
+                }
+            }
+            if let Some(a) = self.known_args.last() {
+                self.matches.push_value(a.get_name(), &command);
+            }
+            // arguments learned from here on belong to the subcommand
+            self.scope = self.known_args.len();
+            self.boundary = Some(loc);
+            self.operands = self.posix;
+            self.commands.push(command.clone());
+            Ok(command)
+        // try to offer a spelling suggestion otherwise say we've hit an unexpected argument
+        } else {
+            if let Some(w) = self.suggest(Class::Subcommand, &command, words) {
+                Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::SuggestSubcommand,
+                    ErrorContext::SuggestWord(command, w.to_string()),
+                    self.use_color,
+                )
+                .with_span(self.span(loc)))
+            } else {
+                self.prioritize_help()?;
+                Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::UnknownSubcommand,
+                    ErrorContext::UnknownSubcommand(
+                        self.known_args.pop().expect("requires positional argument"),
+                        command,
+                    ),
+                    self.use_color,
+                )
+                .with_span(self.span(loc)))
+            }
+        }
     }
 
-    /// Returns all locations in the token stream where the flag identifier `tag` is found.
-    ///
-    /// Information about Option<Vec<T>> vs. empty Vec<T>: https://users.rust-lang.org/t/space-time-usage-to-construct-vec-t-vs-option-vec-t/35596/6
-    fn take_flag_locs(&mut self, tag: &str) -> Vec<usize> {
-        if let Some(slot) = self.opt_store.get_mut(&Tag::Flag(tag.to_owned())) {
-            slot.visit();
-            slot.get_indices().to_vec()
-        } else {
-            Vec::new()
-        }
-    }
+    /// Serves the next `Positional` value in the token stream parsed as `T`.
+    ///
+    /// Errors if parsing fails. If the next argument is not a positional, it will
+    /// not move forward in the token stream.
+    pub fn check_positional<'a, T: ValueParse>(
+        &mut self,
+        p: Positional,
+    ) -> Result<Option<T>, Error> {
+        let result = self.read_positional(p);
+        self.defer(result, None)
+    }
+
+    /// Serves the next `Positional` value as with [check_positional](Cli::check_positional)
+    /// without setting aside the error when [collecting errors](Cli::collect_errors).
+    fn read_positional<T: ValueParse>(&mut self, p: Positional) -> Result<Option<T>, Error> {
+        self.check_limits()?;
+        self.known_args.push(Arg::Positional(p));
+        self.check_list_choices()?;
+        self.try_positional()
+    }
+
+    /// Attempts to extract the next unattached argument to get a positional with valid parsing.
+    ///
+    /// Assumes the [Positional] argument is already added as the last element to the `known_args` vector.
+    fn try_positional<'a, T: ValueParse>(&mut self) -> Result<Option<T>, Error> {
+        self.settle_operands();
+        match self.next_uarg() {
+            Some(word) => match self.parse_value(word) {
+                Ok(r) => Ok(Some(r)),
+                Err(err) => {
+                    self.prioritize_suggestion()?;
+                    Err(err)
+                }
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Parses a `word` given to the most recently learned argument into `T`.
+    ///
+    /// Any value rules declared on the argument are checked before the conversion.
+    fn parse_value<T: ValueParse>(&mut self, word: String) -> Result<T, Error> {
+        let meta = self.known_args.last().and_then(|a| a.as_positional()).map(|p| p.get_meta());
+        // locate the word as the user wrote it, before it is canonicalized
+        let raw = word.clone();
+        let word = match meta {
+            Some(m) => m.canonicalize(word),
+            None => word,
+        };
+        let secret = meta.map(|m| m.secret).unwrap_or(false);
+        let shown = |word: &String| match secret {
+            true => self.matches.get_placeholder().to_string(),
+            false => word.clone(),
+        };
+        if let Some(check) = meta.and_then(|m| m.validator.as_ref()) {
+            if let Err(reason) = check.get()(&word) {
+                let word = shown(&word);
+                let span = self.value_span(&raw);
+                self.prioritize_help()?;
+                return Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::InvalidValue,
+                    ErrorContext::InvalidValue(self.known_args.pop().unwrap(), word, reason),
+                    self.use_color,
+                )
+                .with_span(span));
+            }
+        }
+        if let Some(choices) = meta.map(|m| &m.values).filter(|v| v.is_empty() == false) {
+            if choices.contains(&word) == false {
+                // choices close to a secret would hint at what was typed
+                let suggestion = match secret {
+                    true => None,
+                    false => self.suggest(Class::Value, &word, choices).map(|w| w.to_string()),
+                };
+                let span = self.value_span(&raw);
+                let hinted = secret == false && self.allows_suggestion(Class::Value, &word, choices);
+                let word = shown(&word);
+                // point to the listing flag instead of writing out a long list
+                let context = if choices.len() >= CHOICE_LIST_LIMIT {
+                    let nearest = match hinted {
+                        true => {
+                            self.record(Metric::Suggestions, 1);
+                            self.nearest(&word, choices, NEAREST_CHOICES)
+                                .into_iter()
+                                .map(|w| w.to_string())
+                                .collect()
+                        }
+                        false => Vec::new(),
+                    };
+                    let list = self.flag_word(&Self::list_flag_name(self.known_args.last().unwrap()));
+                    ErrorContext::TruncatedChoice(self.known_args.pop().unwrap(), word, nearest, list)
+                } else {
+                    let choices = choices.clone();
+                    ErrorContext::InvalidChoice(self.known_args.pop().unwrap(), word, choices, suggestion)
+                };
+                self.prioritize_help()?;
+                return Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::InvalidChoice,
+                    context,
+                    self.use_color,
+                )
+                .with_span(span));
+            }
+        }
+        let text = match meta.map(|m| m.localized).unwrap_or(false) {
+            true => self.number_format.normalize(&word),
+            false => Ok(word.clone()),
+        };
+        let arg = self.known_args.last().unwrap();
+        match text.and_then(|text| T::parse(arg, &text).map(|r| (r, text))) {
+            Ok((r, text)) => {
+                let name = self.known_args.last().unwrap().get_name();
+                match secret {
+                    true => self.matches.push_secret(name, text),
+                    false => self.matches.push_value(name, text),
+                }
+                self.record(Metric::Values, 1);
+                Ok(r)
+            }
+            Err(err) => {
+                let word = shown(&word);
+                let span = self.value_span(&raw);
+                self.prioritize_help()?;
+                let arg = self.known_args.pop().unwrap();
+                let (kind, context) = match err.into_reason() {
+                    Reason::Failed(err) => {
+                        (ErrorKind::BadType, ErrorContext::FailedCast(arg, word, err))
+                    }
+                    Reason::Invalid(reason) => {
+                        (ErrorKind::InvalidValue, ErrorContext::InvalidValue(arg, word, reason))
+                    }
+                };
+                Err(Error::new(self.help.clone(), kind, context, self.use_color).with_span(span))
+            }
+        }
+    }
+
+    /// Names the flag that lists every possible value of the argument `a`.
+    fn list_flag_name(a: &Arg) -> String {
+        let p = a.as_positional().unwrap();
+        match &p.get_meta().list_flag {
+            Some(name) => name.clone(),
+            None => format!("list-{}s", p.get_name()),
+        }
+    }
+
+    /// Checks if the user asked to see every possible value of the most recently
+    /// learned argument by raising its listing flag.
+    ///
+    /// The flag only exists for arguments with at least [CHOICE_LIST_LIMIT] possible values.
+    fn check_list_choices(&mut self) -> Result<(), Error> {
+        let choices = match self.known_args.last().and_then(|a| a.as_positional()) {
+            Some(p) if p.get_possible_values().len() >= CHOICE_LIST_LIMIT => {
+                p.get_possible_values().clone()
+            }
+            _ => return Ok(()),
+        };
+        let locs = self.take_flag_locs(&Self::list_flag_name(self.known_args.last().unwrap()));
+        if locs.is_empty() == true {
+            return Ok(());
+        }
+        self.pull_flag(locs, false);
+        self.prioritize_help()?;
+        Err(Error::new(
+            self.help.clone(),
+            ErrorKind::ListChoices,
+            ErrorContext::ListChoices(choices),
+            self.use_color,
+        ))
+    }
+
+    /// Forces the next [Positional] to exist from token stream.
+    ///
+    /// Errors if parsing fails or if no unattached argument is left in the token stream.
+    pub fn require_positional<'a, T: ValueParse>(&mut self, p: Positional) -> Result<T, Error> {
+        if let Some(value) = self.read_positional(p)? {
+            Ok(value)
+        } else {
+            self.prioritize_help()?;
+            self.is_empty()?;
+            if let Some(value) = self.prompt_value()? {
+                return Ok(value);
+            }
+            Err(Error::new(
+                self.usage_help(),
+                ErrorKind::MissingPositional,
+                ErrorContext::FailedArg(self.known_args.pop().unwrap()),
+                self.use_color,
+            ))
+        }
+    }
+
+    /// Forces all the next [Positional] to be captured from the token stream.
+    ///
+    /// Errors if parsing fails or if zero unattached arguments are left in the token stream to begin.
+    ///
+    /// The resulting vector is guaranteed to have `.len() >= 1`.
+    pub fn require_positional_all<'a, T: ValueParse>(
+        &mut self,
+        p: Positional,
+    ) -> Result<Vec<T>, Error> {
+        if p.is_trailing() == true {
+            let result = self.read_positional_all(p)?;
+            if result.is_empty() == true {
+                self.prioritize_help()?;
+                self.is_empty()?;
+                return Err(Error::new(
+                    self.usage_help(),
+                    ErrorKind::MissingPositional,
+                    ErrorContext::FailedArg(self.known_args.pop().unwrap()),
+                    self.use_color,
+                ));
+            }
+            return Ok(result);
+        }
+        let mut result = Vec::<T>::new();
+        result.push(self.require_positional(p)?);
+        while let Some(v) = self.try_positional()? {
+            result.push(v);
+        }
+        Ok(result)
+    }
+
+    /// Captures all remaining [Positional] values from the token stream.
+    ///
+    /// Errors if parsing fails. The resulting vector is empty when no unattached
+    /// arguments are left.
+    pub fn check_positional_all<'a, T: ValueParse>(
+        &mut self,
+        p: Positional,
+    ) -> Result<Vec<T>, Error> {
+        let result = self.read_positional_all(p);
+        self.defer(result, Vec::new())
+    }
+
+    /// Captures all remaining [Positional] values as with
+    /// [check_positional_all](Cli::check_positional_all) without setting aside
+    /// the error when [collecting errors](Cli::collect_errors).
+    fn read_positional_all<T: ValueParse>(&mut self, p: Positional) -> Result<Vec<T>, Error> {
+        if p.is_trailing() == true {
+            self.check_limits()?;
+            self.forwards = true;
+            self.operands = true;
+            self.known_args.push(Arg::Positional(p));
+            return self.take_trailing();
+        }
+        let mut result = Vec::<T>::new();
+        if let Some(v) = self.read_positional(p)? {
+            result.push(v);
+            while let Some(v) = self.try_positional()? {
+                result.push(v);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Takes every argument from the next unattached argument onward verbatim,
+    /// parsing each as a value of the last learned [Positional].
+    ///
+    /// The built-in flags and the internal `--clif-` flags are handled wherever
+    /// they appear, so they are not taken.
+    ///
+    /// Errors if one of the arguments was already read by another argument.
+    fn take_trailing<T: ValueParse>(&mut self) -> Result<Vec<T>, Error> {
+        self.settle_operands();
+        let start = match self.tokens.iter().position(|t| {
+            matches!(t, Some(Token::UnattachedArgument(_, _)) | Some(Token::Terminator(_)))
+        }) {
+            Some(i) if matches!(self.tokens[i], Some(Token::UnattachedArgument(_, _))) => i,
+            _ => return Ok(Vec::new()),
+        };
+        let builtins = [self.flag_word(symbol::TIMINGS), self.flag_word(symbol::UNSTABLE)];
+        let taken: Vec<usize> = (start..self.tokens.len())
+            .filter(|i| {
+                let claimed = self.bindings[*i].as_ref().and_then(|b| b.arg.as_ref());
+                self.tokens[*i].is_some() || claimed.is_some_and(|a| builtins.contains(a)) == false
+            })
+            .collect();
+        for &i in &taken {
+            if self.tokens[i].is_none() == true {
+                let claimed = self.bindings[i].as_ref().and_then(|b| b.arg.clone());
+                self.prioritize_help()?;
+                return Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::TrailingConflict,
+                    ErrorContext::TrailingConflict(
+                        self.known_args.pop().unwrap(),
+                        self.lexemes[i].1.clone(),
+                        claimed.unwrap_or_default(),
+                    ),
+                    self.use_color,
+                ));
+            }
+        }
+        for &i in &taken {
+            self.tokens[i] = None;
+            self.bind(i, Role::Positional);
+        }
+        // the captured flags are no longer available to be queried
+        self.opt_store.retain(|_, slot| {
+            slot.pointers.retain(|p| *p < start);
+            slot.get_indices().is_empty() == false
+        });
+        // the internal flags were never tokens, so only the arguments of tokens are taken
+        let mut words: Vec<String> = Vec::new();
+        let mut last = None;
+        for &i in &taken {
+            let arg = self.lexemes[i].0;
+            if last != Some(arg) {
+                words.push(self.args[arg].clone());
+                last = Some(arg);
+            }
+        }
+        words.into_iter().map(|w| self.parse_value(w)).collect()
+    }
+
+    /// Captures all remaining [Positional] values from the token stream, expecting
+    /// at least `min` and at most `max` of them.
+    ///
+    /// Errors if parsing fails or if the number of unattached arguments left in the
+    /// token stream is outside of the bounds. There is no upper bound when `max`
+    /// is `None`.
+    pub fn require_positional_n<'a, T: ValueParse>(
+        &mut self,
+        p: Positional,
+        min: usize,
+        max: Option<usize>,
+    ) -> Result<Vec<T>, Error> {
+        let result = self.read_positional_all(p)?;
+        if result.len() < min {
+            self.prioritize_help()?;
+            self.is_empty()?;
+            return Err(Error::new(
+                self.usage_help(),
+                ErrorKind::MissingPositional,
+                ErrorContext::TooFewValues(self.known_args.pop().unwrap(), result.len(), min),
+                self.use_color,
+            ));
+        }
+        match max {
+            Some(n) if result.len() > n => {
+                self.prioritize_help()?;
+                Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::ExceedingMaxCount,
+                    ErrorContext::ExceededThreshold(self.known_args.pop().unwrap(), result.len(), n),
+                    self.use_color,
+                ))
+            }
+            _ => Ok(result),
+        }
+    }
+
+    /// Iterates through the list of tokens to find the first suggestion against a flag to return.
+    ///
+    /// Returns ok if cannot make a suggestion.
+    fn prioritize_suggestion(&self) -> Result<(), Error> {
+        let mut kv: Vec<(&String, &Vec<usize>)> = self
+            .opt_store
+            .iter()
+            .map(|(tag, slot)| (tag.as_ref(), slot.get_indices()))
+            .collect::<Vec<(&String, &Vec<usize>)>>();
+        kv.sort_by(|a, b| a.1.first().unwrap().cmp(b.1.first().unwrap()));
+        let bank: Vec<&str> = self.known_args_as_flag_names().into_iter().collect();
+        let r = kv
+            .iter()
+            .find_map(|f| match self.tokens.get(*f.1.first().unwrap()).unwrap() {
+                Some(Token::Flag(_)) => {
+                    if let Some(word) = self.suggest(Class::Flag, f.0, &bank) {
+                        Some(Error::new(
+                            self.help.clone(),
+                            ErrorKind::SuggestArg,
+                            ErrorContext::SuggestWord(
+                                self.flag_word(f.0),
+                                self.flag_word(word),
+                            ),
+                            self.use_color,
+                        ))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            });
+        if self.asking_for_help == true {
+            Ok(())
+        } else if let Some(e) = r {
+            Err(e)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Queries for a value of `Optional`.
+    ///
+    /// A switch may carry its value within the same argument, as in `-j4` or
+    /// `-ofile.txt`. Query such options before any flags whose switches could
+    /// appear in the value. When the switch ends a cluster, as in `-xzf a.tgz`,
+    /// the value is the next argument and the earlier switches remain flags.
+    ///
+    /// When the option is not supplied, its default from the [Config] is used
+    /// instead, if any.
+    ///
+    /// Errors if there are multiple values or if parsing fails.
+    pub fn check_option<'a, T: ValueParse>(&mut self, o: Optional) -> Result<Option<T>, Error> {
+        let result = match self.read_option(o) {
+            Ok(Some(value)) => Ok(Some(value)),
+            Ok(None) => self.config_value(),
+            Err(err) => Err(err),
+        };
+        self.defer(result, None)
+    }
+
+    /// Parses the default from the [Config] for the most recently learned
+    /// argument.
+    fn config_value<T: ValueParse>(&mut self) -> Result<Option<T>, Error> {
+        let name = self.known_args.last().unwrap().get_name();
+        match self.config.get(name) {
+            Some(word) => Ok(Some(self.parse_value(word)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the value of `Optional` given on the command line.
+    fn read_option<T: ValueParse>(&mut self, o: Optional) -> Result<Option<T>, Error> {
+        self.check_limits()?;
+        // collect information on where the flag can be found
+        let mut locs = self.take_flag_locs(o.get_flag().get_name());
+        locs.extend(self.take_scoped_switch_locs(o.get_flag())?);
+        self.attach_cluster_values(&locs);
+        self.known_args.push(self.prefixed(Arg::Optional(o)));
+        self.check_list_choices()?;
+        let mut positions = locs.clone();
+        positions.sort();
+        // pull values from where the option flags were found (including switch)
+        let mut values = self.pull_flag(locs, true);
+        self.record_raised(values.len());
+        self.check_unstable(values.len())?;
+        if let Some(fixed) = self.check_frozen(values.len())? {
+            values = vec![fixed];
+        }
+        match values.len() {
+            1 => {
+                if let Some(word) = values.pop().unwrap() {
+                    Ok(Some(self.parse_value(word)?))
+                } else {
+                    self.prioritize_help()?;
+                    Err(Error::new(
+                        self.help.clone(),
+                        ErrorKind::ExpectingValue,
+                        ErrorContext::FailedArg(self.known_args.pop().unwrap()),
+                        self.use_color,
+                    )
+                    .with_span(positions.first().and_then(|i| self.span(*i))))
+                }
+            }
+            0 => Ok(None),
+            _ => {
+                self.prioritize_help()?;
+                // point to the first repeated occurrence
+                Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::DuplicateOptions,
+                    ErrorContext::FailedArg(self.known_args.pop().unwrap()),
+                    self.use_color,
+                )
+                .with_span(positions.get(1).and_then(|i| self.span(*i))))
+            }
+        }
+    }
+
+    /// Queries for a value given either behind an `Optional` or as the next positional.
+    ///
+    /// The option form takes precedence: the positional form is only read when the
+    /// option was not supplied, so `app build x` and `app build --target x` are
+    /// equivalent. Supplying the option more than once is a duplicate error, and an
+    /// unattached argument left over when the option form is used is reported by
+    /// [is_empty](Cli::is_empty). The [Config] default is used only when the
+    /// value is supplied in neither form.
+    pub fn check_option_positional<'a, T: ValueParse>(
+        &mut self,
+        o: Optional,
+    ) -> Result<Option<T>, Error> {
+        let result = self.read_option_positional(o);
+        self.defer(result, None)
+    }
+
+    /// Queries for a value behind an `Optional` or as the next positional as with
+    /// [check_option_positional](Cli::check_option_positional) without setting
+    /// aside the error when [collecting errors](Cli::collect_errors).
+    fn read_option_positional<T: ValueParse>(&mut self, o: Optional) -> Result<Option<T>, Error> {
+        if let Some(value) = self.read_option(o)? {
+            return Ok(Some(value));
+        }
+        // the `Optional` is the most recently learned argument for the fallbacks
+        match self.try_positional()? {
+            Some(value) => Ok(Some(value)),
+            None => self.config_value(),
+        }
+    }
+
+    /// Forces a value to exist either behind an `Optional` or as the next positional.
+    ///
+    /// Errors if parsing fails or if the value is supplied in neither form.
+    pub fn require_option_positional<'a, T: ValueParse>(
+        &mut self,
+        o: Optional,
+    ) -> Result<T, Error> {
+        if let Some(value) = self.read_option_positional(o)? {
+            Ok(value)
+        } else {
+            self.prioritize_help()?;
+            self.is_empty()?;
+            if let Some(value) = self.prompt_value()? {
+                return Ok(value);
+            }
+            Err(Error::new(
+                self.usage_help(),
+                ErrorKind::MissingPositional,
+                ErrorContext::FailedArg(self.known_args.pop().unwrap()),
+                self.use_color,
+            ))
+        }
+    }
+
+    /// Queries for up to `n` values behind an `Optional`.
+    ///
+    /// Errors if a parsing fails from string or if the number of detected optionals is > n.
+    pub fn check_option_n<'a, T: ValueParse>(
+        &mut self,
+        o: Optional,
+        n: usize,
+    ) -> Result<Option<Vec<T>>, Error> {
+        let values = self.check_option_all::<T>(o)?;
+        let result = match values {
+            // verify the size of the vector does not exceed `n`
+            Some(r) => match r.len() <= n {
+                true => Ok(Some(r)),
+                false => Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::ExceedingMaxCount,
+                    ErrorContext::ExceededThreshold(self.known_args.pop().unwrap(), r.len(), n),
+                    self.use_color,
+                )),
+            },
+            None => Ok(None),
+        };
+        self.defer(result, None)
+    }
+
+    /// Queries for all values behind an `Optional`.
+    ///
+    /// When the option is not supplied, its defaults from the [Config] are used
+    /// instead, if any.
+    ///
+    /// Errors if a parsing fails from string.
+    pub fn check_option_all<'a, T: ValueParse>(
+        &mut self,
+        o: Optional,
+    ) -> Result<Option<Vec<T>>, Error> {
+        let result = match self.read_option_all(o) {
+            Ok(values) if values.is_empty() == true => self.config_values(),
+            Ok(values) => Ok(Some(values.into_iter().map(|(_, v)| v).collect())),
+            Err(err) => Err(err),
+        };
+        self.defer(result, None)
+    }
+
+    /// Parses the defaults from the [Config] for the most recently learned
+    /// argument, splitting each on its delimiter.
+    fn config_values<T: ValueParse>(&mut self) -> Result<Option<Vec<T>>, Error> {
+        let name = self.known_args.last().unwrap().get_name();
+        let words = match self.config.get_all(name) {
+            Some(words) => words,
+            None => return Ok(None),
+        };
+        let mut values = Vec::with_capacity(words.len());
+        for word in words {
+            for piece in self.split_value(word) {
+                values.push(self.parse_value(piece)?);
+            }
+        }
+        Ok(Some(values))
+    }
+
+    /// Queries for all values behind an `Optional` along with where each was
+    /// supplied.
+    ///
+    /// [Merging](Occurrences::merge) the occurrences of different options keeps
+    /// them in command-line order, for CLIs where the order of the flags defines a
+    /// pipeline, such as `--filter a --map b --filter c`. Each value's index in the
+    /// original arguments is available through [iter](Occurrences::iter), where
+    /// the program name is at index 0.
+    ///
+    /// Errors if a parsing fails from string.
+    pub fn check_option_occurrences<'a, T: ValueParse>(
+        &mut self,
+        o: Optional,
+    ) -> Result<Occurrences<T>, Error> {
+        let result = self.read_option_all(o).map(Occurrences::new);
+        self.defer(result, Occurrences::default())
+    }
+
+    /// Queries for all values behind an `Optional`, where each occurrence takes
+    /// the consecutive arguments following it up to the next flag, as in
+    /// `--files a.txt b.txt c.txt`.
+    ///
+    /// At most `max` values are taken per occurrence; any arguments beyond them
+    /// are left for positionals. Query this option before any positionals or
+    /// subcommands that could follow it. When the option is not supplied, its
+    /// defaults from the [Config] are used instead, if any.
+    ///
+    /// Errors if an occurrence has no value or if a parsing fails from string.
+    pub fn check_option_greedy<'a, T: ValueParse>(
+        &mut self,
+        o: Optional,
+        max: usize,
+    ) -> Result<Option<Vec<T>>, Error> {
+        let result = self.read_option_greedy(o, max);
+        self.defer(result, None)
+    }
+
+    /// Queries for all values behind an `Optional` as with
+    /// [check_option_greedy](Cli::check_option_greedy) without setting aside the
+    /// error when [collecting errors](Cli::collect_errors).
+    fn read_option_greedy<T: ValueParse>(
+        &mut self,
+        o: Optional,
+        max: usize,
+    ) -> Result<Option<Vec<T>>, Error> {
+        self.check_limits()?;
+        // collect information on where the flag can be found
+        let mut locs = self.take_flag_locs(o.get_flag().get_name());
+        locs.extend(self.take_scoped_switch_locs(o.get_flag())?);
+        locs.sort();
+        self.attach_cluster_values(&locs);
+        self.known_args.push(self.prefixed(Arg::Optional(o)));
+        self.check_list_choices()?;
+        let mut values: Vec<Option<String>> = Vec::new();
+        let mut missing = None;
+        for i in locs {
+            let first = match self.pull_flag(vec![i], true).pop() {
+                Some(v) => v,
+                None => continue,
+            };
+            let taken = first.is_some();
+            values.push(first);
+            if taken == false {
+                missing = missing.or(Some(i));
+                continue;
+            }
+            // skip past the value just taken to find the arguments following it
+            let mut j = i + 1;
+            while let Some(None) = self.tokens.get(j) {
+                match self.bindings.get(j) {
+                    Some(Some(Binding { role: Role::Value, .. })) => j += 1,
+                    _ => break,
+                }
+            }
+            let mut count = 1;
+            while count < max {
+                match self.tokens.get(j) {
+                    Some(Some(Token::UnattachedArgument(_, _))) => {
+                        self.bind(j, Role::Value);
+                        values.push(Some(self.tokens[j].take().unwrap().take_str()));
+                        count += 1;
+                        j += 1;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        self.record_raised(values.len());
+        self.check_unstable(values.len())?;
+        if let Some(fixed) = self.check_frozen(values.len())? {
+            values = vec![fixed];
+        }
+        if values.is_empty() == true {
+            return self.config_values();
+        }
+        // try to convert each value into the type T
+        let mut transform = Vec::<T>::with_capacity(values.len());
+        for val in values {
+            if let Some(word) = val {
+                for piece in self.split_value(word) {
+                    transform.push(self.parse_value(piece)?);
+                }
+            } else {
+                self.prioritize_help()?;
+                return Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::ExpectingValue,
+                    ErrorContext::FailedArg(self.known_args.pop().unwrap()),
+                    self.use_color,
+                )
+                .with_span(missing.and_then(|i| self.span(i))));
+            }
+        }
+        Ok(Some(transform))
+    }
+
+    /// Splits `word` on the delimiter of the most recently learned argument,
+    /// dropping empty pieces.
+    ///
+    /// The word is returned whole if the argument has no delimiter.
+    fn split_value(&self, word: String) -> Vec<String> {
+        let delimiter = self
+            .known_args
+            .last()
+            .and_then(|a| a.as_positional())
+            .and_then(|p| p.get_meta().delimiter);
+        match delimiter {
+            Some(d) => word
+                .split(d)
+                .filter(|p| p.is_empty() == false)
+                .map(|p| p.to_string())
+                .collect(),
+            None => vec![word],
+        }
+    }
+
+    /// Pulls and parses every value behind an `Optional` in the order they appear,
+    /// paired with the token index of the option.
+    fn read_option_all<T: ValueParse>(&mut self, o: Optional) -> Result<Vec<(usize, T)>, Error> {
+        self.check_limits()?;
+        // collect information on where the flag can be found
+        let mut locs = self.take_flag_locs(o.get_flag().get_name());
+        locs.extend(self.take_scoped_switch_locs(o.get_flag())?);
+        locs.sort();
+        self.attach_cluster_values(&locs);
+        self.known_args.push(self.prefixed(Arg::Optional(o)));
+        self.check_list_choices()?;
+        // pull values from where the option flags were found (including switch)
+        let mut values = Vec::with_capacity(locs.len());
+        let mut missing = None;
+        for i in locs {
+            if let Some(val) = self.pull_flag(vec![i], true).pop() {
+                if val.is_none() == true {
+                    missing = missing.or(Some(i));
+                }
+                // record the index of the original argument, after the program name
+                values.push((self.lexemes[i].0 + 1, val));
+            }
+        }
+        self.record_raised(values.len());
+        self.check_unstable(values.len())?;
+        if let Some(fixed) = self.check_frozen(values.len())? {
+            values = vec![(0, fixed)];
+        }
+        // try to convert each value into the type T
+        let mut transform = Vec::<(usize, T)>::with_capacity(values.len());
+        for (i, val) in values {
+            if let Some(word) = val {
+                for piece in self.split_value(word) {
+                    transform.push((i, self.parse_value(piece)?));
+                }
+            } else {
+                self.prioritize_help()?;
+                return Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::ExpectingValue,
+                    ErrorContext::FailedArg(self.known_args.pop().unwrap()),
+                    self.use_color,
+                )
+                .with_span(missing.and_then(|i| self.span(i))));
+            }
+        }
+        Ok(transform)
+    }
+
+    /// Queries if a flag was raised once and only once.
+    ///
+    /// When the flag is not raised, its boolean default from the [Config] is used
+    /// instead, if any.
+    ///
+    /// Errors if the flag has an attached value or was raised multiple times. A
+    /// [negatable](Flag::negatable) flag is read as with
+    /// [check_bool_flag](Cli::check_bool_flag), where `--no-<name>` is `false`.
+    pub fn check_flag<'a>(&mut self, f: Flag) -> Result<bool, Error> {
+        if f.is_negatable() == true {
+            return self.check_bool_flag(f).map(|enabled| enabled.unwrap_or(false));
+        }
+        let result = match self.read_flag_all(f) {
+            // the flag was not raised, so the config may enable it
+            Ok(0) => match self.config_flag() {
+                Ok(Some(true)) => {
+                    let name = self.known_args.last().unwrap().get_name();
+                    self.matches.push_count(name, 1);
+                    Ok(true)
+                }
+                Ok(_) => Ok(false),
+                Err(err) => Err(err),
+            },
+            Ok(1) => Ok(true),
+            Ok(_) => {
+                self.prioritize_help()?;
+                Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::DuplicateOptions,
+                    ErrorContext::FailedArg(self.known_args.pop().unwrap()),
+                    self.use_color,
+                ))
+            }
+            Err(err) => Err(err),
+        };
+        self.defer(result, false)
+    }
+
+    /// Queries for the number of times a flag was raised.
+    ///
+    /// Errors if the flag has an attached value. Returning a zero indicates the flag was never raised.
+    pub fn check_flag_all<'a>(&mut self, f: Flag) -> Result<usize, Error> {
+        let result = self.read_flag_all(f);
+        self.defer(result, 0)
+    }
+
+    /// Queries for the number of times a flag was raised as with
+    /// [check_flag_all](Cli::check_flag_all) without setting aside the error
+    /// when [collecting errors](Cli::collect_errors).
+    fn read_flag_all(&mut self, f: Flag) -> Result<usize, Error> {
+        // a negatable flag is raised at most once, since `--no-<name>` turns it off
+        if f.is_negatable() == true {
+            return self.read_bool_flag(f).map(|enabled| usize::from(enabled == Some(true)));
+        }
+        self.check_limits()?;
+        // collect information on where the flag can be found
+        let mut locs = self.take_flag_locs(f.get_name());
+        // try to find the switch locations
+        locs.extend(self.take_scoped_switch_locs(&f)?);
+        self.retain_before_operand(&mut locs, &f);
+        self.known_args.push(self.prefixed(Arg::Flag(f)));
+        let positions = locs.clone();
+        let mut occurences = self.pull_flag(locs, false);
+        self.record_raised(occurences.len());
+        self.check_unstable(occurences.len())?;
+        if self.check_frozen(occurences.len())?.is_some() {
+            occurences = vec![None];
+        }
+        // verify there are no values attached to this flag
+        if let Some(val) = occurences.iter_mut().find(|p| p.is_some()) {
+            let val = val.take().unwrap();
+            let token = self.find_valued_flag_token(&positions);
+            let suggestion = self.suggest_option_for(&val);
+            let span = self.value_span(&val);
+            self.prioritize_help()?;
+            return Err(Error::new(
+                self.help.clone(),
+                ErrorKind::UnexpectedValue,
+                ErrorContext::UnexpectedFlagValue(self.known_args.pop().unwrap(), token, suggestion),
+                self.use_color,
+            )
+            .with_span(span));
+        } else {
+            let raised = occurences.len() != 0;
+            // check if the user is asking for help by raising the help flag
+            if let Some(hp) = &self.help {
+                if raised == true
+                    && hp.get_flag().get_name()
+                        == self
+                            .known_args
+                            .last()
+                            .unwrap()
+                            .as_flag()
+                            .unwrap()
+                            .get_name()
+                {
+                    self.asking_for_help = true;
+                }
+            }
+            if raised == true {
+                let name = self.known_args.last().unwrap().get_name();
+                self.matches.push_count(name, occurences.len());
+            }
+            // return the number of times the flag was raised
+            Ok(occurences.len())
+        }
+    }
+
+    /// Returns the command-line argument where a flag at one of the `positions` was
+    /// given an attached value, such as `--verbose=9`.
+    fn find_valued_flag_token(&self, positions: &[usize]) -> String {
+        positions
+            .iter()
+            .find(|i| match (self.bindings.get(**i), self.bindings.get(**i + 1)) {
+                (Some(Some(flag)), Some(Some(value))) => {
+                    flag.role == Role::Flag
+                        && value.role == Role::Value
+                        && self.lexemes[**i].0 == self.lexemes[**i + 1].0
+                }
+                _ => false,
+            })
+            .map(|i| self.args[self.lexemes[*i].0].to_string())
+            .unwrap_or(String::new())
+    }
+
+    /// Suggests a learned option with a name similar to the most recently learned
+    /// flag, written as it would be supplied with the value `val`.
+    fn suggest_option_for(&self, val: &str) -> Option<String> {
+        let name = self.known_args.last()?.as_flag()?.get_name();
+        let bank: Vec<&str> = self
+            .known_args
+            .iter()
+            .filter_map(|a| match a {
+                Arg::Optional(o) => Some(o.get_flag().get_name()),
+                _ => None,
+            })
+            .collect();
+        let closest = self.suggest(Class::Flag, name, &bank)?;
+        Some(format!("{}={}", self.flag_word(closest), val))
+    }
+
+    /// Queries for the number of times a flag was raised as a [Count].
+    ///
+    /// A [Count] converts into a [Verbosity](crate::value::Verbosity) for flags like `-vvv`.
+    /// Errors if the flag has an attached value.
+    pub fn check_flag_count(&mut self, f: Flag) -> Result<Count, Error> {
+        Ok(Count::new(self.check_flag_all(f)?))
+    }
+
+    /// Queries for the number of times a flag was raised up until `n` times.
+    ///
+    /// Errors if the flag has an attached value. Returning a zero indicates the flag was never raised.
+    pub fn check_flag_n<'a>(&mut self, f: Flag, n: usize) -> Result<usize, Error> {
+        let occurences = self.check_flag_all(f)?;
+        // verify the size of the vector does not exceed `n`
+        let result = match occurences <= n {
+            true => Ok(occurences),
+            false => Err(Error::new(
+                self.help.clone(),
+                ErrorKind::ExceedingMaxCount,
+                ErrorContext::ExceededThreshold(self.known_args.pop().unwrap(), occurences, n),
+                self.use_color,
+            )),
+        };
+        self.defer(result, 0)
+    }
+
+    /// Queries for a flag that can be given an explicit boolean value, such as
+    /// `--cache=false`, so a setting enabled by default can be turned off.
+    ///
+    /// The flag alone reads as `true`, and an attached value of `true`, `false`,
+    /// `1`, or `0` sets it explicitly. A [negatable](Flag::negatable) flag also
+    /// reads `--no-<name>` as `false`. Returns `None` if the flag was not raised,
+    /// so a default from elsewhere, such as a config file, can apply. Errors if
+    /// the flag is raised more than once or given any other value.
+    pub fn check_bool_flag(&mut self, f: Flag) -> Result<Option<bool>, Error> {
+        let result = match self.read_bool_flag(f) {
+            Ok(None) => self.config_flag().map(|enabled| {
+                if let Some(enabled) = enabled {
+                    let name = self.known_args.last().unwrap().get_name();
+                    self.matches.push_value(name, enabled.to_string());
+                }
+                enabled
+            }),
+            result => result,
+        };
+        self.defer(result, None)
+    }
+
+    /// Reads the boolean default from the [Config] for the most recently learned
+    /// flag.
+    fn config_flag(&mut self) -> Result<Option<bool>, Error> {
+        let name = self.known_args.last().unwrap().get_name();
+        match self.config.get(name) {
+            Some(word) => Ok(Some(self.parse_bool(word)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads `word` as the explicit value of the most recently learned flag.
+    ///
+    /// Errors if `word` is not `true`, `false`, `1`, or `0`.
+    fn parse_bool(&mut self, word: String) -> Result<bool, Error> {
+        match word.as_str() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => {
+                let choices = vec![String::from("true"), String::from("false")];
+                let suggestion = self.suggest(Class::Value, &word, &choices).map(|w| w.to_string());
+                let span = self.value_span(&word);
+                self.prioritize_help()?;
+                Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::InvalidChoice,
+                    ErrorContext::InvalidChoice(self.known_args.pop().unwrap(), word, choices, suggestion),
+                    self.use_color,
+                )
+                .with_span(span))
+            }
+        }
+    }
+
+    /// Queries for a boolean flag as with [check_bool_flag](Cli::check_bool_flag)
+    /// without setting aside the error when [collecting errors](Cli::collect_errors).
+    fn read_bool_flag(&mut self, f: Flag) -> Result<Option<bool>, Error> {
+        self.check_limits()?;
+        let mut locs = self.take_flag_locs(f.get_name());
+        locs.extend(self.take_scoped_switch_locs(&f)?);
+        let mut negated = match f.is_negatable() {
+            true => self.take_flag_locs(&format!("no-{}", f.get_name())),
+            false => Vec::new(),
+        };
+        self.retain_before_operand(&mut locs, &f);
+        self.retain_before_operand(&mut negated, &f);
+        self.known_args.push(self.prefixed(Arg::Flag(f)));
+        let mut values = self.pull_flag(locs, false);
+        // the inverse reads as an explicit `false` but cannot be given a value itself
+        let inverted = self.pull_flag(negated.clone(), false);
+        if let Some(val) = inverted.iter().flatten().next() {
+            let token = self.find_valued_flag_token(&negated);
+            let span = self.value_span(val);
+            self.prioritize_help()?;
+            return Err(Error::new(
+                self.help.clone(),
+                ErrorKind::UnexpectedValue,
+                ErrorContext::UnexpectedFlagValue(self.known_args.pop().unwrap(), token, None),
+                self.use_color,
+            )
+            .with_span(span));
+        }
+        values.extend(inverted.iter().map(|_| Some(String::from("false"))));
+        self.record_raised(values.len());
+        self.check_unstable(values.len())?;
+        if let Some(fixed) = self.check_frozen(values.len())? {
+            values = vec![fixed];
+        }
+        if values.len() > 1 {
+            self.prioritize_help()?;
+            return Err(Error::new(
+                self.help.clone(),
+                ErrorKind::DuplicateOptions,
+                ErrorContext::FailedArg(self.known_args.pop().unwrap()),
+                self.use_color,
+            ));
+        }
+        let enabled = match values.pop() {
+            None => return Ok(None),
+            Some(None) => true,
+            Some(Some(word)) => self.parse_bool(word)?,
+        };
+        let name = self.known_args.last().unwrap().get_name();
+        self.matches.push_value(name, enabled.to_string());
+        Ok(Some(enabled))
+    }
+
+    /// Registers a flag that nested subcommands may query, even when it is supplied
+    /// before the subcommand's name.
+    ///
+    /// Call this before [check_command](Cli::check_command) so the flag is not
+    /// reported as an out-of-context argument.
+    pub fn global_flag(&mut self, f: Flag) -> () {
+        self.register_global(Arg::Flag(f));
+    }
+
+    /// Registers an option that nested subcommands may query, even when it is
+    /// supplied before the subcommand's name.
+    pub fn global_option(&mut self, o: Optional) -> () {
+        self.register_global(Arg::Optional(o));
+    }
+
+    /// Registers the long flags `names` of the current command so unambiguous
+    /// abbreviations of them are accepted, as in `--verb` for `--verbose`.
+    ///
+    /// Call this before checking any of the flags. Errors if an abbreviation
+    /// is the prefix of more than one of the `names`.
+    pub fn expand_flag_prefixes<T: AsRef<str>>(&mut self, names: &[T]) -> Result<(), Error> {
+        let mut keys: Vec<(String, usize)> = self
+            .opt_store
+            .iter()
+            .filter_map(|(tag, slot)| match tag {
+                Tag::Flag(key) if slot.is_visited() == false => Some((key.clone(), *slot.first()?)),
+                _ => None,
+            })
+            .collect();
+        // resolve in the order supplied so the first ambiguity is reported
+        keys.sort_by_key(|(_, i)| *i);
+        for (key, loc) in keys {
+            if names.iter().any(|n| n.as_ref() == key) == true {
+                continue;
+            }
+            let candidates: Vec<&str> = names
+                .iter()
+                .map(|n| n.as_ref())
+                .filter(|n| n.starts_with(key.as_str()))
+                .collect();
+            match candidates.len() {
+                0 => (),
+                1 => {
+                    let slot = self.opt_store.remove(&Tag::Flag(key)).unwrap();
+                    self.opt_store
+                        .entry(Tag::Flag(candidates[0].to_string()))
+                        .or_insert(Slot::new())
+                        .absorb(slot);
+                }
+                _ => {
+                    self.prioritize_help()?;
+                    return Err(Error::new(
+                        self.help.clone(),
+                        ErrorKind::AmbiguousFlag,
+                        ErrorContext::Ambiguous(
+                            self.flag_word(&key),
+                            candidates.iter().map(|c| self.flag_word(c)).collect(),
+                        ),
+                        self.use_color,
+                    )
+                    .with_span(self.span(loc)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn register_global(&mut self, a: Arg) -> () {
+        let f = a.as_flag().unwrap();
+        // marking the occurrences as visited keeps them out of the out-of-context check
+        self.take_flag_locs(f.get_name());
+        if let Some(c) = f.get_switch() {
+            self.take_switch_locs(c);
+        }
+        self.known_args.push(self.prefixed(a));
+    }
+
+    /// Declares that the flags or options named in `names` must be used together.
+    ///
+    /// The constraint is verified by [is_empty](Cli::is_empty) once every argument
+    /// has been queried, so it may be declared before or after the arguments themselves.
+    pub fn requires_together<T: AsRef<str>>(&mut self, names: &[T]) -> () {
+        self.together
+            .push(names.iter().map(|n| n.as_ref().to_string()).collect());
+    }
+
+    /// Remembers the most recently learned flag as raised if it was found `n` > 0 times.
+    ///
+    /// Raising a deprecated flag adds a warning.
+    fn record_raised(&mut self, n: usize) -> () {
+        if n > 0 {
+            if let Some(a) = self.known_args.last() {
+                let f = a.as_flag().unwrap();
+                if let Some(note) = f.get_deprecation() {
+                    let warning = format!("argument '{}' is deprecated: {}", a, note);
+                    // a flag read by both a command and its subcommand is reported once
+                    if self.warnings.contains(&warning) == false {
+                        self.warnings.push(warning);
+                    }
+                }
+                self.raised.insert(f.get_name().to_string());
+            }
+        }
+    }
+
+    /// Verifies the most recently learned flag is stable or unstable features are
+    /// enabled, if it was found `n` > 0 times.
+    fn check_unstable(&mut self, n: usize) -> Result<(), Error> {
+        let unstable = self
+            .known_args
+            .last()
+            .and_then(|a| a.as_flag())
+            .map(|f| f.is_unstable())
+            .unwrap_or(false);
+        if n == 0 || unstable == false || self.unstable == true {
+            return Ok(());
+        }
+        self.prioritize_help()?;
+        Err(Error::new(
+            self.help.clone(),
+            ErrorKind::UnstableArg,
+            ErrorContext::UnstableArg(
+                self.known_args.pop().unwrap(),
+                self.flag_word(symbol::UNSTABLE),
+            ),
+            self.use_color,
+        ))
+    }
+
+    /// Returns the value fixed by the application for the most recently learned
+    /// flag, if it is frozen.
+    ///
+    /// Errors if the user supplied the frozen flag `n` times.
+    fn check_frozen(&mut self, n: usize) -> Result<Option<Option<String>>, Error> {
+        let name = self.known_args.last().unwrap().get_name();
+        let fixed = match self.frozen.iter().find(|(f, _)| f == name) {
+            Some((_, value)) => value.clone(),
+            None => return Ok(None),
+        };
+        if n > 0 {
+            self.prioritize_help()?;
+            return Err(Error::new(
+                self.help.clone(),
+                ErrorKind::FrozenArg,
+                ErrorContext::FrozenArg(self.known_args.pop().unwrap()),
+                self.use_color,
+            ));
+        }
+        self.raised.insert(name.to_string());
+        Ok(Some(fixed))
+    }
+
+    /// Finds the learned argument whose flag is called `name`.
+    ///
+    /// Falls back to a plain [Flag] when the argument was never queried.
+    fn known_arg_by_flag_name(&self, name: &str) -> Arg {
+        self.known_args
+            .iter()
+            .find(|a| a.as_flag().map(|f| f.get_name() == name).unwrap_or(false))
+            .cloned()
+            .unwrap_or(self.prefixed(Arg::Flag(Flag::new(name))))
+    }
+
+    /// Verifies the constraints declared among arguments hold for the raised arguments.
+    fn check_constraints(&self) -> Result<(), Error> {
+        for arg in &self.known_args {
+            let f = match arg.as_flag() {
+                Some(f) if self.raised.contains(f.get_name()) => f,
+                _ => continue,
+            };
+            if let Some(missing) = f
+                .get_requirements()
+                .iter()
+                .find(|n| self.raised.contains(n.as_str()) == false)
+            {
+                return Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::MissingRequirement,
+                    ErrorContext::MissingRequirement(arg.clone(), self.known_arg_by_flag_name(missing)),
+                    self.use_color,
+                ));
+            }
+        }
+        for group in &self.together {
+            let present = group.iter().find(|n| self.raised.contains(n.as_str()));
+            let missing = group.iter().find(|n| self.raised.contains(n.as_str()) == false);
+            if let (Some(present), Some(missing)) = (present, missing) {
+                return Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::RequiredTogether,
+                    ErrorContext::RequiredTogether(
+                        self.known_arg_by_flag_name(present),
+                        self.known_arg_by_flag_name(missing),
+                    ),
+                    self.use_color,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Transforms the list of `known_args` into a list of the names for every available
+    /// flag.
+    ///
+    /// This method is useful for acquiring a word bank to offer a flag spelling suggestion.
+    fn known_args_as_flag_names(&self) -> HashSet<&str> {
+        // note: collect into a `std::collections::HashSet` to avoid dupe
+        self.known_args
+            .iter()
+            .filter_map(|f| match f {
+                Arg::Flag(f) => Some(f.get_name()),
+                Arg::Optional(o) => Some(o.get_flag().get_name()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the first index where a flag/switch still remains in the token stream.
+    ///
+    /// The flag must occur in the token stream before the `breakpoint` index. If
+    /// the `opt_store` hashmap is empty, it will return none.
+    fn find_first_flag_left(&self, breakpoint: usize) -> Option<(&str, usize)> {
+        let mut min_i: Option<(&str, usize)> = None;
+        let mut opt_it = self
+            .opt_store
+            .iter()
+            .filter(|(_, slot)| slot.is_visited() == false);
+        while let Some((key, val)) = opt_it.next() {
+            // check if this flag's index comes before the currently known minimum index
+            min_i = if *val.first().unwrap() < breakpoint
+                && (min_i.is_none() || min_i.unwrap().1 > *val.first().unwrap())
+            {
+                Some((key.as_ref(), *val.first().unwrap()))
+            } else {
+                min_i
+            };
+        }
+        min_i
+    }
+
+    /// Verifies there are no uncaught flags behind a given index.
+    fn capture_bad_flag<'a>(&self, i: usize) -> Result<Option<(&str, &str, usize)>, Error> {
+        if let Some((key, val)) = self.find_first_flag_left(i) {
+            self.prioritize_help()?;
+            // check what type of token it was to determine if it was called with '-' or '--'
+            if let Some(t) = self.tokens.get(val).unwrap() {
+                let prefix = match t {
+                    Token::Switch(_, _) | Token::EmptySwitch(_) => self.switch_prefix.as_str(),
+                    Token::Flag(_) => {
+                        // try to match it with a valid flag from word bank
+                        let bank: Vec<&str> = self.known_args_as_flag_names().into_iter().collect();
+                        if let Some(closest) = self.suggest(Class::Flag, key, &bank) {
+                            return Err(Error::new(
+                                self.help.clone(),
+                                ErrorKind::SuggestArg,
+                                ErrorContext::SuggestWord(
+                                    self.flag_word(key),
+                                    self.flag_word(closest),
+                                ),
+                                self.use_color,
+                            )
+                            .with_span(self.span(val)));
+                        }
+                        self.flag_prefix.as_str()
+                    }
+                    _ => panic!("no other tokens are allowed in hashmap"),
+                };
+                Ok(Some((prefix, key, val)))
+            } else {
+                panic!("this token's values have been removed")
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Rewrites the user's command-line with the terminator placed before the token
+    /// at index `i`, so it and every argument after it are forwarded.
+    ///
+    /// Secret values are redacted and each word is quoted for the shell as needed.
+    fn insert_terminator(&self, i: usize) -> String {
+        let argv = self.lexemes[i].0;
+        let mut words: Vec<String> = self.program.iter().map(|p| complete::shell_word(p)).collect();
+        for (j, arg) in self.redacted_args().iter().enumerate() {
+            if j == argv {
+                words.push(self.flag_prefix.clone());
+            }
+            // the original terminator would now be forwarded as well
+            if j > argv && arg == &self.flag_prefix {
+                continue;
+            }
+            words.push(complete::shell_word(arg));
+        }
+        words.join(" ")
+    }
+
+    /// Verifies there are no more tokens remaining in the stream as with
+    /// [is_empty](Cli::is_empty), then reports the errors set aside while
+    /// [collecting errors](Cli::collect_errors).
+    pub fn finish(&mut self) -> Result<(), Error> {
+        let result = self.is_empty();
+        self.gather(result)
+    }
+
+    /// Sets aside the error of `result` when collecting errors, returning
+    /// `fallback` in its place.
+    ///
+    /// Informational errors, such as help, are always returned.
+    fn defer<T>(&mut self, result: Result<T, Error>, fallback: T) -> Result<T, Error> {
+        match (result, self.collected.as_mut()) {
+            (Err(err), Some(errors)) if err.is_informational() == false => {
+                errors.push(Deferred::new(err));
+                Ok(fallback)
+            }
+            (result, _) => result,
+        }
+    }
+
+    /// Reports the errors set aside while collecting errors along with the
+    /// error of `result`, combining them when there is more than one.
+    pub(crate) fn gather<T>(&mut self, result: Result<T, Error>) -> Result<T, Error> {
+        let mut errors: Vec<Error> = match self.collected.as_mut() {
+            Some(collected) => collected.drain(..).map(|d| d.restore()).collect(),
+            None => return result,
+        };
+        let value = match result {
+            // informational errors are shown on their own
+            Err(err) if err.is_informational() == true => return Err(err),
+            Err(err) => {
+                errors.push(err);
+                None
+            }
+            Ok(value) => Some(value),
+        };
+        match (errors.len(), value) {
+            (0, Some(value)) => Ok(value),
+            (1, _) => Err(errors.pop().unwrap()),
+            _ => {
+                let line = std::iter::once(self.program.clone().unwrap_or_default())
+                    .chain(self.redacted_args())
+                    .collect();
+                Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::Multiple,
+                    ErrorContext::Multiple(errors),
+                    self.use_color,
+                )
+                .with_line(line))
+            }
+        }
+    }
+
+    /// Verifies there are no more tokens remaining in the stream.
+    ///
+    /// Note this mutates the referenced self only if an error is found.
+    pub fn is_empty<'a>(&'a self) -> Result<(), Error> {
+        self.check_limits()?;
+        // report how the arguments were classified instead of running the command
+        if self.is_internal_raised(internal::EXPLAIN) == true {
+            return Err(Error::new(
+                None,
+                ErrorKind::Explain,
+                ErrorContext::Explain(self.explain()),
+                self.use_color,
+            ));
+        }
+        self.prioritize_help()?;
+        // leftovers are taken by `unknown` when parsing only the known arguments
+        let leftover = match self.parse_known {
+            true => None,
+            false => self.capture_bad_flag(self.tokens.len())?,
+        };
+        // check if map is empty, and return the minimum found index.
+        if let Some((prefix, key, i)) = leftover {
+            let word = format!("{}{}", prefix, key);
+            let context = match self.forwards {
+                true => ErrorContext::ForwardedArg(word, self.insert_terminator(i)),
+                false => ErrorContext::UnexpectedArg(word),
+            };
+            Err(Error::new(
+                self.help.clone(),
+                ErrorKind::UnexpectedArg,
+                context,
+                self.use_color,
+            )
+            .with_span(self.span(i)))
+        // find first non-none token
+        } else if let Some(i) = self
+            .tokens
+            .iter()
+            .position(|p| p.is_some())
+            .filter(|_| self.parse_known == false)
+        {
+            match &self.tokens[i] {
+                Some(Token::UnattachedArgument(_, word)) => Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::UnexpectedArg,
+                    ErrorContext::UnexpectedArg(word.to_string()),
+                    self.use_color,
+                )
+                .with_span(self.span(i))),
+                Some(Token::Terminator(_)) => Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::UnexpectedArg,
+                    ErrorContext::UnexpectedArg(self.flag_prefix.clone()),
+                    self.use_color,
+                )
+                .with_span(self.span(i))),
+                // a flag set aside with its value, such as a built-in flag given one
+                Some(Token::Flag(_)) if matches!(self.tokens.get(i + 1), Some(Some(Token::AttachedArgument(_, _)))) => {
+                    let name = self.lexemes[i].1[self.flag_prefix.len()..].to_string();
+                    let value = self.lexemes[i + 1].1.clone();
+                    Err(Error::new(
+                        self.help.clone(),
+                        ErrorKind::UnexpectedValue,
+                        ErrorContext::UnexpectedValue(self.prefixed(Arg::Flag(Flag::new(name))), value),
+                        self.use_color,
+                    )
+                    .with_span(self.span(i + 1)))
+                }
+                // flags whose occurrences were set aside, such as unqueried global flags
+                _ => Err(Error::new(
+                    self.help.clone(),
+                    ErrorKind::UnexpectedArg,
+                    ErrorContext::UnexpectedArg(self.lexemes[i].1.clone()),
+                    self.use_color,
+                )
+                .with_span(self.span(i))),
+            }
+        } else {
+            self.check_constraints()?;
+            // report the resolved settings instead of running the command
+            if self.is_internal_raised(internal::CHECK) == true {
+                return Err(Error::new(
+                    None,
+                    ErrorKind::Check,
+                    ErrorContext::Check(self.settings().to_string()),
+                    self.use_color,
+                ));
+            }
+            Ok(())
+        }
+    }
+
+    /// Returns where the value of the argument `name` came from.
+    fn source(&self, name: &str) -> Source {
+        // positionals are bound to tokens without being recorded as raised
+        let bound = self.bindings.iter().flatten().filter_map(|b| b.arg.as_ref()).any(|arg| {
+            self.known_args.iter().any(|a| a.get_name() == name && &a.to_string() == arg)
+        });
+        if self.frozen.iter().any(|(f, _)| f == name) {
+            Source::Frozen
+        } else if self.raised.contains(name) == true || bound == true {
+            Source::CommandLine
+        } else {
+            self.config.source(name).unwrap_or(Source::CommandLine)
+        }
+    }
+
+    /// Lists every argument read along with its resolved value and where the
+    /// value came from, with secret values redacted.
+    pub fn settings(&self) -> Table {
+        let matches = self.matches.redacted();
+        matches.iter().fold(
+            Table::new(&["Setting", "Value", "Source"]),
+            |table, m| {
+                let value = match (m.get_values().is_empty(), m.get_count()) {
+                    (false, _) => m.get_values().join(", "),
+                    (true, 1) => String::from("true"),
+                    (true, n) => n.to_string(),
+                };
+                table.row(&[m.get_name(), &value, &self.source(m.get_name()).to_string()])
+            },
+        )
+    }
+
+    /// Grabs the flag/switch from the token stream, and collects.
+    ///
+    /// If an argument were to follow it will be in the vector.
+    fn pull_flag(&mut self, locations: Vec<usize>, with_uarg: bool) -> Vec<Option<String>> {
+        // remove all flag instances located at each index `i` in the vector `locations`
+        let mut values = Vec::with_capacity(locations.len());
+        let hyphen_values = with_uarg == true
+            && self
+                .known_args
+                .last()
+                .and_then(|a| a.as_positional())
+                .map(|p| p.get_meta().hyphen_values)
+                .unwrap_or(false);
+        for i in locations {
+            // skip flags already consumed as the value of another option
+            if let Some(Some(Binding { role: Role::Value, .. })) = self.bindings.get(i) {
+                continue;
+            }
+            // remove the flag instance from the token stream
+            let argv = self.lexemes[i].0;
+            self.tokens.get_mut(i).unwrap().take();
+            self.bind(i, Role::Flag);
+            // check the next position for a value
+            let value = match self.tokens.get(i + 1) {
+                Some(Some(Token::AttachedArgument(_, _))) => true,
+                // do not take unattached arguments unless told by parameter
+                Some(Some(Token::UnattachedArgument(_, _))) => with_uarg,
+                // take the entire next argument when it begins with a hyphen
+                Some(Some(Token::Flag(j))) | Some(Some(Token::Switch(j, _))) | Some(Some(Token::EmptySwitch(j)))
+                    if hyphen_values == true && *j != argv =>
+                {
+                    let j = *j;
+                    for k in self.remove_argument(i + 1, j) {
+                        self.bind(k, Role::Value);
+                    }
+                    values.push(Some(self.args[j].to_string()));
+                    continue;
+                }
+                _ => false,
+            };
+            values.push(match value {
+                true => {
+                    self.bind(i + 1, Role::Value);
+                    Some(self.tokens[i + 1].take().unwrap().take_str())
+                }
+                false => None,
+            });
+        }
+        values
+    }
+
+    /// Joins the characters following each switch in `locations` within its cluster
+    /// into an attached argument, so `-j4` is read as `-j=4`.
+    fn attach_cluster_values(&mut self, locations: &[usize]) -> () {
+        for &i in locations {
+            let argv = match self.tokens.get(i) {
+                Some(Some(Token::Switch(j, _))) => *j,
+                _ => continue,
+            };
+            if let Some(Some(Token::Switch(j, _))) = self.tokens.get(i + 1) {
+                if *j == argv {
+                    let start = self.spans[i + 1].get_range().start;
+                    let rest = self.args[argv][start..].to_string();
+                    self.remove_argument(i + 1, argv);
+                    self.lexemes[i + 1].1 = rest.clone();
+                    self.spans[i + 1] = Span::new(argv + 1, start, self.args[argv].len());
+                    self.tokens[i + 1] = Some(Token::AttachedArgument(argv, rest));
+                }
+            }
+        }
+    }
+
+    /// Removes every token lexed from the command-line argument at index `argv`,
+    /// starting from token `start`.
+    ///
+    /// Returns the range of token indices that were removed.
+    fn remove_argument(&mut self, start: usize, argv: usize) -> std::ops::Range<usize> {
+        let mut end = start;
+        while end < self.tokens.len() && self.lexemes[end].0 == argv {
+            self.tokens[end] = None;
+            // the token no longer stands for a flag or switch
+            self.opt_store.retain(|_, slot| {
+                slot.remove(end);
+                slot.first().is_some()
+            });
+            end += 1;
+        }
+        start..end
+    }
+
+    /// Takes the arguments left unread, in the order they were supplied, such
+    /// as the unknown flags and extra positionals when [parsing only the known
+    /// arguments](Cli::parse_known).
+    ///
+    /// An argument is returned as written unless part of it was read, as in a
+    /// cluster of switches where only some are known, in which case only the
+    /// unread switches are returned. Arguments after the terminator are left
+    /// for [check_remainder](Cli::check_remainder).
+    pub fn unknown(&mut self) -> Vec<String> {
+        let unread: Vec<usize> = (0..self.tokens.len())
+            .filter(|&i| match &self.tokens[i] {
+                Some(Token::Terminator(_)) | Some(Token::Ignore(_, _)) | None => false,
+                Some(_) => true,
+            })
+            .collect();
+        let mut words = Vec::new();
+        // gather the unread tokens of each argument
+        for group in unread.chunk_by(|&a, &b| self.lexemes[a].0 == self.lexemes[b].0) {
+            let argv = self.lexemes[group[0]].0;
+            let total = self.lexemes.iter().filter(|(a, _)| *a == argv).count();
+            let word = match group.len() == total {
+                true => self.args[argv].clone(),
+                false => {
+                    let rest: String = group
+                        .iter()
+                        .map(|&i| match &self.tokens[i] {
+                            Some(Token::Switch(_, c)) => c.to_string(),
+                            Some(Token::AttachedArgument(_, v)) => format!("={}", v),
+                            _ => self.lexemes[i].1.clone(),
+                        })
+                        .collect();
+                    format!("{}{}", self.switch_prefix, rest)
+                }
+            };
+            words.push(word);
+        }
+        // forget the flags taken so they are not reported as left over
+        for slot in self.opt_store.values_mut() {
+            unread.iter().for_each(|&i| slot.remove(i));
+        }
+        self.opt_store
+            .retain(|_, slot| slot.is_visited() == true || slot.first().is_some());
+        for i in unread {
+            self.tokens[i].take();
+            if let Some(b) = self.bindings.get_mut(i) {
+                *b = Some(Binding {
+                    role: Role::Unknown,
+                    arg: None,
+                    fragment: None,
+                    secret: false,
+                });
+            }
+        }
+        words
+    }
+
+    /// Removes the ignored tokens from the stream, if they exist.
+    ///
+    /// Errors if an `AttachedArg` is found (could only be immediately after terminator)
+    /// after the terminator.
+    pub fn check_remainder(&mut self) -> Result<Vec<String>, Error> {
+        self.check_limits()?;
+        self.forwards = true;
+        self.tokens
+            .iter_mut()
+            .zip(self.bindings.iter_mut())
+            // the terminator may have been read as a value already
+            .skip_while(|(tkn, _)| match tkn {
+                Some(Token::Terminator(_)) | Some(Token::Ignore(_, _)) => false,
+                _ => true,
+            })
+            .filter_map(|(tkn, binding)| {
+                match tkn {
+                    // remove the terminator from the stream
+                    Some(Token::Terminator(_)) => {
+                        tkn.take().unwrap();
+                        *binding = Some(Binding {
+                            role: Role::Terminator,
+                            arg: None,
+                            fragment: None,
+                            secret: false,
+                        });
+                        None
+                    }
+                    Some(Token::Ignore(_, _)) => {
+                        *binding = Some(Binding {
+                            role: Role::Remainder,
+                            arg: None,
+                            fragment: None,
+                            secret: false,
+                        });
+                        Some(Ok(tkn.take().unwrap().take_str()))
+                    }
+                    Some(Token::AttachedArgument(_, _)) => Some(Err(Error::new(
+                        self.help.clone(),
+                        ErrorKind::UnexpectedValue,
+                        ErrorContext::UnexpectedValue(
+                            Arg::Flag(Flag::new("")),
+                            tkn.take().unwrap().take_str(),
+                        ),
+                        self.use_color,
+                    ))),
+                    _ => panic!("no other tokens should exist beyond terminator {:?}", tkn),
+                }
+            })
+            .collect()
+    }
+
+    /// Drops the locations of the flag `f` that come after the current command's
+    /// first operand.
+    fn retain_before_operand(&self, locs: &mut Vec<usize>, f: &Flag) -> () {
+        if let Some(cutoff) = self.operand_start(Some(f)) {
+            locs.retain(|i| *i < cutoff);
+        }
+    }
+
+    /// Returns all locations in the token stream where the flag identifier `tag` is found.
+    ///
+    /// Information about Option<Vec<T>> vs. empty Vec<T>: https://users.rust-lang.org/t/space-time-usage-to-construct-vec-t-vs-option-vec-t/35596/6
+    fn take_flag_locs(&mut self, tag: &str) -> Vec<usize> {
+        let cutoff = self.operand_start(None).unwrap_or(self.tokens.len());
+        if let Some(slot) = self.opt_store.get_mut(&Tag::Flag(tag.to_owned())) {
+            slot.visit();
+            slot.get_indices().iter().copied().filter(|i| *i < cutoff).collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Returns the index of the current command's first operand when flags stop
+    /// being read there.
+    ///
+    /// An argument directly after a flag that was not queried yet may still be
+    /// that flag's value, so it does not count as an operand unless the flag is
+    /// `f`, which takes no value.
+    fn operand_start(&self, f: Option<&Flag>) -> Option<usize> {
+        if self.operands == false {
+            return None;
+        }
+        let valued = |j: usize| match &self.tokens[j] {
+            Some(Token::Flag(_)) => {
+                let name = self.lexemes[j].1.strip_prefix(self.flag_prefix.as_str());
+                f.is_none() || name != f.map(|f| f.get_name())
+            }
+            Some(Token::Switch(_, c)) => f.is_none() || f.and_then(|f| f.get_switch()) != Some(c),
+            _ => false,
+        };
+        let start = self.boundary.map(|b| b + 1).unwrap_or(0);
+        (start..self.tokens.len())
+            .take_while(|i| matches!(self.tokens[*i], Some(Token::Terminator(_))) == false)
+            .find(|i| match &self.tokens[*i] {
+                Some(Token::UnattachedArgument(_, _)) => match i.checked_sub(1) {
+                    Some(j) if j >= start => valued(j) == false,
+                    _ => true,
+                },
+                Some(_) => false,
+                None => matches!(
+                    self.bindings[*i].as_ref().map(|b| &b.role),
+                    Some(Role::Positional) | Some(Role::Subcommand)
+                ),
+            })
+    }
+
+    /// Turns the flags and options left after the current command's first operand
+    /// back into the arguments they were lexed from in [posix mode](Cli::posix_mode),
+    /// so they are read as operands.
+    fn settle_operands(&mut self) -> () {
+        if self.posix == false {
+            return;
+        }
+        let cutoff = match self.operand_start(None) {
+            Some(i) => i,
+            None => return,
+        };
+        let mut i = cutoff + 1;
+        while i < self.escaped.unwrap_or(self.tokens.len()) {
+            let n = match &self.tokens[i] {
+                Some(Token::Terminator(_)) => break,
+                Some(Token::Flag(n)) | Some(Token::Switch(n, _)) | Some(Token::EmptySwitch(n))
+                    if self.lexemes[i - 1].0 != *n =>
+                {
+                    *n
+                }
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            };
+            // a cluster of switches or a flag with an attached value is one operand
+            let end = (i + 1..self.tokens.len())
+                .find(|j| self.lexemes[*j].0 != n)
+                .unwrap_or(self.tokens.len());
+            for slot in self.opt_store.values_mut() {
+                slot.remove(i);
+                slot.cut(i + 1..end);
+            }
+            self.opt_store.retain(|_, slot| slot.get_indices().is_empty() == false);
+            self.tokens.drain(i + 1..end);
+            self.lexemes.drain(i + 1..end);
+            self.spans.drain(i + 1..end);
+            self.bindings.drain(i + 1..end);
+            if let Some(start) = self.escaped.as_mut() {
+                *start -= end - i - 1;
+            }
+            self.tokens[i] = Some(Token::UnattachedArgument(n, self.args[n].clone()));
+            self.lexemes[i].1 = self.args[n].clone();
+            self.spans[i] = Span::new(n + 1, 0, self.args[n].len());
+            i += 1;
+        }
+    }
+
+    /// Returns the locations in the token stream where the switch for flag `f` is
+    /// found within the active command's scope.
+    ///
+    /// Errors if another flag in the current command already claimed the same switch.
+    /// When a parent command assigned the switch to a different flag, only the
+    /// occurrences after the subcommand are considered.
+    fn take_scoped_switch_locs(&mut self, f: &Flag) -> Result<Vec<usize>, Error> {
+        let c = match f.get_switch() {
+            Some(c) => c,
+            None => return Ok(Vec::new()),
+        };
+        let claimed = |a: &&Arg| match a.as_flag() {
+            Some(g) => g.get_switch() == Some(c) && g.get_name() != f.get_name(),
+            None => false,
+        };
+        if let Some(other) = self.known_args[self.scope..].iter().find(claimed) {
+            return Err(Error::new(
+                self.help.clone(),
+                ErrorKind::DuplicateSwitch,
+                ErrorContext::DuplicateSwitch(*c, other.clone(), self.prefixed(Arg::Flag(f.clone()))),
+                self.use_color,
+            ));
+        }
+        let parent_claimed = self.known_args[..self.scope].iter().find(claimed).is_some();
+        let mut locs = self.take_switch_locs(c);
+        if let (true, Some(boundary)) = (parent_claimed, self.boundary) {
+            locs.retain(|i| *i > boundary);
+        }
+        Ok(locs)
+    }
+
+    /// Returns all locations in the token stream where the switch identifier `c` is found.
+    fn take_switch_locs(&mut self, c: &char) -> Vec<usize> {
+        // allocate &str to the stack and not the heap to get from store
+        let mut arr = [0; 4];
+        let tag = c.encode_utf8(&mut arr);
+
+        let cutoff = self.operand_start(None).unwrap_or(self.tokens.len());
+        if let Some(slot) = self.opt_store.get_mut(&Tag::Switch(tag.to_owned())) {
+            slot.visit();
+            slot.get_indices().iter().copied().filter(|i| *i < cutoff).collect()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Builds a `Cli` with the default settings from arguments beginning with the
+/// program name, such as within a test:
+///
+/// ```
+/// use clif::Cli;
+///
+/// let cli = Cli::from_iter(["orbit", "new", "my.lib"]);
+/// ```
+impl<S: AsRef<str>> FromIterator<S> for Cli {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Self::new().tokenize(iter.into_iter().map(|s| s.as_ref().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    /// Helper test fn to write vec of &str as iterator for Cli parameter.
+    fn args<'a>(args: Vec<&'a str>) -> Box<dyn Iterator<Item = String> + 'a> {
+        Box::new(args.into_iter().map(|f| f.to_string()).into_iter())
+    }
+
+    #[test]
+    fn get_all_optionals() {
+        // option provided multiple times
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "plan",
+            "--fileset",
+            "a",
+            "--fileset=b",
+            "--fileset",
+            "c",
+        ]));
+        let sets: Vec<String> = cli
+            .check_option_all(Optional::new("fileset"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(sets, vec!["a", "b", "c"]);
+        // failing case- bad conversion of 'c' to an integer
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "plan",
+            "--digit",
+            "10",
+            "--digit=9",
+            "--digit",
+            "c",
+        ]));
+        assert_eq!(
+            cli.check_option_all::<i32>(Optional::new("digit")).is_err(),
+            true
+        ); // bad conversion
+           // option provided as valid integers
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "plan",
+            "--digit",
+            "10",
+            "--digit=9",
+            "--digit",
+            "1",
+        ]));
+        let sets: Vec<i32> = cli
+            .check_option_all(Optional::new("digit"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(sets, vec![10, 9, 1]);
+        // option provided once
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "plan", "--fileset", "a"]));
+        let sets: Vec<String> = cli
+            .check_option_all(Optional::new("fileset"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(sets, vec!["a"]);
+        // option not provided
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "plan"]));
+        let sets: Option<Vec<String>> = cli.check_option_all(Optional::new("fileset")).unwrap();
+        assert_eq!(sets, None);
+    }
+
+    #[test]
+    fn match_command() {
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "get",
+            "rary.gates",
+            "--instance",
+            "--component",
+        ]));
+        // successfully matched 'get' command
+        assert_eq!(
+            cli.match_command(&["new", "get", "install", "edit"])
+                .unwrap(),
+            "get".to_string()
+        );
+
+        let mut cli = Cli::new().threshold(4).tokenize(args(vec![
+            "orbit",
+            "got",
+            "rary.gates",
+            "--instance",
+            "--component",
+        ]));
+        // suggest 'get' command
+        assert!(cli
+            .match_command(&["new", "get", "install", "edit"])
+            .is_err());
+    }
+
+    #[test]
+    #[should_panic = "requires positional argument"]
+    fn match_command_no_arg() {
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "got",
+            "rary.gates",
+            "--instance",
+            "--component",
+        ]));
+        // cli has no positional arguments loaded to report as error... panic
+        assert!(cli
+            .match_command(&["new", "get", "install", "edit"])
+            .is_err());
+    }
+
+    #[test]
+    fn find_first_flag_left() {
+        let cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "--help",
+            "new",
+            "rary.gates",
+            "--vcs",
+            "git",
+        ]));
+        assert_eq!(
+            cli.find_first_flag_left(cli.tokens.len()),
+            Some(("help", 0))
+        );
+
+        let cli = Cli::new().tokenize(args(vec!["orbit", "new", "rary.gates"]));
+        assert_eq!(cli.find_first_flag_left(cli.tokens.len()), None);
+
+        let cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "new",
+            "rary.gates",
+            "--vcs",
+            "git",
+            "--help",
+        ]));
+        assert_eq!(cli.find_first_flag_left(cli.tokens.len()), Some(("vcs", 2)));
+
+        let cli = Cli::new().tokenize(args(vec!["orbit", "new", "rary.gates", "-c=git", "--help"]));
+        assert_eq!(cli.find_first_flag_left(cli.tokens.len()), Some(("c", 2)));
+
+        let cli = Cli::new().tokenize(args(vec!["orbit", "new", "rary.gates", "-c=git", "--help"]));
+        assert_eq!(cli.find_first_flag_left(1), None); // check before 'rary.gates' position
+
+        let cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "--unknown",
+            "new",
+            "rary.gates",
+            "-c=git",
+            "--help",
+        ]));
+        assert_eq!(cli.find_first_flag_left(1), Some(("unknown", 0))); // check before 'new' subcommand
+    }
+
+    #[test]
+    fn processed_all_args() {
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "--upgrade",
+            "new",
+            "rary.gates",
+            "--vcs",
+            "git",
+        ]));
+        // tokens are still in token stream
+        let _ = cli.check_flag(Flag::new("upgrade")).unwrap();
+        let _: Option<String> = cli.check_option(Optional::new("vcs")).unwrap();
+        let _: String = cli.require_positional(Positional::new("command")).unwrap();
+        let _: String = cli.require_positional(Positional::new("ip")).unwrap();
+        // no more tokens left in stream
+        assert_eq!(cli.is_empty().unwrap(), ());
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "new", "rary.gates", symbol::FLAG]));
+        // removes only valid args/flags/opts
+        let _ = cli.check_flag(Flag::new("help")).unwrap();
+        let _: Option<String> = cli.check_option(Optional::new("vcs")).unwrap();
+        let _: String = cli.require_positional(Positional::new("command")).unwrap();
+        let _: String = cli.require_positional(Positional::new("ip")).unwrap();
+        // unexpected '--'
+        assert!(cli.is_empty().is_err());
+
+        let cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "--help",
+            "new",
+            "rary.gates",
+            "--vcs",
+            "git",
+        ]));
+        // no tokens were removed (help will also raise error)
+        assert!(cli.is_empty().is_err());
+
+        let mut cli =
+            Cli::new().tokenize(args(vec!["orbit", symbol::FLAG, "some", "extra", "words"]));
+        let _: Vec<String> = cli.check_remainder().unwrap();
+        // terminator removed as well as its arguments that were ignored
+        assert_eq!(cli.is_empty().unwrap(), ());
+    }
+
+    #[test]
+    fn tokenizer() {
+        let cli = Cli::new().tokenize(args(vec![]));
+        assert_eq!(cli.tokens, vec![]);
+
+        let cli = Cli::new().tokenize(args(vec!["orbit"]));
+        assert_eq!(cli.tokens, vec![]);
+
+        let cli = Cli::new().tokenize(args(vec!["orbit", "--help"]));
+        assert_eq!(cli.tokens, vec![Some(Token::Flag(0))]);
+
+        let cli = Cli::new().tokenize(args(vec!["orbit", "--help", "-v"]));
+        assert_eq!(
+            cli.tokens,
+            vec![Some(Token::Flag(0)), Some(Token::Switch(1, 'v'))],
+        );
+
+        let cli = Cli::new().tokenize(args(vec!["orbit", "new", "rary.gates"]));
+        assert_eq!(
+            cli.tokens,
+            vec![
+                Some(Token::UnattachedArgument(0, "new".to_string())),
+                Some(Token::UnattachedArgument(1, "rary.gates".to_string())),
+            ],
+        );
+
+        let cli = Cli::new().tokenize(args(vec!["orbit", "--help", "-vh"]));
+        assert_eq!(
+            cli.tokens,
+            vec![
+                Some(Token::Flag(0)),
+                Some(Token::Switch(1, 'v')),
+                Some(Token::Switch(1, 'h')),
+            ],
+        );
+
+        let cli = Cli::new().tokenize(args(vec!["orbit", "--help", "-vhc=10"]));
+        assert_eq!(
+            cli.tokens,
+            vec![
+                Some(Token::Flag(0)),
+                Some(Token::Switch(1, 'v')),
+                Some(Token::Switch(1, 'h')),
+                Some(Token::Switch(1, 'c')),
+                Some(Token::AttachedArgument(1, "10".to_string())),
+            ],
+        );
+
+        // an attached argument can sneak in behind a terminator
+        let cli = Cli::new().tokenize(args(vec!["orbit", "--=value", "extra"]));
+        assert_eq!(
+            cli.tokens,
+            vec![
+                Some(Token::Terminator(0)),
+                Some(Token::AttachedArgument(0, "value".to_string())),
+                Some(Token::Ignore(1, "extra".to_string())),
+            ]
+        );
+
+        // final boss
+        let cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "--help",
+            "-v",
+            "new",
+            "ip",
+            "--lib",
+            "--name=rary.gates",
+            "--help",
+            "-sci",
+            symbol::FLAG,
+            "--map",
+            "synthesis",
+            "-jto",
+        ]));
+        assert_eq!(
+            cli.tokens,
+            vec![
+                Some(Token::Flag(0)),
+                Some(Token::Switch(1, 'v')),
+                Some(Token::UnattachedArgument(2, "new".to_string())),
+                Some(Token::UnattachedArgument(3, "ip".to_string())),
+                Some(Token::Flag(4)),
+                Some(Token::Flag(5)),
+                Some(Token::AttachedArgument(5, "rary.gates".to_string())),
+                Some(Token::Flag(6)),
+                Some(Token::Switch(7, 's')),
+                Some(Token::Switch(7, 'c')),
+                Some(Token::Switch(7, 'i')),
+                Some(Token::Terminator(8)),
+                Some(Token::Ignore(9, "--map".to_string())),
+                Some(Token::Ignore(10, "synthesis".to_string())),
+                Some(Token::Ignore(11, "-jto".to_string())),
+            ],
+        );
+    }
+
+    #[test]
+    fn find_flags_and_switches() {
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "--help",
+            "-v",
+            "new",
+            "ip",
+            "--lib",
+            "--name=rary.gates",
+            "--help",
+            "-sci",
+            "-i",
+            "--",
+            "--map",
+            "synthesis",
+            "-jto",
+        ]));
+
+        // detects 0
+        assert_eq!(cli.take_flag_locs("version"), vec![]);
+        // detects 1
+        assert_eq!(cli.take_flag_locs("lib"), vec![4]);
+        // detects multiple
+        assert_eq!(cli.take_flag_locs("help"), vec![0, 7]);
+        // flag was past terminator and marked as ignore
+        assert_eq!(cli.take_flag_locs("map"), vec![]);
+        // filters out arguments
+        assert_eq!(cli.take_flag_locs("rary.gates"), vec![]);
+
+        // detects 0
+        assert_eq!(cli.take_switch_locs(&'q'), vec![]);
+        // detects 1
+        assert_eq!(cli.take_switch_locs(&'v'), vec![1]);
+        // detects multiple
+        assert_eq!(cli.take_switch_locs(&'i'), vec![10, 11]);
+        // switch was past terminator and marked as ignore
+        assert_eq!(cli.take_switch_locs(&'j'), vec![]);
+    }
+
+    #[test]
+    fn flags_in_map() {
+        let cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "--help",
+            "-v",
+            "new",
+            "ip",
+            "--lib",
+            "--name=rary.gates",
+            "--help",
+            "-sci",
+            symbol::FLAG,
+            "--map",
+            "synthesis",
+            "-jto",
+        ]));
+        let mut opt_store = HashMap::<Tag<String>, Slot>::new();
+        // store long options
+        opt_store.insert(
+            Tag::Flag("help".to_string()),
+            Slot {
+                pointers: vec![0, 7],
+                visited: false,
+            },
+        );
+        opt_store.insert(
+            Tag::Flag("lib".to_string()),
+            Slot {
+                pointers: vec![4],
+                visited: false,
+            },
+        );
+        opt_store.insert(
+            Tag::Flag("name".to_string()),
+            Slot {
+                pointers: vec![5],
+                visited: false,
+            },
+        );
+        // stores switches too
+        opt_store.insert(
+            Tag::Switch("v".to_string()),
+            Slot {
+                pointers: vec![1],
+                visited: false,
+            },
+        );
+        opt_store.insert(
+            Tag::Switch("s".to_string()),
+            Slot {
+                pointers: vec![8],
+                visited: false,
+            },
+        );
+        opt_store.insert(
+            Tag::Switch("c".to_string()),
+            Slot {
+                pointers: vec![9],
+                visited: false,
+            },
+        );
+        opt_store.insert(
+            Tag::Switch("i".to_string()),
+            Slot {
+                pointers: vec![10],
+                visited: false,
+            },
+        );
+        assert_eq!(cli.opt_store, opt_store);
+    }
+
+    #[test]
+    fn take_unattached_args() {
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "--help",
+            "-v",
+            "new",
+            "ip",
+            "--lib",
+            "--name=rary.gates",
+            "--help",
+            "-scii",
+            "get",
+            symbol::FLAG,
+            "--map",
+            "synthesis",
+            "-jto",
+        ]));
+
+        assert_eq!(cli.next_uarg().unwrap(), "new".to_string());
+        assert_eq!(cli.next_uarg().unwrap(), "ip".to_string());
+        assert_eq!(cli.next_uarg().unwrap(), "get".to_string());
+        assert_eq!(cli.next_uarg(), None);
+    }
+
+    #[test]
+    fn take_remainder_args() {
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "--help",
+            "-v",
+            "new",
+            "ip",
+            "--lib",
+            "--name=rary.gates",
+            "--help",
+            "-scii",
+            "get",
+            symbol::FLAG,
+            "--map",
+            "synthesis",
+            "-jto",
+        ]));
+        assert_eq!(
+            cli.check_remainder().unwrap(),
+            vec!["--map", "synthesis", "-jto"]
+        );
+        // the items were removed from the token stream
+        assert_eq!(cli.check_remainder().unwrap(), Vec::<String>::new());
+
+        // an attached argument can sneak in behind a terminator (handle in a result fn)
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--=value", "extra"]));
+        assert!(cli.check_remainder().is_err());
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--help"]));
+        // the terminator was never found
+        assert_eq!(cli.check_remainder().unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn pull_values_from_flags() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--help"]));
+        let locs = cli.take_flag_locs("help");
+        assert_eq!(cli.pull_flag(locs, false), vec![None]);
+        assert_eq!(cli.tokens.get(0), Some(&None));
+
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "--name",
+            "gates",
+            "arg",
+            "--lib",
+            "new",
+            "--name=gates2",
+            "--opt=1",
+            "--opt",
+            "--help",
+        ]));
+        let locs = cli.take_flag_locs("lib");
+        assert_eq!(cli.pull_flag(locs, false), vec![None]);
+        // token no longer exists
+        assert_eq!(cli.tokens.get(3), Some(&None));
+
+        // gets strings and removes both instances of flag from token stream
+        let locs = cli.take_flag_locs("name");
+        assert_eq!(
+            cli.pull_flag(locs, true),
+            vec![Some("gates".to_string()), Some("gates2".to_string())]
+        );
+        assert_eq!(cli.tokens.get(0), Some(&None));
+        assert_eq!(cli.tokens.get(5), Some(&None));
+
+        let locs = cli.take_flag_locs("opt");
+        assert_eq!(cli.pull_flag(locs, true), vec![Some("1".to_string()), None]);
+
+        // gets switches as well from the store
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit",
+            "--name",
+            "gates",
+            "-sicn",
+            "dut",
+            "new",
+            "-vl=direct",
+            "--help",
+            "-l",
+            "-m",
+            "install",
+        ]));
+        let locs = cli.take_switch_locs(&'l');
+        assert_eq!(
+            cli.pull_flag(locs, true),
+            vec![Some("direct".to_string()), None]
+        );
+        assert_eq!(cli.tokens.get(9), Some(&None));
+        assert_eq!(cli.tokens.get(12), Some(&None));
+        let locs = cli.take_switch_locs(&'s');
+        assert_eq!(cli.pull_flag(locs, true), vec![None]);
+        let locs = cli.take_switch_locs(&'v');
+        assert_eq!(cli.pull_flag(locs, true), vec![None]);
+        let locs = cli.take_switch_locs(&'i');
+        assert_eq!(cli.pull_flag(locs, true), vec![None]);
+        let locs = cli.take_switch_locs(&'c');
+        assert_eq!(cli.pull_flag(locs, false), vec![None]);
+        let locs = cli.take_switch_locs(&'m');
+        assert_eq!(cli.pull_flag(locs, false), vec![None]);
+    }
+
+    #[test]
+    fn check_flag() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--help", "--verbose", "get"]));
+        assert_eq!(cli.check_flag(Flag::new("help")).unwrap(), true);
+        assert_eq!(cli.check_flag(Flag::new("verbose")).unwrap(), true);
+        assert_eq!(cli.check_flag(Flag::new("version")).unwrap(), false);
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--upgrade", "-u"]));
+        assert_eq!(
+            cli.check_flag(Flag::new("upgrade").switch('u'))
+                .unwrap_err()
+                .kind(),
+            ErrorKind::DuplicateOptions
+        );
+
+        let mut cli =
+            Cli::new().tokenize(args(vec!["orbit", "--verbose", "--verbose", "--version=9"]));
+        assert_eq!(
+            cli.check_flag(Flag::new("verbose")).unwrap_err().kind(),
+            ErrorKind::DuplicateOptions
+        );
+        assert_eq!(
+            cli.check_flag(Flag::new("version")).unwrap_err().kind(),
+            ErrorKind::UnexpectedValue
+        );
+    }
+
+    #[test]
+    fn check_positional() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "new", "rary.gates"]));
+        assert_eq!(
+            cli.check_positional::<String>(Positional::new("command"))
+                .unwrap(),
+            Some("new".to_string())
+        );
+        assert_eq!(
+            cli.check_positional::<String>(Positional::new("ip"))
+                .unwrap(),
+            Some("rary.gates".to_string())
+        );
+        assert_eq!(
+            cli.check_positional::<i32>(Positional::new("path"))
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn check_option() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "command", "--rate", "10"]));
+        assert_eq!(cli.check_option(Optional::new("rate")).unwrap(), Some(10));
+
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit", "--flag", "--rate=9", "command", "-r", "14",
+        ]));
+        assert_eq!(
+            cli.check_option::<i32>(Optional::new("rate").switch('r'))
+                .unwrap_err()
+                .kind(),
+            ErrorKind::DuplicateOptions
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--flag", "-r", "14"]));
+        assert_eq!(
+            cli.check_option(Optional::new("rate").switch('r')).unwrap(),
+            Some(14)
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--flag", "--rate", "--verbose"]));
+        assert_eq!(
+            cli.check_option::<i32>(Optional::new("rate"))
+                .unwrap_err()
+                .kind(),
+            ErrorKind::ExpectingValue
+        );
+
+        let mut cli =
+            Cli::new().tokenize(args(vec!["orbit", "--flag", "--rate", "five", "--verbose"]));
+        assert!(cli.check_option::<i32>(Optional::new("rate")).is_err());
+    }
+
+    #[test]
+    fn negative_numbers() {
+        // by default a negative number is read as a switch cluster
+        let cli = Cli::new().tokenize(args(vec!["add", "-5", "3"]));
+        assert_eq!(cli.tokens[0], Some(Token::Switch(0, '5')));
+
+        let mut cli = Cli::new()
+            .allow_negative_numbers()
+            .tokenize(args(vec!["add", "-5", "-0.5", "-inf", "--rate", "-2"]));
+        assert_eq!(
+            cli.tokens[..3],
+            [
+                Some(Token::UnattachedArgument(0, "-5".to_string())),
+                Some(Token::UnattachedArgument(1, "-0.5".to_string())),
+                Some(Token::Switch(2, 'i')),
+            ]
+        );
+        assert_eq!(cli.check_option(Optional::new("rate")).unwrap(), Some(-2));
+        assert_eq!(cli.require_positional::<i8>(Positional::new("lhs")).unwrap(), -5);
+        assert_eq!(cli.require_positional::<f32>(Positional::new("rhs")).unwrap(), -0.5);
+    }
+
+    #[test]
+    fn read_args_from_sources() {
+        use crate::source::Line;
+
+        // a line typed into a repl has no program name
+        let mut cli = Cli::new().read_args(Line::new("add 1 '2'"));
+        assert_eq!(cli.program, None);
+        assert_eq!(
+            cli.require_positional::<String>(Positional::new("command")).unwrap(),
+            "add"
+        );
+        assert_eq!(
+            cli.require_positional_all::<u8>(Positional::new("num")).unwrap(),
+            vec![1, 2]
+        );
+
+        let path = std::env::temp_dir().join("clif-response-file.txt");
+        std::fs::write(&path, "--verbose\n'two words'\n").unwrap();
+        let argv = vec![
+            String::from("orbit"),
+            format!("@{}", path.display()),
+            String::from("@missing-clif-file"),
+        ];
+        let mut cli = Cli::new().allow_response_files().read_args(argv);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(cli.program, Some(String::from("orbit")));
+        assert_eq!(cli.check_flag(Flag::new("verbose")).unwrap(), true);
+        assert_eq!(
+            cli.require_positional_all::<String>(Positional::new("word")).unwrap(),
+            vec!["two words", "@missing-clif-file"]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn read_invalid_unicode() {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+
+        struct Bytes;
+
+        impl ArgSource for Bytes {
+            fn read(&mut self) -> Vec<OsString> {
+                vec![OsString::from("cat"), OsString::from_vec(vec![b'a', 0xff])]
+            }
+        }
+
+        let mut cli = Cli::new().read_args(Bytes);
+        let err = cli.require_positional::<String>(Positional::new("file")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidUnicode);
+        assert_eq!(err.to_string(), "argument 'a\u{fffd}' is not valid unicode");
+    }
+
+    #[test]
+    fn option_prefixes() {
+        let mut cli = Cli::new()
+            .option_prefixes("/", "/")
+            .tokenize(args(vec!["copy", "/v", "/out=b.txt", "/", "a.txt"]));
+        assert_eq!(cli.check_flag(Flag::new("v")).unwrap(), true);
+        assert_eq!(
+            cli.check_option::<String>(Optional::new("out")).unwrap(),
+            Some(String::from("b.txt"))
+        );
+        assert_eq!(
+            cli.require_positional_all::<String>(Positional::new("file")).unwrap(),
+            vec!["/", "a.txt"]
+        );
+        assert!(cli.is_empty().is_ok());
+
+        let mut cli = Cli::new()
+            .option_prefixes("+", "++")
+            .tokenize(args(vec!["opts", "+ab", "++color", "never", "++", "--x"]));
+        assert_eq!(cli.check_flag(Flag::new("all").switch('a')).unwrap(), true);
+        assert_eq!(cli.check_flag(Flag::new("bold").switch('b')).unwrap(), true);
+        assert_eq!(
+            cli.check_option::<String>(Optional::new("color")).unwrap(),
+            Some(String::from("never"))
+        );
+        assert_eq!(cli.check_remainder().unwrap(), vec!["--x"]);
+        assert!(cli.is_empty().is_ok());
+
+        // errors and suggestions name flags with the chosen prefix
+        let mut cli = Cli::new()
+            .option_prefixes("/", "/")
+            .threshold(2)
+            .tokenize(args(vec!["copy", "/verbos"]));
+        assert_eq!(cli.check_flag(Flag::new("verbose")).unwrap(), false);
+        let err = cli.is_empty().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::SuggestArg);
+        assert!(err.to_string().contains("'/verbose'"));
+
+        let mut cli = Cli::new()
+            .option_prefixes("/", "/")
+            .tokenize(args(vec!["copy", "/out"]));
+        let err = cli.check_option::<String>(Optional::new("out")).unwrap_err();
+        assert!(err.to_string().contains("'/out <out>'"));
+    }
+
+    #[test]
+    fn token_spans() {
+        let cli = Cli::new().tokenize(args(vec!["tar", "-xvf=a.tar", "--out=dir", "-", "--", "é"]));
+        let spans: Vec<(usize, std::ops::Range<usize>)> =
+            cli.spans.iter().map(|s| (s.get_arg(), s.get_range())).collect();
+        assert_eq!(
+            spans,
+            vec![
+                (1, 1..2),
+                (1, 2..3),
+                (1, 3..4),
+                (1, 5..10),
+                (2, 0..5),
+                (2, 6..9),
+                (3, 0..1),
+                (4, 0..2),
+                (5, 0..2),
+            ]
+        );
+
+        // a value joined to a switch within a cluster
+        let mut cli = Cli::new().tokenize(args(vec!["make", "-vj4x"]));
+        let err = cli
+            .check_option::<u8>(Optional::new("jobs").switch('j'))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BadType);
+        assert_eq!(err.span(), Some(&Span::new(1, 3, 5)));
+
+        // a piece of a delimited value
+        let mut cli = Cli::new().tokenize(args(vec!["run", "--ids=1,x,3"]));
+        let err = cli
+            .check_option_all::<u8>(Optional::new("ids").delimiter(','))
+            .unwrap_err();
+        assert_eq!(err.span(), Some(&Span::new(1, 8, 9)));
+
+        let mut cli = Cli::new().tokenize(args(vec!["run", "a", "--fast"]));
+        assert_eq!(
+            cli.require_positional::<String>(Positional::new("x")).unwrap(),
+            "a"
+        );
+        let err = cli.is_empty().unwrap_err();
+        assert_eq!(err.span(), Some(&Span::new(2, 0, 6)));
+
+        let mut cli = Cli::new().tokenize(args(vec!["run", "--out"]));
+        let err = cli.check_option::<String>(Optional::new("out")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ExpectingValue);
+        assert_eq!(err.span(), Some(&Span::new(1, 0, 5)));
+    }
+
+    #[test]
+    fn posix_mode() {
+        let mut cli = Cli::new().tokenize(args(vec!["ls", "-l", "dir", "-a"]));
+        assert_eq!(cli.check_flag(Flag::new("all").switch('a')).unwrap(), true);
+
+        let mut cli = Cli::new().posix_mode(true).tokenize(args(vec!["ls", "-l", "dir", "-a"]));
+        assert_eq!(cli.check_flag(Flag::new("long").switch('l')).unwrap(), true);
+        assert_eq!(cli.check_flag(Flag::new("all").switch('a')).unwrap(), false);
+        assert_eq!(
+            cli.require_positional_all::<String>(Positional::new("file")).unwrap(),
+            vec!["dir", "-a"]
+        );
+        assert!(cli.is_empty().is_ok());
+
+        // a cluster or an attached value after the first operand is one operand
+        let mut cli = Cli::new()
+            .posix_mode(true)
+            .tokenize(args(vec!["ls", "dir", "-la", "--color=never"]));
+        assert_eq!(cli.check_flag(Flag::new("long").switch('l')).unwrap(), false);
+        assert_eq!(
+            cli.require_positional_all::<String>(Positional::new("file")).unwrap(),
+            vec!["dir", "-la", "--color=never"]
+        );
+        assert!(cli.is_empty().is_ok());
+
+        // an attached value does not end the options
+        let mut cli = Cli::new()
+            .posix_mode(true)
+            .tokenize(args(vec!["tar", "-f=out.tar", "-v", "src", "--verbose"]));
+        assert_eq!(cli.check_flag(Flag::new("verbose").switch('v')).unwrap(), true);
+        assert_eq!(
+            cli.check_option::<String>(Optional::new("file").switch('f')).unwrap(),
+            Some(String::from("out.tar"))
+        );
+        assert_eq!(
+            cli.require_positional_all::<String>(Positional::new("path")).unwrap(),
+            vec!["src", "--verbose"]
+        );
+
+        // neither does a separate value
+        let mut cli = Cli::new()
+            .posix_mode(true)
+            .tokenize(args(vec!["tar", "-f", "out.tar", "-v"]));
+        assert_eq!(cli.check_flag(Flag::new("verbose").switch('v')).unwrap(), true);
+        assert_eq!(
+            cli.check_option::<String>(Optional::new("file").switch('f')).unwrap(),
+            Some(String::from("out.tar"))
+        );
+        assert!(cli.is_empty().is_ok());
+
+        // a subcommand's name is not its operand
+        let mut cli = Cli::new()
+            .posix_mode(true)
+            .tokenize(args(vec!["orbit", "get", "--force", "ip", "--all"]));
+        cli.known_args.push(Arg::Positional(Positional::new("command")));
+        assert_eq!(cli.match_command(&["get"]).unwrap(), "get");
+        assert_eq!(cli.check_flag(Flag::new("force")).unwrap(), true);
+        assert_eq!(cli.check_flag(Flag::new("all")).unwrap(), false);
+        assert_eq!(
+            cli.require_positional_all::<String>(Positional::new("ip")).unwrap(),
+            vec!["ip", "--all"]
+        );
+        assert!(cli.is_empty().is_ok());
+    }
+
+    #[test]
+    fn command_prefixes() {
+        let words = ["install", "init", "build"];
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "inst"]));
+        cli.known_args.push(Arg::Positional(Positional::new("command")));
+        assert_eq!(cli.match_command(&words).unwrap_err().kind(), ErrorKind::UnknownSubcommand);
+
+        let mut cli = Cli::new().allow_command_prefixes().tokenize(args(vec!["orbit", "inst"]));
+        cli.known_args.push(Arg::Positional(Positional::new("command")));
+        assert_eq!(cli.match_command(&words).unwrap(), "install");
+        assert_eq!(cli.matches().get_value("command"), Some("install"));
+
+        let mut cli = Cli::new().allow_command_prefixes().tokenize(args(vec!["orbit", "in"]));
+        cli.known_args.push(Arg::Positional(Positional::new("command")));
+        let err = cli.match_command(&words).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AmbiguousSubcommand);
+        assert_eq!(
+            err.to_string(),
+            "subcommand 'in' is ambiguous\n\nPossible matches: install, init"
+        );
+    }
+
+    #[test]
+    fn flag_prefixes() {
+        let names = ["verbose", "version", "color"];
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--verb", "--col=never", "--verbose"]));
+        cli.expand_flag_prefixes(&names).unwrap();
+        assert_eq!(cli.check_flag_n(Flag::new("verbose"), 2).unwrap(), 2);
+        assert_eq!(cli.check_flag(Flag::new("version")).unwrap(), false);
+        assert_eq!(
+            cli.check_option(Optional::new("color")).unwrap(),
+            Some(String::from("never"))
+        );
+        assert!(cli.is_empty().is_ok());
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--ver"]));
+        let err = cli.expand_flag_prefixes(&names).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AmbiguousFlag);
+        assert_eq!(
+            err.to_string(),
+            "argument '--ver' is ambiguous\n\nPossible matches: --verbose, --version"
+        );
+
+        // unknown flags are left for the usual errors
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--quiet"]));
+        cli.expand_flag_prefixes(&names).unwrap();
+        assert_eq!(cli.is_empty().unwrap_err().kind(), ErrorKind::UnexpectedArg);
+    }
+
+    #[test]
+    fn literal_dashes() {
+        // a lone hyphen is always a value
+        let mut cli = Cli::new().tokenize(args(vec!["cat", "-"]));
+        assert_eq!(cli.require_positional::<String>(Positional::new("file")).unwrap(), "-");
+
+        let mut cli = Cli::new().tokenize(args(vec!["echo", "--"]));
+        assert_eq!(
+            cli.require_positional::<String>(Positional::new("text")).unwrap_err().kind(),
+            ErrorKind::UnexpectedArg
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec!["echo", "--", "-n", "a"]));
+        assert_eq!(
+            cli.require_positional::<String>(Positional::new("text").allow_terminator()).unwrap(),
+            "--"
+        );
+        // the arguments after the literal are still the remainder
+        assert_eq!(cli.check_remainder().unwrap(), vec!["-n", "a"]);
+        assert!(cli.is_empty().is_ok());
+
+        // a literal after an escaping terminator is an ordinary value
+        let mut cli = Cli::new()
+            .escape_positionals()
+            .tokenize(args(vec!["grep", "--", "--", "file"]));
+        assert_eq!(cli.require_positional::<String>(Positional::new("pattern")).unwrap(), "--");
+        assert_eq!(cli.require_positional::<String>(Positional::new("file")).unwrap(), "file");
+    }
+
+    #[test]
+    fn escaped_positionals() {
+        // by default the terminator forwards the arguments after it
+        let mut cli = Cli::new().tokenize(args(vec!["remove", "--", "--force-named-file"]));
+        assert_eq!(
+            cli.require_positional::<String>(Positional::new("file")).unwrap_err().kind(),
+            ErrorKind::UnexpectedArg
+        );
+
+        let mut cli = Cli::new()
+            .escape_positionals()
+            .tokenize(args(vec!["remove", "-v", "--", "--force-named-file", "-v"]));
+        assert_eq!(cli.check_flag(Flag::new("verbose").switch('v')).unwrap(), true);
+        assert_eq!(
+            cli.require_positional_all::<String>(Positional::new("file")).unwrap(),
+            vec!["--force-named-file", "-v"]
+        );
+        assert_eq!(cli.check_remainder().unwrap(), Vec::<String>::new());
+        assert!(cli.is_empty().is_ok());
+
+        // an escaped value is not read as a subcommand
+        let mut cli = Cli::new()
+            .escape_positionals()
+            .tokenize(args(vec!["orbit", "--", "add"]));
+        assert_eq!(
+            cli.check_command::<Sub>(Positional::new("command")).unwrap().is_none(),
+            true
+        );
+        assert_eq!(
+            cli.require_positional::<String>(Positional::new("name")).unwrap(),
+            "add"
+        );
+    }
+
+    #[test]
+    fn hyphen_values() {
+        let mut cli = Cli::new().tokenize(args(vec!["grep", "--pattern", "--x=1", "-vq", "file"]));
+        assert_eq!(
+            cli.check_option::<String>(Optional::new("pattern").allow_hyphen_values())
+                .unwrap(),
+            Some("--x=1".to_string())
+        );
+        assert_eq!(cli.check_flag(Flag::new("verbose").switch('v')).unwrap(), true);
+        assert_eq!(cli.check_flag(Flag::new("quiet").switch('q')).unwrap(), true);
+        assert_eq!(
+            cli.require_positional::<String>(Positional::new("file")).unwrap(),
+            "file"
+        );
+        assert!(cli.is_empty().is_ok());
+
+        // the consumed value is never reported as an unknown flag
+        let mut cli = Cli::new().tokenize(args(vec!["calc", "--offset", "-12"]));
+        assert_eq!(
+            cli.check_option::<i32>(Optional::new("offset").allow_hyphen_values())
+                .unwrap(),
+            Some(-12)
+        );
+        assert!(cli.is_empty().is_ok());
+
+        // an attached value is still preferred
+        let mut cli = Cli::new().tokenize(args(vec!["calc", "--offset=3", "-v"]));
+        assert_eq!(
+            cli.check_option::<i32>(Optional::new("offset").allow_hyphen_values())
+                .unwrap(),
+            Some(3)
+        );
+        assert_eq!(cli.check_flag(Flag::new("verbose").switch('v')).unwrap(), true);
+    }
+
+    #[test]
+    fn attached_switch_values() {
+        let mut cli = Cli::new().tokenize(args(vec!["make", "-j4", "-vofile.txt", "-c=1"]));
+        assert_eq!(cli.check_option(Optional::new("jobs").switch('j')).unwrap(), Some(4));
+        assert_eq!(
+            cli.check_option(Optional::new("output").switch('o')).unwrap(),
+            Some(String::from("file.txt"))
+        );
+        assert_eq!(cli.check_option(Optional::new("count").switch('c')).unwrap(), Some(1));
+        assert_eq!(cli.check_flag(Flag::new("verbose").switch('v')).unwrap(), true);
+        // the characters taken as a value are no longer seen as switches
+        assert_eq!(cli.check_flag(Flag::new("file").switch('f')).unwrap(), false);
+        assert!(cli.is_empty().is_ok());
+
+        // flags still split the cluster into individual switches
+        let mut cli = Cli::new().tokenize(args(vec!["tar", "-xzf", "archive.tgz"]));
+        assert_eq!(cli.check_flag(Flag::new("extract").switch('x')).unwrap(), true);
+        assert_eq!(cli.check_flag(Flag::new("gzip").switch('z')).unwrap(), true);
+        assert_eq!(
+            cli.check_option(Optional::new("file").switch('f')).unwrap(),
+            Some(String::from("archive.tgz"))
+        );
+    }
+
+    #[test]
+    fn check_flag_count() {
+        use crate::value::Verbosity;
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "-vvv", "--verbose", "-q"]));
+        let verbose = cli.check_flag_count(Flag::new("verbose").switch('v')).unwrap();
+        assert_eq!(verbose, Count::new(4));
+        let quiet = cli.check_flag_count(Flag::new("quiet").switch('q')).unwrap();
+        assert_eq!(Verbosity::new(verbose, quiet), Verbosity::Trace);
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "-v=2"]));
+        assert_eq!(
+            cli.check_flag_count(Flag::new("verbose").switch('v'))
+                .unwrap_err()
+                .kind(),
+            ErrorKind::UnexpectedValue
+        );
+    }
+
+    #[test]
+    fn check_option_positional() {
+        let target = || Optional::new("target").possible_values(&["debug", "release"]);
+        let mut cli = Cli::new().tokenize(args(vec!["app", "build", "release"]));
+        assert_eq!(
+            cli.require_positional::<String>(Positional::new("command")).unwrap(),
+            "build"
+        );
+        assert_eq!(
+            cli.require_option_positional::<String>(target()).unwrap(),
+            "release"
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec!["app", "--target", "debug"]));
+        assert_eq!(
+            cli.check_option_positional::<String>(target()).unwrap(),
+            Some(String::from("debug"))
+        );
+        assert!(cli.is_empty().is_ok());
+
+        // the option form takes precedence over the positional form
+        let mut cli = Cli::new().tokenize(args(vec!["app", "release", "--target", "debug"]));
+        assert_eq!(
+            cli.check_option_positional::<String>(target()).unwrap(),
+            Some(String::from("debug"))
+        );
+        assert_eq!(cli.is_empty().unwrap_err().kind(), ErrorKind::UnexpectedArg);
+
+        let mut cli = Cli::new().tokenize(args(vec!["app", "--target", "debug", "--target=release"]));
+        assert_eq!(
+            cli.check_option_positional::<String>(target()).unwrap_err().kind(),
+            ErrorKind::DuplicateOptions
+        );
+
+        // value rules apply to both forms
+        let mut cli = Cli::new().tokenize(args(vec!["app", "profile"]));
+        assert_eq!(
+            cli.check_option_positional::<String>(target()).unwrap_err().kind(),
+            ErrorKind::InvalidChoice
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec!["app"]));
+        assert_eq!(
+            cli.require_option_positional::<String>(target()).unwrap_err().kind(),
+            ErrorKind::MissingPositional
+        );
+    }
+
+    #[test]
+    fn take_token_str() {
+        let t = Token::UnattachedArgument(0, "get".to_string());
+        // consumes token and returns its internal string
+        assert_eq!(t.take_str(), "get");
+
+        let t = Token::AttachedArgument(1, "rary.gates".to_string());
+        assert_eq!(t.take_str(), "rary.gates");
+
+        let t = Token::Ignore(7, "--map".to_string());
+        assert_eq!(t.take_str(), "--map");
+    }
+
+    #[test]
+    #[should_panic]
+    fn take_impossible_token_flag_str() {
+        let t = Token::Flag(7);
+        t.take_str();
+    }
+
+    #[test]
+    #[should_panic]
+    fn take_impossible_token_switch_str() {
+        let t = Token::Switch(7, 'h');
+        t.take_str();
+    }
+
+    #[test]
+    #[should_panic]
+    fn take_impossible_token_terminator_str() {
+        let t = Token::Terminator(9);
+        t.take_str();
+    }
+
+    #[test]
+    fn try_help_fail() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--h"]));
+        let locs = cli.take_flag_locs("help");
+        assert_eq!(locs.len(), 0);
+        assert_eq!(cli.pull_flag(locs, false), vec![]);
+    }
+
+    #[test]
+    fn check_option_n() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "command", "--rate", "10"]));
+        assert_eq!(
+            cli.check_option_n(Optional::new("rate"), 1).unwrap(),
+            Some(vec![10])
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "command", "--rate", "10"]));
+        assert_eq!(
+            cli.check_option_n(Optional::new("rate"), 2).unwrap(),
+            Some(vec![10])
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit", "command", "--rate", "10", "--rate", "4", "--rate", "3",
+        ]));
+        assert_eq!(
+            cli.check_option_n::<u8>(Optional::new("rate"), 2).is_err(),
+            true
+        );
+    }
+
+    #[test]
+    fn check_flag_n() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit"]));
+        assert_eq!(cli.check_flag_n(Flag::new("debug"), 4).unwrap(), 0);
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--debug"]));
+        assert_eq!(cli.check_flag_n(Flag::new("debug"), 1).unwrap(), 1);
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--debug", "--debug"]));
+        assert_eq!(cli.check_flag_n(Flag::new("debug"), 3).unwrap(), 2);
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--debug", "--debug", "--debug"]));
+        assert_eq!(cli.check_flag_n(Flag::new("debug"), 3).unwrap(), 3);
+
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit", "--debug", "--debug", "--debug", "--debug",
+        ]));
+        assert_eq!(cli.check_flag_n(Flag::new("debug"), 3).is_err(), true);
+    }
+
+    #[test]
+    fn check_flag_all() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit"]));
+        assert_eq!(cli.check_flag_all(Flag::new("debug")).unwrap(), 0);
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--debug", "--debug", "--debug"]));
+        assert_eq!(cli.check_flag_all(Flag::new("debug")).unwrap(), 3);
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--debug", "--debug", "--debug=1"]));
+        assert_eq!(cli.check_flag_all(Flag::new("debug")).is_err(), true);
+    }
+
+    #[test]
+    fn requires_positional_all() {
+        let mut cli = Cli::new().tokenize(args(vec!["sum", "10", "20", "30"]));
+        assert_eq!(
+            cli.require_positional_all::<u8>(Positional::new("digit"))
+                .unwrap(),
+            vec![10, 20, 30]
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec!["sum"]));
+        assert_eq!(
+            cli.require_positional_all::<u8>(Positional::new("digit"))
+                .unwrap_err()
+                .kind(),
+            ErrorKind::MissingPositional
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec!["sum", "10", "--option", "20", "30"]));
+        // note: remember to always check options and flags before positional arguments
+        assert_eq!(
+            cli.check_option::<u8>(Optional::new("option")).unwrap(),
+            Some(20)
+        );
+        assert_eq!(
+            cli.require_positional_all::<u8>(Positional::new("digit"))
+                .unwrap(),
+            vec![10, 30]
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec!["sum", "100"]));
+        assert_eq!(
+            cli.require_positional_all::<u8>(Positional::new("digit"))
+                .unwrap(),
+            vec![100]
+        );
+    }
+
+    #[test]
+    fn variadic_positionals() {
+        let mut cli = Cli::new().tokenize(args(vec!["add", "a.txt", "b.txt", "c.txt"]));
+        assert_eq!(
+            cli.check_positional_all::<String>(Positional::new("file"))
+                .unwrap(),
+            vec!["a.txt", "b.txt", "c.txt"]
+        );
+        assert_eq!(
+            cli.check_positional_all::<String>(Positional::new("rest"))
+                .unwrap(),
+            Vec::<String>::new()
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec!["add", "1", "2"]));
+        assert_eq!(
+            cli.require_positional_n::<u8>(Positional::new("digit"), 1, Some(2))
+                .unwrap(),
+            vec![1, 2]
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec!["add", "1", "2", "3"]));
+        let err = cli
+            .require_positional_n::<u8>(Positional::new("digit"), 1, Some(2))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ExceedingMaxCount);
+        assert_eq!(
+            err.to_string(),
+            "argument '<digit>' accepts up to 2 values but was supplied 3"
+        );
+
+        let mut cli = Cli::new().tokenize(args(vec!["add", "1"]));
+        let err = cli
+            .require_positional_n::<u8>(Positional::new("digit"), 2, None)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MissingPositional);
+        assert!(err
+            .to_string()
+            .starts_with("argument '<digit>' requires at least 2 values but was supplied 1"));
+    }
+
+    #[test]
+    fn trailing_positionals() {
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit", "exec", "-q", "make", "-j4", "--verbose", "all", "--", "x",
+        ]));
+        assert_eq!(cli.check_flag(Flag::new("quiet").switch('q')).unwrap(), true);
+        assert_eq!(
+            cli.require_positional::<String>(Positional::new("command")).unwrap(),
+            "exec"
+        );
+        assert_eq!(
+            cli.require_positional_all::<String>(Positional::new("program").trailing())
+                .unwrap(),
+            vec!["make", "-j4", "--verbose", "all", "--", "x"]
+        );
+        assert_eq!(cli.is_empty().is_ok(), true);
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--", "x"]));
+        assert_eq!(
+            cli.check_positional_all::<String>(Positional::new("program").trailing())
+                .unwrap(),
+            Vec::<String>::new()
+        );
+        let mut cli = Cli::new().tokenize(args(vec!["orbit"]));
+        assert_eq!(
+            cli.require_positional_all::<String>(Positional::new("program").trailing())
+                .unwrap_err()
+                .kind(),
+            ErrorKind::MissingPositional
+        );
+
+        // flags after the wrapped program are forwarded whatever the query order
+        let mut cli = Cli::new().tokenize(args(vec!["wrap", "-q", "make", "-v", "-q"]));
+        cli.stop_at_operand();
+        assert_eq!(cli.check_flag(Flag::new("quiet").switch('q')).unwrap(), true);
+        assert_eq!(cli.check_flag(Flag::new("verbose").switch('v')).unwrap(), false);
+        assert_eq!(
+            cli.check_positional_all::<String>(Positional::new("program").trailing())
+                .unwrap(),
+            vec!["make", "-v", "-q"]
+        );
+        assert_eq!(cli.is_empty().is_ok(), true);
 
-    /// Returns all locations in the token stream where the switch identifier `c` is found.
-    fn take_switch_locs(&mut self, c: &char) -> Vec<usize> {
-        // allocate &str to the stack and not the heap to get from store
-        let mut arr = [0; 4];
-        let tag = c.encode_utf8(&mut arr);
+        // without the cutoff, a flag queried first takes the wrapped program's flag
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "make", "-v"]));
+        assert_eq!(cli.check_flag(Flag::new("verbose").switch('v')).unwrap(), true);
+        let err = cli
+            .check_positional_all::<String>(Positional::new("program").trailing())
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::TrailingConflict);
+        assert_eq!(
+            err.to_string(),
+            "'-v' is forwarded with '<program>' but was already read by '--verbose'"
+        );
 
-        if let Some(slot) = self.opt_store.get_mut(&Tag::Switch(tag.to_owned())) {
-            slot.visit();
-            slot.get_indices().to_vec()
-        } else {
-            Vec::new()
-        }
+        // the framework's own flags are handled rather than forwarded
+        let mut cli = Cli::new().tokenize(args(vec![
+            "wrap", "run", "x", "--timings", "--clif-explain", "--enable-unstable", "-y",
+        ]));
+        assert_eq!(
+            cli.check_positional_all::<String>(Positional::new("cmd").trailing())
+                .unwrap(),
+            vec!["run", "x", "-y"]
+        );
+        assert_eq!(cli.timings().is_some(), true);
+        assert_eq!(cli.is_internal_raised("explain"), true);
+        assert_eq!(cli.unstable, true);
+        assert_eq!(cli.is_empty().unwrap_err().kind(), ErrorKind::Explain);
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::error::ErrorKind;
+    #[test]
+    fn terminator_guidance() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "run", "--release", "x", "--", "y"]));
+        assert_eq!(
+            cli.require_positional::<String>(Positional::new("command")).unwrap(),
+            "run"
+        );
+        assert_eq!(cli.check_remainder().unwrap(), vec!["y"]);
+        let err = cli.is_empty().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedArg);
+        assert_eq!(
+            err.to_string(),
+            "invalid argument '--release'
 
-    /// Helper test fn to write vec of &str as iterator for Cli parameter.
-    fn args<'a>(args: Vec<&'a str>) -> Box<dyn Iterator<Item = String> + 'a> {
-        Box::new(args.into_iter().map(|f| f.to_string()).into_iter())
+Arguments for the wrapped command must follow '--', as in:
+    orbit run -- --release x y"
+        );
+
+        // the suggested line hides secrets and quotes words with spaces
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit", "run", "--token", "abc", "--name", "a b", "--", "y",
+        ]));
+        let _: Option<String> = cli.check_option(Optional::new("token").secret()).unwrap();
+        let _: String = cli.require_positional(Positional::new("command")).unwrap();
+        let _ = cli.check_remainder().unwrap();
+        assert_eq!(
+            cli.is_empty().unwrap_err().to_string(),
+            "invalid argument '--name'
+
+Arguments for the wrapped command must follow '--', as in:
+    orbit run --token '***' -- --name 'a b' y"
+        );
+
+        // commands that forward nothing keep the plain error
+        let cli = Cli::new().tokenize(args(vec!["orbit", "--release"]));
+        assert_eq!(cli.is_empty().unwrap_err().to_string(), "invalid argument '--release'");
     }
 
     #[test]
-    fn get_all_optionals() {
-        // option provided multiple times
+    fn explain_bindings() {
         let mut cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "plan",
-            "--fileset",
-            "a",
-            "--fileset=b",
-            "--fileset",
-            "c",
+            "orbit", "-v", "--rate=9", "new", "extra", "--", "a",
         ]));
-        let sets: Vec<String> = cli
-            .check_option_all(Optional::new("fileset"))
-            .unwrap()
-            .unwrap();
-        assert_eq!(sets, vec!["a", "b", "c"]);
-        // failing case- bad conversion of 'c' to an integer
+        let _ = cli.check_flag(Flag::new("verbose").switch('v')).unwrap();
+        let _: Option<u8> = cli.check_option(Optional::new("rate")).unwrap();
+        let _: String = cli.require_positional(Positional::new("command")).unwrap();
+        let _ = cli.check_remainder().unwrap();
+        assert_eq!(
+            cli.explain(),
+            "  0  -v      flag '--verbose'
+  1  --rate  flag '--rate <rate>'
+  1  9       value for '--rate <rate>'
+  2  new     positional '<command>'
+  3  extra   unused
+  4  --      terminator
+  5  a       remainder after terminator
+"
+        );
+    }
+
+    #[test]
+    fn secret_values() {
         let mut cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "plan",
-            "--digit",
-            "10",
-            "--digit=9",
-            "--digit",
-            "c",
+            "orbit", "--token=abc", "--user", "me", "-p", "hunter2",
         ]));
+        let token: Option<String> = cli.check_option(Optional::new("token").secret()).unwrap();
+        let _: Option<String> = cli.check_option(Optional::new("user")).unwrap();
+        let password: Option<String> = cli
+            .check_option(Optional::new("password").switch('p').secret())
+            .unwrap();
+        // the command still receives the real values
+        assert_eq!(token, Some("abc".to_string()));
+        assert_eq!(password, Some("hunter2".to_string()));
         assert_eq!(
-            cli.check_option_all::<i32>(Optional::new("digit")).is_err(),
-            true
-        ); // bad conversion
-           // option provided as valid integers
+            cli.redacted_args(),
+            vec!["--token=***", "--user", "me", "-p", "***"]
+        );
+        assert_eq!(cli.explain().contains("abc"), false);
+        assert_eq!(cli.explain().contains("hunter2"), false);
+        assert_eq!(cli.audit()[1].get_text(), "***");
+        assert_eq!(cli.matches().redacted().get_value("password"), Some("***"));
+        let dump = format!("{:?}", cli);
+        assert_eq!(dump.contains("abc"), false);
+        assert_eq!(dump.contains("hunter2"), false);
+        assert!(dump.contains("\"--token=***\""));
+
+        let mut cli = Cli::new()
+            .redaction("<hidden>")
+            .tokenize(args(vec!["orbit", "--pin", "12a"]));
+        let err = cli
+            .check_option::<u16>(Optional::new("pin").secret())
+            .unwrap_err();
+        assert_eq!(err.to_string().contains("12a"), false);
+        assert!(err.to_string().contains("<hidden>"));
+
+        // choices are listed without the value or anything resembling it
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--key", "alphq"]));
+        let err = cli
+            .check_option::<String>(Optional::new("key").secret().possible_values(&["alpha", "beta"]))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidChoice);
+        assert_eq!(
+            err.to_string(),
+            "argument '--key <key>' does not accept '***'\n\nPossible values: alpha, beta"
+        );
+        let many: Vec<String> = (0..CHOICE_LIST_LIMIT).map(|i| format!("alpha{}", i)).collect();
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--key", "alpha1x"]));
+        let err = cli
+            .check_option::<String>(Optional::new("key").secret().possible_values(&many))
+            .unwrap_err();
+        assert_eq!(err.to_string().contains("alpha1"), false);
+        assert!(err.to_string().contains("'***'"));
+    }
+
+    #[test]
+    fn internal_flags() {
         let mut cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "plan",
-            "--digit",
-            "10",
-            "--digit=9",
-            "--digit",
-            "1",
+            "orbit", "--clif-explain", "-v", "--clif-trace=parse", "--", "--clif-record",
         ]));
-        let sets: Vec<i32> = cli
-            .check_option_all(Optional::new("digit"))
-            .unwrap()
-            .unwrap();
-        assert_eq!(sets, vec![10, 9, 1]);
-        // option provided once
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "plan", "--fileset", "a"]));
-        let sets: Vec<String> = cli
-            .check_option_all(Optional::new("fileset"))
+        assert_eq!(cli.is_internal_raised("explain"), true);
+        assert_eq!(cli.get_internal_value("trace"), Some("parse"));
+        // internal flags after the terminator belong to the remainder
+        assert_eq!(cli.is_internal_raised("record"), false);
+        // applications never see the reserved flags
+        assert_eq!(cli.check_flag(Flag::new("clif-explain")).unwrap(), false);
+        assert_eq!(cli.check_flag(Flag::new("verbose").switch('v')).unwrap(), true);
+        assert_eq!(cli.check_remainder().unwrap(), vec!["--clif-record"]);
+
+        let err = cli.is_empty().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Explain);
+        assert_eq!(err.exit_code(), 0);
+        assert_eq!(
+            err.to_string(),
+            "  1  -v             flag '--verbose'
+  3  --             terminator
+  4  --clif-record  remainder after terminator"
+        );
+    }
+
+    #[test]
+    fn check_mode() {
+        // a positional given on the command-line is not reported from the config
+        let config = Config::parse("[cli]\nlang = \"vhdl\"\njobs = \"4\"\nip = \"gates\"\n")
             .unwrap()
+            .env_vars("ORBIT_", vec![("ORBIT_LANG".to_string(), "verilog".to_string())]);
+        let mut cli = Cli::new()
+            .config(config)
+            .freeze_option("target", "x86")
+            .tokenize(args(vec!["orbit", "--clif-check", "-vv", "--token", "abc", "adder"]));
+        assert_eq!(cli.check_flag_all(Flag::new("verbose").switch('v')).unwrap(), 2);
+        assert_eq!(
+            cli.check_option::<String>(Optional::new("token").secret()).unwrap(),
+            Some("abc".to_string())
+        );
+        assert_eq!(cli.check_option::<String>(Optional::new("lang")).unwrap().unwrap(), "verilog");
+        assert_eq!(cli.check_option::<u8>(Optional::new("jobs")).unwrap(), Some(4));
+        assert_eq!(cli.check_option::<String>(Optional::new("target")).unwrap().unwrap(), "x86");
+        assert_eq!(cli.require_positional::<String>(Positional::new("ip")).unwrap(), "adder");
+
+        let err = cli.is_empty().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Check);
+        assert_eq!(err.exit_code(), 0);
+        assert_eq!(
+            err.to_string(),
+            "Setting  Value    Source
+verbose  2        command-line
+token    ***      command-line
+lang     verilog  environment
+jobs     4        config
+target   x86      frozen
+ip       adder    command-line"
+        );
+
+        // rules are still evaluated before the settings are reported
+        let cli = Cli::new().tokenize(args(vec!["orbit", "--clif-check", "extra"]));
+        assert_eq!(cli.is_empty().unwrap_err().kind(), ErrorKind::UnexpectedArg);
+    }
+
+    #[test]
+    fn validate_values() {
+        let port = || {
+            Optional::new("port").validator(|s| match s.starts_with('0') {
+                true => Err(String::from("leading zeros are not allowed")),
+                false => Ok(()),
+            })
+        };
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--port", "8080"]));
+        assert_eq!(cli.check_option::<u16>(port()).unwrap(), Some(8080));
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--port", "080"]));
+        let err = cli.check_option::<u16>(port()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidValue);
+        assert_eq!(
+            err.to_string(),
+            "argument '--port <port>' does not accept '080': leading zeros are not allowed"
+        );
+
+        // validators run before type conversion
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "0x"]));
+        let digit = Positional::new("digit").validator(|s| match s.chars().all(|c| c.is_ascii_digit()) {
+            true => Ok(()),
+            false => Err(String::from("expected only digits")),
+        });
+        assert_eq!(
+            cli.check_positional::<u8>(digit).unwrap_err().kind(),
+            ErrorKind::InvalidValue
+        );
+    }
+
+    #[test]
+    fn check_possible_values() {
+        let when = || Optional::new("color").value("when").possible_values(&["auto", "always", "never"]);
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--color", "never"]));
+        assert_eq!(cli.check_option::<String>(when()).unwrap(), Some(String::from("never")));
+
+        let mut cli = Cli::new().threshold(2).tokenize(args(vec!["orbit", "--color=alwys"]));
+        let err = cli.check_option::<String>(when()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidChoice);
+        assert_eq!(
+            err.to_string(),
+            "argument '--color <when>' does not accept 'alwys'\n\nPossible values: auto, always, never\n\nDid you mean 'always'?"
+        );
+
+        // no suggestion is offered when the value is not close to any choice
+        let mut cli = Cli::new().threshold(2).tokenize(args(vec!["orbit", "rainbow"]));
+        let err = cli
+            .check_positional::<String>(Positional::new("when").possible_values(&["auto", "always", "never"]))
+            .unwrap_err();
+        assert_eq!(err.to_string().contains("Did you mean"), false);
+    }
+
+    #[test]
+    fn truncate_many_choices() {
+        let formats: Vec<String> = (0..20).map(|i| format!("fmt{}", i)).collect();
+        let format = || {
+            Optional::new("format").possible_values(
+                &["json", "yaml", "toml", "xml"]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .chain(formats.iter().cloned())
+                    .collect::<Vec<String>>(),
+            )
+        };
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--format", "yml"]));
+        let err = cli.check_option::<String>(format()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidChoice);
+        assert_eq!(
+            err.to_string(),
+            "argument '--format <format>' does not accept 'yml'\n\nClosest values: yaml, xml, toml\n\nRun with '--list-formats' to see all possible values."
+        );
+
+        // the listing flag reports every choice instead of parsing the value
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--list-formats"]));
+        let err = cli.check_option::<String>(format()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ListChoices);
+        assert_eq!(err.exit_code(), 0);
+        assert_eq!(err.to_string().lines().count(), 24);
+        assert!(err.to_string().starts_with("json\nyaml\n"));
+
+        // small sets of choices do not offer a listing flag
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--list-colors"]));
+        let _ = cli
+            .check_option::<String>(Optional::new("color").possible_values(&["auto", "never"]))
             .unwrap();
-        assert_eq!(sets, vec!["a"]);
-        // option not provided
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "plan"]));
-        let sets: Option<Vec<String>> = cli.check_option_all(Optional::new("fileset")).unwrap();
-        assert_eq!(sets, None);
+        assert_eq!(cli.is_empty().unwrap_err().kind(), ErrorKind::UnexpectedArg);
+
+        // irregular plurals name the listing flag themselves
+        let index = || Optional::new("index").possible_values(&formats).list_flag("list-indices");
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--index", "fmt"]));
+        let err = cli.check_option::<String>(index()).unwrap_err();
+        assert!(err.to_string().ends_with("Run with '--list-indices' to see all possible values."));
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--list-indices"]));
+        let err = cli.check_option::<String>(index()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ListChoices);
     }
 
     #[test]
-    fn match_command() {
-        let mut cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "get",
-            "rary.gates",
-            "--instance",
-            "--component",
-        ]));
-        // successfully matched 'get' command
+    fn required_together() {
+        let parse = |argv: Vec<&str>| -> Result<(), Error> {
+            let mut cli = Cli::new().tokenize(args(argv));
+            cli.requires_together(&["user", "password"]);
+            let _: Option<String> = cli.check_option(Optional::new("user"))?;
+            let _: Option<String> = cli.check_option(Optional::new("password"))?;
+            cli.is_empty()
+        };
+        assert!(parse(vec!["login"]).is_ok());
+        assert!(parse(vec!["login", "--user", "ada", "--password", "1234"]).is_ok());
+
+        let err = parse(vec!["login", "--user", "ada"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::RequiredTogether);
         assert_eq!(
-            cli.match_command(&["new", "get", "install", "edit"])
-                .unwrap(),
-            "get".to_string()
+            err.to_string(),
+            "argument '--user <user>' must be used together with '--password <password>'"
+        );
+        let err = parse(vec!["login", "--password", "1234"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "argument '--password <password>' must be used together with '--user <user>'"
         );
+    }
 
-        let mut cli = Cli::new().threshold(4).tokenize(args(vec![
-            "orbit",
-            "got",
-            "rary.gates",
-            "--instance",
-            "--component",
-        ]));
-        // suggest 'get' command
-        assert!(cli
-            .match_command(&["new", "get", "install", "edit"])
-            .is_err());
+    #[test]
+    fn required_companions() {
+        let parse = |argv: Vec<&str>| -> Result<(), Error> {
+            let mut cli = Cli::new().tokenize(args(argv));
+            let _ = cli.check_flag(Flag::new("force").switch('f').requires("output"))?;
+            let _: Option<String> = cli.check_option(Optional::new("output").switch('o'))?;
+            cli.is_empty()
+        };
+        assert!(parse(vec!["orbit"]).is_ok());
+        assert!(parse(vec!["orbit", "-o", "a.out"]).is_ok());
+        assert!(parse(vec!["orbit", "-fo", "a.out"]).is_ok());
+
+        let err = parse(vec!["orbit", "--force"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MissingRequirement);
+        assert_eq!(
+            err.to_string(),
+            "argument '--force' requires '--output <output>' to also be supplied"
+        );
     }
 
     #[test]
-    #[should_panic = "requires positional argument"]
-    fn match_command_no_arg() {
-        let mut cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "got",
-            "rary.gates",
-            "--instance",
-            "--component",
-        ]));
-        // cli has no positional arguments loaded to report as error... panic
-        assert!(cli
-            .match_command(&["new", "get", "install", "edit"])
-            .is_err());
+    fn global_flags() {
+        let mut cli = Cli::new().tokenize(args(vec!["op", "-v", "--color=never", "add", "9"]));
+        cli.global_flag(Flag::new("verbose").switch('v'));
+        cli.global_option(Optional::new("color"));
+        cli.known_args.push(Arg::Positional(Positional::new("command")));
+        assert_eq!(cli.match_command(&["add"]).unwrap(), "add");
+        // the subcommand reads the flags given before its name
+        assert_eq!(cli.check_flag(Flag::new("verbose").switch('v')).unwrap(), true);
+        assert_eq!(
+            cli.check_option(Optional::new("color")).unwrap(),
+            Some(String::from("never"))
+        );
+        assert_eq!(cli.require_positional::<u8>(Positional::new("lhs")).unwrap(), 9);
+        assert!(cli.is_empty().is_ok());
+
+        // a registered flag the subcommand never queries is still unexpected
+        for flag in ["-v", "--verbose"] {
+            let mut cli = Cli::new().tokenize(args(vec!["op", flag, "add"]));
+            cli.global_flag(Flag::new("verbose").switch('v'));
+            cli.known_args.push(Arg::Positional(Positional::new("command")));
+            assert_eq!(cli.match_command(&["add"]).unwrap(), "add");
+            let err = cli.is_empty().unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::UnexpectedArg);
+            assert_eq!(err.to_string(), format!("invalid argument '{}'", flag));
+        }
+
+        // without registering, the flag is out of context for the subcommand
+        let mut cli = Cli::new().tokenize(args(vec!["op", "-v", "add", "9"]));
+        cli.known_args.push(Arg::Positional(Positional::new("command")));
+        assert_eq!(
+            cli.match_command(&["add"]).unwrap_err().kind(),
+            ErrorKind::OutOfContextArgSuggest
+        );
     }
 
     #[test]
-    fn find_first_flag_left() {
-        let cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "--help",
-            "new",
-            "rary.gates",
-            "--vcs",
-            "git",
-        ]));
+    fn workspace_root() {
+        use crate::command::Command;
+
+        #[derive(Debug)]
+        struct Build {
+            root: PathBuf,
+        }
+
+        impl FromCli for Build {
+            fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+                Ok(Build {
+                    root: cli.check_workspace(Workspace::marker("Cargo.toml"))?,
+                })
+            }
+        }
+
+        impl Command<()> for Build {
+            type Status = PathBuf;
+
+            fn exec(&self, _: &()) -> Self::Status {
+                self.root.clone()
+            }
+        }
+
+        impl Runner<()> for Build {}
+
+        // tests run from the package root, which holds the manifest
+        let cwd = std::env::current_dir().unwrap();
+        let cli = Cli::new().tokenize(args(vec!["orbit", "build"]));
+        assert_eq!(cli.launch::<(), Build>(&()).unwrap(), cwd);
+        // the current directory is only changed when entering is requested
+        assert_eq!(std::env::current_dir().unwrap(), cwd);
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "build"]));
+        let err = cli.check_workspace(Workspace::marker("clif-missing.toml")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MissingWorkspace);
+        assert!(err
+            .to_string()
+            .starts_with("could not find 'clif-missing.toml' in '"));
+    }
+
+    #[test]
+    fn command_availability() {
+        let run = |cli: Cli, a: Availability| -> Result<Cli, Error> {
+            let mut cli = cli;
+            cli.known_args.push(Arg::Positional(Positional::new("command")));
+            cli.match_command(&["fmt"])?;
+            cli.check_availability(a)?;
+            cli.is_empty()?;
+            Ok(cli)
+        };
+        let cli = Cli::new().tokenize(args(vec!["orbit", "fmt"]));
+        let err = run(cli, Availability::Experimental).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnstableCommand);
         assert_eq!(
-            cli.find_first_flag_left(cli.tokens.len()),
-            Some(("help", 0))
+            err.to_string(),
+            "subcommand 'fmt' is experimental\n\nSupply '--enable-unstable' to use it."
+        );
+
+        let cli = Cli::new().tokenize(args(vec!["orbit", "--enable-unstable", "fmt"]));
+        assert!(run(cli, Availability::Experimental).is_ok());
+        // the gate does not take a value
+        let cli = Cli::new().tokenize(args(vec!["orbit", "--enable-unstable=1", "fmt"]));
+        assert_eq!(
+            run(cli, Availability::Stable).unwrap_err().to_string(),
+            "flag '--enable-unstable' cannot accept a value but was given '1'"
+        );
+        let cli = Cli::new().tokenize(args(vec!["orbit", "--enable-unstable=1", "fmt"]));
+        assert_eq!(
+            run(cli, Availability::Experimental).unwrap_err().kind(),
+            ErrorKind::UnstableCommand
+        );
+        let cli = Cli::new()
+            .enable_unstable(true)
+            .tokenize(args(vec!["orbit", "fmt"]));
+        assert!(run(cli, Availability::Experimental).is_ok());
+
+        let cli = Cli::new().tokenize(args(vec!["orbit", "fmt"]));
+        let cli = run(cli, Availability::deprecated("0.2.0").note("use 'format' instead")).unwrap();
+        assert_eq!(
+            cli.warnings(),
+            &vec![String::from("subcommand 'fmt' is deprecated since 0.2.0: use 'format' instead")]
+        );
+
+        let cli = Cli::new().tokenize(args(vec!["orbit", "fmt"]));
+        let err = run(cli, Availability::removed("1.0.0")).unwrap_err();
+        assert_eq!(err.to_string(), "subcommand 'fmt' was removed in 1.0.0");
+
+        // help text carries the badge
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--help"]));
+        cli.check_help(Help::new().quick_text("usage: orbit fmt").availability(&Availability::Experimental))
+            .unwrap();
+        let err = cli.raise_help().unwrap_err();
+        assert_eq!(err.to_string(), "[experimental]\nusage: orbit fmt");
+    }
+
+    #[test]
+    fn unstable_args() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--jobs", "4"]));
+        let err = cli
+            .check_option::<u8>(Optional::new("jobs").unstable())
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnstableArg);
+        assert_eq!(
+            err.to_string(),
+            "argument '--jobs <jobs>' is experimental\n\nSupply '--enable-unstable' to use it."
         );
 
-        let cli = Cli::new().tokenize(args(vec!["orbit", "new", "rary.gates"]));
-        assert_eq!(cli.find_first_flag_left(cli.tokens.len()), None);
+        // unused unstable arguments are not an error
+        let mut cli = Cli::new().tokenize(args(vec!["orbit"]));
+        assert_eq!(cli.check_flag(Flag::new("turbo").unstable()).unwrap(), false);
 
-        let cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "new",
-            "rary.gates",
-            "--vcs",
-            "git",
-            "--help",
-        ]));
-        assert_eq!(cli.find_first_flag_left(cli.tokens.len()), Some(("vcs", 2)));
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--turbo", "--enable-unstable"]));
+        assert_eq!(cli.check_flag(Flag::new("turbo").unstable()).unwrap(), true);
+        assert!(cli.is_empty().is_ok());
+    }
 
-        let cli = Cli::new().tokenize(args(vec!["orbit", "new", "rary.gates", "-c=git", "--help"]));
-        assert_eq!(cli.find_first_flag_left(cli.tokens.len()), Some(("c", 2)));
+    #[test]
+    fn timings_flag() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "build", "--timings"]));
+        assert_eq!(cli.require_positional::<String>(Positional::new("command")).unwrap(), "build");
+        // the flag is handled by the runner rather than the command
+        assert!(cli.is_empty().is_ok());
+        assert!(cli.explain().contains("--timings"));
+        let timings = cli.timings().unwrap().clone();
+        timings.record("link", std::time::Duration::from_millis(5));
+        assert_eq!(cli.timings().unwrap().get_phases()[0].0, "link");
 
-        let cli = Cli::new().tokenize(args(vec!["orbit", "new", "rary.gates", "-c=git", "--help"]));
-        assert_eq!(cli.find_first_flag_left(1), None); // check before 'rary.gates' position
+        let cli = Cli::new().tokenize(args(vec!["orbit", "build"]));
+        assert_eq!(cli.timings(), None);
 
-        let cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "--unknown",
-            "new",
-            "rary.gates",
-            "-c=git",
-            "--help",
-        ]));
-        assert_eq!(cli.find_first_flag_left(1), Some(("unknown", 0))); // check before 'new' subcommand
+        // the flag does not take a value
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "build", "--timings=1"]));
+        assert_eq!(cli.require_positional::<String>(Positional::new("command")).unwrap(), "build");
+        assert_eq!(cli.timings(), None);
+        let err = cli.is_empty().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedValue);
+        assert_eq!(err.to_string(), "flag '--timings' cannot accept a value but was given '1'");
     }
 
     #[test]
-    fn processed_all_args() {
-        let mut cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "--upgrade",
-            "new",
-            "rary.gates",
-            "--vcs",
-            "git",
-        ]));
-        // tokens are still in token stream
-        let _ = cli.check_flag(Flag::new("upgrade")).unwrap();
-        let _: Option<String> = cli.check_option(Optional::new("vcs")).unwrap();
-        let _: String = cli.require_positional(Positional::new("command")).unwrap();
-        let _: String = cli.require_positional(Positional::new("ip")).unwrap();
-        // no more tokens left in stream
-        assert_eq!(cli.is_empty().unwrap(), ());
+    fn value_aliases() {
+        let color = || {
+            Optional::new("color")
+                .possible_values(&["24bit", "256", "none"])
+                .value_alias("truecolor", "24bit")
+                .value_alias("off", "none")
+        };
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--color", "truecolor"]));
+        assert_eq!(
+            cli.check_option::<String>(color()).unwrap(),
+            Some(String::from("24bit"))
+        );
+        // only canonical names are listed as choices
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--color", "on"]));
+        assert_eq!(
+            cli.check_option::<String>(color()).unwrap_err().to_string(),
+            "argument '--color <color>' does not accept 'on'\n\nPossible values: 24bit, 256, none"
+        );
+    }
 
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "new", "rary.gates", symbol::FLAG]));
-        // removes only valid args/flags/opts
-        let _ = cli.check_flag(Flag::new("help")).unwrap();
-        let _: Option<String> = cli.check_option(Optional::new("vcs")).unwrap();
-        let _: String = cli.require_positional(Positional::new("command")).unwrap();
-        let _: String = cli.require_positional(Positional::new("ip")).unwrap();
-        // unexpected '--'
-        assert!(cli.is_empty().is_err());
+    #[test]
+    fn deprecated_args() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--files", "a.v", "-q"]));
+        let _: Option<String> = cli
+            .check_option(Optional::new("files").deprecated("use '--fileset' instead"))
+            .unwrap();
+        assert_eq!(
+            cli.check_flag(Flag::new("quiet").switch('q').deprecated("it has no effect"))
+                .unwrap(),
+            true
+        );
+        let _ = cli.check_flag(Flag::new("quiet").switch('q').deprecated("it has no effect"));
+        assert_eq!(
+            cli.warnings(),
+            &vec![
+                String::from("argument '--files <files>' is deprecated: use '--fileset' instead"),
+                String::from("argument '--quiet' is deprecated: it has no effect"),
+            ]
+        );
+    }
 
-        let cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "--help",
-            "new",
-            "rary.gates",
-            "--vcs",
-            "git",
-        ]));
-        // no tokens were removed (help will also raise error)
-        assert!(cli.is_empty().is_err());
+    #[test]
+    fn flag_given_value() {
+        let mut cli = Cli::new().threshold(4).tokenize(args(vec!["orbit", "-q", "--verbose=9"]));
+        let _: Option<u8> = cli.check_option(Optional::new("verbosity")).unwrap();
+        let err = cli.check_flag(Flag::new("verbose")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedValue);
+        assert_eq!(
+            err.to_string(),
+            "flag '--verbose' does not take a value but was supplied as '--verbose=9'\n\nDid you mean '--verbosity=9'?"
+        );
 
-        let mut cli =
-            Cli::new().tokenize(args(vec!["orbit", symbol::FLAG, "some", "extra", "words"]));
-        let _: Vec<String> = cli.check_remainder().unwrap();
-        // terminator removed as well as its arguments that were ignored
-        assert_eq!(cli.is_empty().unwrap(), ());
+        // switches within a cluster report the whole argument
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "-qv=2"]));
+        let _ = cli.check_flag(Flag::new("quiet").switch('q')).unwrap();
+        let err = cli.check_flag(Flag::new("verbose").switch('v')).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "flag '--verbose' does not take a value but was supplied as '-qv=2'"
+        );
     }
 
     #[test]
-    fn tokenizer() {
-        let cli = Cli::new().tokenize(args(vec![]));
-        assert_eq!(cli.tokens, vec![]);
+    fn input_limits() {
+        let limits = Limits::new().max_args(3).max_arg_len(16).max_tokens(4);
+        let mut cli = Cli::new()
+            .limits(limits.clone())
+            .tokenize(args(vec!["orbit", "--verbose", "-ab", "in.txt"]));
+        assert_eq!(cli.check_flag(Flag::new("verbose")).unwrap(), true);
 
-        let cli = Cli::new().tokenize(args(vec!["orbit"]));
-        assert_eq!(cli.tokens, vec![]);
+        let mut cli = Cli::new()
+            .limits(limits.clone())
+            .tokenize(args(vec!["orbit", "a", "b", "c", "d"]));
+        let err = cli.check_flag(Flag::new("verbose")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::LimitsExceeded);
+        assert_eq!(err.to_string(), "command line exceeds the limit of 3 arguments");
+        // the input is discarded
+        assert_eq!(cli.tokens.is_empty(), true);
 
-        let cli = Cli::new().tokenize(args(vec!["orbit", "--help"]));
-        assert_eq!(cli.tokens, vec![Some(Token::Flag(0))]);
+        let long = "x".repeat(17);
+        let cli = Cli::new()
+            .limits(limits.clone())
+            .tokenize(args(vec!["orbit", &long]));
+        assert_eq!(
+            cli.is_empty().unwrap_err().to_string(),
+            "command line exceeds the limit of 16 bytes per argument"
+        );
 
-        let cli = Cli::new().tokenize(args(vec!["orbit", "--help", "-v"]));
+        let mut cli = Cli::new()
+            .limits(limits)
+            .tokenize(args(vec!["orbit", "-abcde"]));
         assert_eq!(
-            cli.tokens,
-            vec![Some(Token::Flag(0)), Some(Token::Switch(1, 'v'))],
+            cli.check_positional::<String>(Positional::new("file")).unwrap_err().kind(),
+            ErrorKind::LimitsExceeded
         );
+    }
 
-        let cli = Cli::new().tokenize(args(vec!["orbit", "new", "rary.gates"]));
+    #[test]
+    fn suggestion_budget() {
+        let limits = Limits::new().max_suggestion_work(64);
+        let mut cli = Cli::new()
+            .threshold(4)
+            .limits(limits.clone())
+            .tokenize(args(vec!["orbit", "ad"]));
         assert_eq!(
-            cli.tokens,
-            vec![
-                Some(Token::UnattachedArgument(0, "new".to_string())),
-                Some(Token::UnattachedArgument(1, "rary.gates".to_string())),
-            ],
+            cli.check_command::<Sub>(Positional::new("command")).unwrap_err().kind(),
+            ErrorKind::SuggestSubcommand
         );
 
-        let cli = Cli::new().tokenize(args(vec!["orbit", "--help", "-vh"]));
+        // comparing a long word exceeds the budget so no suggestion is offered
+        let word = format!("ad{}", "d".repeat(40));
+        let mut cli = Cli::new()
+            .threshold(4)
+            .limits(limits)
+            .tokenize(args(vec!["orbit", &word]));
         assert_eq!(
-            cli.tokens,
-            vec![
-                Some(Token::Flag(0)),
-                Some(Token::Switch(1, 'v')),
-                Some(Token::Switch(1, 'h')),
-            ],
+            cli.check_command::<Sub>(Positional::new("command")).unwrap_err().kind(),
+            ErrorKind::UnknownSubcommand
         );
+    }
 
-        let cli = Cli::new().tokenize(args(vec!["orbit", "--help", "-vhc=10"]));
+    #[test]
+    fn suggestion_classes() {
+        let config = SuggestionConfig::new().subcommands(false);
+        let mut cli = Cli::new()
+            .threshold(4)
+            .suggestions(config.clone())
+            .tokenize(args(vec!["orbit", "ad"]));
         assert_eq!(
-            cli.tokens,
-            vec![
-                Some(Token::Flag(0)),
-                Some(Token::Switch(1, 'v')),
-                Some(Token::Switch(1, 'h')),
-                Some(Token::Switch(1, 'c')),
-                Some(Token::AttachedArgument(1, "10".to_string())),
-            ],
+            cli.check_command::<Sub>(Positional::new("command")).unwrap_err().kind(),
+            ErrorKind::UnknownSubcommand
         );
 
-        // an attached argument can sneak in behind a terminator
-        let cli = Cli::new().tokenize(args(vec!["orbit", "--=value", "extra"]));
+        // flag suggestions are still offered
+        let mut cli = Cli::new()
+            .threshold(4)
+            .suggestions(config)
+            .tokenize(args(vec!["orbit", "--verbos"]));
+        assert_eq!(cli.check_flag(Flag::new("verbose")).unwrap(), false);
+        assert_eq!(cli.is_empty().unwrap_err().kind(), ErrorKind::SuggestArg);
+
+        let mut cli = Cli::new()
+            .threshold(4)
+            .suggestions(SuggestionConfig::new().values(false))
+            .tokenize(args(vec!["orbit", "--color=alwys"]));
+        let err = cli
+            .check_option::<String>(Optional::new("color").possible_values(&["always", "never"]))
+            .unwrap_err();
         assert_eq!(
-            cli.tokens,
-            vec![
-                Some(Token::Terminator(0)),
-                Some(Token::AttachedArgument(0, "value".to_string())),
-                Some(Token::Ignore(1, "extra".to_string())),
-            ]
+            err.to_string(),
+            "argument '--color <color>' does not accept 'alwys'\n\nPossible values: always, never"
         );
+    }
 
-        // final boss
-        let cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "--help",
-            "-v",
-            "new",
-            "ip",
-            "--lib",
-            "--name=rary.gates",
-            "--help",
-            "-sci",
-            symbol::FLAG,
-            "--map",
-            "synthesis",
-            "-jto",
-        ]));
+    #[test]
+    fn relative_threshold() {
+        // a short word must match exactly
+        let mut cli = Cli::new().relative_threshold(4).tokenize(args(vec!["orbit", "ad"]));
         assert_eq!(
-            cli.tokens,
-            vec![
-                Some(Token::Flag(0)),
-                Some(Token::Switch(1, 'v')),
-                Some(Token::UnattachedArgument(2, "new".to_string())),
-                Some(Token::UnattachedArgument(3, "ip".to_string())),
-                Some(Token::Flag(4)),
-                Some(Token::Flag(5)),
-                Some(Token::AttachedArgument(5, "rary.gates".to_string())),
-                Some(Token::Flag(6)),
-                Some(Token::Switch(7, 's')),
-                Some(Token::Switch(7, 'c')),
-                Some(Token::Switch(7, 'i')),
-                Some(Token::Terminator(8)),
-                Some(Token::Ignore(9, "--map".to_string())),
-                Some(Token::Ignore(10, "synthesis".to_string())),
-                Some(Token::Ignore(11, "-jto".to_string())),
-            ],
+            cli.check_command::<Sub>(Positional::new("command")).unwrap_err().kind(),
+            ErrorKind::UnknownSubcommand
+        );
+
+        // a long word is allowed an edit for every 4 characters
+        let mut cli = Cli::new().relative_threshold(4).tokenize(args(vec!["orbit", "--verbsoe"]));
+        assert_eq!(cli.check_flag(Flag::new("verbose")).unwrap(), false);
+        let err = cli.is_empty().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::SuggestArg);
+        assert_eq!(err.to_string().contains("--verbose"), true);
+
+        // a fixed threshold set afterward replaces the relative one
+        assert_eq!(Cli::new().relative_threshold(4).threshold.threshold("version", "verbose"), 2);
+        assert_eq!(
+            Cli::new().relative_threshold(4).threshold(3).threshold.threshold("version", "verbose"),
+            3
         );
     }
 
     #[test]
-    fn find_flags_and_switches() {
-        let mut cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "--help",
-            "-v",
-            "new",
-            "ip",
-            "--lib",
-            "--name=rary.gates",
-            "--help",
-            "-sci",
-            "-i",
-            "--",
-            "--map",
-            "synthesis",
-            "-jto",
-        ]));
+    fn auto_threshold() {
+        let cli = || Cli::new().threshold_policy(ThresholdPolicy::Auto);
+        let mut c = cli().tokenize(args(vec!["orbit", "ad"]));
+        assert_eq!(
+            c.check_command::<Sub>(Positional::new("command")).unwrap_err().kind(),
+            ErrorKind::UnknownSubcommand
+        );
 
-        // detects 0
-        assert_eq!(cli.take_flag_locs("version"), vec![]);
-        // detects 1
-        assert_eq!(cli.take_flag_locs("lib"), vec![4]);
-        // detects multiple
-        assert_eq!(cli.take_flag_locs("help"), vec![0, 7]);
-        // flag was past terminator and marked as ignore
-        assert_eq!(cli.take_flag_locs("map"), vec![]);
-        // filters out arguments
-        assert_eq!(cli.take_flag_locs("rary.gates"), vec![]);
+        let mut c = cli().tokenize(args(vec!["orbit", "--hel"]));
+        assert_eq!(c.check_flag(Flag::new("help")).unwrap(), false);
+        let err = c.is_empty().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::SuggestArg);
+        assert_eq!(err.to_string().contains("--help"), true);
+    }
 
-        // detects 0
-        assert_eq!(cli.take_switch_locs(&'q'), vec![]);
-        // detects 1
-        assert_eq!(cli.take_switch_locs(&'v'), vec![1]);
-        // detects multiple
-        assert_eq!(cli.take_switch_locs(&'i'), vec![10, 11]);
-        // switch was past terminator and marked as ignore
-        assert_eq!(cli.take_switch_locs(&'j'), vec![]);
+    #[test]
+    fn suggestions_ignore_case() {
+        let mut cli = Cli::new().threshold(2).tokenize(args(vec!["orbit", "GET"]));
+        cli.known_args.push(Arg::Positional(Positional::new("command")));
+        assert_eq!(
+            cli.match_command(&["get", "set"]).unwrap_err().kind(),
+            ErrorKind::UnknownSubcommand
+        );
+
+        let config = SuggestionConfig::new().ignore_case(true);
+        let mut cli = Cli::new()
+            .threshold(2)
+            .suggestions(config.clone())
+            .tokenize(args(vec!["orbit", "GET"]));
+        cli.known_args.push(Arg::Positional(Positional::new("command")));
+        let err = cli.match_command(&["get", "set"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::SuggestSubcommand);
+        assert_eq!(err.to_string().contains("'get'"), true);
+
+        let mut cli = Cli::new()
+            .threshold(2)
+            .suggestions(config)
+            .tokenize(args(vec!["orbit", "--Verbose"]));
+        assert_eq!(cli.check_flag(Flag::new("verbose")).unwrap(), false);
+        let err = cli.is_empty().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::SuggestArg);
+        assert_eq!(err.to_string().contains("--verbose"), true);
+    }
+
+    #[derive(Debug)]
+    struct Pair {
+        _lhs: u8,
+        _rhs: u8,
+    }
+
+    impl FromCli for Pair {
+        fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+            cli.check_flag(Flag::new("verbose"))?;
+            let _: Option<String> = cli.check_option(Optional::new("mode"))?;
+            Ok(Pair {
+                _lhs: cli.require_positional(Positional::new("lhs"))?,
+                _rhs: cli.require_positional(Positional::new("rhs"))?,
+            })
+        }
+    }
+
+    #[test]
+    fn partial_matches() {
+        let mut cli =
+            Cli::new().tokenize(args(vec!["add", "--verbose", "9", "x", "--mode=fast"]));
+        let partial = cli.parse_partial::<Pair>().unwrap_err();
+        assert_eq!(partial.get_error().kind(), ErrorKind::BadType);
+        let matches = partial.get_matches();
+        assert_eq!(matches.get_count("verbose"), 1);
+        assert_eq!(matches.get_value("mode"), Some("fast"));
+        assert_eq!(matches.get_value("lhs"), Some("9"));
+        assert_eq!(matches.contains("rhs"), false);
+
+        let mut cli = Cli::new().tokenize(args(vec!["add", "1", "2"]));
+        assert!(cli.parse_partial::<Pair>().is_ok());
+        assert_eq!(cli.matches().get_value("rhs"), Some("2"));
+        assert_eq!(cli.matches().contains("verbose"), false);
+    }
+
+    #[test]
+    fn parse_metrics() {
+        let counts = Arc::new(std::sync::Mutex::new(HashMap::<Metric, usize>::new()));
+        let sink = {
+            let counts = Arc::clone(&counts);
+            move |metric, n| *counts.lock().unwrap().entry(metric).or_insert(0) += n
+        };
+        let count = |metric| counts.lock().unwrap().get(&metric).copied().unwrap_or(0);
+
+        let mut cli = Cli::new()
+            .metrics(sink.clone())
+            .tokenize(args(vec!["add", "--verbose", "9", "x", "--mode=fast"]));
+        assert_eq!(count(Metric::Tokens), 5);
+        assert!(cli.parse_partial::<Pair>().is_err());
+        assert_eq!(count(Metric::Values), 2);
+        assert_eq!(count(Metric::Errors), 1);
+        assert_eq!(count(Metric::Suggestions), 0);
+
+        let mut cli = Cli::new()
+            .metrics(sink)
+            .threshold(2)
+            .tokenize(args(vec!["add", "1", "2", "--verbos"]));
+        assert!(cli.parse_partial::<Pair>().is_ok());
+        assert_eq!(cli.is_empty().unwrap_err().kind(), ErrorKind::SuggestArg);
+        assert_eq!(count(Metric::Tokens), 8);
+        assert_eq!(count(Metric::Values), 4);
+        assert_eq!(count(Metric::Suggestions), 1);
+
+        // nothing is recorded without a sink
+        let cli = Cli::new().tokenize(args(vec!["add"]));
+        assert_eq!(cli.metrics, None);
+    }
+
+    #[test]
+    fn prompt_missing() {
+        let asked = Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let prompter = {
+            let asked = Arc::clone(&asked);
+            move |text: &str| {
+                asked.lock().unwrap().push(text.to_string());
+                match text.starts_with("lhs") {
+                    true => Some(String::from("7\n")),
+                    false => Some(String::from("\n")),
+                }
+            }
+        };
+        let mut cli = Cli::new().prompter(prompter.clone()).tokenize(args(vec!["add"]));
+        assert_eq!(cli.require_positional::<u8>(Positional::new("lhs")).unwrap(), 7);
+        assert_eq!(
+            cli.require_option_positional::<u8>(Optional::new("rhs").prompt_default("3"))
+                .unwrap(),
+            3
+        );
+        assert_eq!(*asked.lock().unwrap(), vec!["lhs: ", "rhs [3]: "]);
+        assert_eq!(cli.matches().get_value("lhs"), Some("7"));
+
+        // a blank answer without a default is still missing
+        let mut cli = Cli::new().prompter(prompter.clone()).tokenize(args(vec!["add"]));
+        assert_eq!(
+            cli.require_positional::<u8>(Positional::new("rhs")).unwrap_err().kind(),
+            ErrorKind::MissingPositional
+        );
+        // answers are parsed like any other value
+        let mut cli = Cli::new()
+            .prompter(|_: &str| Some(String::from("x")))
+            .tokenize(args(vec!["add"]));
+        assert_eq!(
+            cli.require_positional::<u8>(Positional::new("lhs")).unwrap_err().kind(),
+            ErrorKind::BadType
+        );
+        // supplied arguments are never prompted for
+        let mut cli = Cli::new().prompter(prompter).tokenize(args(vec!["add", "1"]));
+        assert_eq!(cli.require_positional::<u8>(Positional::new("lhs")).unwrap(), 1);
+        assert_eq!(asked.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn prompt_secrets() {
+        let token = || Optional::new("token").secret().prompt_default("guest");
+        // secrets are never read by a prompter that echoes its input
+        let mut cli = Cli::new()
+            .prompter(|_: &str| Some(String::from("hunter2")))
+            .tokenize(args(vec!["login"]));
+        assert_eq!(
+            cli.require_option_positional::<String>(token()).unwrap_err().kind(),
+            ErrorKind::MissingPositional
+        );
+
+        let mut cli = Cli::new()
+            .prompt_hidden(|text: &str| match text {
+                "token [***]: " => Some(String::from("hunter2\n")),
+                _ => None,
+            })
+            .tokenize(args(vec!["login"]));
+        assert_eq!(cli.require_option_positional::<String>(token()).unwrap(), "hunter2");
+        assert_eq!(format!("{:?}", cli.matches()).contains("hunter2"), false);
+        assert_eq!(cli.settings().to_string().contains("hunter2"), false);
+
+        // the default is used without revealing it in the prompt
+        let mut cli = Cli::new()
+            .prompt_hidden(|_: &str| Some(String::new()))
+            .tokenize(args(vec!["login"]));
+        assert_eq!(cli.require_option_positional::<String>(token()).unwrap(), "guest");
+
+        // invalid answers are reported without the value
+        let mut cli = Cli::new()
+            .prompt_hidden(|_: &str| Some(String::from("12a")))
+            .tokenize(args(vec!["login"]));
+        let err = cli
+            .require_positional::<u16>(Positional::new("pin").secret())
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BadType);
+        assert_eq!(err.to_string().contains("12a"), false);
     }
 
     #[test]
-    fn flags_in_map() {
-        let cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "--help",
-            "-v",
-            "new",
-            "ip",
-            "--lib",
-            "--name=rary.gates",
-            "--help",
-            "-sci",
-            symbol::FLAG,
-            "--map",
-            "synthesis",
-            "-jto",
+    fn option_occurrences() {
+        #[derive(Debug, PartialEq)]
+        enum Step {
+            Filter(String),
+            Map(String),
+        }
+
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit", "--filter", "a", "-m", "b", "--filter=c", "--map", "d",
         ]));
-        let mut opt_store = HashMap::<Tag<String>, Slot>::new();
-        // store long options
-        opt_store.insert(
-            Tag::Flag("help".to_string()),
-            Slot {
-                pointers: vec![0, 7],
-                visited: false,
-            },
+        let filters = cli
+            .check_option_occurrences::<String>(Optional::new("filter"))
+            .unwrap();
+        let maps = cli
+            .check_option_occurrences::<String>(Optional::new("map").switch('m'))
+            .unwrap();
+        assert_eq!(filters.len(), 2);
+        let pipeline = filters
+            .map(Step::Filter)
+            .merge(maps.map(Step::Map))
+            .fold_into(Vec::new(), |mut steps, s| {
+                steps.push(s);
+                steps
+            });
+        assert_eq!(
+            pipeline,
+            vec![
+                Step::Filter("a".to_string()),
+                Step::Map("b".to_string()),
+                Step::Filter("c".to_string()),
+                Step::Map("d".to_string()),
+            ]
         );
-        opt_store.insert(
-            Tag::Flag("lib".to_string()),
-            Slot {
-                pointers: vec![4],
-                visited: false,
-            },
+        assert_eq!(cli.is_empty().is_ok(), true);
+
+        // values keep the index of the argument they were supplied with
+        let mut cli = Cli::new().tokenize(args(vec![
+            "cc", "-I", "inc", "-vL=lib", "-I", "src", "--lib", "/usr/lib",
+        ]));
+        cli.check_flag(Flag::new("verbose").switch('v')).unwrap();
+        let includes = cli
+            .check_option_occurrences::<String>(Optional::new("include").switch('I'))
+            .unwrap();
+        let libs = cli
+            .check_option_occurrences::<String>(Optional::new("lib").switch('L'))
+            .unwrap();
+        assert_eq!(
+            includes.iter().map(|(i, v)| (i, v.as_str())).collect::<Vec<_>>(),
+            vec![(1, "inc"), (4, "src")]
         );
-        opt_store.insert(
-            Tag::Flag("name".to_string()),
-            Slot {
-                pointers: vec![5],
-                visited: false,
-            },
+        assert_eq!(
+            libs.into_indexed(),
+            vec![(3, "lib".to_string()), (6, "/usr/lib".to_string())]
         );
-        // stores switches too
-        opt_store.insert(
-            Tag::Switch("v".to_string()),
-            Slot {
-                pointers: vec![1],
-                visited: false,
-            },
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--filter"]));
+        assert_eq!(
+            cli.check_option_occurrences::<String>(Optional::new("filter"))
+                .unwrap_err()
+                .kind(),
+            ErrorKind::ExpectingValue
         );
-        opt_store.insert(
-            Tag::Switch("s".to_string()),
-            Slot {
-                pointers: vec![8],
-                visited: false,
-            },
+    }
+
+    #[test]
+    fn delimited_values() {
+        let mut cli = Cli::new().tokenize(args(vec![
+            "cargo", "--features", "a,b", "--features=c", "-j", "1,,2",
+        ]));
+        assert_eq!(
+            cli.check_option_all::<String>(Optional::new("features").delimiter(','))
+                .unwrap(),
+            Some(vec!["a".to_string(), "b".to_string(), "c".to_string()])
         );
-        opt_store.insert(
-            Tag::Switch("c".to_string()),
-            Slot {
-                pointers: vec![9],
-                visited: false,
-            },
+        assert_eq!(cli.matches().get_values("features"), &["a", "b", "c"]);
+        assert_eq!(
+            cli.check_option_all::<u8>(Optional::new("jobs").switch('j').delimiter(','))
+                .unwrap(),
+            Some(vec![1, 2])
         );
-        opt_store.insert(
-            Tag::Switch("i".to_string()),
-            Slot {
-                pointers: vec![10],
-                visited: false,
-            },
+
+        let mut cli = Cli::new().tokenize(args(vec!["cargo", "--features", "a,b"]));
+        assert_eq!(
+            cli.check_option_all::<String>(Optional::new("features")).unwrap(),
+            Some(vec!["a,b".to_string()])
+        );
+        let mut cli = Cli::new().tokenize(args(vec!["cargo", "--jobs", "1,x"]));
+        assert_eq!(
+            cli.check_option_all::<u8>(Optional::new("jobs").delimiter(','))
+                .unwrap_err()
+                .kind(),
+            ErrorKind::BadType
         );
-        assert_eq!(cli.opt_store, opt_store);
     }
 
     #[test]
-    fn take_unattached_args() {
+    fn greedy_option() {
         let mut cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "--help",
-            "-v",
-            "new",
-            "ip",
-            "--lib",
-            "--name=rary.gates",
-            "--help",
-            "-scii",
-            "get",
-            symbol::FLAG,
-            "--map",
-            "synthesis",
-            "-jto",
+            "cat", "--files", "a.txt", "b.txt", "-v", "--files=c.txt", "d.txt", "e.txt", "out",
         ]));
+        assert_eq!(cli.check_flag(Flag::new("verbose").switch('v')).unwrap(), true);
+        assert_eq!(
+            cli.check_option_greedy::<String>(Optional::new("files"), 3).unwrap(),
+            Some(vec![
+                "a.txt".to_string(),
+                "b.txt".to_string(),
+                "c.txt".to_string(),
+                "d.txt".to_string(),
+                "e.txt".to_string(),
+            ])
+        );
+        // arguments beyond the maximum are left for positionals
+        assert_eq!(
+            cli.require_positional::<String>(Positional::new("dest")).unwrap(),
+            "out"
+        );
+        assert_eq!(cli.is_empty().is_ok(), true);
 
-        assert_eq!(cli.next_uarg().unwrap(), "new".to_string());
-        assert_eq!(cli.next_uarg().unwrap(), "ip".to_string());
-        assert_eq!(cli.next_uarg().unwrap(), "get".to_string());
-        assert_eq!(cli.next_uarg(), None);
+        let mut cli = Cli::new().tokenize(args(vec!["cat", "-f", "a", "b", "c"]));
+        assert_eq!(
+            cli.check_option_greedy::<String>(Optional::new("files").switch('f'), 2)
+                .unwrap()
+                .map(|v| v.len()),
+            Some(2)
+        );
+        assert_eq!(cli.is_empty().unwrap_err().kind(), ErrorKind::UnexpectedArg);
+
+        let mut cli = Cli::new().tokenize(args(vec!["cat", "--files", "--verbose"]));
+        assert_eq!(
+            cli.check_option_greedy::<String>(Optional::new("files"), 3)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::ExpectingValue
+        );
     }
 
     #[test]
-    fn take_remainder_args() {
-        let mut cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "--help",
-            "-v",
-            "new",
-            "ip",
-            "--lib",
-            "--name=rary.gates",
-            "--help",
-            "-scii",
-            "get",
-            symbol::FLAG,
-            "--map",
-            "synthesis",
-            "-jto",
-        ]));
+    fn localized_numbers() {
+        let mut cli = Cli::new()
+            .number_format(NumberFormat::for_locale("de_DE"))
+            .tokenize(args(vec!["pay", "--amount", "1.234,56", "--count=1.000", "2,5"]));
         assert_eq!(
-            cli.check_remainder().unwrap(),
-            vec!["--map", "synthesis", "-jto"]
+            cli.check_option::<f64>(Optional::new("amount").localized()).unwrap(),
+            Some(1234.56)
         );
-        // the items were removed from the token stream
-        assert_eq!(cli.check_remainder().unwrap(), Vec::<String>::new());
+        assert_eq!(cli.matches().get_value("amount"), Some("1234.56"));
+        assert_eq!(
+            cli.check_option::<u32>(Optional::new("count").localized()).unwrap(),
+            Some(1000)
+        );
+        // arguments not marked as localized are parsed as written
+        let err = cli.require_positional::<f64>(Positional::new("rate")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BadType);
 
-        // an attached argument can sneak in behind a terminator (handle in a result fn)
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--=value", "extra"]));
-        assert!(cli.check_remainder().is_err());
+        let mut cli = Cli::new().tokenize(args(vec!["pay", "1,234.5"]));
+        assert_eq!(
+            cli.require_positional::<f64>(Positional::new("amount").localized()).unwrap(),
+            1234.5
+        );
 
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--help"]));
-        // the terminator was never found
-        assert_eq!(cli.check_remainder().unwrap(), Vec::<String>::new());
+        // a group separator out of place is not silently dropped
+        let mut cli = Cli::new()
+            .number_format(NumberFormat::for_locale("de_DE"))
+            .tokenize(args(vec!["pay", "1.5"]));
+        let err = cli.require_positional::<f64>(Positional::new("amount").localized()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidValue);
     }
 
     #[test]
-    fn pull_values_from_flags() {
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--help"]));
-        let locs = cli.take_flag_locs("help");
-        assert_eq!(cli.pull_flag(locs, false), vec![None]);
-        assert_eq!(cli.tokens.get(0), Some(&None));
+    fn stdin_placeholder() {
+        use crate::value::StdinOr;
+        use std::path::PathBuf;
 
-        let mut cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "--name",
-            "gates",
-            "arg",
-            "--lib",
-            "new",
-            "--name=gates2",
-            "--opt=1",
-            "--opt",
-            "--help",
-        ]));
-        let locs = cli.take_flag_locs("lib");
-        assert_eq!(cli.pull_flag(locs, false), vec![None]);
-        // token no longer exists
-        assert_eq!(cli.tokens.get(3), Some(&None));
+        let mut cli = Cli::new().tokenize(args(vec!["cat", "-", "-n", "notes.txt"]));
+        assert_eq!(cli.check_flag(Flag::new("number").switch('n')).unwrap(), true);
+        assert_eq!(
+            cli.require_positional::<StdinOr<PathBuf>>(Positional::new("file")).unwrap(),
+            StdinOr::Stdin
+        );
+        assert_eq!(
+            cli.require_positional::<StdinOr<PathBuf>>(Positional::new("file")).unwrap(),
+            StdinOr::Value(PathBuf::from("notes.txt"))
+        );
+        assert_eq!(cli.is_empty().is_ok(), true);
 
-        // gets strings and removes both instances of flag from token stream
-        let locs = cli.take_flag_locs("name");
+        let mut cli = Cli::new().tokenize(args(vec!["cat", "--input", "-"]));
         assert_eq!(
-            cli.pull_flag(locs, true),
-            vec![Some("gates".to_string()), Some("gates2".to_string())]
+            cli.check_option::<StdinOr<String>>(Optional::new("input")).unwrap(),
+            Some(StdinOr::Stdin)
         );
-        assert_eq!(cli.tokens.get(0), Some(&None));
-        assert_eq!(cli.tokens.get(5), Some(&None));
+    }
 
-        let locs = cli.take_flag_locs("opt");
-        assert_eq!(cli.pull_flag(locs, true), vec![Some("1".to_string()), None]);
+    #[test]
+    fn frozen_args() {
+        let cli = || {
+            Cli::new()
+                .freeze_flag("sandbox")
+                .freeze_option("root", "/srv")
+        };
+        let mut c = cli().tokenize(args(vec!["orbit", "build"]));
+        assert_eq!(c.check_flag(Flag::new("sandbox")).unwrap(), true);
+        assert_eq!(
+            c.check_option::<String>(Optional::new("root")).unwrap(),
+            Some("/srv".to_string())
+        );
+        assert_eq!(c.matches().get_value("root"), Some("/srv"));
 
-        // gets switches as well from the store
-        let mut cli = Cli::new().tokenize(args(vec![
-            "orbit",
-            "--name",
-            "gates",
-            "-sicn",
-            "dut",
-            "new",
-            "-vl=direct",
-            "--help",
-            "-l",
-            "-m",
-            "install",
-        ]));
-        let locs = cli.take_switch_locs(&'l');
+        let mut c = cli().tokenize(args(vec!["orbit", "--root=/"]));
+        let err = c.check_option::<String>(Optional::new("root")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::FrozenArg);
         assert_eq!(
-            cli.pull_flag(locs, true),
-            vec![Some("direct".to_string()), None]
+            err.to_string(),
+            "argument '--root <root>' is fixed by the application and cannot be supplied"
+        );
+        let mut c = cli().tokenize(args(vec!["orbit", "--sandbox"]));
+        assert_eq!(
+            c.check_flag_all(Flag::new("sandbox")).unwrap_err().kind(),
+            ErrorKind::FrozenArg
+        );
+        let mut c = cli().tokenize(args(vec!["orbit", "--root", "/"]));
+        assert_eq!(
+            c.check_option_all::<String>(Optional::new("root")).unwrap_err().kind(),
+            ErrorKind::FrozenArg
+        );
+        let mut c = cli().tokenize(args(vec!["orbit", "--root", "/"]));
+        assert_eq!(
+            c.check_option_greedy::<String>(Optional::new("root"), 2).unwrap_err().kind(),
+            ErrorKind::FrozenArg
+        );
+        let mut c = cli().tokenize(args(vec!["orbit"]));
+        assert_eq!(
+            c.check_option_greedy::<String>(Optional::new("root"), 2).unwrap(),
+            Some(vec!["/srv".to_string()])
         );
-        assert_eq!(cli.tokens.get(9), Some(&None));
-        assert_eq!(cli.tokens.get(12), Some(&None));
-        let locs = cli.take_switch_locs(&'s');
-        assert_eq!(cli.pull_flag(locs, true), vec![None]);
-        let locs = cli.take_switch_locs(&'v');
-        assert_eq!(cli.pull_flag(locs, true), vec![None]);
-        let locs = cli.take_switch_locs(&'i');
-        assert_eq!(cli.pull_flag(locs, true), vec![None]);
-        let locs = cli.take_switch_locs(&'c');
-        assert_eq!(cli.pull_flag(locs, false), vec![None]);
-        let locs = cli.take_switch_locs(&'m');
-        assert_eq!(cli.pull_flag(locs, false), vec![None]);
     }
 
-    #[test]
-    fn check_flag() {
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--help", "--verbose", "get"]));
-        assert_eq!(cli.check_flag(Flag::new("help")).unwrap(), true);
-        assert_eq!(cli.check_flag(Flag::new("verbose")).unwrap(), true);
-        assert_eq!(cli.check_flag(Flag::new("version")).unwrap(), false);
+    #[test]
+    fn composed_fragments() {
+        #[derive(Debug, PartialEq)]
+        struct Logging {
+            verbose: bool,
+        }
+
+        impl FromCli for Logging {
+            fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+                Ok(Logging {
+                    verbose: cli.check_flag(Flag::new("verbose").switch('v'))?,
+                })
+            }
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Build {
+            jobs: Option<u8>,
+        }
+
+        impl FromCli for Build {
+            fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+                Ok(Build {
+                    jobs: cli.check_option(Optional::new("jobs").switch('j'))?,
+                })
+            }
+        }
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "-v", "--jobs", "4"]));
+        assert_eq!(cli.check_fragment::<Logging>().unwrap(), Logging { verbose: true });
+        assert_eq!(cli.check_fragment::<Build>().unwrap(), Build { jobs: Some(4) });
+        assert_eq!(cli.is_empty().unwrap(), ());
+
+        // the host declares a flag the library fragment already owns
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--verbose"]));
+        cli.check_flag(Flag::new("verbose")).unwrap();
+        let err = cli.check_fragment::<Logging>().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DuplicateArg);
+        assert_eq!(
+            err.to_string(),
+            "argument '--verbose' is declared by more than one command-line fragment"
+        );
+
+        // a fragment that swallows a positional is found in the audit
+        #[derive(Debug)]
+        struct Target;
 
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--upgrade", "-u"]));
+        impl FromCli for Target {
+            fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+                cli.check_positional::<String>(Positional::new("target"))?;
+                Ok(Target)
+            }
+        }
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "-v", "build", "extra"]));
+        cli.check_fragment::<Logging>().unwrap();
+        cli.check_fragment::<Target>().unwrap();
+        let audit = cli.audit();
+        assert_eq!(audit.len(), 3);
+        assert_eq!(audit[1].get_index(), 2);
+        assert_eq!(audit[1].get_text(), "build");
+        assert_eq!(audit[1].get_arg(), Some("<target>"));
+        assert!(audit[1].get_fragment().unwrap().ends_with("::Target"));
+        assert!(audit[0].get_fragment().unwrap().ends_with("::Logging"));
+        assert_eq!(audit[2].is_consumed(), false);
+        assert_eq!(audit[2].get_fragment(), None);
+        assert!(cli.explain().contains("positional '<target>' in fragment '"));
+
+        // the fragments assign the same switch to different arguments
+        let mut cli = Cli::new().tokenize(args(vec!["orbit"]));
+        cli.check_flag(Flag::new("json").switch('j')).unwrap();
+        let err = cli.check_fragment::<Build>().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DuplicateSwitch);
         assert_eq!(
-            cli.check_flag(Flag::new("upgrade").switch('u'))
-                .unwrap_err()
-                .kind(),
-            ErrorKind::DuplicateOptions
+            err.to_string(),
+            "switch '-j' cannot be assigned to both '--json' and '--jobs'"
         );
+    }
 
-        let mut cli =
-            Cli::new().tokenize(args(vec!["orbit", "--verbose", "--verbose", "--version=9"]));
+    #[test]
+    fn config_defaults() {
+        let config = Config::parse("[cli]\nlang = \"vhdl\"\nip = \"gates\"\njobs = \"x\"\n")
+            .unwrap()
+            .env_vars("ORBIT_", vec![("ORBIT_LANG".to_string(), "verilog".to_string())]);
+        // command line > env > config
+        let mut cli = Cli::new()
+            .config(config.clone())
+            .tokenize(args(vec!["orbit", "--lang", "c"]));
         assert_eq!(
-            cli.check_flag(Flag::new("verbose")).unwrap_err().kind(),
-            ErrorKind::DuplicateOptions
+            cli.check_option::<String>(Optional::new("lang")).unwrap(),
+            Some("c".to_string())
         );
+        let mut cli = Cli::new().config(config.clone()).tokenize(args(vec!["orbit", "adder"]));
         assert_eq!(
-            cli.check_flag(Flag::new("version")).unwrap_err().kind(),
-            ErrorKind::UnexpectedValue
+            cli.check_option::<String>(Optional::new("lang")).unwrap(),
+            Some("verilog".to_string())
         );
-    }
+        assert_eq!(cli.matches().get_value("lang"), Some("verilog"));
+        // the positional form is preferred over the config
+        assert_eq!(
+            cli.check_option_positional::<String>(Optional::new("ip")).unwrap(),
+            Some("adder".to_string())
+        );
+        let mut cli = Cli::new().config(config.clone()).tokenize(args(vec!["orbit"]));
+        assert_eq!(
+            cli.check_option_positional::<String>(Optional::new("ip")).unwrap(),
+            Some("gates".to_string())
+        );
+        assert_eq!(
+            cli.check_option::<u8>(Optional::new("jobs")).unwrap_err().kind(),
+            ErrorKind::BadType
+        );
+        assert_eq!(cli.check_option::<u8>(Optional::new("count")).unwrap(), None);
 
-    #[test]
-    fn check_positional() {
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "new", "rary.gates"]));
+        // flags and options with many values read their defaults too
+        let config = Config::parse(
+            "[cli]\nforce = true\ncache = false\nfeatures = [\"a\", \"b\"]\nlibs = \"x;y\"\nbad = \"yes\"\n",
+        )
+        .unwrap();
+        let mut cli = Cli::new().config(config.clone()).tokenize(args(vec!["orbit"]));
+        assert_eq!(cli.check_flag(Flag::new("force")).unwrap(), true);
+        assert_eq!(cli.check_flag(Flag::new("quiet")).unwrap(), false);
+        assert_eq!(cli.check_bool_flag(Flag::new("cache")).unwrap(), Some(false));
         assert_eq!(
-            cli.check_positional::<String>(Positional::new("command"))
-                .unwrap(),
-            Some("new".to_string())
+            cli.check_option_all::<String>(Optional::new("features")).unwrap(),
+            Some(vec![String::from("a"), String::from("b")])
         );
         assert_eq!(
-            cli.check_positional::<String>(Positional::new("ip"))
+            cli.check_option_greedy::<String>(Optional::new("libs").delimiter(';'), 2)
                 .unwrap(),
-            Some("rary.gates".to_string())
+            Some(vec![String::from("x"), String::from("y")])
         );
         assert_eq!(
-            cli.check_positional::<i32>(Positional::new("path"))
-                .unwrap(),
-            None
+            cli.check_flag(Flag::new("bad")).unwrap_err().kind(),
+            ErrorKind::InvalidChoice
+        );
+        // the command line still takes precedence
+        let mut cli = Cli::new()
+            .config(config)
+            .tokenize(args(vec!["orbit", "--cache", "--features", "c"]));
+        assert_eq!(cli.check_bool_flag(Flag::new("cache")).unwrap(), Some(true));
+        assert_eq!(
+            cli.check_option_all::<String>(Optional::new("features")).unwrap(),
+            Some(vec![String::from("c")])
         );
     }
 
     #[test]
-    fn check_option() {
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "command", "--rate", "10"]));
-        assert_eq!(cli.check_option(Optional::new("rate")).unwrap(), Some(10));
+    fn deterministic_clock() {
+        let cli = Cli::new().deterministic(true);
+        assert_eq!(cli.get_clock(), &Clock::frozen());
+        let cli = cli.deterministic(false);
+        assert_eq!(cli.get_clock().is_deterministic(), false);
+        let cli = Cli::new().clock(Clock::system().seed(7));
+        assert_eq!(cli.get_clock().random("tip"), Clock::frozen().seed(7).random("tip"));
+
+        // the phases summarized by --timings are measured by the clock
+        let cli = Cli::new()
+            .tokenize(args(vec!["orbit", "--timings"]))
+            .deterministic(true);
+        cli.timings()
+            .unwrap()
+            .time("link", || std::thread::sleep(std::time::Duration::from_millis(2)));
+        assert_eq!(
+            cli.timings().unwrap().to_table().to_string(),
+            "phase  seconds\nlink   0.000\n"
+        );
+    }
+
+    #[test]
+    fn spec_matches() {
+        let spec = Spec::new("orbit")
+            .flag(Flag::new("verbose").switch('v'), "")
+            .command(
+                Spec::new("new")
+                    .option(Optional::new("lang").value("hdl"), "")
+                    .positional(Positional::new("ip"), ""),
+            )
+            .command(Spec::new("plan"));
 
         let mut cli = Cli::new().tokenize(args(vec![
-            "orbit", "--flag", "--rate=9", "command", "-r", "14",
+            "orbit", "-vv", "new", "--lang", "vhdl", "gates",
         ]));
+        let matches = cli.check_spec(&spec).unwrap();
+        assert_eq!(matches.get_count("verbose"), 2);
+        assert_eq!(matches.get_value("command"), Some("new"));
+        assert_eq!(matches.get_value("lang"), Some("vhdl"));
+        assert_eq!(matches.get_value("ip"), Some("gates"));
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit"]));
+        assert_eq!(cli.check_spec(&spec).unwrap().is_empty(), true);
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "new"]));
         assert_eq!(
-            cli.check_option::<i32>(Optional::new("rate").switch('r'))
-                .unwrap_err()
-                .kind(),
-            ErrorKind::DuplicateOptions
+            cli.check_spec(&spec).unwrap_err().kind(),
+            ErrorKind::MissingPositional
         );
 
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--flag", "-r", "14"]));
-        assert_eq!(
-            cli.check_option(Optional::new("rate").switch('r')).unwrap(),
-            Some(14)
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "plan", "--help"]));
+        let err = cli.check_spec(&spec).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Help);
+        assert_eq!(err.to_string(), "Usage:\n    orbit plan\n");
+    }
+
+    #[test]
+    fn short_and_long_help() {
+        let help = Help::new().quick_text("brief").long_text("full");
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "-h"]));
+        cli.check_help(help.clone()).unwrap();
+        assert_eq!(cli.is_empty().unwrap_err().to_string(), "brief");
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--help"]));
+        cli.check_help(help.clone()).unwrap();
+        assert_eq!(cli.is_empty().unwrap_err().to_string(), "full");
+
+        // the quick text stands in for a missing long text
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--help"]));
+        cli.check_help(Help::new().quick_text("brief")).unwrap();
+        assert_eq!(cli.is_empty().unwrap_err().to_string(), "brief");
+
+        let spec = Spec::new("orbit")
+            .flag(Flag::new("force"), "overwrite files")
+            .details("Existing files in the output directory are replaced.");
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "-h"]));
+        let err = cli.check_spec(&spec).unwrap_err();
+        assert_eq!(err.to_string().contains("replaced"), false);
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--help"]));
+        let err = cli.check_spec(&spec).unwrap_err();
+        assert!(err.to_string().ends_with(
+            "    --force   overwrite files\n        Existing files in the output directory are replaced.\n"
+        ));
+    }
+
+    #[test]
+    fn help_subcommand() {
+        let spec = Spec::new("orbit").command(
+            Spec::new("new")
+                .command(Spec::new("ip").positional(Positional::new("name"), ""))
+                .command(Spec::new("lib")),
         );
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "help", "new", "ip"]));
+        let err = cli.check_spec(&spec).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Help);
+        assert!(err.to_string().starts_with("Usage:\n    orbit new ip <name>\n"));
 
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--flag", "--rate", "--verbose"]));
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "new", "help", "lib"]));
+        let err = cli.check_spec(&spec).unwrap_err();
+        assert_eq!(err.to_string(), "Usage:\n    orbit new lib\n");
+
+        // without a command path the current command's help is shown
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "help"]));
+        let err = cli.check_spec(&spec).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Help);
+        assert!(err.to_string().starts_with("Usage:\n    orbit <command>"));
+
+        // a real subcommand named 'help' is matched as usual
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "help"]));
+        cli.check_help(Help::new()).unwrap();
+        cli.known_args.push(Arg::Positional(Positional::new("command")));
+        assert_eq!(cli.match_command(&["help", "new"]).unwrap(), "help");
+    }
+
+    #[test]
+    fn collect_errors() {
+        let mut cli = Cli::new()
+            .collect_errors()
+            .tokenize(args(vec!["make", "--jobs", "x", "--verbose=1", "--fast", "a", "b"]));
+        assert_eq!(cli.check_option::<u8>(Optional::new("jobs")).unwrap(), None);
+        assert_eq!(cli.check_flag(Flag::new("verbose")).unwrap(), false);
         assert_eq!(
-            cli.check_option::<i32>(Optional::new("rate"))
-                .unwrap_err()
-                .kind(),
-            ErrorKind::ExpectingValue
+            cli.check_positional::<String>(Positional::new("target")).unwrap(),
+            Some(String::from("a"))
+        );
+        let err = cli.finish().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Multiple);
+        let kinds: Vec<ErrorKind> = match err.context() {
+            ErrorContext::Multiple(errors) => errors.iter().map(|e| e.kind()).collect(),
+            _ => panic!("expected multiple errors"),
+        };
+        assert_eq!(
+            kinds,
+            vec![ErrorKind::BadType, ErrorKind::UnexpectedValue, ErrorKind::UnexpectedArg]
         );
+        // the errors set aside keep their context and span
+        match err.context() {
+            ErrorContext::Multiple(errors) => {
+                match errors[0].context() {
+                    ErrorContext::FailedCast(arg, value, cause) => {
+                        assert_eq!(arg, &Arg::Optional(Optional::new("jobs")));
+                        assert_eq!(value, "x");
+                        assert_eq!(cause.to_string(), "invalid digit found in string");
+                    }
+                    _ => panic!("expected a failed cast"),
+                }
+                assert_eq!(errors[0].span(), Some(&Span::new(2, 0, 1)));
+            }
+            _ => panic!("expected multiple errors"),
+        }
+        let text = err.styled(&style::Theme::default()).color(false).to_string();
+        assert!(text.starts_with("error: argument '--jobs <jobs>' failed to process 'x'"));
+        assert!(text.contains("\n\n    make --jobs x --verbose=1 --fast a b\n                ^\n"));
+        assert!(text.ends_with("\n\nerror: invalid argument '--fast'\n\n    make --jobs x --verbose=1 --fast a b\n                              ^^^^^^"));
 
-        let mut cli =
-            Cli::new().tokenize(args(vec!["orbit", "--flag", "--rate", "five", "--verbose"]));
-        assert!(cli.check_option::<i32>(Optional::new("rate")).is_err());
+        // a single error is reported as it is and help is never set aside
+        let mut cli = Cli::new().collect_errors().tokenize(args(vec!["make", "--jobs", "x"]));
+        assert_eq!(cli.check_option::<u8>(Optional::new("jobs")).unwrap(), None);
+        assert_eq!(cli.finish().unwrap_err().kind(), ErrorKind::BadType);
+        let mut cli = Cli::new().collect_errors().tokenize(args(vec!["make", "--jobs", "x", "-h"]));
+        cli.check_help(Help::new()).unwrap();
+        assert_eq!(cli.check_option::<u8>(Optional::new("jobs")).unwrap_err().kind(), ErrorKind::Help);
+
+        // a value given in either form is set aside as well
+        let mut cli = Cli::new().collect_errors().tokenize(args(vec!["make", "x"]));
+        assert_eq!(cli.check_option_positional::<u8>(Optional::new("jobs")).unwrap(), None);
+        assert_eq!(cli.finish().unwrap_err().kind(), ErrorKind::BadType);
+
+        // an error that stops parsing is reported with the ones set aside
+        let mut cli = Cli::new().collect_errors().tokenize(args(vec!["make", "--fast"]));
+        assert_eq!(cli.check_flag(Flag::new("fast")).unwrap(), true);
+        let err = cli.require_positional::<u8>(Positional::new("n")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MissingPositional);
     }
 
     #[test]
-    fn take_token_str() {
-        let t = Token::UnattachedArgument(0, "get".to_string());
-        // consumes token and returns its internal string
-        assert_eq!(t.take_str(), "get");
-
-        let t = Token::AttachedArgument(1, "rary.gates".to_string());
-        assert_eq!(t.take_str(), "rary.gates");
+    fn convenience_constructors() {
+        let mut cli = Cli::from_iter(["orbit", "new", "--force"]);
+        assert_eq!(cli.program, Some(String::from("orbit")));
+        assert_eq!(cli.check_flag(Flag::new("force")).unwrap(), true);
+        assert_eq!(
+            cli.require_positional::<String>(Positional::new("command")).unwrap(),
+            "new"
+        );
+        cli.is_empty().unwrap();
+        let cli: Cli = vec![String::from("orbit")].into_iter().collect();
+        assert_eq!(cli.args, Vec::<String>::new());
 
-        let t = Token::Ignore(7, "--map".to_string());
-        assert_eq!(t.take_str(), "--map");
+        // the process's arguments are read as any other source of arguments
+        let cli = Cli::new().read_args(vec![String::from("orbit"), String::from("new")]);
+        assert_eq!(cli.program, Some(String::from("orbit")));
+        assert_eq!(cli.args, vec!["new"]);
     }
 
     #[test]
-    #[should_panic]
-    fn take_impossible_token_flag_str() {
-        let t = Token::Flag(7);
-        t.take_str();
+    fn parse_known_args() {
+        let mut cli = Cli::new().parse_known().tokenize(args(vec![
+            "wrap", "-vq", "--color=always", "--jobs", "4", "build", "--", "x",
+        ]));
+        assert_eq!(cli.check_flag(Flag::new("verbose").switch('v')).unwrap(), true);
+        assert_eq!(cli.check_option::<u8>(Optional::new("jobs")).unwrap(), Some(4));
+        cli.is_empty().unwrap();
+        assert_eq!(cli.unknown(), vec!["-q", "--color=always", "build"]);
+        assert_eq!(cli.unknown(), Vec::<String>::new());
+        assert_eq!(cli.check_remainder().unwrap(), vec!["x"]);
+        let explain = cli.explain();
+        let line = explain.lines().find(|l| l.contains("build")).unwrap();
+        assert!(line.ends_with("  unknown, passed along"));
+
+        // the leftovers are still reported without the mode
+        let mut cli = Cli::new().tokenize(args(vec!["wrap", "--color", "build"]));
+        assert_eq!(cli.is_empty().unwrap_err().kind(), ErrorKind::UnexpectedArg);
+        assert_eq!(cli.unknown(), vec!["--color", "build"]);
+        cli.is_empty().unwrap();
     }
 
     #[test]
-    #[should_panic]
-    fn take_impossible_token_switch_str() {
-        let t = Token::Switch(7, 'h');
-        t.take_str();
+    fn command_path() {
+        let mut cli = Cli::new().tokenize(args(vec!["./target/orbit", "new", "ip", "--force"]));
+        assert_eq!(cli.command_path(), "orbit");
+        cli.known_args.push(Arg::Positional(Positional::new("command")));
+        cli.match_command(&["new", "plan"]).unwrap();
+        cli.known_args.push(Arg::Positional(Positional::new("command")));
+        cli.match_command(&["ip", "lib"]).unwrap();
+        assert_eq!(cli.get_commands(), &["new", "ip"]);
+        assert_eq!(cli.command_path(), "orbit new ip");
+        cli.check_flag(Flag::new("force")).unwrap();
+        assert_eq!(cli.usage(), "Usage:\n    orbit new ip [options]");
+
+        let cli = Cli::new().read_args(crate::source::Line::new("new"));
+        assert_eq!(cli.command_path(), "");
     }
 
     #[test]
-    #[should_panic]
-    fn take_impossible_token_terminator_str() {
-        let t = Token::Terminator(9);
-        t.take_str();
+    fn answer_completion_requests() {
+        let spec = Spec::new("orbit").option(
+            Optional::new("lang").possible_values(&["vhdl", "verilog"]),
+            "language of the ip",
+        );
+        let cli = Cli::new()
+            .dynamic_completion(spec.clone())
+            .tokenize(args(vec!["orbit", "__complete", "--lang", "ve"]));
+        assert_eq!(cli.completion_response(), Some(String::from("verilog\n:\n")));
+
+        // requests are only answered once enabled
+        let cli = Cli::new().tokenize(args(vec!["orbit", "__complete", "--lang", "ve"]));
+        assert_eq!(cli.completion_response(), None);
+        let cli = Cli::new()
+            .dynamic_completion(spec)
+            .tokenize(args(vec!["orbit", "--lang", "vhdl"]));
+        assert_eq!(cli.completion_response(), None);
     }
 
     #[test]
-    fn try_help_fail() {
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--h"]));
-        let locs = cli.take_flag_locs("help");
-        assert_eq!(locs.len(), 0);
-        assert_eq!(cli.pull_flag(locs, false), vec![]);
+    fn color_choice_preset() {
+        // the decision of the style module is shared by every test, so it is left as is
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--color=never"]));
+        assert_eq!(cli.read_color().unwrap(), ColorChoice::Never);
+        assert_eq!(cli.use_color, false);
+        assert_eq!(ColorChoice::Never.to_override(), Some(false));
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--color", "always"]));
+        assert_eq!(cli.read_color().unwrap(), ColorChoice::Always);
+        assert_eq!(cli.use_color, true);
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit"]));
+        assert_eq!(cli.read_color().unwrap(), ColorChoice::Auto);
+        assert_eq!(cli.use_color, true);
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--color", "sometimes"]));
+        assert_eq!(cli.read_color().unwrap_err().kind(), ErrorKind::InvalidChoice);
     }
 
     #[test]
-    fn check_option_n() {
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "command", "--rate", "10"]));
+    fn parse_with_arg_context() {
+        use crate::value::{ParseError, ValueParse};
+
+        /// A path resolved against a directory named by the argument.
+        #[derive(Debug, PartialEq)]
+        struct Resolved(String);
+
+        impl ValueParse for Resolved {
+            fn parse(arg: &Arg, text: &str) -> Result<Self, ParseError> {
+                match text.starts_with('/') {
+                    true => Err(ParseError::invalid("expected a relative path")),
+                    false => Ok(Resolved(format!("{}/{}", arg.get_name(), text))),
+                }
+            }
+        }
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--build", "out", "lib"]));
         assert_eq!(
-            cli.check_option_n(Optional::new("rate"), 1).unwrap(),
-            Some(vec![10])
+            cli.check_option::<Resolved>(Optional::new("build")).unwrap(),
+            Some(Resolved(String::from("build/out")))
         );
-
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "command", "--rate", "10"]));
         assert_eq!(
-            cli.check_option_n(Optional::new("rate"), 2).unwrap(),
-            Some(vec![10])
+            cli.require_positional::<Resolved>(Positional::new("src")).unwrap(),
+            Resolved(String::from("src/lib"))
         );
 
-        let mut cli = Cli::new().tokenize(args(vec![
-            "orbit", "command", "--rate", "10", "--rate", "4", "--rate", "3",
-        ]));
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "/tmp"]));
+        let err = cli.require_positional::<Resolved>(Positional::new("src")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidValue);
         assert_eq!(
-            cli.check_option_n::<u8>(Optional::new("rate"), 2).is_err(),
-            true
+            err.to_string(),
+            "argument '<src>' does not accept '/tmp': expected a relative path"
         );
+
+        // types implementing `FromStr` report conversion failures as before
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "x"]));
+        let err = cli.require_positional::<u8>(Positional::new("n")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BadType);
     }
 
     #[test]
-    fn check_flag_n() {
+    fn check_bool_flag() {
+        let cache = || Flag::new("cache").switch('c');
         let mut cli = Cli::new().tokenize(args(vec!["orbit"]));
-        assert_eq!(cli.check_flag_n(Flag::new("debug"), 4).unwrap(), 0);
+        assert_eq!(cli.check_bool_flag(cache()).unwrap(), None);
 
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--debug"]));
-        assert_eq!(cli.check_flag_n(Flag::new("debug"), 1).unwrap(), 1);
+        for (arg, expected) in [
+            ("--cache", true),
+            ("-c", true),
+            ("--cache=true", true),
+            ("--cache=1", true),
+            ("--cache=false", false),
+            ("--cache=0", false),
+        ] {
+            let mut cli = Cli::new().tokenize(args(vec!["orbit", arg]));
+            assert_eq!(cli.check_bool_flag(cache()).unwrap(), Some(expected));
+            cli.is_empty().unwrap();
+        }
 
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--debug", "--debug"]));
-        assert_eq!(cli.check_flag_n(Flag::new("debug"), 3).unwrap(), 2);
+        // separate words are left for positionals
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--cache", "false"]));
+        assert_eq!(cli.check_bool_flag(cache()).unwrap(), Some(true));
+        assert_eq!(
+            cli.require_positional::<String>(Positional::new("name")).unwrap(),
+            "false"
+        );
 
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--debug", "--debug", "--debug"]));
-        assert_eq!(cli.check_flag_n(Flag::new("debug"), 3).unwrap(), 3);
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--cache=flase"]));
+        let err = cli.check_bool_flag(cache()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidChoice);
+        assert_eq!(
+            err.to_string(),
+            "argument '--cache' does not accept 'flase'\n\nPossible values: true, false"
+        );
 
-        let mut cli = Cli::new().tokenize(args(vec![
-            "orbit", "--debug", "--debug", "--debug", "--debug",
-        ]));
-        assert_eq!(cli.check_flag_n(Flag::new("debug"), 3).is_err(), true);
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--cache", "--cache=0"]));
+        assert_eq!(cli.check_bool_flag(cache()).unwrap_err().kind(), ErrorKind::DuplicateOptions);
+
+        let mut cli = Cli::new()
+            .freeze_option("cache", "false")
+            .tokenize(args(vec!["orbit"]));
+        assert_eq!(cli.check_bool_flag(cache()).unwrap(), Some(false));
     }
 
     #[test]
-    fn check_flag_all() {
+    fn negatable_flags() {
+        let verbose = || Flag::new("verbose").negatable();
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--no-verbose"]));
+        assert_eq!(cli.check_bool_flag(verbose()).unwrap(), Some(false));
+        cli.is_empty().unwrap();
+
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--verbose"]));
+        assert_eq!(cli.check_bool_flag(verbose()).unwrap(), Some(true));
         let mut cli = Cli::new().tokenize(args(vec!["orbit"]));
-        assert_eq!(cli.check_flag_all(Flag::new("debug")).unwrap(), 0);
+        assert_eq!(cli.check_bool_flag(verbose()).unwrap(), None);
 
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--debug", "--debug", "--debug"]));
-        assert_eq!(cli.check_flag_all(Flag::new("debug")).unwrap(), 3);
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--verbose", "--no-verbose"]));
+        assert_eq!(
+            cli.check_bool_flag(verbose()).unwrap_err().kind(),
+            ErrorKind::DuplicateOptions
+        );
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--no-verbose=1"]));
+        assert_eq!(
+            cli.check_bool_flag(verbose()).unwrap_err().kind(),
+            ErrorKind::UnexpectedValue
+        );
 
-        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--debug", "--debug", "--debug=1"]));
-        assert_eq!(cli.check_flag_all(Flag::new("debug")).is_err(), true);
+        // the inverse is only accepted when the flag opts in
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--no-verbose"]));
+        assert_eq!(cli.check_bool_flag(Flag::new("verbose")).unwrap(), None);
+        assert_eq!(cli.is_empty().unwrap_err().kind(), ErrorKind::UnexpectedArg);
+
+        // a spec reads its negatable flags as booleans
+        let spec = Spec::new("orbit").flag(verbose(), "show more details");
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--no-verbose"]));
+        cli.check_spec(&spec).unwrap();
+        cli.is_empty().unwrap();
     }
 
     #[test]
-    fn requires_positional_all() {
-        let mut cli = Cli::new().tokenize(args(vec!["sum", "10", "20", "30"]));
+    fn negatable_flag_read_as_count() {
+        let verbose = || Flag::new("verbose").negatable();
+        let cli = |words: Vec<&str>| Cli::new().tokenize(args(words));
+
+        let mut c = cli(vec!["orbit", "--no-verbose"]);
+        assert_eq!(c.check_flag(verbose()).unwrap(), false);
+        c.is_empty().unwrap();
+        let mut c = cli(vec!["orbit", "--verbose"]);
+        assert_eq!(c.check_flag(verbose()).unwrap(), true);
+
+        let mut c = cli(vec!["orbit", "--no-verbose"]);
+        assert_eq!(c.check_flag_all(verbose()).unwrap(), 0);
+        c.is_empty().unwrap();
+        let mut c = cli(vec!["orbit", "--verbose"]);
+        assert_eq!(c.check_flag_all(verbose()).unwrap(), 1);
+
+        let mut c = cli(vec!["orbit", "--no-verbose"]);
+        assert_eq!(c.check_flag_count(verbose()).unwrap(), Count::new(0));
+        c.is_empty().unwrap();
+        let mut c = cli(vec!["orbit", "--verbose"]);
+        assert_eq!(c.check_flag_count(verbose()).unwrap(), Count::new(1));
+
+        let mut c = cli(vec!["orbit", "--no-verbose"]);
+        assert_eq!(c.check_flag_n(verbose(), 1).unwrap(), 0);
+        c.is_empty().unwrap();
+        let mut c = cli(vec!["orbit", "--verbose"]);
+        assert_eq!(c.check_flag_n(verbose(), 1).unwrap(), 1);
+
+        // the flag and its inverse together are still a duplicate
+        let mut c = cli(vec!["orbit", "--verbose", "--no-verbose"]);
         assert_eq!(
-            cli.require_positional_all::<u8>(Positional::new("digit"))
-                .unwrap(),
-            vec![10, 20, 30]
+            c.check_flag_all(verbose()).unwrap_err().kind(),
+            ErrorKind::DuplicateOptions
         );
+    }
 
-        let mut cli = Cli::new().tokenize(args(vec!["sum"]));
+    #[test]
+    fn cluster_then_separate_value() {
+        let extract = || Flag::new("extract").switch('x');
+        let gzip = || Flag::new("gzip").switch('z');
+        let file = || Optional::new("file").switch('f');
+
+        // the last switch of a cluster takes the next argument whether its flags or
+        // option are queried first, as long as the positionals are queried after
+        let mut cli = Cli::new().tokenize(args(vec!["tar", "-xzf", "a.tgz", "dir"]));
+        assert_eq!(cli.check_flag(extract()).unwrap(), true);
+        assert_eq!(cli.check_flag(gzip()).unwrap(), true);
+        assert_eq!(cli.check_option::<String>(file()).unwrap(), Some(String::from("a.tgz")));
+        assert_eq!(cli.require_positional::<String>(Positional::new("dir")).unwrap(), "dir");
+        cli.is_empty().unwrap();
+
+        let mut cli = Cli::new().tokenize(args(vec!["tar", "-xzf", "a.tgz", "dir"]));
+        assert_eq!(cli.check_option::<String>(file()).unwrap(), Some(String::from("a.tgz")));
+        assert_eq!(cli.check_flag(gzip()).unwrap(), true);
+        assert_eq!(cli.check_flag(extract()).unwrap(), true);
+        assert_eq!(cli.require_positional::<String>(Positional::new("dir")).unwrap(), "dir");
+        cli.is_empty().unwrap();
+        assert!(cli.explain().contains("  1  a.tgz  value for '--file <file>'"));
+
+        // switches after the option's within the cluster are its value instead
+        let mut cli = Cli::new().tokenize(args(vec!["tar", "-xfz", "a.tgz"]));
+        assert_eq!(cli.check_option::<String>(file()).unwrap(), Some(String::from("z")));
+        assert_eq!(cli.check_flag(extract()).unwrap(), true);
+        assert_eq!(cli.is_empty().unwrap_err().kind(), ErrorKind::UnexpectedArg);
+
+        let mut cli = Cli::new().tokenize(args(vec!["tar", "-xzf"]));
         assert_eq!(
-            cli.require_positional_all::<u8>(Positional::new("digit"))
-                .unwrap_err()
-                .kind(),
-            ErrorKind::MissingPositional
+            cli.check_option::<String>(file()).unwrap_err().kind(),
+            ErrorKind::ExpectingValue
         );
+    }
 
-        let mut cli = Cli::new().tokenize(args(vec!["sum", "10", "--option", "20", "30"]));
-        // note: remember to always check options and flags before positional arguments
+    #[test]
+    fn dump_remaining_tokens() {
+        let mut cli = Cli::new().tokenize(args(vec![
+            "orbit", "-vq", "--jobs=4", "build", "-", "-=y", "--", "x",
+        ]));
+        assert_eq!(cli.check_flag(Flag::new("verbose").switch('v')).unwrap(), true);
+        let tokens = cli.dump_tokens();
         assert_eq!(
-            cli.check_option::<u8>(Optional::new("option")).unwrap(),
-            Some(20)
+            tokens
+                .iter()
+                .map(|t| (t.get_index(), t.get_kind(), t.get_text()))
+                .collect::<Vec<(usize, TokenKind, &str)>>(),
+            vec![
+                (1, TokenKind::Switch, "-q"),
+                (2, TokenKind::Flag, "--jobs"),
+                (2, TokenKind::AttachedArgument, "4"),
+                (3, TokenKind::Argument, "build"),
+                (4, TokenKind::Argument, "-"),
+                (5, TokenKind::EmptySwitch, "-"),
+                (5, TokenKind::AttachedArgument, "y"),
+                (6, TokenKind::Terminator, "--"),
+                (7, TokenKind::Remainder, "x"),
+            ]
         );
+        assert_eq!(format!("{:?}", tokens[1]), "Flag(2, \"--jobs\")");
+        assert!(format!("{:#?}", tokens).starts_with("[\n    Switch(1, \"-q\"),\n    Flag(2,"));
+    }
+
+    #[test]
+    fn synthesized_usage() {
+        let mut cli = Cli::new().tokenize(args(vec!["/usr/bin/orbit", "add", "--verbose", "1"]));
+        cli.check_help(Help::new().quick_text("Adds two numbers.")).unwrap();
+        cli.check_flag(Flag::new("verbose")).unwrap();
+        cli.known_args.push(Arg::Positional(Positional::new("command")));
+        cli.match_command(&["add"]).unwrap();
+        cli.check_flag(Flag::new("force")).unwrap();
+        assert_eq!(cli.require_positional::<u8>(Positional::new("lhs")).unwrap(), 1);
+        let err = cli.require_positional::<u8>(Positional::new("rhs")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MissingPositional);
         assert_eq!(
-            cli.require_positional_all::<u8>(Positional::new("digit"))
-                .unwrap(),
-            vec![10, 30]
+            err.to_string(),
+            "missing positional argument '<rhs>'\n\nUsage:\n    orbit add [options] <lhs> <rhs>"
         );
+        assert_eq!(cli.usage(), "Usage:\n    orbit add [options] <lhs>");
 
-        let mut cli = Cli::new().tokenize(args(vec!["sum", "100"]));
+        // a usage given with the help is kept
+        let mut cli = Cli::new().tokenize(args(vec!["orbit"]));
+        cli.check_help(Help::new().usage("Usage: orbit <ip>")).unwrap();
+        let err = cli.require_positional::<String>(Positional::new("ip")).unwrap_err();
+        assert_eq!(err.to_string(), "missing positional argument '<ip>'\n\nUsage: orbit <ip>");
+    }
+
+    #[derive(Debug)]
+    struct Sub;
+
+    impl FromCli for Sub {
+        fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+            cli.match_command(&["add", "sub"])?;
+            Ok(Sub)
+        }
+    }
+
+    #[test]
+    fn exit_codes() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "--help", "x"]));
+        cli.check_help(Help::new()).unwrap();
+        assert_eq!(cli.is_empty().unwrap_err().exit_code(), 0);
+
+        let cli = Cli::new().tokenize(args(vec!["orbit", "x"]));
+        let err = cli.is_empty().unwrap_err();
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(err.with_exit_code(3).exit_code(), 3);
+
+        let rule: Result<u8, std::num::ParseIntError> = "a".parse::<u8>();
+        let err = Error::validate(rule).unwrap_err();
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(err.with_exit_code(65).exit_code(), 65);
+    }
+
+    #[test]
+    fn conflicting_switches() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "-c"]));
+        assert_eq!(cli.check_flag(Flag::new("color").switch('c')).unwrap(), true);
+        let err = cli.check_flag(Flag::new("count").switch('c')).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DuplicateSwitch);
         assert_eq!(
-            cli.require_positional_all::<u8>(Positional::new("digit"))
-                .unwrap(),
-            vec![100]
+            err.to_string(),
+            "switch '-c' cannot be assigned to both '--color' and '--count'"
+        );
+        // reusing the same flag is not a conflict
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "-c"]));
+        assert_eq!(cli.check_flag(Flag::new("color").switch('c')).unwrap(), true);
+        assert_eq!(cli.check_flag(Flag::new("color").switch('c')).unwrap(), true);
+    }
+
+    #[test]
+    fn scoped_switches() {
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "-c", "get"]));
+        // parent command
+        assert_eq!(cli.check_flag(Flag::new("color").switch('c')).unwrap(), true);
+        cli.known_args.push(Arg::Positional(Positional::new("command")));
+        assert_eq!(cli.match_command(&["get"]).unwrap(), "get");
+        // the subcommand declares '-c' for a different flag and never sees the parent's occurrence
+        assert_eq!(
+            cli.check_option::<u8>(Optional::new("count").switch('c')).unwrap(),
+            None
+        );
+        // without scoping the parent's occurrence would swallow the subcommand as its value
+        let mut cli = Cli::new().tokenize(args(vec!["orbit", "-c", "get"]));
+        assert_eq!(
+            cli.check_option::<u8>(Optional::new("count").switch('c')).unwrap_err().kind(),
+            ErrorKind::BadType
         );
     }
 }
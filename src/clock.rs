@@ -0,0 +1,134 @@
+//! Sources of time and randomness for time-dependent features.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Supplies the current time and random numbers to features that depend on
+/// them, such as capping how often a hint is shown or timestamping history.
+///
+/// By default the system clock and an unpredictable random source are used.
+/// Fixing the time and seeding the random source makes every run reproducible,
+/// which tests and record/replay tooling rely on.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Clock {
+    time: Option<SystemTime>,
+    seed: Option<u64>,
+}
+
+impl Clock {
+    /// Creates a clock reading the system time with an unpredictable random
+    /// source.
+    pub fn system() -> Self {
+        Self::default()
+    }
+
+    /// Creates a clock frozen at the Unix epoch with a random source seeded by 0.
+    pub fn frozen() -> Self {
+        Self::system().at(UNIX_EPOCH).seed(0)
+    }
+
+    /// Fixes the time reported by the clock.
+    pub fn at(mut self, time: SystemTime) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Seeds the random source so it produces the same numbers on every run.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Checks if both the time and the random source are fixed.
+    pub fn is_deterministic(&self) -> bool {
+        self.time.is_some() && self.seed.is_some()
+    }
+
+    pub fn now(&self) -> SystemTime {
+        self.time.unwrap_or_else(SystemTime::now)
+    }
+
+    /// Returns the time elapsed since the Unix epoch.
+    pub fn since_epoch(&self) -> Duration {
+        self.now().duration_since(UNIX_EPOCH).unwrap_or_default()
+    }
+
+    /// Starts measuring the time elapsed from now.
+    ///
+    /// A clock with a fixed time measures every duration as zero.
+    pub fn stopwatch(&self) -> Stopwatch {
+        Stopwatch {
+            start: match self.time {
+                Some(_) => None,
+                None => Some(Instant::now()),
+            },
+        }
+    }
+
+    /// Draws a random number for the purpose named `key`.
+    ///
+    /// A seeded source always returns the same number for the same `key`, so
+    /// features should name each draw distinctly.
+    pub fn random(&self, key: &str) -> u64 {
+        match self.seed {
+            Some(seed) => {
+                // FNV-1a over the seed and key, followed by a final mix
+                let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+                for b in seed.to_le_bytes().iter().chain(key.as_bytes()) {
+                    h ^= *b as u64;
+                    h = h.wrapping_mul(0x0100_0000_01b3);
+                }
+                h ^= h >> 33;
+                h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+                h ^ (h >> 33)
+            }
+            None => {
+                let mut hasher = RandomState::new().build_hasher();
+                hasher.write(key.as_bytes());
+                hasher.finish()
+            }
+        }
+    }
+}
+
+/// Measures the time elapsed since it was started from a [Clock].
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Stopwatch {
+    start: Option<Instant>,
+}
+
+impl Stopwatch {
+    pub fn elapsed(&self) -> Duration {
+        self.start.map(|s| s.elapsed()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn frozen_sources() {
+        let clock = Clock::frozen();
+        assert_eq!(clock.is_deterministic(), true);
+        assert_eq!(clock.now(), UNIX_EPOCH);
+        assert_eq!(clock.random("hint"), Clock::frozen().random("hint"));
+        assert_ne!(clock.random("hint"), clock.random("history"));
+        assert_ne!(clock.random("hint"), clock.clone().seed(1).random("hint"));
+
+        let clock = Clock::system().at(UNIX_EPOCH + Duration::from_secs(90));
+        assert_eq!(clock.since_epoch(), Duration::from_secs(90));
+        // the random source is still unpredictable
+        assert_eq!(clock.is_deterministic(), false);
+        assert_eq!(Clock::system().is_deterministic(), false);
+
+        // durations are measured as zero while the time is fixed
+        let watch = clock.stopwatch();
+        std::thread::sleep(Duration::from_millis(2));
+        assert_eq!(watch.elapsed(), Duration::ZERO);
+        let watch = Clock::system().stopwatch();
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(watch.elapsed() >= Duration::from_millis(2));
+    }
+}
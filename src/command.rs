@@ -1,6 +1,10 @@
 use crate::cli::Cli;
 use crate::error::Error;
+use crate::source::Env;
 use std::fmt::Debug;
+use std::fmt::Display;
+#[cfg(feature = "async")]
+use std::future::Future;
 
 pub trait Command<T>: Debug {
     type Status;
@@ -22,6 +26,152 @@ pub trait FromCli {
 
 pub trait Runner<T>: Command<T> + FromCli + Debug {}
 
+/// A [Command] whose execution is asynchronous, for applications built on an
+/// async runtime.
+///
+/// Implementations may write `async fn exec`, as long as the returned future can
+/// be sent across threads.
+#[cfg(feature = "async")]
+pub trait AsyncCommand<T>: Debug {
+    type Status;
+    fn exec(&self, context: &T) -> impl Future<Output = Self::Status> + Send;
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncRunner<T>: AsyncCommand<T> + FromCli + Debug {}
+
+/// Converts the status returned by a [Command] into a process exit code.
+pub trait ExitStatus {
+    fn exit_code(&self) -> u8;
+
+    /// Describes why the command failed, which is written to stderr before
+    /// exiting.
+    fn failure(&self) -> Option<String> {
+        None
+    }
+}
+
+impl ExitStatus for () {
+    fn exit_code(&self) -> u8 {
+        0
+    }
+}
+
+impl ExitStatus for u8 {
+    fn exit_code(&self) -> u8 {
+        *self
+    }
+}
+
+impl ExitStatus for bool {
+    /// Maps success (`true`) to 0 and failure to 1.
+    fn exit_code(&self) -> u8 {
+        match self {
+            true => 0,
+            false => 1,
+        }
+    }
+}
+
+impl<T, E: Display> ExitStatus for Result<T, E> {
+    /// Maps `Ok` to 0 and `Err` to 1.
+    fn exit_code(&self) -> u8 {
+        match self {
+            Ok(_) => 0,
+            Err(_) => 1,
+        }
+    }
+
+    /// Describes the error of `Err`.
+    fn failure(&self) -> Option<String> {
+        self.as_ref().err().map(|e| e.to_string())
+    }
+}
+
+/// Parses the process's command-line arguments into `T` and executes it with
+/// `context`, then exits the process.
+///
+/// Help and errors are printed as with [Cli::go], and the exit code is taken
+/// from the command's [ExitStatus] when it runs, after writing its
+/// [failure](ExitStatus::failure), if any, to stderr.
+pub fn run<C, T: Runner<C>>(context: C) -> !
+where
+    T::Status: ExitStatus,
+{
+    std::process::exit(execute::<C, T>(context) as i32)
+}
+
+/// Parses the process's command-line arguments into `T` and executes it with
+/// `context`, returning the exit code instead of exiting the process.
+pub fn execute<C, T: Runner<C>>(context: C) -> u8
+where
+    T::Status: ExitStatus,
+{
+    let cli = Cli::new().read_args(Env);
+    match cli.launch::<C, T>(&context) {
+        Ok(status) => {
+            if let Some(reason) = status.failure() {
+                eprintln!("error: {}", reason);
+            }
+            status.exit_code()
+        }
+        Err(err) => {
+            err.print();
+            err.exit_code()
+        }
+    }
+}
+
+/// The support status of a subcommand.
+///
+/// A subcommand declares its status with [Cli::check_availability] at the start
+/// of its `from_cli` so the gating and messages are consistent across commands.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub enum Availability {
+    #[default]
+    Stable,
+    /// Usable only when unstable features are enabled.
+    Experimental,
+    /// Still usable but produces a warning.
+    Deprecated { since: String, note: Option<String> },
+    /// No longer usable.
+    Removed { since: String, note: Option<String> },
+}
+
+impl Availability {
+    pub fn deprecated<T: AsRef<str>>(since: T) -> Self {
+        Self::Deprecated {
+            since: since.as_ref().to_string(),
+            note: None,
+        }
+    }
+
+    pub fn removed<T: AsRef<str>>(since: T) -> Self {
+        Self::Removed {
+            since: since.as_ref().to_string(),
+            note: None,
+        }
+    }
+
+    /// Attaches a note, such as the replacement to use, to a deprecated or removed status.
+    pub fn note<T: AsRef<str>>(mut self, text: T) -> Self {
+        if let Self::Deprecated { note, .. } | Self::Removed { note, .. } = &mut self {
+            *note = Some(text.as_ref().to_string());
+        }
+        self
+    }
+
+    /// Returns the label displayed alongside the command's help text.
+    pub fn badge(&self) -> Option<String> {
+        match self {
+            Self::Stable => None,
+            Self::Experimental => Some(String::from("[experimental]")),
+            Self::Deprecated { since, .. } => Some(format!("[deprecated since {}]", since)),
+            Self::Removed { since, .. } => Some(format!("[removed in {}]", since)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -49,6 +199,8 @@ mod test {
         }
     }
 
+    impl Runner<()> for Add {}
+
     impl Add {
         /// Simple fn to return an answer for the `Add` test command.
         fn run(&self) -> String {
@@ -170,6 +322,71 @@ mod test {
         );
     }
 
+    #[test]
+    fn exit_statuses() {
+        assert_eq!(().exit_code(), 0);
+        assert_eq!(7u8.exit_code(), 7);
+        assert_eq!(false.exit_code(), 1);
+        assert_eq!(Ok::<(), String>(()).exit_code(), 0);
+        assert_eq!(Err::<(), String>(String::from("failed")).exit_code(), 1);
+        assert_eq!(Ok::<(), String>(()).failure(), None);
+        assert_eq!(
+            Err::<(), String>(String::from("failed")).failure(),
+            Some(String::from("failed"))
+        );
+
+        // the status of the executed command is returned
+        let cli = Cli::new().tokenize(args(vec!["add", "9", "10"]));
+        assert_eq!(cli.launch::<(), Add>(&()).unwrap().exit_code(), 0);
+        let cli = Cli::new().tokenize(args(vec!["add", "9"]));
+        assert_eq!(cli.launch::<(), Add>(&()).unwrap_err().exit_code(), 2);
+    }
+
+    #[cfg(feature = "async")]
+    mod asynchronous {
+        use super::*;
+        use std::future::Future;
+        use std::pin::pin;
+        use std::task::{Context, Poll, Waker};
+
+        /// The `Add` test command executed asynchronously.
+        #[derive(Debug)]
+        struct Sum(Add);
+
+        impl FromCli for Sum {
+            fn from_cli<'c>(cli: &'c mut Cli) -> Result<Self, Error> {
+                Ok(Self(Add::from_cli(cli)?))
+            }
+        }
+
+        impl AsyncCommand<u32> for Sum {
+            type Status = u32;
+
+            async fn exec(&self, offset: &u32) -> Self::Status {
+                self.0.lhs + self.0.rhs + offset
+            }
+        }
+
+        impl AsyncRunner<u32> for Sum {}
+
+        /// Polls `f` to completion on the current thread.
+        fn block_on<F: Future>(f: F) -> F::Output {
+            let mut f = pin!(f);
+            let mut cx = Context::from_waker(Waker::noop());
+            loop {
+                if let Poll::Ready(r) = f.as_mut().poll(&mut cx) {
+                    return r;
+                }
+            }
+        }
+
+        #[test]
+        fn async_runner() {
+            let cli = Cli::new().tokenize(args(vec!["add", "9", "10"]));
+            assert_eq!(block_on(cli.go_async::<u32, Sum>(&1)), 20);
+        }
+    }
+
     #[test]
     fn nested_commands() {
         let mut cli = Cli::new().tokenize(args(vec!["op", "add", "9", "10"]));
@@ -0,0 +1,708 @@
+//! Comparison of two command-line interfaces exported with
+//! [Spec::to_json](crate::spec::Spec::to_json).
+
+use crate::json::{self, Value};
+use crate::spec::SCHEMA_VERSION;
+use std::fmt::Display;
+
+/// Whether a change to an interface can break existing invocations.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Impact {
+    /// An invocation accepted before may now be rejected or behave differently.
+    Breaking,
+    /// Every invocation accepted before is still accepted.
+    Additive,
+}
+
+impl Display for Impact {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Breaking => write!(f, "breaking"),
+            Self::Additive => write!(f, "additive"),
+        }
+    }
+}
+
+/// A single difference between two interfaces.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Change {
+    impact: Impact,
+    command: String,
+    description: String,
+}
+
+impl Change {
+    pub fn get_impact(&self) -> Impact {
+        self.impact
+    }
+
+    /// Returns the full name of the command the change was made to, such as
+    /// `orbit get`.
+    pub fn get_command(&self) -> &str {
+        self.command.as_ref()
+    }
+
+    pub fn get_description(&self) -> &str {
+        self.description.as_ref()
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}: {}", self.impact, self.command, self.description)
+    }
+}
+
+/// The changes from one interface to another.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Diff {
+    changes: Vec<Change>,
+}
+
+impl Diff {
+    pub fn get_changes(&self) -> &Vec<Change> {
+        &self.changes
+    }
+
+    /// Checks if any change can break existing invocations.
+    pub fn is_breaking(&self) -> bool {
+        self.breaking().next().is_some()
+    }
+
+    pub fn breaking(&self) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(|c| c.impact == Impact::Breaking)
+    }
+
+    pub fn additive(&self) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(|c| c.impact == Impact::Additive)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn push<T: AsRef<str>>(&mut self, impact: Impact, command: &str, description: T) -> () {
+        self.changes.push(Change {
+            impact,
+            command: command.to_string(),
+            description: description.as_ref().to_string(),
+        });
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, change) in self.changes.iter().enumerate() {
+            match i {
+                0 => write!(f, "{}", change)?,
+                _ => write!(f, "\n{}", change)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A document that could not be compared.
+#[derive(Debug, PartialEq)]
+pub struct DiffError {
+    document: &'static str,
+    reason: String,
+}
+
+impl DiffError {
+    fn new<T: AsRef<str>>(document: &'static str, reason: T) -> Self {
+        Self {
+            document,
+            reason: reason.as_ref().to_string(),
+        }
+    }
+}
+
+impl std::error::Error for DiffError {}
+
+impl Display for DiffError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} spec is invalid: {}", self.document, self.reason)
+    }
+}
+
+/// Categorizes the changes from the interface exported as `old_json` to the one
+/// exported as `new_json`.
+///
+/// Only changes that affect which invocations are accepted are reported, so
+/// edits to descriptions and value types are ignored. Positionals are compared
+/// by position, so renaming one is not a change. A subcommand that was removed
+/// while another with the same arguments was added is reported as renamed.
+/// Adding an argument is breaking only when it is
+/// [required](crate::spec::Spec::required), and taking fewer or more values
+/// is breaking only when it rejects a count accepted before.
+///
+/// Arguments and subcommands that were experimental in `old_json` are exempt
+/// from stability, so changes to them are additive.
+///
+/// Errors if either document is not a spec of the current [SCHEMA_VERSION].
+pub fn diff(old_json: &str, new_json: &str) -> Result<Diff, DiffError> {
+    let old = read("old", old_json)?;
+    let new = read("new", new_json)?;
+    let mut diff = Diff::default();
+    compare_commands(&old, &new, &old.name, &mut diff);
+    Ok(diff)
+}
+
+/// The parts of a command that decide which invocations it accepts.
+#[derive(Debug, PartialEq)]
+struct Command {
+    name: String,
+    status: String,
+    args: Vec<Param>,
+    commands: Vec<Command>,
+}
+
+/// The parts of an argument that decide which invocations it accepts.
+#[derive(Debug, PartialEq)]
+struct Param {
+    kind: String,
+    name: String,
+    switch: Option<String>,
+    value: Option<String>,
+    values: Vec<String>,
+    aliases: Vec<String>,
+    requires: Vec<String>,
+    unstable: bool,
+    required: bool,
+    min: i64,
+    max: Option<i64>,
+}
+
+impl Param {
+    fn is_positional(&self) -> bool {
+        self.kind == "positional"
+    }
+
+    /// Writes the argument the way it is typed on the command-line.
+    fn label(&self) -> String {
+        match (self.kind.as_ref(), &self.value) {
+            ("positional", _) => format!("<{}>", self.name),
+            (_, Some(v)) => format!("--{} <{}>", self.name, v),
+            (_, None) => format!("--{}", self.name),
+        }
+    }
+}
+
+fn read(document: &'static str, text: &str) -> Result<Command, DiffError> {
+    let doc = json::parse(text).map_err(|e| DiffError::new(document, e.to_string()))?;
+    let schema = doc.get("schema").and_then(|s| s.as_str());
+    let version = doc.get("version").and_then(|v| v.as_int());
+    if schema != Some("clif-spec") || version != Some(SCHEMA_VERSION) {
+        return Err(DiffError::new(
+            document,
+            format!("expected schema 'clif-spec' version {}", SCHEMA_VERSION),
+        ));
+    }
+    match doc.get("command") {
+        Some(c) => read_command(c).map_err(|e| DiffError::new(document, e)),
+        None => Err(DiffError::new(document, "missing field 'command'")),
+    }
+}
+
+fn field<'a>(v: &'a Value, key: &str) -> Result<&'a Value, String> {
+    v.get(key).ok_or(format!("missing field '{}'", key))
+}
+
+fn string(v: &Value, key: &str) -> Result<String, String> {
+    match field(v, key)?.as_str() {
+        Some(s) => Ok(s.to_string()),
+        None => Err(format!("field '{}' must be a string", key)),
+    }
+}
+
+fn opt_string(v: &Value, key: &str) -> Result<Option<String>, String> {
+    match field(v, key)? {
+        Value::Null => Ok(None),
+        s => Ok(Some(
+            s.as_str()
+                .ok_or(format!("field '{}' must be a string", key))?
+                .to_string(),
+        )),
+    }
+}
+
+fn array<'a>(v: &'a Value, key: &str) -> Result<&'a Vec<Value>, String> {
+    field(v, key)?
+        .as_array()
+        .ok_or(format!("field '{}' must be an array", key))
+}
+
+fn strings(v: &Value, key: &str) -> Result<Vec<String>, String> {
+    array(v, key)?
+        .iter()
+        .map(|s| s.as_str().map(|s| s.to_string()))
+        .collect::<Option<Vec<String>>>()
+        .ok_or(format!("field '{}' must be an array of strings", key))
+}
+
+fn read_command(v: &Value) -> Result<Command, String> {
+    Ok(Command {
+        name: string(v, "name")?,
+        status: string(field(v, "availability")?, "status")?,
+        args: array(v, "args")?
+            .iter()
+            .map(read_param)
+            .collect::<Result<_, _>>()?,
+        commands: array(v, "commands")?
+            .iter()
+            .map(read_command)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn read_param(v: &Value) -> Result<Param, String> {
+    let aliases = array(v, "aliases")?
+        .iter()
+        .map(|a| string(a, "alias"))
+        .collect::<Result<_, _>>()?;
+    let kind = string(v, "kind")?;
+    // documents written before arity was recorded take the parser's defaults
+    let required = match v.get("required") {
+        Some(r) => r
+            .as_bool()
+            .ok_or(String::from("field 'required' must be a boolean"))?,
+        None => kind == "positional",
+    };
+    let (min, max) = match v.get("arity") {
+        Some(a) => (
+            field(a, "min")?
+                .as_int()
+                .ok_or(String::from("field 'min' must be an integer"))?,
+            match field(a, "max")? {
+                Value::Null => None,
+                m => Some(
+                    m.as_int()
+                        .ok_or(String::from("field 'max' must be an integer"))?,
+                ),
+            },
+        ),
+        None => match kind.as_ref() {
+            "flag" => (0, Some(0)),
+            _ => (1, Some(1)),
+        },
+    };
+    Ok(Param {
+        kind,
+        name: string(v, "name")?,
+        switch: opt_string(v, "switch")?,
+        value: opt_string(v, "value")?,
+        values: strings(v, "possible_values")?,
+        aliases,
+        requires: strings(v, "requires")?,
+        unstable: field(v, "unstable")?.as_bool().unwrap_or(false),
+        required,
+        min,
+        max,
+    })
+}
+
+/// Picks the impact of a change that would otherwise be breaking.
+fn breaking(exempt: bool) -> Impact {
+    match exempt {
+        true => Impact::Additive,
+        false => Impact::Breaking,
+    }
+}
+
+/// Picks the impact of adding an argument, which only breaks invocations that
+/// leave it out when it is required.
+fn added(required: bool, exempt: bool) -> Impact {
+    match required {
+        true => breaking(exempt),
+        false => Impact::Additive,
+    }
+}
+
+fn compare_commands(old: &Command, new: &Command, path: &str, diff: &mut Diff) -> () {
+    let exempt = old.status == "experimental";
+    match (old.status.as_ref(), new.status.as_ref()) {
+        (a, b) if a == b => (),
+        (_, "removed") => diff.push(breaking(exempt), path, "command was removed"),
+        (_, "experimental") => diff.push(Impact::Breaking, path, "command is now experimental"),
+        ("experimental", _) | ("removed", _) => {
+            diff.push(Impact::Additive, path, "command is now available")
+        }
+        _ => (),
+    }
+    compare_args(old, new, path, diff);
+
+    let removed: Vec<&Command> = old
+        .commands
+        .iter()
+        .filter(|o| new.commands.iter().any(|n| n.name == o.name) == false)
+        .collect();
+    let mut added: Vec<&Command> = new
+        .commands
+        .iter()
+        .filter(|n| old.commands.iter().any(|o| o.name == n.name) == false)
+        .collect();
+    for o in removed {
+        let exempt = exempt || o.status == "experimental";
+        // a command with the same arguments under a new name was renamed
+        match added
+            .iter()
+            .position(|n| n.args == o.args && n.commands == o.commands)
+        {
+            Some(i) => {
+                let n = added.remove(i);
+                diff.push(
+                    breaking(exempt),
+                    path,
+                    format!("renamed subcommand '{}' to '{}'", o.name, n.name),
+                );
+            }
+            None => diff.push(
+                breaking(exempt),
+                path,
+                format!("removed subcommand '{}'", o.name),
+            ),
+        }
+    }
+    for n in added {
+        diff.push(
+            Impact::Additive,
+            path,
+            format!("added subcommand '{}'", n.name),
+        );
+    }
+    for o in &old.commands {
+        if let Some(n) = new.commands.iter().find(|n| n.name == o.name) {
+            compare_commands(o, n, &format!("{} {}", path, o.name), diff);
+        }
+    }
+}
+
+fn compare_args(old: &Command, new: &Command, path: &str, diff: &mut Diff) -> () {
+    let exempt = old.status == "experimental";
+    let (old_pos, old_named): (Vec<&Param>, Vec<&Param>) =
+        old.args.iter().partition(|a| a.is_positional() == true);
+    let (new_pos, new_named): (Vec<&Param>, Vec<&Param>) =
+        new.args.iter().partition(|a| a.is_positional() == true);
+    // flags and options are matched by name
+    for o in &old_named {
+        let exempt = exempt || o.unstable;
+        match new_named.iter().find(|n| n.name == o.name) {
+            Some(n) => compare_param(o, n, exempt, path, diff),
+            None => diff.push(
+                breaking(exempt),
+                path,
+                format!("removed {} '{}'", o.kind, o.label()),
+            ),
+        }
+    }
+    for n in new_named
+        .iter()
+        .filter(|n| old_named.iter().any(|o| o.name == n.name) == false)
+    {
+        diff.push(
+            added(n.required, exempt),
+            path,
+            format!("added {} '{}'", n.kind, n.label()),
+        );
+    }
+    // positionals are matched by position
+    for (o, n) in old_pos.iter().zip(new_pos.iter()) {
+        compare_param(o, n, exempt || o.unstable, path, diff);
+    }
+    for o in old_pos.iter().skip(new_pos.len()) {
+        let exempt = exempt || o.unstable;
+        diff.push(
+            breaking(exempt),
+            path,
+            format!("removed positional '{}'", o.label()),
+        );
+    }
+    for n in new_pos.iter().skip(old_pos.len()) {
+        diff.push(
+            added(n.required, exempt),
+            path,
+            format!("added positional '{}'", n.label()),
+        );
+    }
+}
+
+fn compare_param(old: &Param, new: &Param, exempt: bool, path: &str, diff: &mut Diff) -> () {
+    let label = old.label();
+    if old.kind != new.kind {
+        diff.push(
+            breaking(exempt),
+            path,
+            format!("'{}' changed from {} to {}", label, old.kind, new.kind),
+        );
+        return;
+    }
+    match (&old.switch, &new.switch) {
+        (a, b) if a == b => (),
+        (Some(a), b) => {
+            diff.push(
+                breaking(exempt),
+                path,
+                format!("removed switch '-{}' from '{}'", a, label),
+            );
+            if let Some(b) = b {
+                diff.push(
+                    Impact::Additive,
+                    path,
+                    format!("added switch '-{}' to '{}'", b, label),
+                );
+            }
+        }
+        (None, Some(b)) => diff.push(
+            Impact::Additive,
+            path,
+            format!("added switch '-{}' to '{}'", b, label),
+        ),
+        (None, None) => (),
+    }
+    // an empty list of possible values accepts any value
+    match (old.values.is_empty(), new.values.is_empty()) {
+        (true, false) => diff.push(
+            breaking(exempt),
+            path,
+            format!("'{}' now restricts its values", label),
+        ),
+        (false, true) => diff.push(
+            Impact::Additive,
+            path,
+            format!("'{}' now accepts any value", label),
+        ),
+        _ => {
+            for v in old
+                .values
+                .iter()
+                .filter(|v| new.values.contains(v) == false)
+            {
+                diff.push(
+                    breaking(exempt),
+                    path,
+                    format!("'{}' no longer accepts '{}'", label, v),
+                );
+            }
+            for v in new
+                .values
+                .iter()
+                .filter(|v| old.values.contains(v) == false)
+            {
+                diff.push(
+                    Impact::Additive,
+                    path,
+                    format!("'{}' now accepts '{}'", label, v),
+                );
+            }
+        }
+    }
+    for a in old
+        .aliases
+        .iter()
+        .filter(|a| new.aliases.contains(a) == false)
+    {
+        diff.push(
+            breaking(exempt),
+            path,
+            format!("'{}' no longer accepts the alias '{}'", label, a),
+        );
+    }
+    for a in new
+        .aliases
+        .iter()
+        .filter(|a| old.aliases.contains(a) == false)
+    {
+        diff.push(
+            Impact::Additive,
+            path,
+            format!("'{}' now accepts the alias '{}'", label, a),
+        );
+    }
+    for r in new
+        .requires
+        .iter()
+        .filter(|r| old.requires.contains(r) == false)
+    {
+        diff.push(
+            breaking(exempt),
+            path,
+            format!("'{}' now requires '--{}'", label, r),
+        );
+    }
+    for r in old
+        .requires
+        .iter()
+        .filter(|r| new.requires.contains(r) == false)
+    {
+        diff.push(
+            Impact::Additive,
+            path,
+            format!("'{}' no longer requires '--{}'", label, r),
+        );
+    }
+    match (old.required, new.required) {
+        (false, true) => diff.push(
+            breaking(exempt),
+            path,
+            format!("'{}' is now required", label),
+        ),
+        (true, false) => diff.push(
+            Impact::Additive,
+            path,
+            format!("'{}' is no longer required", label),
+        ),
+        _ => (),
+    }
+    if new.min > old.min {
+        diff.push(
+            breaking(exempt),
+            path,
+            format!("'{}' now takes at least {} values", label, new.min),
+        );
+    } else if new.min < old.min {
+        diff.push(
+            Impact::Additive,
+            path,
+            format!("'{}' now takes at least {} values", label, new.min),
+        );
+    }
+    // no maximum accepts any number of values
+    match (old.max, new.max) {
+        (a, b) if a == b => (),
+        (None, Some(b)) => diff.push(
+            breaking(exempt),
+            path,
+            format!("'{}' now takes at most {} values", label, b),
+        ),
+        (Some(a), Some(b)) if b < a => diff.push(
+            breaking(exempt),
+            path,
+            format!("'{}' now takes at most {} values", label, b),
+        ),
+        (_, Some(b)) => diff.push(
+            Impact::Additive,
+            path,
+            format!("'{}' now takes at most {} values", label, b),
+        ),
+        (_, None) => diff.push(
+            Impact::Additive,
+            path,
+            format!("'{}' now takes any number of values", label),
+        ),
+    }
+    match (old.unstable, new.unstable) {
+        (false, true) => diff.push(
+            Impact::Breaking,
+            path,
+            format!("'{}' is now experimental", label),
+        ),
+        (true, false) => diff.push(Impact::Additive, path, format!("'{}' is now stable", label)),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arg::{Flag, Optional, Positional};
+    use crate::spec::Spec;
+
+    fn old() -> Spec {
+        Spec::new("orbit")
+            .flag(Flag::new("verbose").switch('v'), "print more")
+            .flag(Flag::new("trace").unstable(), "trace internal calls")
+            .command(
+                Spec::new("get")
+                    .option(
+                        Optional::new("format")
+                            .switch('f')
+                            .possible_values(&["json", "toml"]),
+                        "output format",
+                    )
+                    .positional(Positional::new("ip"), "name of the ip"),
+            )
+            .command(Spec::new("tree").flag(Flag::new("all"), "show all"))
+    }
+
+    #[test]
+    fn categorize_changes() {
+        let new = Spec::new("orbit")
+            .flag(Flag::new("verbose"), "print more verbosely")
+            .option(Optional::new("jobs"), "threads to use")
+            .command(
+                Spec::new("get")
+                    .option(
+                        Optional::new("format").possible_values(&["json", "yaml"]),
+                        "output format",
+                    )
+                    .positional(Positional::new("name"), "name of the ip")
+                    .positional(Positional::new("version"), "version of the ip"),
+            )
+            .command(Spec::new("deps").flag(Flag::new("all"), "show all"));
+        let diff = diff(&old().to_json(), &new.to_json()).unwrap();
+        assert_eq!(diff.is_breaking(), true);
+        assert_eq!(
+            diff.to_string(),
+            "breaking: orbit: removed switch '-v' from '--verbose'
+additive: orbit: removed flag '--trace'
+additive: orbit: added option '--jobs <jobs>'
+breaking: orbit: renamed subcommand 'tree' to 'deps'
+breaking: orbit get: removed switch '-f' from '--format <format>'
+breaking: orbit get: '--format <format>' no longer accepts 'toml'
+additive: orbit get: '--format <format>' now accepts 'yaml'
+breaking: orbit get: added positional '<version>'"
+        );
+        assert_eq!(diff.additive().count(), 3);
+
+        // new subcommands are additive
+        let same = old().command(Spec::new("new")).to_json();
+        let diff = super::diff(&old().to_json(), &same).unwrap();
+        assert_eq!(diff.is_breaking(), false);
+        assert_eq!(diff.to_string(), "additive: orbit: added subcommand 'new'");
+        assert_eq!(super::diff(&same, &same).unwrap().is_empty(), true);
+
+        // optional arguments are additive while required ones are breaking
+        let optional = Spec::new("orbit")
+            .flag(Flag::new("verbose").switch('v'), "print more")
+            .flag(Flag::new("trace").unstable(), "trace internal calls")
+            .command(
+                Spec::new("get")
+                    .option(
+                        Optional::new("format")
+                            .switch('f')
+                            .possible_values(&["json", "toml"]),
+                        "output format",
+                    )
+                    .option(Optional::new("lang"), "language")
+                    .option(Optional::new("out"), "output file")
+                    .required(true)
+                    .positional(Positional::new("ip"), "name of the ip")
+                    .arity(1, None)
+                    .positional(Positional::new("version"), "version of the ip")
+                    .required(false),
+            )
+            .command(Spec::new("tree").flag(Flag::new("all"), "show all"));
+        let diff = super::diff(&old().to_json(), &optional.to_json()).unwrap();
+        assert_eq!(
+            diff.to_string(),
+            "additive: orbit get: added option '--lang <lang>'
+breaking: orbit get: added option '--out <out>'
+additive: orbit get: '<ip>' now takes any number of values
+additive: orbit get: added positional '<version>'"
+        );
+    }
+
+    #[test]
+    fn reject_documents() {
+        let spec = old().to_json();
+        assert_eq!(
+            diff("{}", &spec).unwrap_err().to_string(),
+            "old spec is invalid: expected schema 'clif-spec' version 1"
+        );
+        assert_eq!(
+            diff(&spec, "{").unwrap_err().to_string(),
+            "new spec is invalid: line 1: expected a quoted key"
+        );
+    }
+}
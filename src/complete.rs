@@ -0,0 +1,609 @@
+//! Shell completion scripts generated from a [Spec].
+//!
+//! The script offers a command's subcommands, flags, and the values of its
+//! arguments. Values come from an argument's possible values or, failing that,
+//! its [ValueHint], so an argument hinted as a [FilePath](ValueHint::FilePath)
+//! completes the files in the current directory.
+//!
+//! ```
+//! use clif::arg::{Optional, ValueHint};
+//! use clif::complete::Shell;
+//! use clif::spec::Spec;
+//!
+//! let spec = Spec::new("orbit").option(
+//!     Optional::new("config").value_hint(ValueHint::FilePath),
+//!     "read settings from a file",
+//! );
+//! let script = spec.to_completion(Shell::Bash);
+//! assert!(script.ends_with("complete -F _orbit orbit\n"));
+//! ```
+
+use crate::arg::{Arg, Positional, ValueHint};
+use crate::command::Availability;
+use crate::spec::{Entry, Spec};
+
+/// The hidden subcommand a program answers dynamic completion requests under.
+pub const COMPLETE: &str = "__complete";
+
+/// A shell able to load a generated completion script.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Shell {
+    Bash,
+    Zsh,
+}
+
+/// A command within the tree along with the names leading to it.
+struct Node<'a> {
+    path: String,
+    spec: &'a Spec,
+}
+
+/// Writes the completion script for `spec` understood by `shell`.
+pub(crate) fn script(spec: &Spec, shell: Shell) -> String {
+    let mut nodes = Vec::new();
+    walk(spec, String::new(), &mut nodes);
+    let func = function_name(spec.get_name());
+
+    let mut text = String::new();
+    if shell == Shell::Zsh {
+        text.push_str(&format!("#compdef {}\n\n", spec.get_name()));
+    }
+    let (cur, prev, words) = match shell {
+        Shell::Bash => (
+            "${COMP_WORDS[COMP_CWORD]}",
+            "${COMP_WORDS[COMP_CWORD-1]}",
+            "${COMP_WORDS[@]:1:COMP_CWORD-1}",
+        ),
+        Shell::Zsh => (
+            "${words[CURRENT]}",
+            "${words[CURRENT-1]}",
+            "${(@)words[2,CURRENT-1]}",
+        ),
+    };
+    text.push_str(&format!("{}() {{\n", func));
+    text.push_str(&format!("    local cur=\"{}\"\n", cur));
+    text.push_str(&format!("    local prev=\"{}\"\n", prev));
+    text.push_str(&format!("    local cmd={}\n", quote(spec.get_name())));
+    text.push_str("    local n=0\n    local skip=\"\"\n    local word\n");
+
+    // find the command being completed and count the positionals before the cursor
+    text.push_str(&format!("    for word in \"{}\"; do\n", words));
+    text.push_str("        if [[ -n \"${skip}\" ]]; then\n");
+    text.push_str("            skip=\"\"\n            continue\n        fi\n");
+    text.push_str("        case \"${cmd} ${word}\" in\n");
+    let commands: Vec<String> = nodes.iter().skip(1).map(|n| quote(&n.path)).collect();
+    if commands.is_empty() == false {
+        arm(
+            &mut text,
+            shell,
+            12,
+            &commands.join("|"),
+            &["cmd=\"${cmd} ${word}\"", "n=0"],
+        );
+    }
+    let valued: Vec<String> = nodes
+        .iter()
+        .flat_map(|n| {
+            options(n.spec).flat_map(move |(words, _)| {
+                words.into_iter().map(move |w| quote(&format!("{} {}", n.path, w)))
+            })
+        })
+        .collect();
+    if valued.is_empty() == false {
+        arm(&mut text, shell, 12, &valued.join("|"), &["skip=1"]);
+    }
+    arm(
+        &mut text,
+        shell,
+        12,
+        "*",
+        &["[[ \"${word}\" != -* ]] && n=$((n + 1))"],
+    );
+    text.push_str("        esac\n    done\n\n");
+
+    if shell == Shell::Bash {
+        text.push_str("    COMPREPLY=()\n");
+    }
+    text.push_str("    case \"${cmd}\" in\n");
+    for node in &nodes {
+        let body = complete_command(node.spec, shell);
+        let body: Vec<&str> = body.iter().map(|l| l.as_str()).collect();
+        arm(&mut text, shell, 8, &quote(&node.path), &body);
+    }
+    text.push_str("    esac\n}\n\n");
+    match shell {
+        Shell::Bash => text.push_str(&format!("complete -F {} {}\n", func, spec.get_name())),
+        Shell::Zsh => text.push_str(&format!("compdef {} {}\n", func, spec.get_name())),
+    }
+    text
+}
+
+/// Writes the completion script for the program `name` that asks the program
+/// itself for the candidates with [COMPLETE].
+pub(crate) fn dynamic_script(name: &str, shell: Shell) -> String {
+    let func = function_name(name);
+
+    let mut text = String::new();
+    match shell {
+        Shell::Bash => {
+            text.push_str(&format!("{}() {{\n", func));
+            text.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+            text.push_str("    local -a lines\n");
+            text.push_str(&format!(
+                "    mapfile -t lines < <({} {} \"${{COMP_WORDS[@]:1:COMP_CWORD}}\" 2>/dev/null)\n",
+                quote(name),
+                COMPLETE
+            ));
+            // nothing is written when the program fails to answer
+            text.push_str("    [[ ${#lines[@]} -gt 0 ]] || return 0\n");
+            text.push_str("    local directive=\"${lines[${#lines[@]}-1]}\"\n");
+            text.push_str("    unset 'lines[${#lines[@]}-1]'\n");
+            text.push_str("    COMPREPLY=()\n");
+        }
+        Shell::Zsh => {
+            text.push_str(&format!("#compdef {}\n\n{}() {{\n", name, func));
+            text.push_str("    local -a lines\n");
+            text.push_str(&format!(
+                "    lines=(\"${{(@f)$({} {} \"${{(@)words[2,CURRENT]}}\" 2>/dev/null)}}\")\n",
+                quote(name),
+                COMPLETE
+            ));
+            text.push_str("    local directive=\"${lines[-1]}\"\n");
+            text.push_str("    lines=(\"${(@)lines[1,-2]}\")\n");
+        }
+    }
+    text.push_str("    case \"${directive}\" in\n");
+    for hint in HINTS {
+        if let Some(action) = hint_action(hint, shell) {
+            arm(&mut text, shell, 8, directive(hint), &[&action]);
+        }
+    }
+    match shell {
+        Shell::Bash => arm(&mut text, shell, 8, "*", &["COMPREPLY=(\"${lines[@]}\")"]),
+        Shell::Zsh => arm(&mut text, shell, 8, "*", &["compadd -- \"${(@)lines}\""]),
+    }
+    text.push_str("    esac\n}\n\n");
+    match shell {
+        Shell::Bash => text.push_str(&format!("complete -F {} {}\n", func, name)),
+        Shell::Zsh => text.push_str(&format!("compdef {} {}\n", func, name)),
+    }
+    text
+}
+
+/// Answers a dynamic completion request for `spec`, where `words` follow the
+/// program name and end with the word under the cursor.
+///
+/// Each candidate is written on its own line, followed by a line naming the
+/// [directive] the script falls back to when the value is described only by a
+/// hint.
+pub(crate) fn respond<T: AsRef<str>>(spec: &Spec, words: &[T]) -> String {
+    let (candidates, hint) = candidates(spec, words);
+    let mut text = String::new();
+    for c in candidates {
+        text.push_str(&c);
+        text.push('\n');
+    }
+    text.push_str(directive(hint));
+    text.push('\n');
+    text
+}
+
+/// Finds the candidates for the last of `words`, along with the hint of the
+/// value being completed when there is nothing else to offer.
+fn candidates<T: AsRef<str>>(spec: &Spec, words: &[T]) -> (Vec<String>, ValueHint) {
+    let (cur, before) = match words.split_last() {
+        Some((cur, before)) => (cur.as_ref(), before),
+        None => ("", words),
+    };
+    let mut node = spec;
+    let mut n = 0;
+    let mut pending: Option<&Positional> = None;
+    for word in before.iter().map(|w| w.as_ref()) {
+        if pending.take().is_some() {
+            continue;
+        }
+        if let Some(c) = node
+            .get_commands()
+            .iter()
+            .find(|c| c.get_name() == word && is_usable(c))
+        {
+            node = c;
+            n = 0;
+        } else if let Some((_, value)) = options(node).find(|(w, _)| w.iter().any(|w| w == word)) {
+            pending = Some(value);
+        } else if word.starts_with('-') == false {
+            n += 1;
+        }
+    }
+
+    let matching = |words: Vec<String>| -> Vec<String> {
+        words.into_iter().filter(|w| w.starts_with(cur)).collect()
+    };
+    if let Some(value) = pending {
+        return complete_value(value, cur);
+    }
+    if cur.starts_with('-') == true {
+        let flags = node
+            .documented_args()
+            .filter(|e| e.get_arg().as_flag().is_some())
+            .flat_map(flag_words)
+            .collect();
+        return (matching(flags), ValueHint::Unknown);
+    }
+    let commands: Vec<String> = node
+        .get_commands()
+        .iter()
+        .filter(|c| is_usable(c))
+        .map(|c| c.get_name().to_string())
+        .collect();
+    if commands.is_empty() == false {
+        return (matching(commands), ValueHint::Unknown);
+    }
+    let positional = node
+        .documented_args()
+        .filter_map(|e| match e.get_arg() {
+            Arg::Positional(p) => Some(p),
+            _ => None,
+        })
+        .nth(n);
+    match positional {
+        Some(p) => complete_value(p, cur),
+        None => (Vec::new(), ValueHint::Unknown),
+    }
+}
+
+/// Finds the candidates for the partial value `cur` of `p`.
+fn complete_value(p: &Positional, cur: &str) -> (Vec<String>, ValueHint) {
+    if let Some(f) = &p.get_meta().completer {
+        return (f.get()(cur), ValueHint::Unknown);
+    }
+    match p.get_possible_values().is_empty() {
+        false => (
+            p.get_possible_values()
+                .iter()
+                .filter(|v| v.starts_with(cur))
+                .cloned()
+                .collect(),
+            ValueHint::Unknown,
+        ),
+        true => (Vec::new(), p.get_value_hint()),
+    }
+}
+
+/// Every hint a dynamic completion request can fall back to.
+const HINTS: [ValueHint; 7] = [
+    ValueHint::FilePath,
+    ValueHint::DirPath,
+    ValueHint::ExecutablePath,
+    ValueHint::CommandName,
+    ValueHint::Hostname,
+    ValueHint::Username,
+    ValueHint::Url,
+];
+
+/// Names the completion the script falls back to for values described by
+/// `hint`.
+fn directive(hint: ValueHint) -> &'static str {
+    match hint {
+        ValueHint::Unknown => ":",
+        ValueHint::FilePath => ":file",
+        ValueHint::DirPath => ":dir",
+        ValueHint::ExecutablePath => ":executable",
+        ValueHint::CommandName => ":command",
+        ValueHint::Hostname => ":hostname",
+        ValueHint::Username => ":username",
+        ValueHint::Url => ":url",
+    }
+}
+
+/// Lists `spec` and every usable subcommand beneath it, parents first.
+fn walk<'a>(spec: &'a Spec, path: String, nodes: &mut Vec<Node<'a>>) {
+    let path = match path.is_empty() {
+        true => spec.get_name().to_string(),
+        false => format!("{} {}", path, spec.get_name()),
+    };
+    let children = spec.get_commands().iter().filter(|c| is_usable(c));
+    nodes.push(Node {
+        path: path.clone(),
+        spec,
+    });
+    for child in children {
+        walk(child, path.clone(), nodes);
+    }
+}
+
+/// Checks if the command `spec` can be run without enabling unstable features.
+fn is_usable(spec: &Spec) -> bool {
+    matches!(
+        spec.get_availability(),
+        Availability::Stable | Availability::Deprecated { .. }
+    )
+}
+
+/// Iterates over the options of `spec` with the words that raise each one.
+fn options(spec: &Spec) -> impl Iterator<Item = (Vec<String>, &Positional)> {
+    spec.documented_args().filter_map(|e| match e.get_arg() {
+        Arg::Optional(o) => Some((flag_words(e), o.get_positional())),
+        _ => None,
+    })
+}
+
+/// Lists the words that raise the flag or option of `entry`.
+fn flag_words(entry: &Entry) -> Vec<String> {
+    let flag = entry.get_arg().as_flag().unwrap();
+    let mut words = vec![flag.to_string()];
+    if let Some(c) = flag.get_switch() {
+        words.push(format!("-{}", c));
+    }
+    if flag.is_negatable() == true {
+        words.push(format!("--no-{}", flag.get_name()));
+    }
+    words
+}
+
+/// Writes the lines that complete the word under the cursor for the command
+/// `spec`.
+fn complete_command(spec: &Spec, shell: Shell) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    let options: Vec<(Vec<String>, &Positional)> = options(spec).collect();
+    if options.is_empty() == false {
+        lines.push(String::from("case \"${prev}\" in"));
+        for (words, value) in options {
+            let mut body = Vec::new();
+            if let Some(action) = action(value, shell) {
+                body.push(action);
+            }
+            body.push(String::from("return 0"));
+            let body: Vec<&str> = body.iter().map(|l| l.as_str()).collect();
+            let mut text = String::new();
+            arm(&mut text, shell, 4, &words.join("|"), &body);
+            lines.extend(text.lines().map(|l| l.to_string()));
+        }
+        lines.push(String::from("esac"));
+    }
+
+    let flags: Vec<String> = spec
+        .documented_args()
+        .filter(|e| e.get_arg().as_flag().is_some())
+        .flat_map(flag_words)
+        .collect();
+    if flags.is_empty() == false {
+        lines.push(String::from("if [[ \"${cur}\" == -* ]]; then"));
+        lines.push(format!("    {}", offer(&flags, shell)));
+        lines.push(String::from("    return 0"));
+        lines.push(String::from("fi"));
+    }
+
+    let commands: Vec<String> = spec
+        .get_commands()
+        .iter()
+        .filter(|c| is_usable(c))
+        .map(|c| c.get_name().to_string())
+        .collect();
+    if commands.is_empty() == false {
+        lines.push(offer(&commands, shell));
+    } else {
+        let positionals: Vec<String> = spec
+            .documented_args()
+            .filter_map(|e| match e.get_arg() {
+                Arg::Positional(p) => Some(p),
+                _ => None,
+            })
+            .enumerate()
+            .filter_map(|(i, p)| action(p, shell).map(|a| (i, a)))
+            .fold(String::new(), |mut text, (i, a)| {
+                arm(&mut text, shell, 4, &i.to_string(), &[&a]);
+                text
+            })
+            .lines()
+            .map(|l| l.to_string())
+            .collect();
+        if positionals.is_empty() == false {
+            lines.push(String::from("case \"${n}\" in"));
+            lines.extend(positionals);
+            lines.push(String::from("esac"));
+        }
+    }
+    if lines.is_empty() == true {
+        lines.push(String::from(":"));
+    }
+    lines
+}
+
+/// Returns the command that completes a value of `p`, or `None` if nothing can
+/// be offered.
+fn action(p: &Positional, shell: Shell) -> Option<String> {
+    match p.get_possible_values().is_empty() {
+        false => Some(offer(p.get_possible_values(), shell)),
+        true => hint_action(p.get_value_hint(), shell),
+    }
+}
+
+/// Returns the command that completes a value described by `hint`, or `None` if
+/// `shell` has nothing to offer for it.
+fn hint_action(hint: ValueHint, shell: Shell) -> Option<String> {
+    let command = match (shell, hint) {
+        (_, ValueHint::Unknown) => return None,
+        (Shell::Bash, ValueHint::Url) => return None,
+        (Shell::Bash, ValueHint::FilePath) => "compgen -f",
+        (Shell::Bash, ValueHint::DirPath) => "compgen -d",
+        (Shell::Bash, ValueHint::ExecutablePath) => "compgen -f",
+        (Shell::Bash, ValueHint::CommandName) => "compgen -c",
+        (Shell::Bash, ValueHint::Hostname) => "compgen -A hostname",
+        (Shell::Bash, ValueHint::Username) => "compgen -u",
+        (Shell::Zsh, ValueHint::FilePath) => "_files",
+        (Shell::Zsh, ValueHint::DirPath) => "_files -/",
+        (Shell::Zsh, ValueHint::ExecutablePath) => "_files -g '*(-*)'",
+        (Shell::Zsh, ValueHint::CommandName) => "_command_names",
+        (Shell::Zsh, ValueHint::Hostname) => "_hosts",
+        (Shell::Zsh, ValueHint::Username) => "_users",
+        (Shell::Zsh, ValueHint::Url) => "_urls",
+    };
+    Some(match shell {
+        Shell::Bash => format!("COMPREPLY=($({} -- \"${{cur}}\"))", command),
+        Shell::Zsh => command.to_string(),
+    })
+}
+
+/// Returns the command that offers the fixed list of `words`.
+fn offer(words: &[String], shell: Shell) -> String {
+    match shell {
+        Shell::Bash => format!(
+            "COMPREPLY=($(compgen -W {} -- \"${{cur}}\"))",
+            quote(&words.join(" "))
+        ),
+        Shell::Zsh => {
+            let words: Vec<String> = words.iter().map(|w| quote(w)).collect();
+            format!("compadd -- {}", words.join(" "))
+        }
+    }
+}
+
+/// Writes a branch of a `case` statement matching `pattern` that runs `body`,
+/// indented by `indent` spaces.
+fn arm(text: &mut String, shell: Shell, indent: usize, pattern: &str, body: &[&str]) {
+    match shell {
+        Shell::Bash => text.push_str(&format!("{:indent$}{})\n", "", pattern, indent = indent)),
+        Shell::Zsh => text.push_str(&format!("{:indent$}({})\n", "", pattern, indent = indent)),
+    }
+    for line in body {
+        text.push_str(&format!("{:indent$}{}\n", "", line, indent = indent + 4));
+    }
+    text.push_str(&format!("{:indent$};;\n", "", indent = indent + 4));
+}
+
+/// Names the shell function completing the program `name`.
+fn function_name(name: &str) -> String {
+    std::iter::once('_')
+        .chain(name.chars().map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        }))
+        .collect()
+}
+
+/// Quotes `word` so the shell reads it literally.
+fn quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+/// Quotes `word` only when the shell would not read it literally as written.
+pub(crate) fn shell_word(word: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c);
+    match word.is_empty() == false && word.chars().all(plain) {
+        true => word.to_string(),
+        false => quote(word),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arg::{Flag, Optional};
+
+    fn orbit() -> Spec {
+        Spec::new("orbit")
+            .flag(Flag::new("version"), "print the version")
+            .command(
+                Spec::new("new")
+                    .flag(Flag::new("force").switch('f'), "overwrite an existing directory")
+                    .flag(Flag::new("git").negatable(), "initialize a repository")
+                    .option(
+                        Optional::new("lang").switch('l').possible_values(&["vhdl", "verilog"]),
+                        "language of the ip",
+                    )
+                    .positional(
+                        Positional::new("path").value_hint(ValueHint::DirPath),
+                        "directory to create",
+                    ),
+            )
+            .command(
+                Spec::new("fetch")
+                    .option(
+                        Optional::new("host").value_hint(ValueHint::Hostname),
+                        "server to download from",
+                    )
+                    .positional(Positional::new("url").value_hint(ValueHint::Url), "ip to fetch"),
+            )
+            .command(Spec::new("old").availability(Availability::removed("1.0")))
+    }
+
+    #[test]
+    fn bash_script() {
+        let script = orbit().to_completion(Shell::Bash);
+        assert!(script.contains("            'orbit new'|'orbit fetch')\n"));
+        assert!(script.contains(
+            "            'orbit new --lang'|'orbit new -l'|'orbit fetch --host')\n                skip=1\n"
+        ));
+        assert!(script.contains(
+            "                --lang|-l)\n                    COMPREPLY=($(compgen -W 'vhdl verilog' -- \"${cur}\"))\n                    return 0\n"
+        ));
+        assert!(script.contains("                    COMPREPLY=($(compgen -A hostname -- \"${cur}\"))\n"));
+        assert!(script.contains(
+            "                0)\n                    COMPREPLY=($(compgen -d -- \"${cur}\"))\n"
+        ));
+        assert!(script.contains("COMPREPLY=($(compgen -W '--force -f --git --no-git --lang -l' -- \"${cur}\"))"));
+        assert!(script.contains("COMPREPLY=($(compgen -W 'new fetch' -- \"${cur}\"))"));
+        // removed commands and urls, which bash cannot complete, are left out
+        assert_eq!(script.contains("old"), false);
+        assert_eq!(script.contains("case \"${n}\" in\n                esac"), false);
+        assert!(script.ends_with("complete -F _orbit orbit\n"));
+    }
+
+    #[test]
+    fn zsh_script() {
+        let script = orbit().to_completion(Shell::Zsh);
+        assert!(script.starts_with("#compdef orbit\n\n_orbit() {\n"));
+        assert!(script.contains("            ('orbit new'|'orbit fetch')\n"));
+        assert!(script.contains("                (--lang|-l)\n                    compadd -- 'vhdl' 'verilog'\n"));
+        assert!(script.contains("                (--host)\n                    _hosts\n"));
+        assert!(script.contains("                (0)\n                    _files -/\n"));
+        assert!(script.contains("                (0)\n                    _urls\n"));
+        assert!(script.ends_with("compdef _orbit orbit\n"));
+    }
+
+    #[test]
+    fn dynamic_candidates() {
+        let spec = orbit().command(
+            Spec::new("get").positional(
+                Positional::new("ip").candidates(|prefix: &str| {
+                    ["gates", "alu", "adder"]
+                        .iter()
+                        .filter(|c| c.starts_with(prefix))
+                        .map(|c| c.to_string())
+                        .collect()
+                }),
+                "ip to install",
+            ),
+        );
+        assert_eq!(respond(&spec, &[""]), "new\nfetch\nget\n:\n");
+        assert_eq!(respond(&spec, &["new", "--l"]), "--lang\n:\n");
+        assert_eq!(respond(&spec, &["new", "-l", "v"]), "vhdl\nverilog\n:\n");
+        assert_eq!(respond(&spec, &["new", "-l", "vhdl", ""]), ":dir\n");
+        assert_eq!(respond(&spec, &["new", "-l", "vhdl", "lib", ""]), ":\n");
+        assert_eq!(respond(&spec, &["fetch", "--host", ""]), ":hostname\n");
+        assert_eq!(respond(&spec, &["get", "a"]), "alu\nadder\n:\n");
+        // removed commands are not entered
+        assert_eq!(respond(&spec, &["old", ""]), "new\nfetch\nget\n:\n");
+        assert_eq!(respond::<&str>(&spec, &[]), "new\nfetch\nget\n:\n");
+
+        let script = spec.to_dynamic_completion(Shell::Bash);
+        assert!(script.contains("mapfile -t lines < <('orbit' __complete \"${COMP_WORDS[@]:1:COMP_CWORD}\" 2>/dev/null)\n"));
+        assert!(script.contains("2>/dev/null)\n    [[ ${#lines[@]} -gt 0 ]] || return 0\n"));
+        assert!(script.contains("        :dir)\n            COMPREPLY=($(compgen -d -- \"${cur}\"))\n"));
+        assert_eq!(script.contains(":url"), false);
+        let script = spec.to_dynamic_completion(Shell::Zsh);
+        assert!(script.contains("        (:url)\n            _urls\n"));
+        assert!(script.contains("        (*)\n            compadd -- \"${(@)lines}\"\n"));
+    }
+
+    #[test]
+    fn quote_words() {
+        assert_eq!(quote("orbit new"), "'orbit new'");
+        assert_eq!(quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_word("--release"), "--release");
+        assert_eq!(shell_word("a b"), "'a b'");
+        assert_eq!(shell_word(""), "''");
+    }
+}
@@ -0,0 +1,220 @@
+//! Persistent defaults for options read from a configuration file and the
+//! environment.
+//!
+//! A [Config] is opt-in: once handed to a [Cli](crate::Cli), any flag or option
+//! not supplied on the command line falls back to an environment variable, and
+//! then to the `[cli]` table of a TOML file, keyed by the flag or option's
+//! name:
+//!
+//! ```toml
+//! [cli]
+//! lang = "vhdl"
+//! jobs = 4
+//! ```
+//!
+//! Flags read a boolean default with [check_flag](crate::Cli::check_flag) and
+//! [check_bool_flag](crate::Cli::check_bool_flag), and options read theirs with
+//! every `check_option` form except
+//! [check_option_occurrences](crate::Cli::check_option_occurrences), whose
+//! values must each come from a position on the command line. Counted flags and
+//! positionals do not read defaults.
+//!
+//! The environment is read when the variables are captured with
+//! [env](Config::env), never while parsing.
+
+use crate::matches::Source;
+use crate::spec::Spec;
+use crate::toml::{self, Value};
+use std::path::Path;
+
+pub use crate::toml::ParseError;
+
+/// Name of the table holding option defaults.
+const TABLE: &str = "cli";
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Config {
+    root: toml::Table,
+    env: Vec<(String, String)>,
+}
+
+impl Config {
+    /// Creates a configuration without any defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the defaults from the TOML `text`.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            root: toml::parse(text)?,
+            env: Vec::new(),
+        })
+    }
+
+    /// Reads the defaults from the TOML file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Captures the process's environment variables starting with `prefix` as
+    /// defaults that take precedence over the file.
+    ///
+    /// The rest of a variable's name is lowercased with underscores replaced by
+    /// hyphens to find its option, so `ORBIT_BUILD_DIR` supplies `--build-dir`
+    /// for the prefix `ORBIT_`.
+    pub fn env<T: AsRef<str>>(self, prefix: T) -> Self {
+        self.env_vars(prefix, std::env::vars())
+    }
+
+    /// Captures the variables in `vars` starting with `prefix`, as with
+    /// [env](Config::env).
+    pub fn env_vars<T, I>(mut self, prefix: T, vars: I) -> Self
+    where
+        T: AsRef<str>,
+        I: IntoIterator<Item = (String, String)>,
+    {
+        for (key, value) in vars {
+            if let Some(rest) = key.strip_prefix(prefix.as_ref()) {
+                let name = rest.to_lowercase().replace('_', "-");
+                self.env.retain(|(n, _)| n != &name);
+                self.env.push((name, value));
+            }
+        }
+        self
+    }
+
+    fn table(&self) -> Option<&toml::Table> {
+        match self.root.get(TABLE) {
+            Some(Value::Table(t)) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Returns the default for the option `name`, preferring the environment
+    /// over the file.
+    ///
+    /// Arrays in the file are joined with commas.
+    pub fn get(&self, name: &str) -> Option<String> {
+        match self.env.iter().find(|(n, _)| n == name) {
+            Some((_, v)) => Some(v.clone()),
+            None => self.table()?.get(name).map(|v| v.to_plain_string()),
+        }
+    }
+
+    /// Returns the defaults for the option `name` as separate values, preferring
+    /// the environment over the file.
+    ///
+    /// Each element of an array in the file is a separate value, while a
+    /// variable is a single value.
+    pub fn get_all(&self, name: &str) -> Option<Vec<String>> {
+        match self.env.iter().find(|(n, _)| n == name) {
+            Some((_, v)) => Some(vec![v.clone()]),
+            None => match self.table()?.get(name)? {
+                Value::Array(a) => Some(a.iter().map(|v| v.to_plain_string()).collect()),
+                v => Some(vec![v.to_plain_string()]),
+            },
+        }
+    }
+
+    /// Returns which layer supplies the default for the option `name`, if any.
+    pub fn source(&self, name: &str) -> Option<Source> {
+        match self.env.iter().any(|(n, _)| n == name) {
+            true => Some(Source::Env),
+            false => self.table()?.get(name).map(|_| Source::Config),
+        }
+    }
+
+    /// Lists the problems with the file's defaults according to the flags and
+    /// options described in `spec`.
+    ///
+    /// Every key must name a flag or option, flags take booleans, and options with
+    /// possible values only accept those values or their aliases.
+    pub fn validate(&self, spec: &Spec) -> Vec<String> {
+        let table = match self.root.get(TABLE) {
+            Some(Value::Table(t)) => t,
+            Some(_) => return vec![format!("'{}' must be a table", TABLE)],
+            None => return Vec::new(),
+        };
+        let mut problems = Vec::new();
+        for (key, value) in table.iter() {
+            let entry = match spec.find_flag(key) {
+                Some(e) => e,
+                None => {
+                    problems.push(format!("unknown key '{}'", key));
+                    continue;
+                }
+            };
+            match entry.get_arg().as_positional() {
+                // a flag only accepts a boolean
+                None => {
+                    if matches!(value, Value::Bool(_)) == false {
+                        problems.push(format!("key '{}' expects a boolean", key));
+                    }
+                }
+                Some(p) => {
+                    let choices = p.get_possible_values();
+                    let word = p.get_meta().canonicalize(value.to_plain_string());
+                    if choices.is_empty() == false && choices.contains(&word) == false {
+                        problems.push(format!(
+                            "key '{}' does not accept '{}'",
+                            key,
+                            value.to_plain_string()
+                        ));
+                    }
+                }
+            }
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arg::{Flag, Optional};
+
+    #[test]
+    fn layered_defaults() {
+        let config = Config::parse("[cli]\nlang = \"vhdl\"\njobs = 4\nfiles = [\"a\", \"b\"]\n")
+            .unwrap()
+            .env_vars(
+                "ORBIT_",
+                vec![
+                    ("ORBIT_JOBS".to_string(), "8".to_string()),
+                    ("ORBIT_BUILD_DIR".to_string(), "out".to_string()),
+                    ("HOME".to_string(), "/root".to_string()),
+                ],
+            );
+        assert_eq!(config.get("lang"), Some("vhdl".to_string()));
+        assert_eq!(config.get("jobs"), Some("8".to_string()));
+        assert_eq!(config.get("build-dir"), Some("out".to_string()));
+        assert_eq!(config.get("files"), Some("a,b".to_string()));
+        assert_eq!(config.get("home"), None);
+        assert_eq!(Config::new().get("lang"), None);
+    }
+
+    #[test]
+    fn validate_against_spec() {
+        let spec = Spec::new("orbit")
+            .flag(Flag::new("verbose"), "print more")
+            .command(Spec::new("new").option(
+                Optional::new("lang").value("hdl").possible_values(&["vhdl", "verilog"]),
+                "primary language",
+            ));
+        let config = Config::parse("[cli]\nverbose = true\nlang = \"vhdl\"\n").unwrap();
+        assert_eq!(config.validate(&spec), Vec::<String>::new());
+
+        let config = Config::parse("[cli]\nverbose = 1\nlang = \"c\"\ncolour = \"red\"\n").unwrap();
+        assert_eq!(
+            config.validate(&spec),
+            vec![
+                "key 'verbose' expects a boolean",
+                "key 'lang' does not accept 'c'",
+                "unknown key 'colour'",
+            ]
+        );
+        let config = Config::parse("cli = 1\n").unwrap();
+        assert_eq!(config.validate(&spec), vec!["'cli' must be a table"]);
+    }
+}
@@ -0,0 +1,305 @@
+//! Data-driven regression testing for command-line grammars.
+//!
+//! A corpus is a TOML fixture file listing invocations and their expected
+//! outcomes. Each `[[case]]` table requires `args` (including the program name)
+//! and `expect`, which is either `"ok"` or the name of an [ErrorKind](crate::ErrorKind). An
+//! optional `message` requires the error's text to contain the given snippet.
+//!
+//! ```toml
+//! [[case]]
+//! name = "adds two numbers"
+//! args = ["add", "9", "10"]
+//! expect = "ok"
+//!
+//! [[case]]
+//! name = "requires the right-hand side"
+//! args = ["add", "9"]
+//! expect = "MissingPositional"
+//! message = "<rhs>"
+//! ```
+
+use crate::cli::Cli;
+use crate::command::FromCli;
+use crate::toml;
+use std::fmt::Display;
+use std::path::Path;
+
+pub use crate::toml::ParseError;
+
+/// The outcome a case is expected to produce.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expect {
+    Ok,
+    Error(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Case {
+    name: String,
+    args: Vec<String>,
+    expect: Expect,
+    message: Option<String>,
+}
+
+impl Case {
+    pub fn get_name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    pub fn get_args(&self) -> &Vec<String> {
+        &self.args
+    }
+
+    pub fn get_expect(&self) -> &Expect {
+        &self.expect
+    }
+}
+
+/// A case that did not produce its expected outcome.
+#[derive(Debug, PartialEq)]
+pub struct Failure {
+    case: String,
+    reason: String,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "case '{}' failed: {}", self.case, self.reason)
+    }
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct Corpus {
+    cases: Vec<Case>,
+}
+
+impl Corpus {
+    /// Creates an empty corpus.
+    pub fn new() -> Self {
+        Self { cases: Vec::new() }
+    }
+
+    /// Reads the cases defined in the fixture `text`.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut corpus = Self::new();
+        corpus.extend(text)?;
+        Ok(corpus)
+    }
+
+    /// Reads the cases from a fixture file, or from every `.toml` file when `path`
+    /// is a directory.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut corpus = Self::new();
+        let path = path.as_ref();
+        let mut files = match path.is_dir() {
+            true => std::fs::read_dir(path)?
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().map(|e| e == "toml").unwrap_or(false))
+                .collect(),
+            false => vec![path.to_path_buf()],
+        };
+        // keep the order of cases deterministic across platforms
+        files.sort();
+        for file in files {
+            corpus.extend(&std::fs::read_to_string(&file)?)?;
+        }
+        Ok(corpus)
+    }
+
+    fn extend(&mut self, text: &str) -> Result<(), ParseError> {
+        let doc = toml::parse(text)?;
+        let tables = match doc.get("case") {
+            Some(v) => v.as_tables().cloned().unwrap_or_default(),
+            None => Vec::new(),
+        };
+        for (i, table) in tables.iter().enumerate() {
+            let name = match table.get("name").and_then(|v| v.as_str()) {
+                Some(n) => n.to_string(),
+                None => format!("#{}", self.cases.len() + 1),
+            };
+            let missing = |key: &str| {
+                ParseError::new(
+                    case_line(text, i),
+                    format!("case '{}' is missing '{}'", name, key),
+                )
+            };
+            let args = table
+                .get("args")
+                .and_then(|v| v.as_array())
+                .map(|a| a.iter().map(|v| v.to_plain_string()).collect())
+                .ok_or_else(|| missing("args"))?;
+            let expect = match table.get("expect").and_then(|v| v.as_str()) {
+                Some("ok") => Expect::Ok,
+                Some(kind) => Expect::Error(kind.to_string()),
+                None => return Err(missing("expect")),
+            };
+            self.cases.push(Case {
+                name,
+                args,
+                expect,
+                message: table
+                    .get("message")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn get_cases(&self) -> &Vec<Case> {
+        &self.cases
+    }
+
+    /// Parses every case into `T` with a default [Cli].
+    pub fn run<T: FromCli>(&self) -> Result<(), Vec<Failure>> {
+        self.run_with::<T, _>(Cli::new)
+    }
+
+    /// Parses every case into `T` using the [Cli] created by `setup`.
+    ///
+    /// All cases are run before reporting, so a single call lists every failure.
+    pub fn run_with<T: FromCli, F: Fn() -> Cli>(&self, setup: F) -> Result<(), Vec<Failure>> {
+        let failures: Vec<Failure> = self
+            .cases
+            .iter()
+            .filter_map(|case| {
+                let mut cli = setup().tokenize(case.args.iter().map(|a| a.to_string()));
+                let result = T::from_cli(&mut cli);
+                let reason = match (&case.expect, result) {
+                    (Expect::Ok, Ok(_)) => None,
+                    (Expect::Ok, Err(e)) => Some(format!(
+                        "expected success but got {:?} error: {}",
+                        e.kind(),
+                        e
+                    )),
+                    (Expect::Error(kind), Ok(_)) => {
+                        Some(format!("expected {} error but parsing succeeded", kind))
+                    }
+                    (Expect::Error(kind), Err(e)) => {
+                        if &format!("{:?}", e.kind()) != kind {
+                            Some(format!("expected {} error but got {:?}: {}", kind, e.kind(), e))
+                        } else {
+                            match &case.message {
+                                Some(m) if !e.to_string().contains(m.as_str()) => Some(format!(
+                                    "expected message to contain '{}' but got: {}",
+                                    m, e
+                                )),
+                                _ => None,
+                            }
+                        }
+                    }
+                };
+                reason.map(|r| Failure {
+                    case: case.name.clone(),
+                    reason: r,
+                })
+            })
+            .collect();
+        match failures.is_empty() {
+            true => Ok(()),
+            false => Err(failures),
+        }
+    }
+}
+
+/// Returns the line number of the `n`th `[[case]]` header in `text`.
+fn case_line(text: &str, n: usize) -> usize {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| line.trim() == "[[case]]")
+        .nth(n)
+        .map(|(i, _)| i + 1)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arg::{Flag, Positional};
+    use crate::error::Error;
+
+    #[derive(Debug)]
+    struct Add {
+        _lhs: u8,
+        _rhs: u8,
+        _verbose: bool,
+    }
+
+    impl FromCli for Add {
+        fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+            let add = Add {
+                _verbose: cli.check_flag(Flag::new("verbose"))?,
+                _lhs: cli.require_positional(Positional::new("lhs"))?,
+                _rhs: cli.require_positional(Positional::new("rhs"))?,
+            };
+            cli.is_empty()?;
+            Ok(add)
+        }
+    }
+
+    const FIXTURE: &str = r#"
+[[case]]
+name = "adds two numbers"
+args = ["add", "9", "10", "--verbose"]
+expect = "ok"
+
+[[case]]
+name = "requires the right-hand side"
+args = ["add", "9"]
+expect = "MissingPositional"
+message = "<rhs>"
+
+[[case]]
+args = ["add", "9", "999"]
+expect = "BadType"
+"#;
+
+    #[test]
+    fn run_corpus() {
+        let corpus = Corpus::parse(FIXTURE).unwrap();
+        assert_eq!(corpus.get_cases().len(), 3);
+        assert_eq!(corpus.get_cases()[2].get_name(), "#3");
+        assert_eq!(corpus.run::<Add>(), Ok(()));
+    }
+
+    #[test]
+    fn report_failures() {
+        let corpus = Corpus::parse(
+            r#"
+[[case]]
+name = "wrong kind"
+args = ["add", "9"]
+expect = "BadType"
+
+[[case]]
+name = "unexpected success"
+args = ["add", "1", "2"]
+expect = "MissingPositional"
+"#,
+        )
+        .unwrap();
+        let failures = corpus.run::<Add>().unwrap_err();
+        assert_eq!(failures.len(), 2);
+        assert!(failures[0].to_string().starts_with("case 'wrong kind' failed"));
+    }
+
+    #[test]
+    fn require_args_and_expect() {
+        let err = Corpus::parse(
+            r#"
+[[case]]
+args = ["add", "1", "2"]
+expect = "ok"
+
+[[case]]
+name = "no outcome"
+args = ["add", "1"]
+"#,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "line 6: case 'no outcome' is missing 'expect'");
+
+        let err = Corpus::parse("[[case]]\nexpect = \"ok\"\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: case '#1' is missing 'args'");
+    }
+}
@@ -0,0 +1,232 @@
+//! Reporting panics raised while a command executes.
+//!
+//! An application opts in with [Cli::catch_panics](crate::Cli::catch_panics).
+//! A panic is then reported like any other error, asking the user to file a
+//! bug with the invocation that caused it, instead of printing a raw message
+//! and backtrace.
+
+use crate::error::{Error, ErrorContext, ErrorKind};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, MutexGuard, Once, PoisonError};
+use std::thread::{self, ThreadId};
+#[cfg(feature = "async")]
+use std::{future::Future, pin::Pin, task};
+
+/// The threads running a guarded command, each with the location of the last
+/// panic raised on it.
+static GUARDED: Mutex<Vec<(ThreadId, Option<String>)>> = Mutex::new(Vec::new());
+
+/// Installs the panic hook shared by every guard.
+static HOOK: Once = Once::new();
+
+fn guarded() -> MutexGuard<'static, Vec<(ThreadId, Option<String>)>> {
+    GUARDED.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Silences the panics raised on guarded threads, recording where they were
+/// raised, and passes every other panic to the hook set before it.
+fn install_hook() -> () {
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let id = thread::current().id();
+            let mut threads = guarded();
+            match threads.iter_mut().rev().find(|(t, _)| *t == id) {
+                Some((_, location)) => *location = info.location().map(|l| l.to_string()),
+                None => {
+                    drop(threads);
+                    previous(info)
+                }
+            }
+        }));
+    });
+}
+
+/// The details attached to a panic's bug report.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Crash {
+    url: String,
+    invocation: String,
+    use_color: bool,
+}
+
+impl Crash {
+    pub fn new(url: String, invocation: String, use_color: bool) -> Self {
+        Self {
+            url,
+            invocation,
+            use_color,
+        }
+    }
+
+    /// Runs `f`, returning a `CommandPanic` error if it panics.
+    ///
+    /// The panic message is silenced for panics raised on the calling thread
+    /// while `f` runs so the error is the only report the user sees. Panics on
+    /// other threads still reach the hook that was set before the first guard,
+    /// and a hook that `f` sets is left in place.
+    pub fn guard<T, F: FnOnce() -> T>(&self, f: F) -> Result<T, Error> {
+        install_hook();
+        let id = thread::current().id();
+        guarded().push((id, None));
+        let result = panic::catch_unwind(AssertUnwindSafe(f));
+        let location = {
+            let mut threads = guarded();
+            match threads.iter().rposition(|(t, _)| *t == id) {
+                Some(i) => threads.remove(i).1,
+                None => None,
+            }
+        };
+        result.map_err(|p| {
+            let reason = match location {
+                Some(loc) => format!("{} at {}", panic_message(&p), loc),
+                None => panic_message(&p),
+            };
+            Error::new(
+                None,
+                ErrorKind::CommandPanic,
+                ErrorContext::CommandPanic(reason, self.url.clone(), self.invocation.clone()),
+                self.use_color,
+            )
+        })
+    }
+}
+
+/// A future whose every poll is [guarded](Crash::guard), so a panic raised
+/// while a command is awaited is reported on whichever thread polls it.
+#[cfg(feature = "async")]
+pub(crate) struct Guarded<F: Future> {
+    crash: Crash,
+    future: Pin<Box<F>>,
+}
+
+#[cfg(feature = "async")]
+impl<F: Future> Guarded<F> {
+    pub fn new(crash: Crash, future: F) -> Self {
+        Self {
+            crash,
+            future: Box::pin(future),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<F: Future> Future for Guarded<F> {
+    type Output = Result<F::Output, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = &mut *self;
+        match this.crash.guard(|| this.future.as_mut().poll(cx)) {
+            Ok(task::Poll::Ready(status)) => task::Poll::Ready(Ok(status)),
+            Ok(task::Poll::Pending) => task::Poll::Pending,
+            Err(err) => task::Poll::Ready(Err(err)),
+        }
+    }
+}
+
+/// Returns the message a panic was raised with.
+pub(crate) fn panic_message(payload: &Box<dyn Any + Send>) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(s) => s.to_string(),
+        None => match payload.downcast_ref::<String>() {
+            Some(s) => s.clone(),
+            None => String::from("unknown cause"),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arg::Optional;
+    use crate::cli::Cli;
+    use crate::command::{Command, FromCli, Runner};
+
+    /// `Boom` panics whenever it executes.
+    #[derive(Debug)]
+    struct Boom {
+        token: Option<String>,
+    }
+
+    impl FromCli for Boom {
+        fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+            let app = Boom {
+                token: cli.check_option(Optional::new("token").secret())?,
+            };
+            cli.is_empty()?;
+            Ok(app)
+        }
+    }
+
+    impl Command<()> for Boom {
+        type Status = ();
+
+        fn exec(&self, _: &()) -> Self::Status {
+            panic!("token {:?} was rejected", self.token.is_some())
+        }
+    }
+
+    impl Runner<()> for Boom {}
+
+    #[test]
+    fn guard_panics() {
+        let crash = Crash::new(
+            String::from("https://example.com/issues"),
+            String::from("tool --token *** run"),
+            false,
+        );
+        assert_eq!(crash.guard(|| 7).unwrap(), 7);
+
+        let err = crash.guard(|| -> u8 { panic!("out of cheese") }).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::CommandPanic);
+        assert_eq!(err.exit_code(), 70);
+        let text = err.to_string();
+        assert!(text.starts_with("the command panicked: out of cheese at "));
+        assert!(text.contains("crash.rs:"));
+        assert!(text.contains("https://example.com/issues"));
+        assert!(text.contains("    tool --token *** run"));
+
+        // panics on other threads are left to the previous hook
+        let line = line!() + 5;
+        let err = crash
+            .guard(|| -> u8 {
+                let other = thread::spawn(|| panic!("elsewhere"));
+                assert!(other.join().is_err());
+                panic!("here")
+            })
+            .unwrap_err();
+        let text = err.to_string();
+        assert!(text.starts_with("the command panicked: here at "));
+        assert!(text.contains(&format!("crash.rs:{}:", line)));
+        assert!(guarded().iter().all(|(t, _)| *t != thread::current().id()));
+    }
+
+    #[test]
+    fn catch_command_panics() {
+        let cli = Cli::new()
+            .catch_panics("https://example.com/issues")
+            .tokenize(["tool", "--token", "abc"].iter().map(|s| s.to_string()));
+        let err = cli.launch::<(), Boom>(&()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::CommandPanic);
+        assert_eq!(err.exit_code(), 70);
+        let text = err.to_string();
+        assert!(text.contains("token true was rejected"));
+        assert!(text.ends_with("\n    tool --token ***"));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn guard_futures() {
+        let mut cx = task::Context::from_waker(task::Waker::noop());
+        let crash = Crash::new(String::from("https://example.com/issues"), String::new(), false);
+        let mut ok = Guarded::new(crash.clone(), async { 7 });
+        assert_eq!(Pin::new(&mut ok).poll(&mut cx).map(|r| r.unwrap()), task::Poll::Ready(7));
+
+        let mut boom = Guarded::new(crash, async { panic!("out of cheese") });
+        match Pin::new(&mut boom).poll(&mut cx) {
+            task::Poll::Ready(Err(err)) => assert_eq!(err.kind(), ErrorKind::CommandPanic),
+            _ => panic!("expected the panic to be reported"),
+        }
+    }
+}
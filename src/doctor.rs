@@ -0,0 +1,289 @@
+//! A self-test command for checking an installation.
+//!
+//! A [Doctor] inspects the environment the application runs in and reports each
+//! finding as a row of a table, so users can diagnose their own setup before
+//! filing an issue:
+//!
+//! ```
+//! use clif::arg::Flag;
+//! use clif::cmd::{Command, Context};
+//! use clif::doctor::Doctor;
+//! use clif::spec::Spec;
+//!
+//! let spec = Spec::new("orbit").flag(Flag::new("verbose"), "print more");
+//! let doctor = Doctor::new(spec).check("cache", || Ok(String::from("writable")));
+//! let ctx = Context::captured();
+//! assert_eq!(doctor.exec(&ctx), true);
+//! assert!(ctx.take_stdout().contains("cache     pass"));
+//! ```
+
+use crate::command::Command;
+use crate::config::Config;
+use crate::output::{Context, Table};
+use crate::spec::Spec;
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Status {
+    Pass,
+    /// Something is missing but the application still works.
+    Warn,
+    Fail,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pass => write!(f, "pass"),
+            Self::Warn => write!(f, "warn"),
+            Self::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+/// The outcome of a single check.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Finding {
+    check: String,
+    status: Status,
+    detail: String,
+}
+
+impl Finding {
+    fn new<T: AsRef<str>, U: AsRef<str>>(check: T, status: Status, detail: U) -> Self {
+        Self {
+            check: check.as_ref().to_string(),
+            status,
+            detail: detail.as_ref().to_string(),
+        }
+    }
+
+    pub fn get_check(&self) -> &str {
+        self.check.as_ref()
+    }
+
+    pub fn get_status(&self) -> Status {
+        self.status
+    }
+
+    pub fn get_detail(&self) -> &str {
+        self.detail.as_ref()
+    }
+}
+
+type Probe = Box<dyn Fn() -> Result<String, String> + Send + Sync>;
+
+/// A command that checks the terminal, configuration file, plugin directory,
+/// and installed completions of an application.
+///
+/// Configuration files are validated against the [Spec] as described by
+/// [Config::validate].
+pub struct Doctor {
+    spec: Spec,
+    config: Option<PathBuf>,
+    plugins: Option<PathBuf>,
+    completions: Vec<PathBuf>,
+    probes: Vec<(String, Probe)>,
+}
+
+impl std::fmt::Debug for Doctor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Doctor")
+            .field("spec", &self.spec.get_name())
+            .field("config", &self.config)
+            .field("plugins", &self.plugins)
+            .field("completions", &self.completions)
+            .field(
+                "probes",
+                &self.probes.iter().map(|(n, _)| n).collect::<Vec<&String>>(),
+            )
+            .finish()
+    }
+}
+
+impl Doctor {
+    pub fn new(spec: Spec) -> Self {
+        Self {
+            spec,
+            config: None,
+            plugins: None,
+            completions: Vec::new(),
+            probes: Vec::new(),
+        }
+    }
+
+    /// Validates the configuration file at `path`.
+    pub fn config<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.config = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Checks that plugins can be discovered in the directory `dir`.
+    pub fn plugins<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.plugins = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Checks that a completion script is installed at `path`.
+    pub fn completion<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.completions.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Adds a check specific to the application, which returns a detail on
+    /// success and a reason on failure.
+    pub fn check<T, F>(mut self, name: T, probe: F) -> Self
+    where
+        T: AsRef<str>,
+        F: Fn() -> Result<String, String> + Send + Sync + 'static,
+    {
+        self.probes.push((name.as_ref().to_string(), Box::new(probe)));
+        self
+    }
+
+    /// Runs every check in order.
+    pub fn diagnose(&self) -> Vec<Finding> {
+        let mut findings = vec![self.check_terminal()];
+        if let Some(path) = &self.config {
+            findings.push(self.check_config(path));
+        }
+        if let Some(dir) = &self.plugins {
+            findings.push(check_plugins(dir));
+        }
+        for path in &self.completions {
+            findings.push(match path.is_file() {
+                true => Finding::new("completion", Status::Pass, path.display().to_string()),
+                false => Finding::new(
+                    "completion",
+                    Status::Warn,
+                    format!("not installed at {}", path.display()),
+                ),
+            });
+        }
+        for (name, probe) in &self.probes {
+            findings.push(match probe() {
+                Ok(detail) => Finding::new(name, Status::Pass, detail),
+                Err(reason) => Finding::new(name, Status::Fail, reason),
+            });
+        }
+        findings
+    }
+
+    fn check_terminal(&self) -> Finding {
+        let kind = |is_tty: bool| match is_tty {
+            true => "terminal",
+            false => "not a terminal",
+        };
+        let colors = match crate::style::should_color(&std::io::stdout()) {
+            true => "enabled",
+            false => "disabled",
+        };
+        Finding::new(
+            "terminal",
+            Status::Pass,
+            format!(
+                "stdout is {}, stderr is {}, colors {}",
+                kind(std::io::stdout().is_terminal()),
+                kind(std::io::stderr().is_terminal()),
+                colors
+            ),
+        )
+    }
+
+    fn check_config(&self, path: &Path) -> Finding {
+        if path.is_file() == false {
+            return Finding::new(
+                "config",
+                Status::Warn,
+                format!("no file at {}", path.display()),
+            );
+        }
+        let problems = match Config::load(path) {
+            Ok(config) => config.validate(&self.spec),
+            Err(e) => vec![e.to_string()],
+        };
+        match problems.is_empty() {
+            true => Finding::new("config", Status::Pass, path.display().to_string()),
+            false => Finding::new("config", Status::Fail, problems.join("; ")),
+        }
+    }
+}
+
+fn check_plugins(dir: &Path) -> Finding {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => Finding::new(
+            "plugins",
+            Status::Pass,
+            format!("{} found in {}", entries.count(), dir.display()),
+        ),
+        Err(_) => Finding::new(
+            "plugins",
+            Status::Warn,
+            format!("cannot read {}", dir.display()),
+        ),
+    }
+}
+
+impl Command<Context> for Doctor {
+    type Status = bool;
+
+    /// Writes a table of the findings to stdout and returns `false` if any check
+    /// failed.
+    fn exec(&self, context: &Context) -> Self::Status {
+        let findings = self.diagnose();
+        let table = findings.iter().fold(
+            Table::new(&["check", "status", "detail"]),
+            |table, f| table.row(&[f.get_check(), &f.get_status().to_string(), f.get_detail()]),
+        );
+        let _ = context.stdout().print(table);
+        findings.iter().all(|f| f.get_status() != Status::Fail)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arg::{Flag, Optional};
+
+    #[test]
+    fn diagnose() {
+        let spec = Spec::new("orbit")
+            .flag(Flag::new("verbose"), "print more")
+            .command(Spec::new("new").option(
+                Optional::new("lang").value("hdl").possible_values(&["vhdl", "verilog"]),
+                "primary language",
+            ));
+        let dir = std::env::temp_dir().join(format!("clif-doctor-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = dir.join("config.toml");
+        std::fs::write(&config, "[cli]\nverbose = \"yes\"\n").unwrap();
+        let doctor = Doctor::new(spec)
+            .config(&config)
+            .plugins(dir.join("plugins"))
+            .completion(dir.join("orbit.bash"))
+            .check("cache", || Err(String::from("not writable")));
+        let findings = doctor.diagnose();
+        let statuses: Vec<(&str, Status)> = findings
+            .iter()
+            .map(|f| (f.get_check(), f.get_status()))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("terminal", Status::Pass),
+                ("config", Status::Fail),
+                ("plugins", Status::Warn),
+                ("completion", Status::Warn),
+                ("cache", Status::Fail),
+            ]
+        );
+        let ctx = Context::captured();
+        assert_eq!(doctor.exec(&ctx), false);
+        assert!(ctx
+            .take_stdout()
+            .contains("config      fail    key 'verbose' expects a boolean"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
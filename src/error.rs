@@ -1,21 +1,25 @@
-#[cfg(feature = "color")]
-use crayon::*;
-
 use crate::arg::Arg;
 use crate::help::Help;
+use crate::i18n::{self, Catalog, English, Message};
+use crate::output::{self, Output};
+use crate::style::{self, Styled, Theme};
 use std::fmt::Display;
 
 const NEW_PARAGRAPH: &str = "\n\n";
 
-mod exit_code {
+pub(crate) mod exit_code {
     pub const BAD: u8 = 101;
+    // internal software error (EX_SOFTWARE), set apart from Rust's own panic status
+    pub const PANIC: u8 = 70;
     pub const OKAY: u8 = 0;
+    pub const USAGE: u8 = 2;
 }
 
 type Value = String;
 type Subcommand = String;
 type Suggestion = String;
 type MaxCount = usize;
+type MinCount = usize;
 type CurCount = usize;
 type SomeError = Box<dyn std::error::Error>;
 type Argument = String;
@@ -27,6 +31,38 @@ pub struct Error {
     context: ErrorContext,
     help: Option<Help>,
     kind: ErrorKind,
+    exit_code: Option<u8>,
+    span: Option<Span>,
+    line: Option<Vec<String>>,
+}
+
+/// The location of a token within the command-line.
+///
+/// A span names the argument holding the token, counting the program name as
+/// argument 0, and the byte range of the token within that argument. A switch
+/// within a cluster such as `-abc` covers only its own character, and a value
+/// attached with `=` covers only the text after the sign.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Span {
+    arg: usize,
+    start: usize,
+    end: usize,
+}
+
+impl Span {
+    pub(crate) fn new(arg: usize, start: usize, end: usize) -> Self {
+        Self { arg, start, end }
+    }
+
+    /// Returns the index of the argument, where the program name is 0.
+    pub fn get_arg(&self) -> usize {
+        self.arg
+    }
+
+    /// Returns the byte range of the token within its argument.
+    pub fn get_range(&self) -> std::ops::Range<usize> {
+        self.start..self.end
+    }
 }
 
 impl Error {
@@ -43,6 +79,9 @@ impl Error {
             help: help,
             kind: kind,
             context: context,
+            exit_code: None,
+            span: None,
+            line: None,
         }
     }
 
@@ -51,11 +90,85 @@ impl Error {
         self.kind
     }
 
-    /// Returns `OKAY_CODE` for help error and `BAD_CODE` otherwise.
-    pub fn code(&self) -> u8 {
-        match &self.kind {
-            ErrorKind::Help => exit_code::OKAY,
-            _ => exit_code::BAD,
+    /// Checks if the error only informs the user, as for help, explain, check,
+    /// and value listings, rather than reporting a failure.
+    pub(crate) fn is_informational(&self) -> bool {
+        matches!(
+            self.kind,
+            ErrorKind::Help | ErrorKind::Explain | ErrorKind::Check | ErrorKind::ListChoices
+        )
+    }
+
+    /// Returns the code to exit the process with after reporting this error.
+    ///
+    /// Informational errors (help, explain, check, value listings) exit with 0 and usage
+    /// errors exit with 2, unless a code was set with [with_exit_code](Error::with_exit_code).
+    pub fn exit_code(&self) -> u8 {
+        match (self.exit_code, &self.kind) {
+            (Some(code), _) => code,
+            (None, _) if self.is_informational() == true => exit_code::OKAY,
+            (None, ErrorKind::CommandPanic) => exit_code::PANIC,
+            (None, _) => exit_code::USAGE,
+        }
+    }
+
+    /// Sets the code to exit the process with, such as for an error raised by a
+    /// custom rule.
+    pub fn with_exit_code(mut self, code: u8) -> Self {
+        self.exit_code = Some(code);
+        self
+    }
+
+    /// Returns the location of the token in the command-line that caused the
+    /// error, if it was raised while reading one.
+    pub fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+
+    /// Sets the location of the token that caused the error.
+    pub(crate) fn with_span(mut self, span: Option<Span>) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Returns the command-line the error was raised for, beginning with the
+    /// program name, if it is known.
+    pub(crate) fn get_line(&self) -> Option<&[String]> {
+        self.line.as_deref()
+    }
+
+    /// Sets the command-line, beginning with the program name, to echo beneath
+    /// the message when the error is [printed](Error::print).
+    pub(crate) fn with_line(mut self, line: Vec<String>) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// Writes the error for the user to read.
+    ///
+    /// Informational errors are written to stdout and all others are written to
+    /// stderr with the default [Theme], colored as decided by
+    /// [should_color](style::should_color) for that stream. The command-line is
+    /// echoed with the offending token underlined when the error was raised by
+    /// [Cli::go](crate::Cli::go).
+    pub fn print(&self) -> () {
+        self.print_in(&English)
+    }
+
+    /// Writes the error for the user to read as with [print](Error::print), in
+    /// the language of `catalog`.
+    pub fn print_in(&self, catalog: &dyn Catalog) -> () {
+        match self.is_informational() {
+            true => {
+                let paged = self.kind == ErrorKind::Help
+                    && self.help.as_ref().map(|h| h.is_paged()).unwrap_or(false);
+                if paged == true && output::page(&format!("{}\n", self)) == true {
+                    return;
+                }
+                // exit quietly if the output is piped into a closed reader
+                let _ = Output::stdout().println(self);
+            }
+            false => eprintln!("{}", self.styled(&Theme::default()).catalog(catalog)),
         }
     }
 
@@ -64,18 +177,46 @@ impl Error {
         &self.context
     }
 
-    /// Constructs a simple help tip to insert into an error message if help exists.
-    fn help_tip(&self) -> Option<String> {
-        let flag_str = self.help.as_ref()?.get_flag().to_string();
-        #[cfg(feature = "color")]
-        let flag_str = match self.use_color {
-            true => flag_str.green().to_string(),
-            false => flag_str,
+    /// Writes a simple help tip into an error message if help exists.
+    fn write_help_tip(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        theme: &Theme,
+        catalog: &dyn Catalog,
+        enabled: bool,
+    ) -> Result<(), std::fmt::Error> {
+        let help = match &self.help {
+            Some(h) => h,
+            None => return Ok(()),
         };
-        Some(format!(
-            "{}For more information, try '{}'.",
-            NEW_PARAGRAPH, flag_str
-        ))
+        let flag = theme.get_suggestion().paint(help.get_flag(), enabled);
+        write!(f, "{}", NEW_PARAGRAPH)?;
+        i18n::write_painted(
+            f,
+            catalog,
+            Message::HelpTip,
+            &[&flag],
+            Some((theme.get_hint(), enabled)),
+        )
+    }
+
+    /// Renders the error with the paints of `theme`.
+    ///
+    /// Usage errors are prefixed with `error:`. Colors are used as decided by
+    /// [should_color](style::should_color) for the stream the error would be
+    /// written to, stdout for informational errors and stderr otherwise.
+    pub fn styled<'a>(&'a self, theme: &'a Theme) -> Styled<'a> {
+        Styled::new(self, theme, self.should_color())
+    }
+
+    /// Decides on colors for the stream the error would be written to, so help
+    /// piped from stdout is plain while errors on a terminal's stderr are
+    /// colored, and the reverse.
+    fn should_color(&self) -> bool {
+        match self.is_informational() {
+            true => style::should_color(&std::io::stdout()),
+            false => style::should_color(&std::io::stderr()),
+        }
     }
 
     /// Transforms any error into a custom rule error to be used during [crate::Cli] parsing.
@@ -91,22 +232,88 @@ impl Error {
 #[allow(dead_code)]
 pub enum ErrorContext {
     ExceededThreshold(Arg, CurCount, MaxCount),
+    TooFewValues(Arg, CurCount, MinCount),
     FailedArg(Arg),
     UnexpectedValue(Arg, Value),
+    UnexpectedFlagValue(Arg, Argument, Option<Suggestion>),
     FailedCast(Arg, Value, SomeError),
+    InvalidValue(Arg, Value, String),
+    InvalidChoice(Arg, Value, Vec<Value>, Option<Suggestion>),
+    TruncatedChoice(Arg, Value, Vec<Value>, Argument),
+    ListChoices(Vec<Value>),
     OutofContextArgSuggest(Argument, Subcommand),
     UnexpectedArg(Argument),
+    ForwardedArg(Argument, String),
     SuggestWord(String, Suggestion),
     UnknownSubcommand(Arg, Subcommand),
+    Ambiguous(Argument, Vec<Value>),
+    DuplicateSwitch(char, Arg, Arg),
+    DuplicateArg(Arg),
+    RequiredTogether(Arg, Arg),
+    MissingRequirement(Arg, Arg),
+    UnstableCommand(Subcommand, Argument),
+    UnstableArg(Arg, Argument),
+    FrozenArg(Arg),
+    TrailingConflict(Arg, Argument, String),
+    MissingWorkspace(String, String),
+    PluginPanic(Subcommand, String),
+    CommandPanic(String, String, String),
+    RemovedCommand(Subcommand, String, Option<String>),
+    LimitsExceeded(String, MaxCount),
+    InvalidUnicode(Value),
     CustomRule(SomeError),
+    Explain(String),
+    Check(String),
+    Multiple(Vec<Error>),
     Help,
 }
 
+impl ErrorContext {
+    /// References the argument the error is about, if any.
+    fn get_arg(&self) -> Option<&Arg> {
+        match self {
+            Self::ExceededThreshold(arg, _, _)
+            | Self::TooFewValues(arg, _, _)
+            | Self::FailedArg(arg)
+            | Self::UnexpectedValue(arg, _)
+            | Self::UnexpectedFlagValue(arg, _, _)
+            | Self::FailedCast(arg, _, _)
+            | Self::InvalidValue(arg, _, _)
+            | Self::InvalidChoice(arg, _, _, _)
+            | Self::TruncatedChoice(arg, _, _, _)
+            | Self::UnknownSubcommand(arg, _)
+            | Self::RequiredTogether(arg, _)
+            | Self::MissingRequirement(arg, _)
+            | Self::UnstableArg(arg, _)
+            | Self::FrozenArg(arg)
+            | Self::TrailingConflict(arg, _, _)
+            | Self::DuplicateArg(arg) => Some(arg),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum ErrorKind {
     BadType,
+    InvalidValue,
+    InvalidChoice,
     MissingPositional,
     DuplicateOptions,
+    DuplicateSwitch,
+    DuplicateArg,
+    RequiredTogether,
+    MissingRequirement,
+    UnstableCommand,
+    UnstableArg,
+    FrozenArg,
+    TrailingConflict,
+    MissingWorkspace,
+    PluginPanic,
+    CommandPanic,
+    RemovedCommand,
+    LimitsExceeded,
+    InvalidUnicode,
     ExpectingValue,
     UnexpectedValue,
     OutOfContextArgSuggest,
@@ -114,9 +321,15 @@ pub enum ErrorKind {
     SuggestArg,
     SuggestSubcommand,
     UnknownSubcommand,
+    AmbiguousFlag,
+    AmbiguousSubcommand,
     CustomRule,
     Help,
+    Explain,
+    Check,
+    ListChoices,
     ExceedingMaxCount,
+    Multiple,
 }
 
 impl std::error::Error for Error {}
@@ -124,136 +337,377 @@ impl std::error::Error for Error {}
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         #[cfg(feature = "color")]
-        let color = |a: ColoredString| -> String {
-            match self.use_color {
-                true => a.to_string(),
-                false => a.get_data().to_string(),
-            }
-        };
+        let enabled = self.use_color == true && self.should_color() == true;
+        #[cfg(not(feature = "color"))]
+        let enabled = false;
+        self.write_message(f, &Theme::default(), &English, enabled)
+    }
+}
 
+impl Error {
+    /// Writes the message describing the error in the language of `catalog`,
+    /// painting its parts with `theme` when `enabled`.
+    ///
+    /// Usage errors are followed by the help's example that applies to them, if
+    /// any.
+    pub(crate) fn write_message(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        theme: &Theme,
+        catalog: &dyn Catalog,
+        enabled: bool,
+    ) -> Result<(), std::fmt::Error> {
+        self.write_body(f, theme, catalog, enabled)?;
+        match self.kind {
+            // examples are already shown in place of the usage
+            ErrorKind::MissingPositional => Ok(()),
+            _ if self.is_informational() == true => Ok(()),
+            _ => match self.example(false) {
+                Some(ex) => {
+                    let ex = theme.get_suggestion().apply(ex, enabled);
+                    write!(f, "{}", NEW_PARAGRAPH)?;
+                    i18n::write(f, catalog, Message::Example, &[&ex])
+                }
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Selects the help's example for this error, falling back to a generic one
+    /// only if `generic` is true.
+    fn example(&self, generic: bool) -> Option<&str> {
+        let arg = self.context.get_arg().map(|a| a.get_name());
+        self.help
+            .as_ref()?
+            .select_example(self.kind, arg, generic)
+            .map(|ex| ex.get_text())
+    }
+
+    fn write_body(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        theme: &Theme,
+        catalog: &dyn Catalog,
+        enabled: bool,
+    ) -> Result<(), std::fmt::Error> {
         match self.context() {
             ErrorContext::ExceededThreshold(arg, cur, max) => {
-                let arg_str = arg.to_string();
-                #[cfg(feature = "color")]
-                let arg_str = color(arg_str.blue());
-                write!(
-                    f,
-                    "option '{}' can be used up to {} times but was supplied {} times",
-                    arg_str, max, cur
-                )
+                let arg_str = theme.get_arg().paint(arg, enabled);
+                let message = match arg {
+                    Arg::Positional(_) => Message::ExceededValues,
+                    _ => Message::ExceededOccurrences,
+                };
+                i18n::write(f, catalog, message, &[&arg_str, max, cur])
+            }
+            ErrorContext::TooFewValues(arg, cur, min) => {
+                let arg_str = theme.get_arg().paint(arg, enabled);
+                i18n::write(f, catalog, Message::TooFewValues, &[&arg_str, min, cur])
             }
             ErrorContext::Help => {
-                write!(
-                    f,
-                    "{}",
-                    self.help.as_ref().unwrap_or(&Help::new()).get_quick_text()
-                )
+                let help = match &self.help {
+                    Some(h) => h,
+                    None => return Ok(()),
+                };
+                if let Some(badge) = help.get_badge() {
+                    let badge = theme.get_value().paint(badge, enabled);
+                    writeln!(f, "{}", badge)?;
+                }
+                help.write_text(f)
             }
             ErrorContext::FailedCast(arg, val, err) => {
-                let arg_str = arg.to_string();
-                #[cfg(feature = "color")]
-                let arg_str = color(arg_str.blue());
-                let val_str = val.to_string();
-                #[cfg(feature = "color")]
-                let val_str = color(val_str.yellow());
-                write!(
-                    f,
-                    "argument '{}' failed to process '{}' due to: {}",
-                    arg_str, val_str, err
-                )
+                let arg_str = theme.get_arg().paint(arg, enabled);
+                let val_str = theme.get_value().paint(val, enabled);
+                i18n::write(f, catalog, Message::FailedCast, &[&arg_str, &val_str, err])
+            }
+            ErrorContext::InvalidValue(arg, val, reason) => {
+                let arg_str = theme.get_arg().paint(arg, enabled);
+                let val_str = theme.get_value().paint(val, enabled);
+                i18n::write(f, catalog, Message::InvalidValue, &[&arg_str, &val_str, reason])
+            }
+            ErrorContext::InvalidChoice(arg, val, choices, suggestion) => {
+                let arg_str = theme.get_arg().paint(arg, enabled);
+                let val_str = theme.get_value().paint(val, enabled);
+                i18n::write(f, catalog, Message::InvalidChoice, &[&arg_str, &val_str])?;
+                write!(f, "{}", NEW_PARAGRAPH)?;
+                i18n::write(f, catalog, Message::PossibleValues, &[&List(choices, ", ")])?;
+                if let Some(word) = suggestion {
+                    let word = theme.get_suggestion().paint(word, enabled);
+                    write!(f, "{}", NEW_PARAGRAPH)?;
+                    i18n::write(f, catalog, Message::DidYouMean, &[&word])?;
+                }
+                Ok(())
+            }
+            ErrorContext::TruncatedChoice(arg, val, nearest, list) => {
+                let arg_str = theme.get_arg().paint(arg, enabled);
+                let val_str = theme.get_value().paint(val, enabled);
+                let list_str = theme.get_suggestion().paint(list, enabled);
+                i18n::write(f, catalog, Message::InvalidChoice, &[&arg_str, &val_str])?;
+                if nearest.is_empty() == false {
+                    write!(f, "{}", NEW_PARAGRAPH)?;
+                    i18n::write(f, catalog, Message::ClosestValues, &[&List(nearest, ", ")])?;
+                }
+                write!(f, "{}", NEW_PARAGRAPH)?;
+                i18n::write(f, catalog, Message::ListValues, &[&list_str])
+            }
+            ErrorContext::ListChoices(choices) => {
+                write!(f, "{}", List(choices, "\n"))
             }
             ErrorContext::FailedArg(arg) => match self.kind() {
                 ErrorKind::MissingPositional => {
-                    let usage = match self.help.as_ref().unwrap_or(&Help::new()).get_usage() {
-                        Some(m) => NEW_PARAGRAPH.to_owned() + m,
-                        None => "".to_owned(),
-                    };
-                    let arg_str = arg.to_string();
-                    #[cfg(feature = "color")]
-                    let arg_str = color(arg_str.blue());
-                    write!(f, "missing positional argument '{}'{}", arg_str, usage)
+                    let arg_str = theme.get_arg().paint(arg, enabled);
+                    i18n::write(f, catalog, Message::MissingPositional, &[&arg_str])?;
+                    // prefer an example of the missing argument over the generic usage
+                    match self.example(true) {
+                        Some(ex) => {
+                            let ex = theme.get_suggestion().paint(ex, enabled);
+                            write!(f, "{}", NEW_PARAGRAPH)?;
+                            i18n::write(f, catalog, Message::Example, &[&ex])
+                        }
+                        None => match self.help.as_ref().and_then(|h| h.get_usage()) {
+                            Some(m) => write!(f, "{}{}", NEW_PARAGRAPH, m),
+                            None => Ok(()),
+                        },
+                    }
                 }
                 ErrorKind::DuplicateOptions => {
-                    let arg_str = arg.to_string();
-                    #[cfg(feature = "color")]
-                    let arg_str = color(arg_str.blue());
-                    write!(f, "argument '{}' can only be supplied once", arg_str)
+                    let arg_str = theme.get_arg().paint(arg, enabled);
+                    i18n::write(f, catalog, Message::DuplicateOptions, &[&arg_str])
                 }
                 ErrorKind::ExpectingValue => {
-                    let arg_str = arg.to_string();
-                    #[cfg(feature = "color")]
-                    let arg_str = color(arg_str.blue());
-                    write!(f, "option '{}' takes 1 value but 0 was supplied", arg_str)
-                }
-                _ => panic!("reached unreachable error kind for a failed argument error context"),
-            },
-            ErrorContext::SuggestWord(word, suggestion) => match self.kind() {
-                ErrorKind::SuggestArg => {
-                    #[cfg(feature = "color")]
-                    let word = color(word.yellow());
-                    #[cfg(feature = "color")]
-                    let suggestion = color(suggestion.green());
-                    write!(
-                        f,
-                        "invalid argument '{}'{}Did you mean '{}'?",
-                        word, NEW_PARAGRAPH, suggestion
-                    )
-                }
-                ErrorKind::SuggestSubcommand => {
-                    #[cfg(feature = "color")]
-                    let word = color(word.yellow());
-                    #[cfg(feature = "color")]
-                    let suggestion = color(suggestion.green());
-                    write!(
-                        f,
-                        "invalid subcommand '{}'{}Did you mean '{}'?",
-                        word, NEW_PARAGRAPH, suggestion
-                    )
+                    let arg_str = theme.get_arg().paint(arg, enabled);
+                    i18n::write(f, catalog, Message::ExpectingValue, &[&arg_str])
                 }
                 _ => panic!("reached unreachable error kind for a failed argument error context"),
             },
+            ErrorContext::SuggestWord(word, suggestion) => {
+                let message = match self.kind() {
+                    ErrorKind::SuggestArg => Message::InvalidArgument,
+                    ErrorKind::SuggestSubcommand => Message::InvalidSubcommand,
+                    _ => panic!("reached unreachable error kind for a failed argument error context"),
+                };
+                let word = theme.get_value().paint(word, enabled);
+                let suggestion = theme.get_suggestion().paint(suggestion, enabled);
+                i18n::write(f, catalog, message, &[&word])?;
+                write!(f, "{}", NEW_PARAGRAPH)?;
+                i18n::write(f, catalog, Message::DidYouMean, &[&suggestion])
+            }
             ErrorContext::OutofContextArgSuggest(arg, subcommand) => {
-                let arg_str = arg.to_string();
-                #[cfg(feature = "color")]
-                let arg_str = color(arg_str.yellow());
-                #[cfg(feature = "color")]
-                let subcommand = color(subcommand.green());
-                write!(f, "argument '{}' is unknown or invalid in the current context{}Maybe move it after '{}'?", arg_str, NEW_PARAGRAPH, subcommand)
+                let arg_str = theme.get_value().paint(arg, enabled);
+                let subcommand = theme.get_suggestion().paint(subcommand, enabled);
+                i18n::write(f, catalog, Message::OutOfContextArg, &[&arg_str])?;
+                write!(f, "{}", NEW_PARAGRAPH)?;
+                i18n::write(f, catalog, Message::MoveAfter, &[&subcommand])
             }
             ErrorContext::UnexpectedValue(flag, val) => {
-                let flag_str = flag.to_string();
-                #[cfg(feature = "color")]
-                let flag_str = color(flag_str.blue());
-                #[cfg(feature = "color")]
-                let val = color(val.yellow());
-                write!(
-                    f,
-                    "flag '{}' cannot accept a value but was given '{}'",
-                    flag_str, val
-                )
+                let flag_str = theme.get_arg().paint(flag, enabled);
+                let val = theme.get_value().paint(val, enabled);
+                i18n::write(f, catalog, Message::UnexpectedValue, &[&flag_str, &val])
+            }
+            ErrorContext::UnexpectedFlagValue(flag, token, suggestion) => {
+                let flag_str = theme.get_arg().paint(flag, enabled);
+                let token = theme.get_value().paint(token, enabled);
+                i18n::write(f, catalog, Message::UnexpectedFlagValue, &[&flag_str, &token])?;
+                if let Some(word) = suggestion {
+                    let word = theme.get_suggestion().paint(word, enabled);
+                    write!(f, "{}", NEW_PARAGRAPH)?;
+                    i18n::write(f, catalog, Message::DidYouMean, &[&word])?;
+                }
+                Ok(())
             }
             ErrorContext::UnexpectedArg(word) => {
-                #[cfg(feature = "color")]
-                let word = color(word.yellow());
-                write!(
-                    f,
-                    "invalid argument '{}'{}",
-                    word,
-                    self.help_tip().unwrap_or(String::new())
-                )
+                let word = theme.get_value().paint(word, enabled);
+                i18n::write(f, catalog, Message::InvalidArgument, &[&word])?;
+                self.write_help_tip(f, theme, catalog, enabled)
+            }
+            ErrorContext::ForwardedArg(word, corrected) => {
+                let word = theme.get_value().paint(word, enabled);
+                let corrected = theme.get_suggestion().paint(corrected, enabled);
+                i18n::write(f, catalog, Message::InvalidArgument, &[&word])?;
+                write!(f, "{}", NEW_PARAGRAPH)?;
+                i18n::write(f, catalog, Message::ForwardedArg, &[&corrected])?;
+                self.write_help_tip(f, theme, catalog, enabled)
+            }
+            ErrorContext::Ambiguous(word, candidates) => {
+                let word = theme.get_value().paint(word, enabled);
+                let message = match self.kind() {
+                    ErrorKind::AmbiguousSubcommand => Message::AmbiguousSubcommand,
+                    _ => Message::AmbiguousArg,
+                };
+                i18n::write(f, catalog, message, &[&word])?;
+                write!(f, "{}", NEW_PARAGRAPH)?;
+                i18n::write(f, catalog, Message::PossibleMatches, &[&List(candidates, ", ")])
             }
             ErrorContext::UnknownSubcommand(arg, subcommand) => {
-                #[cfg(feature = "color")]
-                let subcommand = color(subcommand.yellow());
-                let arg_str = arg.to_string();
-                #[cfg(feature = "color")]
-                let arg_str = color(arg_str.blue());
-                write!(f, "invalid subcommand '{}' for '{}'", subcommand, arg_str)
+                let subcommand = theme.get_value().paint(subcommand, enabled);
+                let arg_str = theme.get_arg().paint(arg, enabled);
+                i18n::write(f, catalog, Message::UnknownSubcommand, &[&subcommand, &arg_str])
+            }
+            ErrorContext::DuplicateSwitch(c, first, second) => {
+                let first = theme.get_arg().paint(first.as_flag().unwrap(), enabled);
+                let second = theme.get_arg().paint(second.as_flag().unwrap(), enabled);
+                i18n::write(f, catalog, Message::DuplicateSwitch, &[c, &first, &second])
+            }
+            ErrorContext::DuplicateArg(arg) => {
+                let arg = theme.get_arg().paint(arg, enabled);
+                i18n::write(f, catalog, Message::DuplicateArg, &[&arg])
+            }
+            ErrorContext::RequiredTogether(present, missing) => {
+                let present = theme.get_arg().paint(present, enabled);
+                let missing = theme.get_arg().paint(missing, enabled);
+                i18n::write(f, catalog, Message::RequiredTogether, &[&present, &missing])
+            }
+            ErrorContext::MissingRequirement(arg, missing) => {
+                let arg = theme.get_arg().paint(arg, enabled);
+                let missing = theme.get_arg().paint(missing, enabled);
+                i18n::write(f, catalog, Message::MissingRequirement, &[&arg, &missing])
+            }
+            ErrorContext::UnstableCommand(command, gate) => {
+                let command = theme.get_value().paint(command, enabled);
+                let gate = theme.get_suggestion().paint(gate, enabled);
+                i18n::write(f, catalog, Message::UnstableCommand, &[&command])?;
+                write!(f, "{}", NEW_PARAGRAPH)?;
+                i18n::write(f, catalog, Message::EnableUnstable, &[&gate])
+            }
+            ErrorContext::UnstableArg(arg, gate) => {
+                let arg = theme.get_arg().paint(arg, enabled);
+                let gate = theme.get_suggestion().paint(gate, enabled);
+                i18n::write(f, catalog, Message::UnstableArg, &[&arg])?;
+                write!(f, "{}", NEW_PARAGRAPH)?;
+                i18n::write(f, catalog, Message::EnableUnstable, &[&gate])
+            }
+            ErrorContext::MissingWorkspace(marker, dir) => {
+                let marker = theme.get_value().paint(marker, enabled);
+                i18n::write(f, catalog, Message::MissingWorkspace, &[&marker, dir])
+            }
+            ErrorContext::PluginPanic(name, reason) => {
+                let name = theme.get_value().paint(name, enabled);
+                i18n::write(f, catalog, Message::PluginPanic, &[&name, reason])
+            }
+            ErrorContext::CommandPanic(reason, url, invocation) => {
+                let url = theme.get_suggestion().paint(url, enabled);
+                i18n::write(f, catalog, Message::CommandPanic, &[reason])?;
+                write!(f, "{}", NEW_PARAGRAPH)?;
+                i18n::write(f, catalog, Message::ReportBug, &[&url, invocation])
+            }
+            ErrorContext::TrailingConflict(arg, word, claimed) => {
+                let arg = theme.get_arg().paint(arg, enabled);
+                let word = theme.get_value().paint(word, enabled);
+                let claimed = theme.get_arg().paint(claimed, enabled);
+                i18n::write(f, catalog, Message::TrailingConflict, &[&word, &arg, &claimed])
+            }
+            ErrorContext::FrozenArg(arg) => {
+                let arg = theme.get_arg().paint(arg, enabled);
+                i18n::write(f, catalog, Message::FrozenArg, &[&arg])
+            }
+            ErrorContext::RemovedCommand(command, since, note) => {
+                let command = theme.get_value().paint(command, enabled);
+                i18n::write(f, catalog, Message::RemovedCommand, &[&command, since])?;
+                if let Some(note) = note {
+                    write!(f, "{}{}", NEW_PARAGRAPH, note)?;
+                }
+                Ok(())
+            }
+            ErrorContext::LimitsExceeded(unit, max) => {
+                i18n::write(f, catalog, Message::LimitsExceeded, &[max, unit])
+            }
+            ErrorContext::InvalidUnicode(text) => {
+                let text = theme.get_value().paint(text, enabled);
+                i18n::write(f, catalog, Message::InvalidUnicode, &[&text])
             }
             ErrorContext::CustomRule(err) => {
                 write!(f, "{}", err)
             }
+            ErrorContext::Explain(report) | ErrorContext::Check(report) => {
+                write!(f, "{}", report.trim_end())
+            }
+            ErrorContext::Multiple(errors) => {
+                for (i, err) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "{}", NEW_PARAGRAPH)?;
+                    }
+                    err.write_message(f, theme, catalog, enabled)?;
+                }
+                Ok(())
+            }
         }?;
         Ok(())
     }
 }
+
+/// An error set aside by a [Cli](crate::Cli) that is
+/// [collecting errors](crate::Cli::collect_errors).
+///
+/// The cause of a failed cast or a custom rule is written out when the error is
+/// set aside, since it cannot be sent across threads along with the `Cli`. The
+/// rest of the context is kept as is.
+#[derive(Debug)]
+pub(crate) struct Deferred(Error);
+
+// SAFETY: `detach` replaces every boxed cause with a `Written` message, which
+// is `Send` and `Sync`, and the rest of the error holds only owned data
+unsafe impl Send for Deferred {}
+unsafe impl Sync for Deferred {}
+
+impl PartialEq for Deferred {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.kind == other.0.kind
+            && self.0.span == other.0.span
+            && self.0.to_string() == other.0.to_string()
+    }
+}
+
+impl Deferred {
+    /// Sets aside `err`, writing out the causes it holds.
+    pub fn new(mut err: Error) -> Self {
+        err.context = detach(err.context);
+        Self(err)
+    }
+
+    /// Turns the set aside error back into an [Error] to report.
+    pub fn restore(self) -> Error {
+        self.0
+    }
+}
+
+/// Replaces the boxed causes within `context` with their written messages.
+fn detach(context: ErrorContext) -> ErrorContext {
+    match context {
+        ErrorContext::FailedCast(arg, value, cause) => {
+            ErrorContext::FailedCast(arg, value, Box::new(Written(cause.to_string())))
+        }
+        ErrorContext::CustomRule(cause) => {
+            ErrorContext::CustomRule(Box::new(Written(cause.to_string())))
+        }
+        ErrorContext::Multiple(errors) => {
+            ErrorContext::Multiple(errors.into_iter().map(|e| Deferred::new(e).0).collect())
+        }
+        context => context,
+    }
+}
+
+/// The message of an error's cause, written out ahead of time.
+#[derive(Debug)]
+struct Written(String);
+
+impl Display for Written {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for Written {}
+
+/// Displays `items` separated by a delimiter.
+struct List<'a>(&'a [String], &'a str);
+
+impl<'a> Display for List<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, item) in self.0.iter().enumerate() {
+            match i {
+                0 => write!(f, "{}", item)?,
+                _ => write!(f, "{}{}", self.1, item)?,
+            }
+        }
+        Ok(())
+    }
+}
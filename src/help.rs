@@ -1,61 +1,339 @@
 use crate::arg::Flag;
+use crate::command::Availability;
+use crate::error::ErrorKind;
 use std::ops::Range;
+use std::sync::Arc;
 
 mod tag {
     pub const FLAG: &str = "help";
     pub const SWITCH: char = 'h';
 }
 
+/// Columns that the paragraphs around the help text are wrapped to by default.
+pub(crate) const WRAP_WIDTH: usize = 80;
+
+/// The text displayed when help is requested and alongside errors.
+///
+/// The text is shared among clones, so handing the same `Help` to every parse
+/// or error does not copy it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Help {
+    text: Arc<Text>,
+    detailed: bool,
+}
+
 #[derive(Debug, PartialEq, Clone)]
-pub struct Help<> {
+struct Text {
     arg: Flag,
     summary: Option<String>,
     usage: Option<String>,
     quick_text: String,
     long_text: Option<String>,
+    badge: Option<String>,
+    examples: Vec<Example>,
+    paged: bool,
+    before: Option<String>,
+    after: Option<String>,
+    width: usize,
+    template: Option<String>,
+}
+
+/// A sample invocation displayed in errors about the arguments it demonstrates.
+///
+/// An example can be narrowed to a kind of error, an argument, or both. When an
+/// error occurs, the example that matches it most specifically is shown, so the
+/// user sees how to supply the exact piece that went wrong:
+///
+/// ```
+/// use clif::{Example, ErrorKind};
+///
+/// let ex = Example::new("orbit new my.lib")
+///     .kind(ErrorKind::MissingPositional)
+///     .arg("ip");
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Example {
+    text: String,
+    kind: Option<ErrorKind>,
+    arg: Option<String>,
+    weight: usize,
+}
+
+impl Example {
+    pub fn new<T: AsRef<str>>(text: T) -> Self {
+        Self {
+            text: text.as_ref().to_string(),
+            kind: None,
+            arg: None,
+            weight: 0,
+        }
+    }
+
+    /// Shows the example only for errors of `kind`.
+    pub fn kind(mut self, kind: ErrorKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Shows the example only for errors about the argument named `name`.
+    pub fn arg<T: AsRef<str>>(mut self, name: T) -> Self {
+        self.arg = Some(name.as_ref().to_string());
+        self
+    }
+
+    /// Breaks ties between equally specific examples, preferring the heavier one.
+    pub fn weight(mut self, w: usize) -> Self {
+        self.weight = w;
+        self
+    }
+
+    pub fn get_text(&self) -> &str {
+        self.text.as_ref()
+    }
+
+    pub fn get_kind(&self) -> Option<ErrorKind> {
+        self.kind
+    }
+
+    pub fn get_arg(&self) -> Option<&str> {
+        self.arg.as_deref()
+    }
+
+    pub fn get_weight(&self) -> usize {
+        self.weight
+    }
+
+    /// Returns how specifically the example matches an error of `kind` about
+    /// `arg`, or `None` if it does not apply.
+    ///
+    /// Matching the argument counts for more than matching the kind.
+    fn specificity(&self, kind: ErrorKind, arg: Option<&str>) -> Option<usize> {
+        let by_kind = match self.kind {
+            Some(k) if k != kind => return None,
+            Some(_) => 1,
+            None => 0,
+        };
+        let by_arg = match self.arg.as_deref() {
+            Some(a) if Some(a) != arg => return None,
+            Some(_) => 2,
+            None => 0,
+        };
+        Some(by_kind + by_arg)
+    }
 }
 
 impl Help {
     pub fn new() -> Self {
         Self {
-            arg: Flag::new(tag::FLAG).switch(tag::SWITCH),
-            summary: None,
-            usage: None,
-            quick_text: String::new(),
-            long_text: None,
+            text: Arc::new(Text {
+                arg: Flag::new(tag::FLAG).switch(tag::SWITCH),
+                summary: None,
+                usage: None,
+                quick_text: String::new(),
+                long_text: None,
+                badge: None,
+                examples: Vec::new(),
+                paged: false,
+                before: None,
+                after: None,
+                width: WRAP_WIDTH,
+                template: None,
+            }),
+            detailed: false,
         }
     }
 
+    /// References the text to modify, copying it first if it is shared.
+    fn text_mut(&mut self) -> &mut Text {
+        Arc::make_mut(&mut self.text)
+    }
+
+    /// Adds an example to display in errors it applies to.
+    pub fn example(mut self, ex: Example) -> Self {
+        self.text_mut().examples.push(ex);
+        self
+    }
+
+    pub fn get_examples(&self) -> &Vec<Example> {
+        &self.text.examples
+    }
+
+    /// Selects the example to display for an error of `kind` about `arg`.
+    ///
+    /// The most specific example wins, then the heaviest, then the one added
+    /// first. Examples without a kind or argument are only selected when
+    /// `generic` is true.
+    pub(crate) fn select_example(
+        &self,
+        kind: ErrorKind,
+        arg: Option<&str>,
+        generic: bool,
+    ) -> Option<&Example> {
+        let mut best: Option<(&Example, usize)> = None;
+        for ex in &self.text.examples {
+            let score = match ex.specificity(kind, arg) {
+                Some(0) if generic == false => continue,
+                Some(s) => s,
+                None => continue,
+            };
+            let better = match best {
+                Some((b, b_score)) => (score, ex.weight) > (b_score, b.weight),
+                None => true,
+            };
+            if better == true {
+                best = Some((ex, score));
+            }
+        }
+        best.map(|(ex, _)| ex)
+    }
+
+    /// Sets the full help shown for the long flag, `--help`, while the switch,
+    /// `-h`, shows the quick text.
+    ///
+    /// The quick text is shown for both when no long text is set.
     pub fn long_text<T: AsRef<str>>(mut self, t: T) -> Self {
-        self.long_text = Some(t.as_ref().to_string());
+        self.text_mut().long_text = Some(t.as_ref().to_string());
         self
     }
 
+    pub fn get_long_text(&self) -> Option<&str> {
+        self.text.long_text.as_deref()
+    }
+
+    /// Selects the long text to be displayed when `detailed` is true.
+    pub(crate) fn detailed(mut self, detailed: bool) -> Self {
+        self.detailed = detailed;
+        self
+    }
+
+    /// Returns the text to display: the long text when the full help was
+    /// requested and one is set, or else the quick text.
+    pub fn get_text(&self) -> &str {
+        match (self.detailed, self.get_long_text()) {
+            (true, Some(text)) => text,
+            _ => self.get_quick_text(),
+        }
+    }
+
     pub fn flag(mut self, f: Flag) -> Self {
-        self.arg = f;
+        self.text_mut().arg = f;
         self
     }
 
     pub fn quick_text<T: AsRef<str>>(mut self, t: T) -> Self {
-        self.quick_text = t.as_ref().to_string();
+        self.text_mut().quick_text = t.as_ref().to_string();
         self
     }
 
     pub fn usage<T: AsRef<str>>(mut self, t: T) -> Self {
-        self.usage = Some(t.as_ref().to_string());
+        self.text_mut().usage = Some(t.as_ref().to_string());
+        self
+    }
+
+    /// Displays the badge of the command's [Availability] above the quick text.
+    pub fn availability(mut self, a: &Availability) -> Self {
+        self.text_mut().badge = a.badge();
         self
     }
 
+    /// Writes the help through the user's pager, `$PAGER` or else `less -R`,
+    /// when it is taller than the terminal.
+    ///
+    /// The help is written directly when stdout is not a terminal, so it can
+    /// still be piped or redirected.
+    pub fn paged(mut self) -> Self {
+        self.text_mut().paged = true;
+        self
+    }
+
+    pub fn is_paged(&self) -> bool {
+        self.text.paged
+    }
+
+    /// Displays `text` above the help, such as a notice about the command.
+    ///
+    /// Paragraphs are [wrapped](Help::wrap_width) unless they contain an
+    /// indented line.
+    pub fn before_help<T: AsRef<str>>(mut self, text: T) -> Self {
+        self.text_mut().before = Some(text.as_ref().to_string());
+        self
+    }
+
+    /// Displays `text` below the help, such as examples or the environment
+    /// variables the command reads.
+    ///
+    /// Paragraphs are [wrapped](Help::wrap_width) unless they contain an
+    /// indented line.
+    pub fn after_help<T: AsRef<str>>(mut self, text: T) -> Self {
+        self.text_mut().after = Some(text.as_ref().to_string());
+        self
+    }
+
+    /// Wraps the paragraphs above and below the help to `width` columns, which
+    /// is 80 by default.
+    pub fn wrap_width(mut self, width: usize) -> Self {
+        self.text_mut().width = width;
+        self
+    }
+
+    /// Lays out help generated from a [Spec](crate::Spec) with `template`, such
+    /// as `"{usage}\n\n{flags}\n{options}\n{subcommands}"`.
+    ///
+    /// The placeholders `{summary}`, `{usage}`, `{args}`, `{flags}`, `{options}`
+    /// and `{subcommands}` are filled with the sections of the generated help.
+    /// Flags and options with a [category](crate::arg::Flag::category) are
+    /// listed under their headings in `{categories}`, or after `{options}` when
+    /// the template leaves it out. Sections with nothing to list are left out
+    /// along with the blank lines around them, and anything else within braces
+    /// is kept as written.
+    pub fn template<T: AsRef<str>>(mut self, t: T) -> Self {
+        self.text_mut().template = Some(t.as_ref().to_string());
+        self
+    }
+
+    pub fn get_template(&self) -> Option<&str> {
+        self.text.template.as_deref()
+    }
+
+    pub fn get_before_help(&self) -> Option<&str> {
+        self.text.before.as_deref()
+    }
+
+    pub fn get_after_help(&self) -> Option<&str> {
+        self.text.after.as_deref()
+    }
+
+    /// Writes the help text between its wrapped preamble and epilogue.
+    pub(crate) fn write_text(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(before) = self.get_before_help() {
+            write!(f, "{}\n\n", wrap(before, self.text.width))?;
+        }
+        let text = self.get_text();
+        write!(f, "{}", text)?;
+        if let Some(after) = self.get_after_help() {
+            let gap = match text.ends_with('\n') {
+                true => "\n",
+                false => "\n\n",
+            };
+            write!(f, "{}{}\n", gap, wrap(after, self.text.width))?;
+        }
+        Ok(())
+    }
+
+    pub fn get_badge(&self) -> Option<&str> {
+        self.text.badge.as_deref()
+    }
+
     pub fn get_flag(&self) -> &Flag {
-        &self.arg
+        &self.text.arg
     }
 
     pub fn get_quick_text(&self) -> &str {
-        self.quick_text.as_ref()
+        self.text.quick_text.as_ref()
     }
 
     pub fn get_usage(&self) -> Option<&str> {
-        Some(self.usage.as_ref()?.as_ref())
+        Some(self.text.usage.as_ref()?.as_ref())
     }
 
     /// References the appropriate lines for a text statement for usage according to the line range `line_bounds`.
@@ -63,6 +341,9 @@ impl Help {
     /// The function will fail to set a usage statement without panicking if the the range is out-of-bounds.
     ///
     /// The range must be specified as `inclusive..exclusive`.
+    ///
+    /// Without a usage statement, errors show one composed from the arguments
+    /// read, as with [Cli::usage](crate::Cli::usage).
     pub fn ref_usage(mut self, line_bounds: Range<usize>) -> Self {
         let mut lines = self.get_quick_text().split_terminator('\n').enumerate();
         // find the starting character
@@ -88,8 +369,8 @@ impl Help {
         }
         // ensure there is a starting and ending character index to reference.
         if start_char.is_some() == true && end_char.is_some() == true {
-            self.usage = Some(
-                self.quick_text
+            self.text_mut().usage = Some(
+                self.text.quick_text
                     .get(start_char.unwrap()..end_char.unwrap())
                     .unwrap().to_owned(),
             );
@@ -98,3 +379,191 @@ impl Help {
         self
     }
 }
+
+/// Fills the placeholders of `template` with the named `sections`.
+///
+/// Blank lines left by empty sections are collapsed so at most one separates
+/// the sections written.
+pub(crate) fn fill(template: &str, sections: &[(&str, String)]) -> String {
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let section = rest[start..].find('}').and_then(|end| {
+            let name = &rest[start + 1..start + end];
+            sections.iter().find(|(n, _)| *n == name).map(|(_, s)| (s, end))
+        });
+        match section {
+            Some((section, end)) => {
+                text.push_str(&rest[..start]);
+                text.push_str(section);
+                rest = &rest[start + end + 1..];
+            }
+            None => {
+                text.push_str(&rest[..start + 1]);
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    while text.contains("\n\n\n") == true {
+        text = text.replace("\n\n\n", "\n\n");
+    }
+    // keep a single newline after the last section and none before the first
+    let end = text.trim_end_matches('\n').len();
+    if end < text.len() {
+        text.truncate(end + 1);
+    }
+    text.trim_start_matches('\n').to_string()
+}
+
+/// Reflows each paragraph of `text` into lines of at most `width` columns.
+///
+/// Paragraphs with an indented line, such as a list of examples, are kept as
+/// written. Words longer than `width` are left whole on their own line.
+pub(crate) fn wrap(text: &str, width: usize) -> String {
+    text.trim_end()
+        .split("\n\n")
+        .map(|para| {
+            if para.lines().any(|l| l.starts_with(char::is_whitespace)) == true {
+                return para.to_string();
+            }
+            let mut lines = Vec::new();
+            let mut line = String::new();
+            for word in para.split_whitespace() {
+                let len = line.chars().count();
+                if len > 0 && len + 1 + word.chars().count() > width {
+                    lines.push(std::mem::take(&mut line));
+                }
+                if line.is_empty() == false {
+                    line.push(' ');
+                }
+                line.push_str(word);
+            }
+            lines.push(line);
+            lines.join("\n")
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn before_and_after_help() {
+        use crate::error::{Error, ErrorContext};
+
+        let help = Help::new()
+            .quick_text("Usage:\n    orbit lock")
+            .before_help("Writes the lockfile for the current ip.")
+            .after_help("The lockfile is written next to the manifest so it can be committed.")
+            .wrap_width(40);
+        let err = Error::new(Some(help), ErrorKind::Help, ErrorContext::Help, false);
+        assert_eq!(
+            err.to_string(),
+            "Writes the lockfile for the current ip.\n\nUsage:\n    orbit lock\n\nThe lockfile is written next to the\nmanifest so it can be committed.\n"
+        );
+    }
+
+    #[test]
+    fn wrap_paragraphs() {
+        let text = "Reads the manifest of the current ip and\nwrites the lockfile.\n\nExamples:\n    orbit lock\n    orbit lock --force\n";
+        assert_eq!(
+            wrap(text, 24),
+            "Reads the manifest of\nthe current ip and\nwrites the lockfile.\n\nExamples:\n    orbit lock\n    orbit lock --force"
+        );
+        assert_eq!(wrap("a verylongword b", 4), "a\nverylongword\nb");
+    }
+
+    #[test]
+    fn select_examples() {
+        let help = Help::new()
+            .example(Example::new("orbit new my.lib --path ./lib"))
+            .example(Example::new("orbit new my.lib").kind(ErrorKind::MissingPositional))
+            .example(
+                Example::new("orbit new my.lib --force")
+                    .kind(ErrorKind::MissingPositional)
+                    .weight(1),
+            )
+            .example(Example::new("orbit new --path ./lib my.lib").arg("path"));
+
+        let text = |kind, arg, generic| {
+            help.select_example(kind, arg, generic).map(|e| e.get_text())
+        };
+        // the heavier of the two examples for the kind is chosen
+        assert_eq!(
+            text(ErrorKind::MissingPositional, Some("ip"), false),
+            Some("orbit new my.lib --force")
+        );
+        // matching the argument beats matching the kind
+        assert_eq!(
+            text(ErrorKind::ExpectingValue, Some("path"), false),
+            Some("orbit new --path ./lib my.lib")
+        );
+        // generic examples are fallbacks only when allowed
+        assert_eq!(text(ErrorKind::BadType, Some("count"), false), None);
+        assert_eq!(
+            text(ErrorKind::BadType, Some("count"), true),
+            Some("orbit new my.lib --path ./lib")
+        );
+        assert_eq!(Help::new().select_example(ErrorKind::BadType, None, true), None);
+    }
+
+    #[test]
+    fn shared_text() {
+        let help = Help::new().quick_text("Usage:\n    orbit new <ip>").ref_usage(0..2);
+        let copy = help.clone();
+        assert_eq!(Arc::ptr_eq(&help.text, &copy.text), true);
+        // modifying a clone leaves the original untouched
+        let copy = copy.usage("Usage: orbit new");
+        assert_eq!(Arc::ptr_eq(&help.text, &copy.text), false);
+        assert_eq!(help.get_usage(), Some("Usage:\n    orbit new <ip>"));
+        assert_eq!(copy.get_quick_text(), help.get_quick_text());
+    }
+
+    #[test]
+    fn examples_in_errors() {
+        use crate::arg::{Arg, Positional};
+        use crate::error::{Error, ErrorContext};
+
+        let help = Help::new()
+            .usage("Usage:\n    orbit new [options] <ip>")
+            .example(Example::new("orbit new my.lib").arg("ip"));
+        let err = Error::new(
+            Some(help.clone()),
+            ErrorKind::MissingPositional,
+            ErrorContext::FailedArg(Arg::Positional(Positional::new("ip"))),
+            false,
+        );
+        assert_eq!(
+            err.to_string(),
+            "missing positional argument '<ip>'\n\nExample: orbit new my.lib"
+        );
+        // the usage is shown when no example applies
+        let err = Error::new(
+            Some(help.clone()),
+            ErrorKind::MissingPositional,
+            ErrorContext::FailedArg(Arg::Positional(Positional::new("path"))),
+            false,
+        );
+        assert_eq!(
+            err.to_string(),
+            "missing positional argument '<path>'\n\nUsage:\n    orbit new [options] <ip>"
+        );
+        let err = Error::new(
+            Some(help),
+            ErrorKind::BadType,
+            ErrorContext::InvalidValue(
+                Arg::Positional(Positional::new("ip")),
+                "1".to_string(),
+                "too short".to_string(),
+            ),
+            false,
+        );
+        assert_eq!(
+            err.to_string(),
+            "argument '<ip>' does not accept '1': too short\n\nExample: orbit new my.lib"
+        );
+    }
+}
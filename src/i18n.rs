@@ -0,0 +1,231 @@
+//! Translations of the messages written for errors.
+//!
+//! Every sentence an [Error](crate::Error) can write is named by a [Message]
+//! and looked up in a [Catalog] when the error is displayed. A template marks
+//! where the message's parts go with numbered placeholders, such as `{0}`, so a
+//! translation can reorder them:
+//!
+//! ```
+//! use clif::i18n::Message;
+//! use std::collections::HashMap;
+//!
+//! let mut catalog = HashMap::new();
+//! catalog.insert(Message::InvalidArgument, String::from("argument invalide '{0}'"));
+//! ```
+//!
+//! Messages missing from a catalog fall back to [English].
+
+use crate::style::Paint;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// A sentence written within an error message.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Message {
+    ExceededValues,
+    ExceededOccurrences,
+    TooFewValues,
+    FailedCast,
+    InvalidValue,
+    InvalidChoice,
+    PossibleValues,
+    ClosestValues,
+    ListValues,
+    DidYouMean,
+    MissingPositional,
+    Example,
+    DuplicateOptions,
+    ExpectingValue,
+    InvalidArgument,
+    InvalidSubcommand,
+    OutOfContextArg,
+    MoveAfter,
+    UnexpectedValue,
+    UnexpectedFlagValue,
+    ForwardedArg,
+    AmbiguousArg,
+    AmbiguousSubcommand,
+    PossibleMatches,
+    UnknownSubcommand,
+    DuplicateSwitch,
+    DuplicateArg,
+    RequiredTogether,
+    MissingRequirement,
+    UnstableCommand,
+    UnstableArg,
+    EnableUnstable,
+    MissingWorkspace,
+    PluginPanic,
+    CommandPanic,
+    ReportBug,
+    TrailingConflict,
+    FrozenArg,
+    RemovedCommand,
+    LimitsExceeded,
+    InvalidUnicode,
+    HelpTip,
+    ErrorLabel,
+}
+
+impl Message {
+    /// Returns the English template of the message.
+    pub fn english(&self) -> &'static str {
+        match self {
+            Self::ExceededValues => "argument '{0}' accepts up to {1} values but was supplied {2}",
+            Self::ExceededOccurrences => {
+                "option '{0}' can be used up to {1} times but was supplied {2} times"
+            }
+            Self::TooFewValues => "argument '{0}' requires at least {1} values but was supplied {2}",
+            Self::FailedCast => "argument '{0}' failed to process '{1}' due to: {2}",
+            Self::InvalidValue => "argument '{0}' does not accept '{1}': {2}",
+            Self::InvalidChoice => "argument '{0}' does not accept '{1}'",
+            Self::PossibleValues => "Possible values: {0}",
+            Self::ClosestValues => "Closest values: {0}",
+            Self::ListValues => "Run with '{0}' to see all possible values.",
+            Self::DidYouMean => "Did you mean '{0}'?",
+            Self::MissingPositional => "missing positional argument '{0}'",
+            Self::Example => "Example: {0}",
+            Self::DuplicateOptions => "argument '{0}' can only be supplied once",
+            Self::ExpectingValue => "option '{0}' takes 1 value but 0 was supplied",
+            Self::InvalidArgument => "invalid argument '{0}'",
+            Self::InvalidSubcommand => "invalid subcommand '{0}'",
+            Self::OutOfContextArg => "argument '{0}' is unknown or invalid in the current context",
+            Self::MoveAfter => "Maybe move it after '{0}'?",
+            Self::UnexpectedValue => "flag '{0}' cannot accept a value but was given '{1}'",
+            Self::UnexpectedFlagValue => "flag '{0}' does not take a value but was supplied as '{1}'",
+            Self::ForwardedArg => {
+                "Arguments for the wrapped command must follow '--', as in:\n    {0}"
+            }
+            Self::AmbiguousArg => "argument '{0}' is ambiguous",
+            Self::AmbiguousSubcommand => "subcommand '{0}' is ambiguous",
+            Self::PossibleMatches => "Possible matches: {0}",
+            Self::UnknownSubcommand => "invalid subcommand '{0}' for '{1}'",
+            Self::DuplicateSwitch => "switch '-{0}' cannot be assigned to both '{1}' and '{2}'",
+            Self::DuplicateArg => "argument '{0}' is declared by more than one command-line fragment",
+            Self::RequiredTogether => "argument '{0}' must be used together with '{1}'",
+            Self::MissingRequirement => "argument '{0}' requires '{1}' to also be supplied",
+            Self::UnstableCommand => "subcommand '{0}' is experimental",
+            Self::UnstableArg => "argument '{0}' is experimental",
+            Self::EnableUnstable => "Supply '{0}' to use it.",
+            Self::MissingWorkspace => "could not find '{0}' in '{1}' or any parent directory",
+            Self::PluginPanic => "command '{0}' provided by a plugin panicked: {1}",
+            Self::CommandPanic => "the command panicked: {0}",
+            Self::ReportBug => {
+                "This is a bug. Please report it at {0} with the command that caused it:\n    {1}"
+            }
+            Self::TrailingConflict => "'{0}' is forwarded with '{1}' but was already read by '{2}'",
+            Self::FrozenArg => "argument '{0}' is fixed by the application and cannot be supplied",
+            Self::RemovedCommand => "subcommand '{0}' was removed in {1}",
+            Self::LimitsExceeded => "command line exceeds the limit of {0} {1}",
+            Self::InvalidUnicode => "argument '{0}' is not valid unicode",
+            Self::HelpTip => "For more information, try '{0}'.",
+            Self::ErrorLabel => "error",
+        }
+    }
+}
+
+/// A source of translated message templates.
+pub trait Catalog: Send + Sync {
+    /// Returns the template for `message`, or `None` to write it in English.
+    fn template(&self, message: Message) -> Option<&str>;
+}
+
+/// The catalog of the messages as written by this crate.
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub struct English;
+
+impl Catalog for English {
+    fn template(&self, message: Message) -> Option<&str> {
+        Some(message.english())
+    }
+}
+
+impl Catalog for HashMap<Message, String> {
+    fn template(&self, message: Message) -> Option<&str> {
+        self.get(&message).map(|t| t.as_str())
+    }
+}
+
+/// Writes `message` from `catalog` with its placeholders replaced by `args`.
+pub(crate) fn write(
+    f: &mut std::fmt::Formatter<'_>,
+    catalog: &dyn Catalog,
+    message: Message,
+    args: &[&dyn Display],
+) -> std::fmt::Result {
+    write_painted(f, catalog, message, args, None)
+}
+
+/// Writes `message` as with [write], painting the text around the placeholders
+/// with `paint` when it is `Some` and enabled.
+pub(crate) fn write_painted(
+    f: &mut std::fmt::Formatter<'_>,
+    catalog: &dyn Catalog,
+    message: Message,
+    args: &[&dyn Display],
+    paint: Option<(&Paint, bool)>,
+) -> std::fmt::Result {
+    let template = catalog.template(message).unwrap_or(message.english());
+    let literal = |f: &mut std::fmt::Formatter<'_>, text: &str| match (paint, text.is_empty()) {
+        (_, true) => Ok(()),
+        (Some((p, enabled)), false) => write!(f, "{}", p.paint(text, enabled)),
+        (None, false) => write!(f, "{}", text),
+    };
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        // placeholders are a number within braces; anything else is kept as written
+        let arg = rest[start..].find('}').and_then(|end| {
+            let i = rest[start + 1..start + end].parse::<usize>().ok()?;
+            Some((args.get(i)?, end))
+        });
+        match arg {
+            Some((arg, end)) => {
+                literal(f, &rest[..start])?;
+                write!(f, "{}", arg)?;
+                rest = &rest[start + end + 1..];
+            }
+            None => {
+                literal(f, &rest[..start + 1])?;
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    literal(f, rest)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Displays `message` from `catalog` with `args`.
+    struct Render<'a>(&'a dyn Catalog, Message, &'a [&'a dyn Display]);
+
+    impl<'a> Display for Render<'a> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write(f, self.0, self.1, self.2)
+        }
+    }
+
+    #[test]
+    fn fill_templates() {
+        assert_eq!(
+            Render(&English, Message::UnknownSubcommand, &[&"bulid", &"<command>"]).to_string(),
+            "invalid subcommand 'bulid' for '<command>'"
+        );
+
+        let mut catalog = HashMap::new();
+        catalog.insert(
+            Message::UnknownSubcommand,
+            String::from("für '{1}' ist '{0}' kein gültiger Unterbefehl {x}"),
+        );
+        assert_eq!(
+            Render(&catalog, Message::UnknownSubcommand, &[&"bulid", &"<command>"]).to_string(),
+            "für '<command>' ist 'bulid' kein gültiger Unterbefehl {x}"
+        );
+        // untranslated messages fall back to english
+        assert_eq!(
+            Render(&catalog, Message::InvalidArgument, &[&"-x"]).to_string(),
+            "invalid argument '-x'"
+        );
+    }
+}
@@ -0,0 +1,380 @@
+//! A minimal writer and reader for JSON documents.
+
+use std::fmt::Display;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Str(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Creates an object from its key-value pairs, keeping their order.
+    pub fn object<T: AsRef<str>>(entries: Vec<(T, Value)>) -> Self {
+        Self::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.as_ref().to_string(), v))
+                .collect(),
+        )
+    }
+
+    pub fn str<T: AsRef<str>>(s: T) -> Self {
+        Self::Str(s.as_ref().to_string())
+    }
+
+    /// Creates a string, or null when `s` is absent.
+    pub fn opt_str<T: AsRef<str>>(s: Option<T>) -> Self {
+        match s {
+            Some(s) => Self::str(s),
+            None => Self::Null,
+        }
+    }
+
+    pub fn strs<T: AsRef<str>>(items: &[T]) -> Self {
+        Self::Array(items.iter().map(Self::str).collect())
+    }
+
+    /// References the value stored under `key` if this is an object.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Self::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(s) => Some(s.as_ref()),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Self::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Self::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Writes the value indented by `depth` levels of two spaces.
+    fn write(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+        let pad = "  ".repeat(depth + 1);
+        match self {
+            Self::Null => write!(f, "null"),
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Int(i) => write!(f, "{}", i),
+            Self::Str(s) => write!(f, "{}", escape(s)),
+            Self::Array(items) if items.is_empty() => write!(f, "[]"),
+            Self::Object(entries) if entries.is_empty() => write!(f, "{{}}"),
+            Self::Array(items) => {
+                writeln!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    write!(f, "{}", pad)?;
+                    item.write(f, depth + 1)?;
+                    writeln!(f, "{}", if i + 1 < items.len() { "," } else { "" })?;
+                }
+                write!(f, "{}]", "  ".repeat(depth))
+            }
+            Self::Object(entries) => {
+                writeln!(f, "{{")?;
+                for (i, (key, item)) in entries.iter().enumerate() {
+                    write!(f, "{}{}: ", pad, escape(key))?;
+                    item.write(f, depth + 1)?;
+                    writeln!(f, "{}", if i + 1 < entries.len() { "," } else { "" })?;
+                }
+                write!(f, "{}}}", "  ".repeat(depth))
+            }
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    line: usize,
+    reason: String,
+}
+
+impl ParseError {
+    fn new<T: AsRef<str>>(line: usize, reason: T) -> Self {
+        Self {
+            line,
+            reason: reason.as_ref().to_string(),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// Reads the single value written in `text`.
+///
+/// Numbers are limited to integers, which is all this crate writes.
+pub fn parse(text: &str) -> Result<Value, ParseError> {
+    let mut reader = Reader {
+        chars: text.chars().peekable(),
+        line: 1,
+    };
+    let value = reader.value()?;
+    match reader.skip_space() {
+        None => Ok(value),
+        Some(c) => Err(reader.error(format!("unexpected trailing character '{}'", c))),
+    }
+}
+
+/// Walks the characters of a document while tracking the current line.
+struct Reader<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error<T: AsRef<str>>(&self, reason: T) -> ParseError {
+        ParseError::new(self.line, reason)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    /// Skips whitespace and peeks at the next character.
+    fn skip_space(&mut self) -> Option<char> {
+        while let Some(c) = self.chars.peek().copied() {
+            match c.is_whitespace() {
+                true => self.next(),
+                false => return Some(c),
+            };
+        }
+        None
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        match self.skip_space() {
+            Some(c) if c == expected => {
+                self.next();
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("expected '{}' but found '{}'", expected, c))),
+            None => Err(self.error(format!("expected '{}' but found the end", expected))),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        match self.skip_space() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => Ok(Value::Str(self.string()?)),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.int(),
+            Some(c) if c.is_ascii_alphabetic() => {
+                let mut word = String::new();
+                while let Some(c) = self.chars.peek().copied().filter(|c| c.is_ascii_alphabetic()) {
+                    word.push(c);
+                    self.next();
+                }
+                match word.as_ref() {
+                    "null" => Ok(Value::Null),
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    _ => Err(self.error(format!("invalid value '{}'", word))),
+                }
+            }
+            Some(c) => Err(self.error(format!("unexpected character '{}'", c))),
+            None => Err(self.error("expected a value but found the end")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, ParseError> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        if self.skip_space() == Some('}') {
+            self.next();
+            return Ok(Value::Object(entries));
+        }
+        loop {
+            if self.skip_space() != Some('"') {
+                return Err(self.error("expected a quoted key"));
+            }
+            let key = self.string()?;
+            self.expect(':')?;
+            entries.push((key, self.value()?));
+            match self.skip_space() {
+                Some(',') => self.next(),
+                Some('}') => {
+                    self.next();
+                    return Ok(Value::Object(entries));
+                }
+                _ => return Err(self.error("expected ',' or '}' in object")),
+            };
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, ParseError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        if self.skip_space() == Some(']') {
+            self.next();
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            match self.skip_space() {
+                Some(',') => self.next(),
+                Some(']') => {
+                    self.next();
+                    return Ok(Value::Array(items));
+                }
+                _ => return Err(self.error("expected ',' or ']' in array")),
+            };
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+        let mut result = String::new();
+        while let Some(c) = self.next() {
+            match c {
+                '"' => return Ok(result),
+                '\\' => match self.next() {
+                    Some('n') => result.push('\n'),
+                    Some('r') => result.push('\r'),
+                    Some('t') => result.push('\t'),
+                    Some('"') => result.push('"'),
+                    Some('\\') => result.push('\\'),
+                    Some('/') => result.push('/'),
+                    Some('u') => {
+                        let code: String = (0..4).filter_map(|_| self.next()).collect();
+                        match u32::from_str_radix(&code, 16).ok().and_then(char::from_u32) {
+                            Some(c) => result.push(c),
+                            None => return Err(self.error(format!("invalid escape sequence '\\u{}'", code))),
+                        }
+                    }
+                    Some(e) => return Err(self.error(format!("unknown escape sequence '\\{}'", e))),
+                    None => break,
+                },
+                _ => result.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn int(&mut self) -> Result<Value, ParseError> {
+        let mut word = String::new();
+        while let Some(c) = self.chars.peek().copied().filter(|c| *c == '-' || c.is_ascii_digit()) {
+            word.push(c);
+            self.next();
+        }
+        match word.parse::<i64>() {
+            Ok(i) => Ok(Value::Int(i)),
+            Err(_) => Err(self.error(format!("invalid number '{}'", word))),
+        }
+    }
+}
+
+/// Quotes `s` as a JSON string.
+fn escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn write_values() {
+        let doc = Value::object(vec![
+            ("name", Value::str("say \"hi\"\n")),
+            ("count", Value::Int(-2)),
+            ("tags", Value::strs(&["a", "b"])),
+            ("none", Value::Array(Vec::new())),
+            ("nested", Value::object(vec![("ok", Value::Bool(true)), ("x", Value::Null)])),
+        ]);
+        assert_eq!(
+            doc.to_string(),
+            r#"{
+  "name": "say \"hi\"\n",
+  "count": -2,
+  "tags": [
+    "a",
+    "b"
+  ],
+  "none": [],
+  "nested": {
+    "ok": true,
+    "x": null
+  }
+}"#
+        );
+        assert_eq!(escape("\u{1}"), "\"\\u0001\"");
+    }
+
+    #[test]
+    fn read_values() {
+        let doc = Value::object(vec![
+            ("name", Value::str("say \"hi\"\n\u{1}")),
+            ("count", Value::Int(-2)),
+            ("tags", Value::strs(&["a", "b"])),
+            ("none", Value::Array(Vec::new())),
+            ("nested", Value::object(vec![("ok", Value::Bool(true)), ("x", Value::Null)])),
+        ]);
+        assert_eq!(parse(&doc.to_string()), Ok(doc.clone()));
+        assert_eq!(doc.get("nested").and_then(|n| n.get("ok")).and_then(|v| v.as_bool()), Some(true));
+        assert_eq!(doc.get("tags").and_then(|t| t.as_array()).map(|t| t.len()), Some(2));
+        assert_eq!(doc.get("count").and_then(|c| c.as_int()), Some(-2));
+
+        assert_eq!(
+            parse("{\n  \"a\": 1\n  \"b\": 2\n}").unwrap_err().to_string(),
+            "line 3: expected ',' or '}' in object"
+        );
+        assert!(parse("[1, 2").is_err());
+        assert!(parse("\"open").is_err());
+        assert!(parse("{} x").is_err());
+        assert!(parse("nope").is_err());
+    }
+}
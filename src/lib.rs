@@ -1,21 +1,68 @@
 mod cli;
+mod clock;
 mod command;
+mod compat;
+mod crash;
 mod error;
 mod help;
+mod json;
+mod limits;
+mod metrics;
+mod retry;
 mod seqalin;
+mod suggest;
+mod toml;
+mod workspace;
 
 pub mod arg;
+pub mod complete;
+pub mod config;
+pub mod corpus;
+pub mod doctor;
+pub mod i18n;
+pub mod matches;
+pub mod output;
+pub mod plugin;
+pub mod provider;
+pub mod report;
+pub mod source;
+pub mod spec;
+pub mod style;
+pub mod test;
+pub mod value;
 
 pub use cli::Cli;
+pub use clock::Clock;
+pub use clock::Stopwatch;
+pub use command::execute;
+pub use command::run;
 pub use error::Error;
 pub use error::ErrorContext;
 pub use error::ErrorKind;
+pub use error::Span;
+pub use help::Example;
 pub use help::Help;
+pub use limits::Limits;
+pub use metrics::Metric;
+pub use metrics::MetricsSink;
+pub use suggest::SuggestionConfig;
+pub use suggest::ThresholdPolicy;
 
 pub mod cmd {
+    #[cfg(feature = "async")]
+    pub use super::command::AsyncCommand;
+    #[cfg(feature = "async")]
+    pub use super::command::AsyncRunner;
+    pub use super::command::Availability;
     pub use super::command::Command;
+    pub use super::command::ExitStatus;
     pub use super::command::FromCli;
     pub use super::command::Runner;
+    pub use super::output::Context;
+    pub use super::output::Warn;
+    pub use super::retry::Retry;
+    pub use super::retry::Transient;
+    pub use super::workspace::Workspace;
 }
 
 // pub use arg::Flag;
@@ -95,6 +142,35 @@ mod tests {
             assert_eq!(program.run(), 19);
         }
 
+        #[test]
+        fn parallel_parsing() {
+            // every thread parses its own arg sets with independent `Cli` instances
+            std::thread::scope(|s| {
+                for t in 0..8u8 {
+                    s.spawn(move || {
+                        for i in 0..500u16 {
+                            let lhs = (i % 128) as u8;
+                            let (lhs_str, rhs_str) = (lhs.to_string(), t.to_string());
+                            let mut cli = Cli::new().threshold(2).tokenize(args(vec![
+                                "radd", "--verbose", &lhs_str, &rhs_str,
+                            ]));
+                            let program = Radd::from_cli(&mut cli).unwrap();
+                            assert_eq!(program.run(), lhs as u16 + t as u16);
+                            assert_eq!(program.verbose, true);
+                            // errors are independent across threads as well
+                            let mut cli = Cli::new().threshold(3).tokenize(args(vec![
+                                "radd", "--verbsoe", &lhs_str, &rhs_str,
+                            ]));
+                            assert_eq!(
+                                Radd::from_cli(&mut cli).unwrap_err().kind(),
+                                error::ErrorKind::SuggestArg
+                            );
+                        }
+                    });
+                }
+            });
+        }
+
         const HELP: &str = "\
 Adds two numbers together.
 
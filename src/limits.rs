@@ -0,0 +1,117 @@
+//! Bounds on the work performed when parsing untrusted input.
+
+/// Caps the size of the input a [Cli](crate::Cli) accepts and the effort spent
+/// on spelling suggestions.
+///
+/// Every limit is unbounded by default. Input exceeding a size limit is discarded
+/// during tokenization and the next query on the `Cli` returns a `LimitsExceeded`
+/// error. Exceeding the suggestion budget only skips the suggestion, so the error
+/// is reported as if no similar word existed.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Limits {
+    args: Option<usize>,
+    arg_len: Option<usize>,
+    tokens: Option<usize>,
+    suggestion_work: Option<usize>,
+}
+
+impl Limits {
+    /// Creates a set of limits without any bounds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates limits suited for command lines received from an untrusted source,
+    /// such as a network connection.
+    pub fn untrusted() -> Self {
+        Self::new()
+            .max_args(1_024)
+            .max_arg_len(4_096)
+            .max_tokens(4_096)
+            .max_suggestion_work(1 << 20)
+    }
+
+    /// Sets the maximum number of arguments, not counting the program name.
+    pub fn max_args(mut self, n: usize) -> Self {
+        self.args = Some(n);
+        self
+    }
+
+    /// Sets the maximum length of a single argument in bytes.
+    pub fn max_arg_len(mut self, n: usize) -> Self {
+        self.arg_len = Some(n);
+        self
+    }
+
+    /// Sets the maximum number of tokens, where every switch within a cluster
+    /// like `-abc` counts as its own token.
+    pub fn max_tokens(mut self, n: usize) -> Self {
+        self.tokens = Some(n);
+        self
+    }
+
+    /// Sets the maximum number of character comparisons a single spelling
+    /// suggestion may perform.
+    pub fn max_suggestion_work(mut self, n: usize) -> Self {
+        self.suggestion_work = Some(n);
+        self
+    }
+
+    /// Checks the argument at position `count` (starting at 1) with `len` bytes.
+    ///
+    /// Returns the description and value of the limit exceeded, if any.
+    pub(crate) fn check_arg(&self, count: usize, len: usize) -> Option<(String, usize)> {
+        match (self.args, self.arg_len) {
+            (Some(max), _) if count > max => Some((String::from("arguments"), max)),
+            (_, Some(max)) if len > max => Some((String::from("bytes per argument"), max)),
+            _ => None,
+        }
+    }
+
+    /// Checks the number of tokens produced so far.
+    pub(crate) fn check_tokens(&self, count: usize) -> Option<(String, usize)> {
+        match self.tokens {
+            Some(max) if count > max => Some((String::from("tokens"), max)),
+            _ => None,
+        }
+    }
+
+    /// Determines if comparing `word` against every word in `bank` stays within
+    /// the suggestion budget.
+    pub(crate) fn allows_suggestion<T: AsRef<str>>(&self, word: &str, bank: &[T]) -> bool {
+        match self.suggestion_work {
+            Some(max) => {
+                let work = bank
+                    .iter()
+                    .map(|w| (w.as_ref().len() + 1).saturating_mul(word.len() + 1))
+                    .fold(0usize, |acc, cells| acc.saturating_add(cells));
+                work <= max
+            }
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn check_sizes() {
+        let limits = Limits::new().max_args(2).max_arg_len(8).max_tokens(3);
+        assert_eq!(limits.check_arg(2, 8), None);
+        assert_eq!(limits.check_arg(3, 1), Some((String::from("arguments"), 2)));
+        assert_eq!(limits.check_arg(1, 9), Some((String::from("bytes per argument"), 8)));
+        assert_eq!(limits.check_tokens(3), None);
+        assert_eq!(limits.check_tokens(4), Some((String::from("tokens"), 3)));
+        assert_eq!(Limits::new().check_arg(usize::MAX, usize::MAX), None);
+    }
+
+    #[test]
+    fn suggestion_budget() {
+        let limits = Limits::new().max_suggestion_work(100);
+        assert_eq!(limits.allows_suggestion("add", &["sub", "mult"]), true);
+        assert_eq!(limits.allows_suggestion(&"x".repeat(99), &["sub"]), false);
+        assert_eq!(Limits::new().allows_suggestion(&"x".repeat(99), &["sub"]), true);
+    }
+}
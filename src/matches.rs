@@ -0,0 +1,405 @@
+//! A record of the arguments read successfully while parsing.
+//!
+//! A [Cli](crate::Cli) records every flag it counts and every value it parses
+//! by the argument's name, in the order they were read. When parsing fails
+//! midway, the [Matches] gathered so far describe what the user got right, so an
+//! interactive frontend can prefill a correction prompt.
+
+use crate::error::Error;
+use crate::json::Value;
+use std::fmt::{Debug, Display};
+
+/// The text shown in place of a secret value by default.
+pub(crate) const PLACEHOLDER: &str = "***";
+
+/// Where the value of an argument came from.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Source {
+    CommandLine,
+    Env,
+    Config,
+    /// Fixed by the application with [freeze_flag](crate::Cli::freeze_flag) or
+    /// [freeze_option](crate::Cli::freeze_option).
+    Frozen,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CommandLine => write!(f, "command-line"),
+            Self::Env => write!(f, "environment"),
+            Self::Config => write!(f, "config"),
+            Self::Frozen => write!(f, "frozen"),
+        }
+    }
+}
+
+/// The values and occurrences read for a single argument.
+#[derive(PartialEq, Clone)]
+pub struct Match {
+    name: String,
+    values: Vec<String>,
+    count: usize,
+    secret: bool,
+}
+
+impl Match {
+    pub fn get_name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    /// Returns the values in their canonical form, before they were parsed.
+    pub fn get_values(&self) -> &Vec<String> {
+        &self.values
+    }
+
+    /// Returns the number of times the argument was supplied.
+    pub fn get_count(&self) -> usize {
+        self.count
+    }
+
+    /// Checks if the argument's values are [secret](crate::arg::Positional::secret).
+    pub fn is_secret(&self) -> bool {
+        self.secret
+    }
+}
+
+impl Debug for Match {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // secret values must not leak into debug dumps
+        let values: Vec<&str> = match self.secret {
+            true => vec![PLACEHOLDER; self.values.len()],
+            false => self.values.iter().map(|v| v.as_ref()).collect(),
+        };
+        f.debug_struct("Match")
+            .field("name", &self.name)
+            .field("values", &values)
+            .field("count", &self.count)
+            .field("secret", &self.secret)
+            .finish()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Matches {
+    entries: Vec<Match>,
+    placeholder: String,
+}
+
+impl Default for Matches {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            placeholder: String::from(PLACEHOLDER),
+        }
+    }
+}
+
+impl Matches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the text shown in place of secret values.
+    pub(crate) fn set_placeholder<T: AsRef<str>>(&mut self, placeholder: T) -> () {
+        self.placeholder = placeholder.as_ref().to_string();
+    }
+
+    pub fn get_placeholder(&self) -> &str {
+        self.placeholder.as_ref()
+    }
+
+    fn entry(&mut self, name: &str) -> &mut Match {
+        match self.entries.iter().position(|m| m.name == name) {
+            Some(i) => &mut self.entries[i],
+            None => {
+                self.entries.push(Match {
+                    name: name.to_string(),
+                    values: Vec::new(),
+                    count: 0,
+                    secret: false,
+                });
+                self.entries.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// Records a `value` read for the argument `name`.
+    pub(crate) fn push_value<T: AsRef<str>>(&mut self, name: &str, value: T) -> () {
+        let m = self.entry(name);
+        m.values.push(value.as_ref().to_string());
+        m.count += 1;
+    }
+
+    /// Records a `value` read for the argument `name` that must not be reported.
+    pub(crate) fn push_secret<T: AsRef<str>>(&mut self, name: &str, value: T) -> () {
+        self.push_value(name, value);
+        self.entry(name).secret = true;
+    }
+
+    /// Records `n` occurrences of the flag `name`.
+    pub(crate) fn push_count(&mut self, name: &str, n: usize) -> () {
+        self.entry(name).count += n;
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Match> {
+        self.entries.iter().find(|m| m.name == name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Returns the first value read for the argument `name`.
+    pub fn get_value(&self, name: &str) -> Option<&str> {
+        self.get(name)?.values.first().map(|v| v.as_ref())
+    }
+
+    /// Returns every value read for the argument `name`.
+    pub fn get_values(&self, name: &str) -> &[String] {
+        self.get(name).map(|m| m.values.as_slice()).unwrap_or_default()
+    }
+
+    /// Returns the number of times the argument `name` was supplied.
+    pub fn get_count(&self, name: &str) -> usize {
+        self.get(name).map(|m| m.count).unwrap_or(0)
+    }
+
+    /// Iterates over the arguments in the order they were first read.
+    pub fn iter(&self) -> impl Iterator<Item = &Match> {
+        self.entries.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns a copy with the values of secret arguments replaced by the
+    /// placeholder, safe to log or store.
+    pub fn redacted(&self) -> Self {
+        let mut copy = self.clone();
+        for m in copy.entries.iter_mut().filter(|m| m.secret == true) {
+            m.values.iter_mut().for_each(|v| *v = self.placeholder.clone());
+        }
+        copy
+    }
+
+    /// Serializes the matches as a JSON object of each argument's values and
+    /// count, with secret values redacted.
+    pub fn to_json(&self) -> String {
+        let entries = self
+            .redacted()
+            .entries
+            .iter()
+            .map(|m| {
+                let doc = Value::object(vec![
+                    ("values", Value::strs(&m.values)),
+                    ("count", Value::Int(m.count as i64)),
+                ]);
+                (m.name.clone(), doc)
+            })
+            .collect();
+        Value::Object(entries).to_string()
+    }
+}
+
+/// What consumed a single token of the command-line.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Consumption {
+    index: usize,
+    text: String,
+    arg: Option<String>,
+    fragment: Option<String>,
+    consumed: bool,
+}
+
+impl Consumption {
+    pub(crate) fn new(
+        index: usize,
+        text: String,
+        arg: Option<String>,
+        fragment: Option<String>,
+        consumed: bool,
+    ) -> Self {
+        Self {
+            index,
+            text,
+            arg,
+            fragment,
+            consumed,
+        }
+    }
+
+    /// Returns the index of the argument the token was lexed from, where the
+    /// program name is at index 0.
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    pub fn get_text(&self) -> &str {
+        self.text.as_ref()
+    }
+
+    /// Returns the argument that consumed the token, if any.
+    pub fn get_arg(&self) -> Option<&str> {
+        self.arg.as_deref()
+    }
+
+    /// Returns the type name of the fragment that consumed the token, if it was
+    /// read as one.
+    pub fn get_fragment(&self) -> Option<&str> {
+        self.fragment.as_deref()
+    }
+
+    /// Checks if the token was consumed at all.
+    pub fn is_consumed(&self) -> bool {
+        self.consumed
+    }
+}
+
+/// The lexical kind of a token in the command-line.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum TokenKind {
+    /// An argument standing on its own, such as a positional or a separate value.
+    Argument,
+    /// A value attached to a flag or switch, as in `--jobs=4` or `-j4`.
+    AttachedArgument,
+    /// A flag, as in `--verbose`.
+    Flag,
+    /// A single switch, which may be one of a cluster, as in `-v`.
+    Switch,
+    /// A switch prefix with no name before its value, as in `-=value`.
+    EmptySwitch,
+    /// The `--` ending the arguments to parse.
+    Terminator,
+    /// An argument after the terminator, left for
+    /// [check_remainder](crate::Cli::check_remainder).
+    Remainder,
+}
+
+/// A token of the command-line that has not been consumed yet.
+///
+/// Its `Debug` form is a single line, such as `Flag(1, "--verbose")`, so a list
+/// of tokens pretty-printed with `{:#?}` shows one token per line.
+#[derive(PartialEq, Clone)]
+pub struct RemainingToken {
+    index: usize,
+    kind: TokenKind,
+    text: String,
+}
+
+impl RemainingToken {
+    pub(crate) fn new(index: usize, kind: TokenKind, text: String) -> Self {
+        Self { index, kind, text }
+    }
+
+    /// Returns the index of the argument the token was lexed from, where the
+    /// program name is at index 0.
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    pub fn get_kind(&self) -> TokenKind {
+        self.kind
+    }
+
+    /// Returns the text the token was lexed as, such as `-v` for a switch taken
+    /// from the cluster `-vq`.
+    pub fn get_text(&self) -> &str {
+        self.text.as_ref()
+    }
+}
+
+impl Debug for RemainingToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}({}, {:?})", self.kind, self.index, self.text)
+    }
+}
+
+/// A failed parse along with the arguments read successfully before the error.
+#[derive(Debug)]
+pub struct Partial {
+    error: Error,
+    matches: Matches,
+}
+
+impl Partial {
+    pub(crate) fn new(error: Error, matches: Matches) -> Self {
+        Self { error, matches }
+    }
+
+    pub fn get_error(&self) -> &Error {
+        &self.error
+    }
+
+    pub fn get_matches(&self) -> &Matches {
+        &self.matches
+    }
+
+    /// Separates the error from the matches.
+    pub fn into_parts(self) -> (Error, Matches) {
+        (self.error, self.matches)
+    }
+}
+
+impl Display for Partial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for Partial {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn record_matches() {
+        let mut m = Matches::new();
+        m.push_count("verbose", 2);
+        m.push_value("file", "a.txt");
+        m.push_value("file", "b.txt");
+        assert_eq!(m.get_count("verbose"), 2);
+        assert_eq!(m.get_value("verbose"), None);
+        assert_eq!(m.get_value("file"), Some("a.txt"));
+        assert_eq!(m.get_values("file"), &["a.txt", "b.txt"]);
+        assert_eq!(m.get_count("file"), 2);
+        assert_eq!(m.contains("quiet"), false);
+        assert_eq!(
+            m.iter().map(|m| m.get_name()).collect::<Vec<&str>>(),
+            vec!["verbose", "file"]
+        );
+    }
+
+    #[test]
+    fn redact_secrets() {
+        let mut m = Matches::new();
+        m.push_value("user", "admin");
+        m.push_secret("token", "hunter2");
+        // the application still reads the real value
+        assert_eq!(m.get_value("token"), Some("hunter2"));
+        assert_eq!(m.redacted().get_value("token"), Some("***"));
+        assert_eq!(m.redacted().get_value("user"), Some("admin"));
+        assert_eq!(format!("{:?}", m).contains("hunter2"), false);
+
+        m.set_placeholder("<redacted>");
+        assert_eq!(
+            m.to_json(),
+            r#"{
+  "user": {
+    "values": [
+      "admin"
+    ],
+    "count": 1
+  },
+  "token": {
+    "values": [
+      "<redacted>"
+    ],
+    "count": 1
+  }
+}"#
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! Counters reported while parsing.
+//!
+//! A [Cli](crate::Cli) reports what it does to a [MetricsSink] so an embedding
+//! application can feed its own metrics system without this crate depending on
+//! one. Nothing is recorded unless a sink is set with
+//! [Cli::metrics](crate::Cli::metrics).
+
+use std::fmt::Display;
+
+/// A quantity counted while parsing.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Metric {
+    /// Tokens lexed from the command-line, reported once by
+    /// [tokenize](crate::Cli::tokenize).
+    Tokens,
+    /// Spelling suggestions computed for unknown words.
+    Suggestions,
+    /// Values parsed for arguments.
+    Values,
+    /// Parsing failures, not counting requests for help and other
+    /// informational errors.
+    Errors,
+    /// Warnings collected while parsing.
+    Warnings,
+}
+
+impl Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tokens => write!(f, "tokens"),
+            Self::Suggestions => write!(f, "suggestions"),
+            Self::Values => write!(f, "values"),
+            Self::Errors => write!(f, "errors"),
+            Self::Warnings => write!(f, "warnings"),
+        }
+    }
+}
+
+/// Receives the counters reported while parsing.
+///
+/// Any `Fn(Metric, usize)` closure is a sink.
+pub trait MetricsSink: Send + Sync {
+    /// Adds `n` to the counter for `metric`.
+    fn record(&self, metric: Metric, n: usize) -> ();
+}
+
+impl<F: Fn(Metric, usize) + Send + Sync> MetricsSink for F {
+    fn record(&self, metric: Metric, n: usize) -> () {
+        self(metric, n)
+    }
+}
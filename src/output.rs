@@ -0,0 +1,522 @@
+//! Writers for help text and command output that handle closed pipes
+//! consistently.
+//!
+//! When output is piped into a program such as `head` that exits early, later
+//! writes fail with a broken pipe. The standard `print!` macros panic in that
+//! case; an [Output] instead applies its [PipePolicy].
+
+use crate::source;
+use crate::style::{self, Theme};
+use std::fmt::Display;
+use std::io::{self, IsTerminal, Stderr, Stdout, Write};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+/// Environment variable naming the program to page long text through.
+const PAGER: &str = "PAGER";
+/// Pager used when `PAGER` is unset, keeping colors with `-R`.
+const DEFAULT_PAGER: &str = "less -R";
+/// Environment variable some shells export with the height of the terminal.
+const LINES: &str = "LINES";
+/// Environment variable some shells export with the width of the terminal.
+const COLUMNS: &str = "COLUMNS";
+
+mod exit_code {
+    // conventional status of a process terminated by SIGPIPE (128 + 13)
+    pub const BROKEN_PIPE: u8 = 141;
+}
+
+/// The action taken when the reader of an [Output] has closed the pipe.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum PipePolicy {
+    /// Exit the process quietly with the given code.
+    Exit(u8),
+    /// Discard this and all following writes.
+    Ignore,
+    /// Return the error to the caller.
+    Propagate,
+}
+
+impl Default for PipePolicy {
+    fn default() -> Self {
+        Self::Exit(exit_code::BROKEN_PIPE)
+    }
+}
+
+#[derive(Debug)]
+pub struct Output<W: Write> {
+    inner: W,
+    policy: PipePolicy,
+    closed: bool,
+}
+
+impl Output<Stdout> {
+    /// Creates an output writing to the standard output stream.
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+}
+
+impl Output<Stderr> {
+    /// Creates an output writing to the standard error stream.
+    pub fn stderr() -> Self {
+        Self::new(io::stderr())
+    }
+}
+
+impl<W: Write> Output<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            policy: PipePolicy::default(),
+            closed: false,
+        }
+    }
+
+    /// Overrides the action taken when the pipe is closed.
+    ///
+    /// The default is to exit with code 141.
+    pub fn policy(mut self, p: PipePolicy) -> Self {
+        self.policy = p;
+        self
+    }
+
+    /// Writes `item` without a trailing newline.
+    pub fn print<T: Display>(&mut self, item: T) -> io::Result<()> {
+        write!(self, "{}", item)
+    }
+
+    /// Writes `item` followed by a newline.
+    pub fn println<T: Display>(&mut self, item: T) -> io::Result<()> {
+        writeln!(self, "{}", item)
+    }
+
+    /// Checks if the reader closed the pipe while writes were being ignored.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Applies the policy to an error from the underlying writer.
+    fn handle(&mut self, err: io::Error) -> io::Result<()> {
+        if err.kind() != io::ErrorKind::BrokenPipe {
+            return Err(err);
+        }
+        match self.policy {
+            PipePolicy::Exit(code) => std::process::exit(code as i32),
+            PipePolicy::Ignore => {
+                self.closed = true;
+                Ok(())
+            }
+            PipePolicy::Propagate => Err(err),
+        }
+    }
+}
+
+impl<W: Write> Write for Output<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.closed == true {
+            return Ok(buf.len());
+        }
+        match self.inner.write(buf) {
+            Ok(n) => Ok(n),
+            Err(e) => self.handle(e).map(|_| buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.closed == true {
+            return Ok(());
+        }
+        match self.inner.flush() {
+            Ok(()) => Ok(()),
+            Err(e) => self.handle(e),
+        }
+    }
+}
+
+/// Rows of text aligned into columns beneath a header.
+///
+/// Each column is as wide as its longest cell, and columns are separated by two
+/// spaces. Trailing whitespace is left off every line.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new<T: AsRef<str>>(header: &[T]) -> Self {
+        Self {
+            header: header.iter().map(|h| h.as_ref().to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row, padding or truncating it to the number of columns.
+    pub fn row<T: AsRef<str>>(mut self, cells: &[T]) -> Self {
+        let mut row: Vec<String> = cells.iter().map(|c| c.as_ref().to_string()).collect();
+        row.resize(self.header.len(), String::new());
+        self.rows.push(row);
+        self
+    }
+
+    pub fn get_rows(&self) -> &Vec<Vec<String>> {
+        &self.rows
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut widths: Vec<usize> = self.header.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (w, cell) in widths.iter_mut().zip(row) {
+                *w = (*w).max(cell.chars().count());
+            }
+        }
+        for row in std::iter::once(&self.header).chain(self.rows.iter()) {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, w)| format!("{:<w$}", cell, w = w))
+                .collect::<Vec<String>>()
+                .join("  ");
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// The destination behind a [Stream].
+#[derive(Debug)]
+enum Sink {
+    Stdout(Output<Stdout>),
+    Stderr(Output<Stderr>),
+    Buffer(Vec<u8>),
+    Discard,
+}
+
+/// A shareable handle to one of a [Context]'s output streams.
+///
+/// Clones of a handle write to the same destination.
+#[derive(Debug, Clone)]
+pub struct Stream {
+    sink: Arc<Mutex<Sink>>,
+}
+
+impl Stream {
+    fn new(sink: Sink) -> Self {
+        Self {
+            sink: Arc::new(Mutex::new(sink)),
+        }
+    }
+
+    /// Writes `item` without a trailing newline.
+    pub fn print<T: Display>(&mut self, item: T) -> io::Result<()> {
+        write!(self, "{}", item)
+    }
+
+    /// Writes `item` followed by a newline.
+    pub fn println<T: Display>(&mut self, item: T) -> io::Result<()> {
+        writeln!(self, "{}", item)
+    }
+
+    /// Removes and returns the text written so far if the stream is captured.
+    fn take(&self) -> String {
+        match &mut *self.sink.lock().unwrap() {
+            Sink::Buffer(b) => String::from_utf8_lossy(&std::mem::take(b)).into_owned(),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut *self.sink.lock().unwrap() {
+            Sink::Stdout(o) => o.write(buf),
+            Sink::Stderr(o) => o.write(buf),
+            Sink::Buffer(b) => b.write(buf),
+            Sink::Discard => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut *self.sink.lock().unwrap() {
+            Sink::Stdout(o) => o.flush(),
+            Sink::Stderr(o) => o.flush(),
+            Sink::Buffer(_) | Sink::Discard => Ok(()),
+        }
+    }
+}
+
+/// The output streams handed to a command while it executes.
+///
+/// Commands write through [stdout](Context::stdout) and [stderr](Context::stderr)
+/// instead of `println!` so the same command can write to the terminal or be
+/// captured in tests, and so quiet and color settings are applied in one place.
+#[derive(Debug, Clone)]
+pub struct Context {
+    stdout: Stream,
+    stderr: Stream,
+    color: bool,
+}
+
+impl Context {
+    /// Creates a context writing to the process's standard streams.
+    pub fn new() -> Self {
+        Self {
+            stdout: Stream::new(Sink::Stdout(Output::stdout())),
+            stderr: Stream::new(Sink::Stderr(Output::stderr())),
+            color: true,
+        }
+    }
+
+    /// Creates a context collecting all output into buffers.
+    ///
+    /// Colors are disabled so captured text can be compared directly.
+    pub fn captured() -> Self {
+        Self {
+            stdout: Stream::new(Sink::Buffer(Vec::new())),
+            stderr: Stream::new(Sink::Buffer(Vec::new())),
+            color: false,
+        }
+    }
+
+    /// Discards everything written to the standard output stream when `quiet` is set.
+    ///
+    /// Errors and warnings written to the standard error stream are kept.
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        if quiet == true {
+            self.stdout = Stream::new(Sink::Discard);
+        }
+        self
+    }
+
+    /// Sets whether commands should color their output.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn is_color_enabled(&self) -> bool {
+        self.color
+    }
+
+    pub fn stdout(&self) -> Stream {
+        self.stdout.clone()
+    }
+
+    pub fn stderr(&self) -> Stream {
+        self.stderr.clone()
+    }
+
+    /// Removes and returns the text captured from the standard output stream.
+    ///
+    /// Returns an empty string for contexts that are not [captured](Context::captured).
+    pub fn take_stdout(&self) -> String {
+        self.stdout.take()
+    }
+
+    /// Removes and returns the text captured from the standard error stream.
+    pub fn take_stderr(&self) -> String {
+        self.stderr.take()
+    }
+}
+
+/// A context that commands report warnings through while they execute, such as
+/// the failed attempts of a [Retry](crate::cmd::Retry).
+pub trait Warn {
+    /// Writes `message` labeled as a warning.
+    fn warn(&self, message: &str) -> ();
+}
+
+impl Warn for Context {
+    fn warn(&self, message: &str) -> () {
+        let enabled = self.color == true && style::should_color(&io::stderr());
+        let label = Theme::default().get_warning().apply("warning", enabled);
+        // a warning that cannot be written is not worth failing the command over
+        let _ = self.stderr().println(format!("{}: {}", label, message));
+    }
+}
+
+/// Commands run without a context write their warnings to the process's stderr.
+impl Warn for () {
+    fn warn(&self, message: &str) -> () {
+        Context::new().warn(message)
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Writes `text` through the user's pager when stdout is a terminal and the
+/// text is taller than it.
+///
+/// Returns `false` without writing anything when the text is not paged, such
+/// as when it fits on the screen or the pager cannot be started.
+pub(crate) fn page(text: &str) -> bool {
+    if io::stdout().is_terminal() == false {
+        return false;
+    }
+    match terminal_height() {
+        Some(height) if is_taller(text, height) == true => (),
+        _ => return false,
+    }
+    let words = pager_command(std::env::var(PAGER).ok());
+    let (program, args) = match words.split_first() {
+        Some(parts) => parts,
+        None => return false,
+    };
+    let mut child = match Command::new(program).args(args).stdin(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(_) => return false,
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // the user may quit the pager before reading everything
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait().is_ok()
+}
+
+/// Checks if `text` needs more than `height` rows, keeping one for the prompt
+/// shown after it.
+fn is_taller(text: &str, height: usize) -> bool {
+    text.lines().count() >= height
+}
+
+/// Splits the pager set in the environment into its program and arguments,
+/// falling back to the default when it is unset or blank.
+fn pager_command(var: Option<String>) -> Vec<String> {
+    match var {
+        Some(v) if v.trim().is_empty() == false => source::split_words(&v),
+        _ => source::split_words(DEFAULT_PAGER),
+    }
+}
+
+/// Returns the number of rows of the terminal, if it can be found.
+fn terminal_height() -> Option<usize> {
+    terminal_size(LINES, 0)
+}
+
+/// Returns the number of columns of the terminal stdout writes to, if it can be
+/// found.
+pub(crate) fn terminal_width() -> Option<usize> {
+    if io::stdout().is_terminal() == false {
+        return None;
+    }
+    terminal_size(COLUMNS, 1)
+}
+
+/// Reads one dimension of the terminal from the environment variable `var`,
+/// falling back to the `field` of what `stty size` reports.
+fn terminal_size(var: &str, field: usize) -> Option<usize> {
+    if let Some(n) = std::env::var(var).ok().and_then(|n| n.parse().ok()) {
+        return Some(n);
+    }
+    #[cfg(unix)]
+    {
+        // `stty size` reports the rows and columns of the terminal it reads from
+        let out = Command::new("stty")
+            .arg("size")
+            .stdin(std::fs::File::open("/dev/tty").ok()?)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())?;
+        String::from_utf8_lossy(&out.stdout)
+            .split_whitespace()
+            .nth(field)?
+            .parse()
+            .ok()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = field;
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn choose_pager() {
+        assert_eq!(pager_command(None), vec!["less", "-R"]);
+        assert_eq!(pager_command(Some(String::from("  "))), vec!["less", "-R"]);
+        assert_eq!(
+            pager_command(Some(String::from("most -s"))),
+            vec!["most", "-s"]
+        );
+        assert_eq!(is_taller("a\nb\nc\n", 3), true);
+        assert_eq!(is_taller("a\nb\n", 3), false);
+    }
+
+    /// A writer whose reader has gone away.
+    struct ClosedPipe;
+
+    impl Write for ClosedPipe {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::from(io::ErrorKind::BrokenPipe))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Err(io::Error::from(io::ErrorKind::BrokenPipe))
+        }
+    }
+
+    #[test]
+    fn broken_pipe_policies() {
+        let mut out = Output::new(ClosedPipe).policy(PipePolicy::Ignore);
+        assert!(out.println("usage: orbit <command>").is_ok());
+        assert!(out.flush().is_ok());
+        assert_eq!(out.is_closed(), true);
+
+        let mut out = Output::new(ClosedPipe).policy(PipePolicy::Propagate);
+        assert_eq!(
+            out.print("text").unwrap_err().kind(),
+            io::ErrorKind::BrokenPipe
+        );
+
+        let mut out = Output::new(Vec::new());
+        out.println(10).unwrap();
+        assert_eq!(out.into_inner(), b"10\n");
+    }
+
+    #[test]
+    fn render_table() {
+        let table = Table::new(&["check", "status", "detail"])
+            .row(&["terminal", "pass"])
+            .row(&["config", "fail", "unknown key 'colour'"]);
+        assert_eq!(
+            table.to_string(),
+            "\
+check     status  detail
+terminal  pass
+config    fail    unknown key 'colour'
+"
+        );
+    }
+
+    #[test]
+    fn capture_context() {
+        let ctx = Context::captured();
+        ctx.stdout().println("9 + 10 = 19").unwrap();
+        ctx.stderr().print("warning: overflow").unwrap();
+        assert_eq!(ctx.take_stdout(), "9 + 10 = 19\n");
+        assert_eq!(ctx.take_stderr(), "warning: overflow");
+        assert_eq!(ctx.take_stdout(), "");
+        assert_eq!(ctx.is_color_enabled(), false);
+
+        let ctx = Context::captured().quiet(true);
+        ctx.stdout().println("hidden").unwrap();
+        ctx.stderr().println("shown").unwrap();
+        assert_eq!(ctx.take_stdout(), "");
+        assert_eq!(ctx.take_stderr(), "shown\n");
+    }
+}
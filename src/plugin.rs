@@ -0,0 +1,352 @@
+//! Subcommands contributed by plugins.
+//!
+//! A plugin implements [CommandProvider] for each subcommand it adds and hands
+//! them to a [Registry] from its registration function. Providers can be
+//! registered directly, or, with the `plugin` feature, loaded from dynamic
+//! libraries that export a [Declaration] with [export_plugin](crate::export_plugin):
+//!
+//! ```ignore
+//! fn register(registry: &mut clif::plugin::Registry) {
+//!     registry.register(Box::new(Provide::<Fmt>::new(Spec::new("fmt"))));
+//! }
+//!
+//! clif::export_plugin!(register);
+//! ```
+//!
+//! Trait objects are not ABI-stable across compilers, so a library is only
+//! loaded when it was built against the same [ABI_VERSION] and version of this
+//! crate. Failures to load a library and panics raised by a plugin never abort
+//! the host application.
+
+use crate::cli::Cli;
+use crate::command::{ExitStatus, Runner};
+use crate::crash::panic_message;
+use crate::error::{exit_code, Error, ErrorContext, ErrorKind};
+use crate::output::Context;
+use crate::spec::Spec;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(feature = "plugin")]
+use std::path::Path;
+
+/// Version of the interface between a host application and its plugins.
+///
+/// This changes whenever [CommandProvider], [Declaration], or [Registry::register]
+/// change in a way that breaks previously built plugins.
+pub const ABI_VERSION: u32 = 1;
+
+/// Version of this crate a plugin must be built against.
+pub const CLIF_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Name of the [Declaration] a dynamic library exports.
+pub const SYMBOL: &str = "CLIF_PLUGIN";
+
+/// A subcommand contributed by a plugin.
+pub trait CommandProvider: Send + Sync {
+    /// Describes the subcommand, whose name selects it on the command-line.
+    fn spec(&self) -> Spec;
+
+    /// Parses the subcommand's arguments from `cli` and executes it, returning
+    /// the code to exit the process with.
+    fn run(&self, cli: &mut Cli, context: &Context) -> Result<u8, Error>;
+}
+
+/// A [CommandProvider] for a [Runner] type.
+pub struct Provide<T> {
+    spec: Spec,
+    runner: PhantomData<fn() -> T>,
+}
+
+impl<T> Provide<T> {
+    pub fn new(spec: Spec) -> Self {
+        Self {
+            spec,
+            runner: PhantomData,
+        }
+    }
+}
+
+impl<T: Runner<Context>> CommandProvider for Provide<T>
+where
+    T::Status: ExitStatus,
+{
+    fn spec(&self) -> Spec {
+        self.spec.clone()
+    }
+
+    fn run(&self, cli: &mut Cli, context: &Context) -> Result<u8, Error> {
+        let app = T::from_cli(cli)?;
+        let status = app.exec(context);
+        if let Some(reason) = status.failure() {
+            let _ = context.stderr().println(format!("error: {}", reason));
+        }
+        Ok(status.exit_code())
+    }
+}
+
+/// The signature of the function a plugin registers its providers with.
+pub type Register = fn(&mut Registry);
+
+/// The entry point a dynamic library exports under the name [SYMBOL].
+#[repr(C)]
+pub struct Declaration {
+    pub abi_version: u32,
+    pub clif_version: &'static str,
+    pub register: Register,
+}
+
+/// Exports a plugin's registration function from a dynamic library.
+#[macro_export]
+macro_rules! export_plugin {
+    ($register:expr) => {
+        #[no_mangle]
+        pub static CLIF_PLUGIN: $crate::plugin::Declaration = $crate::plugin::Declaration {
+            abi_version: $crate::plugin::ABI_VERSION,
+            clif_version: $crate::plugin::CLIF_VERSION,
+            register: $register,
+        };
+    };
+}
+
+/// The collection of subcommands contributed by plugins.
+#[derive(Default)]
+pub struct Registry {
+    providers: Vec<Box<dyn CommandProvider>>,
+    warnings: Vec<String>,
+    // libraries must outlive the providers they contributed, so they are dropped last
+    #[cfg(feature = "plugin")]
+    libraries: Vec<libloading::Library>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a subcommand.
+    ///
+    /// A provider whose name is already registered is skipped with a warning.
+    pub fn register(&mut self, provider: Box<dyn CommandProvider>) -> () {
+        let name = provider.spec().get_name().to_string();
+        match self.find(&name).is_some() {
+            true => self
+                .warnings
+                .push(format!("plugin command '{}' is already registered", name)),
+            false => self.providers.push(provider),
+        }
+    }
+
+    /// Loads the plugin declared by the dynamic library at `path`.
+    ///
+    /// Returns false and records a warning if the library cannot be loaded, was
+    /// built for a different interface, or panics while registering.
+    #[cfg(feature = "plugin")]
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        let path = path.as_ref();
+        match self.try_load(path) {
+            Ok(()) => true,
+            Err(reason) => {
+                self.warnings.push(format!(
+                    "failed to load plugin '{}': {}",
+                    path.display(),
+                    reason
+                ));
+                false
+            }
+        }
+    }
+
+    #[cfg(feature = "plugin")]
+    fn try_load(&mut self, path: &Path) -> Result<(), String> {
+        // SAFETY: loading a library runs its initializers, which is the trust the
+        // host application places in its plugin directory
+        let library = unsafe { libloading::Library::new(path) }.map_err(|e| e.to_string())?;
+        let register = {
+            // SAFETY: the symbol is declared by `export_plugin` with this type
+            let decl = unsafe { library.get::<*const Declaration>(SYMBOL.as_bytes()) }
+                .map_err(|e| e.to_string())?;
+            let decl: &Declaration = unsafe { &**decl };
+            if decl.abi_version != ABI_VERSION {
+                return Err(format!(
+                    "built for ABI version {} but version {} is required",
+                    decl.abi_version, ABI_VERSION
+                ));
+            }
+            if decl.clif_version != CLIF_VERSION {
+                return Err(format!(
+                    "built against clif {} but version {} is required",
+                    decl.clif_version, CLIF_VERSION
+                ));
+            }
+            decl.register
+        };
+        let result = panic::catch_unwind(AssertUnwindSafe(|| register(self)));
+        // keep the library loaded even after a panic since it may have registered providers
+        self.libraries.push(library);
+        result.map_err(|p| format!("panicked while registering: {}", panic_message(&p)))
+    }
+
+    /// Loads every dynamic library in `dir`, returning how many were loaded.
+    ///
+    /// A directory that cannot be read loads no plugins.
+    #[cfg(feature = "plugin")]
+    pub fn load_dir<P: AsRef<Path>>(&mut self, dir: P) -> usize {
+        let mut paths: Vec<_> = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.extension().and_then(|x| x.to_str()) == Some(std::env::consts::DLL_EXTENSION)
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        paths.sort();
+        paths.into_iter().filter(|p| self.load(p)).count()
+    }
+
+    pub fn find(&self, name: &str) -> Option<&dyn CommandProvider> {
+        self.providers
+            .iter()
+            .find(|p| p.spec().get_name() == name)
+            .map(|p| p.as_ref())
+    }
+
+    /// Returns the names of the registered subcommands, for matching them with
+    /// [match_command](Cli::match_command).
+    pub fn get_names(&self) -> Vec<String> {
+        self.providers
+            .iter()
+            .map(|p| p.spec().get_name().to_string())
+            .collect()
+    }
+
+    /// Runs the subcommand `name`, or returns `None` if no plugin provides it.
+    ///
+    /// A panic raised by the plugin is returned as a `PluginPanic` error instead of
+    /// unwinding into the host application.
+    pub fn run(&self, name: &str, cli: &mut Cli, context: &Context) -> Option<Result<u8, Error>> {
+        let provider = self.find(name)?;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| provider.run(cli, context)));
+        Some(result.unwrap_or_else(|p| {
+            Err(Error::new(
+                None,
+                ErrorKind::PluginPanic,
+                ErrorContext::PluginPanic(name.to_string(), panic_message(&p)),
+                false,
+            )
+            .with_exit_code(exit_code::BAD))
+        }))
+    }
+
+    /// Returns the problems found while registering and loading plugins.
+    pub fn warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
+
+    /// Adds the problems found while registering and loading plugins to the
+    /// [warnings](Cli::warnings) of `cli`.
+    pub fn report(&self, cli: &mut Cli) -> () {
+        for w in &self.warnings {
+            cli.warn(w);
+        }
+    }
+}
+
+impl Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Registry")
+            .field("providers", &self.get_names())
+            .field("warnings", &self.warnings)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arg::Positional;
+    use crate::command::{Command, FromCli};
+
+    #[derive(Debug)]
+    struct Echo {
+        word: String,
+    }
+
+    impl FromCli for Echo {
+        fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+            let word = cli.require_positional(Positional::new("word"))?;
+            cli.is_empty()?;
+            Ok(Echo { word })
+        }
+    }
+
+    impl Command<Context> for Echo {
+        type Status = u8;
+
+        fn exec(&self, context: &Context) -> Self::Status {
+            let _ = context.stdout().println(&self.word);
+            3
+        }
+    }
+
+    impl Runner<Context> for Echo {}
+
+    struct Faulty;
+
+    impl CommandProvider for Faulty {
+        fn spec(&self) -> Spec {
+            Spec::new("faulty")
+        }
+
+        fn run(&self, _: &mut Cli, _: &Context) -> Result<u8, Error> {
+            panic!("out of cheese")
+        }
+    }
+
+    fn cli(args: &[&str]) -> Cli {
+        Cli::new().tokenize(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn registry() {
+        let mut registry = Registry::new();
+        registry.register(Box::new(Provide::<Echo>::new(Spec::new("echo"))));
+        registry.register(Box::new(Faulty));
+        registry.register(Box::new(Provide::<Echo>::new(Spec::new("echo"))));
+        assert_eq!(registry.get_names(), vec!["echo", "faulty"]);
+        assert_eq!(
+            registry.warnings(),
+            &vec![String::from("plugin command 'echo' is already registered")]
+        );
+
+        let context = Context::captured();
+        let mut c = cli(&["app", "echo", "hi"]);
+        let name = c.match_command(&registry.get_names()).unwrap();
+        assert_eq!(registry.run(&name, &mut c, &context).unwrap().unwrap(), 3);
+        assert_eq!(context.take_stdout(), "hi\n");
+
+        let mut c = cli(&["app", "faulty"]);
+        let err = registry.run("faulty", &mut c, &context).unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PluginPanic);
+        assert_eq!(err.exit_code(), 101);
+        assert_eq!(
+            err.to_string(),
+            "command 'faulty' provided by a plugin panicked: out of cheese"
+        );
+        assert_eq!(registry.run("missing", &mut c, &context).is_none(), true);
+
+        registry.report(&mut c);
+        assert_eq!(c.warnings().len(), 1);
+    }
+
+    #[cfg(feature = "plugin")]
+    #[test]
+    fn load_failures() {
+        let mut registry = Registry::new();
+        assert_eq!(registry.load("/nonexistent/libplugin.so"), false);
+        assert!(registry.warnings()[0].starts_with("failed to load plugin '/nonexistent/libplugin.so': "));
+        assert_eq!(registry.load_dir("/nonexistent"), 0);
+    }
+}
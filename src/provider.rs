@@ -0,0 +1,123 @@
+//! Ready-made sources of values for validating and completing arguments.
+
+use std::path::{Path, PathBuf};
+
+/// The entries of a directory whose file names match a pattern, such as the
+/// profiles in `~/.app/profiles/*.toml`.
+///
+/// The pattern may contain a single `*` wildcard. The text matched by the
+/// wildcard is the value a user supplies, so the entry `dev.toml` is selected by
+/// the value `dev` under the pattern `*.toml`. A pattern without a wildcard
+/// matches file names exactly.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DirEntries {
+    dir: PathBuf,
+    pattern: String,
+}
+
+impl DirEntries {
+    pub fn new<P: AsRef<Path>, T: AsRef<str>>(dir: P, pattern: T) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            pattern: pattern.as_ref().to_string(),
+        }
+    }
+
+    /// Returns the value selecting each matching entry, sorted alphabetically.
+    ///
+    /// A directory that cannot be read has no candidates.
+    pub fn candidates(&self) -> Vec<String> {
+        let mut values: Vec<String> = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| self.capture(&e.file_name().to_string_lossy()))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        values.sort();
+        values
+    }
+
+    /// Returns the candidates beginning with `prefix` for completing a partial value.
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        self.candidates()
+            .into_iter()
+            .filter(|c| c.starts_with(prefix))
+            .collect()
+    }
+
+    /// Returns the path of the entry selected by `value`.
+    pub fn resolve(&self, value: &str) -> PathBuf {
+        self.dir.join(self.pattern.replacen('*', value, 1))
+    }
+
+    /// Creates a validator accepting only values that select an existing entry.
+    ///
+    /// The returned function can be given to [Positional::validator](crate::arg::Positional::validator)
+    /// or [Optional::validator](crate::arg::Optional::validator).
+    pub fn validator(self) -> impl Fn(&str) -> Result<(), String> + Send + Sync + 'static {
+        move |value: &str| {
+            let candidates = self.candidates();
+            match candidates.iter().any(|c| c == value) {
+                true => Ok(()),
+                false if candidates.is_empty() => Err(format!(
+                    "no entries match '{}'",
+                    self.dir.join(&self.pattern).display()
+                )),
+                false => Err(format!("expected one of: {}", candidates.join(", "))),
+            }
+        }
+    }
+
+    /// Creates a completer offering the values that select an existing entry.
+    ///
+    /// The returned function can be given to [Positional::candidates](crate::arg::Positional::candidates)
+    /// or [Optional::candidates](crate::arg::Optional::candidates).
+    pub fn completer(self) -> impl Fn(&str) -> Vec<String> + Send + Sync + 'static {
+        move |prefix: &str| self.complete(prefix)
+    }
+
+    /// Returns the text matched by the wildcard if `name` fits the pattern.
+    fn capture(&self, name: &str) -> Option<String> {
+        match self.pattern.split_once('*') {
+            Some((prefix, suffix)) => {
+                let rest = name.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                match rest.is_empty() {
+                    true => None,
+                    false => Some(rest.to_string()),
+                }
+            }
+            None => match name == self.pattern {
+                true => Some(name.to_string()),
+                false => None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dir_entries() {
+        let dir = std::env::temp_dir().join(format!("clif-provider-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["dev.toml", "prod.toml", "notes.txt", ".toml"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+        let profiles = DirEntries::new(&dir, "*.toml");
+        assert_eq!(profiles.candidates(), vec!["dev", "prod"]);
+        assert_eq!(profiles.complete("d"), vec!["dev"]);
+        assert_eq!(profiles.resolve("dev"), dir.join("dev.toml"));
+        assert_eq!(profiles.clone().completer()("p"), vec!["prod"]);
+
+        let check = profiles.validator();
+        assert_eq!(check("prod"), Ok(()));
+        assert_eq!(check("notes"), Err(String::from("expected one of: dev, prod")));
+
+        let check = DirEntries::new(&dir, "*.yaml").validator();
+        assert!(check("dev").unwrap_err().starts_with("no entries match"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,275 @@
+//! Progress reporting for long-running commands.
+
+use crate::clock::Clock;
+use crate::output::{Stream, Table};
+use std::io;
+use std::process::{Command, ExitStatus};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A shared handle for a command to announce which phase of work it is in.
+///
+/// Clones of a report share the same phase, so one can be handed to a
+/// [Heartbeat] while the command keeps another.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    phase: Arc<Mutex<Option<String>>>,
+}
+
+impl Report {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Announces the phase of work the command is now in.
+    pub fn set_phase<T: AsRef<str>>(&self, phase: T) {
+        *self.phase.lock().unwrap() = Some(phase.as_ref().to_string());
+    }
+
+    pub fn clear_phase(&self) {
+        *self.phase.lock().unwrap() = None;
+    }
+
+    pub fn get_phase(&self) -> Option<String> {
+        self.phase.lock().unwrap().clone()
+    }
+}
+
+/// Periodically logs that a command is still running once it exceeds a
+/// duration threshold.
+///
+/// This keeps output flowing during long silent steps, which some CI systems
+/// would otherwise kill for inactivity.
+#[derive(Debug)]
+pub struct Heartbeat {
+    report: Report,
+    out: Stream,
+    threshold: Duration,
+    interval: Duration,
+    clock: Clock,
+}
+
+impl Heartbeat {
+    /// Creates a heartbeat that logs to `out` and reads the phase from `report`.
+    ///
+    /// By default the first beat is logged after 60 seconds and then every 30 seconds.
+    pub fn new(report: &Report, out: Stream) -> Self {
+        Self {
+            report: report.clone(),
+            out,
+            threshold: Duration::from_secs(60),
+            interval: Duration::from_secs(30),
+            clock: Clock::system(),
+        }
+    }
+
+    /// Sets how long the command runs before the first beat is logged.
+    pub fn threshold(mut self, d: Duration) -> Self {
+        self.threshold = d;
+        self
+    }
+
+    /// Sets the time between consecutive beats.
+    pub fn interval(mut self, d: Duration) -> Self {
+        self.interval = d;
+        self
+    }
+
+    /// Sets the [Clock] measuring the running time written in each beat, such
+    /// as the one returned by [Cli::get_clock](crate::Cli::get_clock).
+    ///
+    /// Beats are still scheduled by the system clock.
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Runs `work` while logging beats in the background, returning its result.
+    pub fn run<T, F: FnOnce() -> T>(self, work: F) -> T {
+        let (done, wait) = mpsc::channel::<()>();
+        let start = Instant::now();
+        let watch = self.clock.stopwatch();
+        let Heartbeat {
+            report,
+            mut out,
+            threshold,
+            interval,
+            ..
+        } = self;
+        let beats = std::thread::spawn(move || {
+            let mut next = threshold;
+            loop {
+                match wait.recv_timeout(next.saturating_sub(start.elapsed())) {
+                    Err(RecvTimeoutError::Timeout) => {
+                        let _ = out.println(Self::beat(watch.elapsed(), report.get_phase()));
+                        next += interval;
+                    }
+                    _ => break,
+                }
+            }
+        });
+        let result = work();
+        let _ = done.send(());
+        let _ = beats.join();
+        result
+    }
+
+    /// Writes the message logged for a single beat.
+    fn beat(elapsed: Duration, phase: Option<String>) -> String {
+        let secs = elapsed.as_secs();
+        let time = match secs >= 60 {
+            true => format!("{}m{:02}s", secs / 60, secs % 60),
+            false => format!("{}s", secs),
+        };
+        match phase {
+            Some(p) => format!("still running after {} ({})", time, p),
+            None => format!("still running after {}", time),
+        }
+    }
+}
+
+/// The durations of the phases of a command, printed as a summary when the
+/// standard `--timings` flag is supplied.
+///
+/// Parsing and executing the command are timed by [Cli::go](crate::Cli::go). A
+/// command can time its own steps, such as child processes, with the handle
+/// returned by [Cli::timings](crate::Cli::timings). Clones of the handle share
+/// the same records.
+#[derive(Debug, Clone, Default)]
+pub struct Timings {
+    phases: Arc<Mutex<Vec<(String, Duration)>>>,
+    clock: Clock,
+}
+
+impl PartialEq for Timings {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.phases, &other.phases)
+    }
+}
+
+impl Timings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [Clock] measuring the phases timed with [time](Timings::time).
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Records that `phase` took `elapsed`.
+    pub fn record<T: AsRef<str>>(&self, phase: T, elapsed: Duration) -> () {
+        self.phases
+            .lock()
+            .unwrap()
+            .push((phase.as_ref().to_string(), elapsed));
+    }
+
+    /// Runs `work` and records how long it took as `phase`, returning its result.
+    pub fn time<T, F: FnOnce() -> T>(&self, phase: &str, work: F) -> T {
+        let watch = self.clock.stopwatch();
+        let result = work();
+        self.record(phase, watch.elapsed());
+        result
+    }
+
+    /// Runs the child process `cmd` to completion and records how long it took
+    /// under the name of its program.
+    pub fn status(&self, cmd: &mut Command) -> io::Result<ExitStatus> {
+        let phase = format!("child '{}'", cmd.get_program().to_string_lossy());
+        self.time(&phase, || cmd.status())
+    }
+
+    /// Returns the recorded phases in the order they finished.
+    pub fn get_phases(&self) -> Vec<(String, Duration)> {
+        self.phases.lock().unwrap().clone()
+    }
+
+    /// Lists the duration of each phase in seconds.
+    pub fn to_table(&self) -> Table {
+        self.get_phases()
+            .iter()
+            .fold(Table::new(&["phase", "seconds"]), |table, (phase, elapsed)| {
+                table.row(&[phase.clone(), format!("{:.3}", elapsed.as_secs_f64())])
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::output::Context;
+
+    #[test]
+    fn beat_message() {
+        assert_eq!(
+            Heartbeat::beat(Duration::from_secs(95), Some(String::from("linking"))),
+            "still running after 1m35s (linking)"
+        );
+        assert_eq!(
+            Heartbeat::beat(Duration::from_millis(4200), None),
+            "still running after 4s"
+        );
+    }
+
+    #[test]
+    fn log_beats() {
+        let ctx = Context::captured();
+        let report = Report::new();
+        report.set_phase("compiling");
+        let value = Heartbeat::new(&report, ctx.stderr())
+            .threshold(Duration::from_millis(20))
+            .interval(Duration::from_millis(20))
+            .run(|| {
+                std::thread::sleep(Duration::from_millis(150));
+                7
+            });
+        assert_eq!(value, 7);
+        let log = ctx.take_stderr();
+        assert!(log.lines().count() >= 2);
+        assert!(log.lines().all(|l| l == "still running after 0s (compiling)"));
+
+        // short commands never log a beat
+        let ctx = Context::captured();
+        Heartbeat::new(&report, ctx.stderr()).run(|| ());
+        assert_eq!(ctx.take_stderr(), "");
+
+        // a frozen clock reports the same running time on every run
+        let ctx = Context::captured();
+        Heartbeat::new(&report, ctx.stderr())
+            .threshold(Duration::from_millis(1000))
+            .clock(Clock::frozen())
+            .run(|| std::thread::sleep(Duration::from_millis(1100)));
+        assert_eq!(ctx.take_stderr(), "still running after 0s (compiling)\n");
+    }
+
+    #[test]
+    fn record_timings() {
+        let timings = Timings::new();
+        let shared = timings.clone();
+        assert_eq!(timings.time("parse", || 3), 3);
+        shared.record("exec", Duration::from_millis(1250));
+        let phases = timings.get_phases();
+        assert_eq!(phases.len(), 2);
+        assert_eq!(phases[0].0, "parse");
+        assert_eq!(
+            timings.to_table().to_string().lines().last(),
+            Some("exec   1.250")
+        );
+        assert_eq!(timings, shared);
+        assert_ne!(timings, Timings::new());
+
+        let frozen = Timings::new().clock(Clock::frozen());
+        frozen.time("parse", || std::thread::sleep(Duration::from_millis(2)));
+        assert_eq!(frozen.to_table().to_string().lines().last(), Some("parse  0.000"));
+
+        #[cfg(unix)]
+        {
+            let status = timings.status(&mut Command::new("true")).unwrap();
+            assert_eq!(status.success(), true);
+            assert_eq!(timings.get_phases()[2].0, "child 'true'");
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Retrying commands whose failures may be temporary.
+//!
+//! A command opts in by implementing [Transient] to classify its failures. The
+//! application then parses and runs a `Retry<Fetch>` wherever it would have run
+//! `Fetch`.
+
+use crate::arg::Optional;
+use crate::cli::Cli;
+use crate::command::{Command, FromCli, Runner};
+use crate::error::Error;
+use crate::output::Warn;
+use std::time::Duration;
+
+mod tag {
+    pub const RETRIES: &str = "retries";
+    pub const RETRY_DELAY: &str = "retry-delay";
+}
+
+/// Number of seconds waited between attempts when `--retry-delay` is omitted.
+const DEFAULT_DELAY: u64 = 1;
+
+/// A [Command] whose failures may be temporary, such as dropped network
+/// connections, and are worth attempting again.
+pub trait Transient<T>: Command<T> {
+    /// Checks if the `status` of an attempt is a failure that may pass when
+    /// retried.
+    fn is_transient(&self, status: &Self::Status) -> bool;
+}
+
+/// Runs a [Transient] command again after failures it classifies as transient.
+///
+/// Parsing a `Retry` reads the standard `--retries <n>` and
+/// `--retry-delay <seconds>` options before the command's own arguments. The
+/// command is not retried unless `--retries` is supplied. Each failed attempt
+/// is reported as a warning through the command's context.
+#[derive(Debug, PartialEq)]
+pub struct Retry<T> {
+    command: T,
+    retries: usize,
+    delay: Duration,
+}
+
+impl<T> Retry<T> {
+    pub fn get_command(&self) -> &T {
+        &self.command
+    }
+
+    /// Returns the number of attempts made after the first one fails.
+    pub fn get_retries(&self) -> usize {
+        self.retries
+    }
+
+    pub fn get_delay(&self) -> Duration {
+        self.delay
+    }
+
+    pub fn into_inner(self) -> T {
+        self.command
+    }
+}
+
+impl<T: FromCli> FromCli for Retry<T> {
+    fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+        let retries = cli.check_option(Optional::new(tag::RETRIES).value("n"))?;
+        let delay = cli.check_option(Optional::new(tag::RETRY_DELAY).value("seconds"))?;
+        Ok(Self {
+            retries: retries.unwrap_or(0),
+            delay: Duration::from_secs(delay.unwrap_or(DEFAULT_DELAY)),
+            command: T::from_cli(cli)?,
+        })
+    }
+}
+
+impl<C: Warn, T: Transient<C>> Command<C> for Retry<T> {
+    type Status = T::Status;
+
+    fn exec(&self, context: &C) -> Self::Status {
+        let attempts = self.retries + 1;
+        let mut attempt = 1;
+        loop {
+            let status = self.command.exec(context);
+            if attempt == attempts || self.command.is_transient(&status) == false {
+                return status;
+            }
+            context.warn(&format!(
+                "attempt {} of {} failed; retrying in {}s",
+                attempt,
+                attempts,
+                self.delay.as_secs()
+            ));
+            std::thread::sleep(self.delay);
+            attempt += 1;
+        }
+    }
+}
+
+impl<C: Warn, T: Transient<C> + Runner<C>> Runner<C> for Retry<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arg::Positional;
+    use crate::output::Context;
+    use std::cell::Cell;
+
+    fn args<'a>(args: Vec<&'a str>) -> Box<dyn Iterator<Item = String> + 'a> {
+        Box::new(args.into_iter().map(|f| f.to_string()))
+    }
+
+    /// Counts down the failed attempts left before the network is reachable.
+    struct Network {
+        outages: Cell<usize>,
+        context: Context,
+    }
+
+    impl Network {
+        fn new(outages: usize) -> Self {
+            Self {
+                outages: Cell::new(outages),
+                context: Context::captured(),
+            }
+        }
+    }
+
+    impl Warn for Network {
+        fn warn(&self, message: &str) -> () {
+            self.context.warn(message)
+        }
+    }
+
+    /// Fails with a transient error until the network counts down to zero.
+    #[derive(Debug, PartialEq)]
+    struct Fetch {
+        url: String,
+    }
+
+    impl Command<Network> for Fetch {
+        type Status = Result<(), String>;
+
+        fn exec(&self, net: &Network) -> Self::Status {
+            match net.outages.get() {
+                0 => Ok(()),
+                n => {
+                    net.outages.set(n - 1);
+                    Err(format!("failed to reach {}", self.url))
+                }
+            }
+        }
+    }
+
+    impl Transient<Network> for Fetch {
+        fn is_transient(&self, status: &Self::Status) -> bool {
+            status.is_err()
+        }
+    }
+
+    impl Runner<Network> for Fetch {}
+
+    impl FromCli for Fetch {
+        fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+            let url = cli.require_positional(Positional::new("url"))?;
+            cli.is_empty()?;
+            Ok(Self { url })
+        }
+    }
+
+    #[test]
+    fn retry_transient_failures() {
+        let cli = Cli::new().tokenize(args(vec![
+            "fetch",
+            "--retries",
+            "3",
+            "--retry-delay",
+            "0",
+            "a.io",
+        ]));
+        let net = Network::new(2);
+        assert_eq!(cli.launch::<_, Retry<Fetch>>(&net).unwrap(), Ok(()));
+        assert_eq!(net.outages.get(), 0);
+        assert_eq!(
+            net.context.take_stderr(),
+            "warning: attempt 1 of 4 failed; retrying in 0s
+warning: attempt 2 of 4 failed; retrying in 0s
+"
+        );
+
+        // gives up once the retries are spent
+        let cli = Cli::new().tokenize(args(vec![
+            "fetch",
+            "--retries=1",
+            "--retry-delay=0",
+            "a.io",
+        ]));
+        let net = Network::new(5);
+        assert_eq!(
+            cli.launch::<_, Retry<Fetch>>(&net).unwrap(),
+            Err(String::from("failed to reach a.io"))
+        );
+        assert_eq!(net.outages.get(), 3);
+        assert_eq!(
+            net.context.take_stderr(),
+            "warning: attempt 1 of 2 failed; retrying in 0s\n"
+        );
+
+        // without the option a command runs once
+        let mut cli = Cli::new().tokenize(args(vec!["fetch", "a.io"]));
+        let retry = Retry::<Fetch>::from_cli(&mut cli).unwrap();
+        assert_eq!(retry.get_retries(), 0);
+        assert_eq!(retry.get_delay(), Duration::from_secs(1));
+        assert_eq!(retry.into_inner().url, "a.io");
+    }
+}
@@ -15,7 +15,44 @@ pub type Cost = usize;
 /// __space complexity__: O(nm)
 ///
 /// Note: Case sensitivity is not applied within the function.
+#[cfg(test)]
 fn sequence_alignment(s1: &str, s2: &str, gap_penalty: Cost, mismatch_penalty: Cost) -> Cost {
+    alignment(s1, s2, gap_penalty, mismatch_penalty, None)
+}
+
+/// Given two strings `s1` and `s2`, find a min-cost alignment where swapping
+/// two adjacent characters costs the `transposition_penalty` (the restricted
+/// Damerau-Levenshtein distance).
+///
+/// __time complexity__: O(nm)   
+/// __space complexity__: O(nm)
+///
+/// Note: Case sensitivity is not applied within the function.
+fn transposed_alignment(
+    s1: &str,
+    s2: &str,
+    gap_penalty: Cost,
+    mismatch_penalty: Cost,
+    transposition_penalty: Cost,
+) -> Cost {
+    alignment(
+        s1,
+        s2,
+        gap_penalty,
+        mismatch_penalty,
+        Some(transposition_penalty),
+    )
+}
+
+fn alignment(
+    s1: &str,
+    s2: &str,
+    gap_penalty: Cost,
+    mismatch_penalty: Cost,
+    transposition_penalty: Option<Cost>,
+) -> Cost {
+    let s1: Vec<char> = s1.chars().collect();
+    let s2: Vec<char> = s2.chars().collect();
     // create 2D cache filling 0th row and 0th col with gap penalties
     let mut lut = Vec::<Vec<Cost>>::with_capacity(s1.len() + 1);
     for i in 0..=s1.len() {
@@ -42,16 +79,20 @@ fn sequence_alignment(s1: &str, s2: &str, gap_penalty: Cost, mismatch_penalty: C
     };
     // note: enumeration starts at '0' but we want to avoid filling in those
     // indices because they were already computed (thus [i+1][j+1] is used).
-    let mut s1_it = s1.chars().enumerate();
-    while let Some((i, c1)) = s1_it.next() {
-        let mut s2_it = s2.chars().enumerate();
-        while let Some((j, c2)) = s2_it.next() {
+    for (i, c1) in s1.iter().enumerate() {
+        for (j, c2) in s2.iter().enumerate() {
             // choose minimum cost of 3 options
             lut[i + 1][j + 1] = min3(
                 mismatch_penalty * ((c1 != c2) as Cost) + lut[i][j],
                 gap_penalty + lut[i][j + 1],
                 gap_penalty + lut[i + 1][j],
             );
+            // a 4th option swaps this character with the previous one
+            if let Some(penalty) = transposition_penalty {
+                if i > 0 && j > 0 && c1 != c2 && *c1 == s2[j - 1] && s1[i - 1] == *c2 {
+                    lut[i + 1][j + 1] = lut[i + 1][j + 1].min(penalty + lut[i - 1][j - 1]);
+                }
+            }
         }
     }
     lut[s1.len()][s2.len()]
@@ -60,21 +101,44 @@ fn sequence_alignment(s1: &str, s2: &str, gap_penalty: Cost, mismatch_penalty: C
 /// Given a word `s` and a known set of words `bank`, determine which word has
 /// the minimum edit distance to the given word while being below the `threshold`.
 ///
-/// The `gap_penalty` and `mismatch penalty` for sequence alignment are internally set.
+/// The `gap_penalty`, `mismatch penalty`, and `transposition_penalty` for
+/// sequence alignment are internally set.
+#[cfg(test)]
 pub fn sel_min_edit_str<'a, T: AsRef<str>>(
     s: &str,
     bank: &'a [T],
     threshold: Cost,
 ) -> Option<&'a str> {
-    let (w, c) = bank
+    sel_min_edit_str_within(s, bank, |_| threshold)
+}
+
+/// Given a word `s` and a known set of words `bank`, determine which word has
+/// the minimum edit distance to the given word while being below the threshold
+/// computed for that word by `threshold`.
+pub fn sel_min_edit_str_within<'a, T: AsRef<str>, F: Fn(&str) -> Cost>(
+    s: &str,
+    bank: &'a [T],
+    threshold: F,
+) -> Option<&'a str> {
+    let (w, _) = bank
         .iter()
-        .map(|f| (f, sequence_alignment(s, f.as_ref(), 1, 1)))
+        .map(|f| (f, transposed_alignment(s, f.as_ref(), 1, 1, 1)))
+        .filter(|(f, c)| *c < threshold(f.as_ref()))
         .min_by(|x, y| x.1.cmp(&y.1))?;
-    if c < threshold {
-        Some(w.as_ref())
-    } else {
-        None
-    }
+    Some(w.as_ref())
+}
+
+/// Given a word `s` and a known set of words `bank`, select up to `n` words with
+/// the minimum edit distances to the given word, closest first.
+///
+/// Words with equal costs keep their order from the `bank`.
+pub fn sel_nearest_strs<'a, T: AsRef<str>>(s: &str, bank: &'a [T], n: usize) -> Vec<&'a str> {
+    let mut costs: Vec<(&str, Cost)> = bank
+        .iter()
+        .map(|f| (f.as_ref(), transposed_alignment(s, f.as_ref(), 1, 1, 1)))
+        .collect();
+    costs.sort_by(|x, y| x.1.cmp(&y.1));
+    costs.into_iter().take(n).map(|(w, _)| w).collect()
 }
 
 #[cfg(test)]
@@ -96,6 +160,20 @@ mod test {
         assert_eq!(sequence_alignment("ALPHA", "alpha", 2, 1), 5);
     }
 
+    #[test]
+    fn swapped_letters() {
+        assert_eq!(transposed_alignment("gte", "get", 1, 1, 1), 1);
+        assert_eq!(sequence_alignment("gte", "get", 1, 1), 2);
+        assert_eq!(transposed_alignment("--verbsoe", "--verbose", 1, 1, 1), 1);
+        assert_eq!(transposed_alignment("stop", "tops", 2, 1, 1), 4);
+        assert_eq!(transposed_alignment("ab", "ab", 1, 1, 1), 0);
+        assert_eq!(transposed_alignment("", "ba", 1, 1, 1), 2);
+
+        let bank = ["set", "get", "gets"];
+        assert_eq!(sel_nearest_strs("gte", &bank, 1), vec!["get"]);
+        assert_eq!(sel_min_edit_str("gte", &bank, 2), Some("get"));
+    }
+
     #[test]
     fn get_closest_word() {
         let bank: Vec<&str> = vec![];
@@ -108,5 +186,19 @@ mod test {
         assert_eq!(sel_min_edit_str("plug", &bank, 3), Some("plan"));
         assert_eq!(sel_min_edit_str("cck", &bank, 3), Some("check"));
         assert_eq!(sel_min_edit_str("digt", &bank, 3), Some("digit"));
+
+        // each word can be held to its own threshold
+        let by_length = |w: &str| w.len() / 4 + 1;
+        assert_eq!(sel_min_edit_str_within("rn", &bank, by_length), None);
+        assert_eq!(sel_min_edit_str_within("chek", &bank, by_length), Some("check"));
+        assert_eq!(sel_min_edit_str_within("confg", &bank, by_length), Some("config"));
+    }
+
+    #[test]
+    fn nearest_words() {
+        let bank = ["json", "yaml", "toml", "xml", "csv"];
+        assert_eq!(sel_nearest_strs("jsno", &bank, 1), vec!["json"]);
+        assert_eq!(sel_nearest_strs("yml", &bank, 2), vec!["yaml", "xml"]);
+        assert_eq!(sel_nearest_strs("txt", &bank, 9).len(), 5);
     }
 }
@@ -0,0 +1,123 @@
+//! Sources of the arguments given to a program.
+//!
+//! Front-ends differ in where their arguments come from, such as the process's
+//! environment, a line typed into a REPL, or a request received over RPC. Each
+//! is an [ArgSource] read with [Cli::read_args](crate::Cli::read_args), which
+//! handles the program name, decoding, and response files the same way for all
+//! of them.
+
+use std::ffi::OsString;
+
+/// A source of command-line arguments.
+pub trait ArgSource {
+    /// Takes the arguments from the source.
+    fn read(&mut self) -> Vec<OsString>;
+
+    /// Checks if the first argument read names the program, as `argv[0]` does.
+    fn has_program(&self) -> bool {
+        true
+    }
+}
+
+/// The arguments the process was started with.
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub struct Env;
+
+impl ArgSource for Env {
+    fn read(&mut self) -> Vec<OsString> {
+        std::env::args_os().collect()
+    }
+}
+
+/// A fixed list of arguments beginning with the program name, such as within a
+/// test.
+impl ArgSource for Vec<String> {
+    fn read(&mut self) -> Vec<OsString> {
+        self.drain(..).map(OsString::from).collect()
+    }
+}
+
+/// A line of text typed into a REPL, split into arguments as a shell would.
+///
+/// The line does not begin with the program name.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Line(String);
+
+impl Line {
+    pub fn new<T: AsRef<str>>(text: T) -> Self {
+        Self(text.as_ref().to_string())
+    }
+}
+
+impl ArgSource for Line {
+    fn read(&mut self) -> Vec<OsString> {
+        split_words(&self.0).into_iter().map(OsString::from).collect()
+    }
+
+    fn has_program(&self) -> bool {
+        false
+    }
+}
+
+/// Splits `text` into words separated by whitespace.
+///
+/// Whitespace within single or double quotes, or escaped with a backslash, is
+/// kept within the word. A backslash within single quotes is read literally.
+pub(crate) fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('\''), _) => word.get_or_insert_with(String::new).push(c),
+            (_, '\\') => {
+                if let Some(next) = chars.next() {
+                    word.get_or_insert_with(String::new).push(next);
+                }
+            }
+            (None, '\'') | (None, '"') => {
+                quote = Some(c);
+                // quotes begin a word even if it ends up empty
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => {
+                if let Some(w) = word.take() {
+                    words.push(w);
+                }
+            }
+            (_, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(w) = word {
+        words.push(w);
+    }
+    words
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_line() {
+        assert_eq!(
+            split_words("add 1  2\tthree"),
+            vec!["add", "1", "2", "three"]
+        );
+        assert_eq!(
+            split_words(r#"commit -m "fix the \"bug\"" --author='A. Name' path\ with\ spaces ''"#),
+            vec![
+                "commit",
+                "-m",
+                "fix the \"bug\"",
+                "--author=A. Name",
+                "path with spaces",
+                ""
+            ]
+        );
+        assert_eq!(split_words(r"'C:\dir'"), vec![r"C:\dir"]);
+        assert_eq!(split_words("   "), Vec::<String>::new());
+    }
+}
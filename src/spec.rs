@@ -0,0 +1,1237 @@
+//! A static description of a command tree for generating reference material.
+//!
+//! A [Spec] lists the same [Arg] definitions a command checks in its `from_cli`,
+//! along with a short description for each, so documentation stays in step with
+//! the parser's metadata.
+//!
+//! ```
+//! use clif::arg::{Flag, Positional};
+//! use clif::spec::Spec;
+//!
+//! let spec = Spec::new("orbit")
+//!     .summary("An hdl package manager.")
+//!     .flag(Flag::new("version"), "print the version")
+//!     .command(
+//!         Spec::new("new")
+//!             .summary("Create a new ip.")
+//!             .positional(Positional::new("ip"), "name of the ip"),
+//!     );
+//! assert!(spec.to_markdown().contains("## orbit new"));
+//! ```
+
+use crate::arg::{Arg, Flag, Optional, Positional};
+use crate::command::Availability;
+use crate::complete::{self, Shell};
+use crate::help::{self, Help};
+use crate::json::Value;
+
+pub use crate::compat::{diff, Change, Diff, DiffError, Impact};
+
+/// Version of the document written by [Spec::to_json].
+///
+/// The version is incremented whenever a field is renamed, removed, or changes
+/// meaning. New fields may be added without changing the version.
+pub const SCHEMA_VERSION: i64 = 1;
+
+/// An argument and the text describing it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Entry {
+    arg: Arg,
+    about: String,
+    details: Option<String>,
+    value_type: Option<String>,
+    required: bool,
+    arity: (usize, Option<usize>),
+}
+
+/// A line of generated help: the name, its summary, and its extended
+/// description, if shown.
+type Row<'a> = (String, &'a str, Option<&'a str>);
+
+impl Entry {
+    pub fn get_arg(&self) -> &Arg {
+        &self.arg
+    }
+
+    pub fn get_about(&self) -> &str {
+        self.about.as_ref()
+    }
+
+    pub fn get_details(&self) -> Option<&str> {
+        self.details.as_deref()
+    }
+
+    pub fn get_value_type(&self) -> Option<&str> {
+        self.value_type.as_deref()
+    }
+
+    /// Checks if the command fails without this argument.
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Returns the fewest and most values the argument takes, where no maximum
+    /// means any number.
+    pub fn get_arity(&self) -> (usize, Option<usize>) {
+        self.arity
+    }
+
+    /// Lists the entry in the help under `name`, with its details only in the
+    /// full help.
+    fn row(&self, name: String, long: bool) -> Row<'_> {
+        (name, self.about.as_ref(), self.details.as_deref().filter(|_| long))
+    }
+
+    fn to_json_value(&self) -> Value {
+        let (kind, name, value) = match &self.arg {
+            Arg::Flag(f) => ("flag", f.get_name(), None),
+            Arg::Optional(o) => (
+                "option",
+                o.get_flag().get_name(),
+                Some(o.get_positional().get_name()),
+            ),
+            Arg::Positional(p) => ("positional", p.get_name(), None),
+        };
+        let flag = self.arg.as_flag();
+        let meta = self.arg.as_positional().map(|p| p.get_meta());
+        let switch = flag.and_then(|f| f.get_switch()).map(|c| c.to_string());
+        let values = meta.map(|m| m.values.as_slice()).unwrap_or_default();
+        let aliases = meta
+            .map(|m| m.aliases.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|(alias, canonical)| {
+                Value::object(vec![
+                    ("alias", Value::str(alias)),
+                    ("value", Value::str(canonical)),
+                ])
+            })
+            .collect();
+        let requires = flag
+            .map(|f| f.get_requirements().as_slice())
+            .unwrap_or_default();
+        Value::object(vec![
+            ("kind", Value::str(kind)),
+            ("name", Value::str(name)),
+            ("switch", Value::opt_str(switch)),
+            ("value", Value::opt_str(value)),
+            ("about", Value::str(&self.about)),
+            ("type", Value::opt_str(self.value_type.as_ref())),
+            ("possible_values", Value::strs(values)),
+            ("aliases", Value::Array(aliases)),
+            (
+                "hyphen_values",
+                Value::Bool(meta.map(|m| m.hyphen_values).unwrap_or(false)),
+            ),
+            ("requires", Value::strs(requires)),
+            (
+                "unstable",
+                Value::Bool(flag.map(|f| f.is_unstable()).unwrap_or(false)),
+            ),
+            ("deprecated", Value::opt_str(flag.and_then(|f| f.get_deprecation()))),
+            ("required", Value::Bool(self.required)),
+            (
+                "arity",
+                Value::object(vec![
+                    ("min", Value::Int(self.arity.0 as i64)),
+                    (
+                        "max",
+                        self.arity.1.map(|n| Value::Int(n as i64)).unwrap_or(Value::Null),
+                    ),
+                ]),
+            ),
+        ])
+    }
+}
+
+/// Where a flag's switch is placed relative to its long name.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum SwitchOrder {
+    /// `-v, --verbose`
+    First,
+    /// `--verbose, -v`
+    Last,
+}
+
+/// How switches are shown beside their long flags in generated help.
+///
+/// With the switch placed first, options without a switch are indented so every
+/// long name starts in the same column:
+///
+/// ```text
+/// -f, --force
+///     --lang <hdl>
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct SwitchLayout {
+    separator: String,
+    order: SwitchOrder,
+}
+
+impl Default for SwitchLayout {
+    fn default() -> Self {
+        Self {
+            separator: String::from(", "),
+            order: SwitchOrder::First,
+        }
+    }
+}
+
+impl SwitchLayout {
+    /// Creates a layout placing the switch first, separated by a comma.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the text written between the switch and the long name.
+    pub fn separator<T: AsRef<str>>(mut self, text: T) -> Self {
+        self.separator = text.as_ref().to_string();
+        self
+    }
+
+    pub fn order(mut self, order: SwitchOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn get_separator(&self) -> &str {
+        self.separator.as_ref()
+    }
+
+    pub fn get_order(&self) -> SwitchOrder {
+        self.order
+    }
+
+    /// Writes the name of `arg` with its switch, padding names without a switch
+    /// when `align` is set so the long names line up.
+    fn label(&self, arg: &Arg, align: bool) -> String {
+        let name = help_name(arg);
+        let switch = arg.as_flag().and_then(|f| f.get_switch());
+        match (switch, self.order) {
+            (Some(c), SwitchOrder::First) => format!("-{}{}{}", c, self.separator, name),
+            (Some(c), SwitchOrder::Last) => format!("{}{}-{}", name, self.separator, c),
+            (None, SwitchOrder::First) if align == true => {
+                format!("{}{}", " ".repeat(2 + self.separator.len()), name)
+            }
+            (None, _) => name,
+        }
+    }
+}
+
+/// Writes the name of `arg` as shown in generated help, where a negatable flag
+/// is written as `--[no-]<name>`.
+fn help_name(arg: &Arg) -> String {
+    let name = arg.to_string();
+    match arg {
+        Arg::Flag(f) if f.is_negatable() == true => {
+            let (prefix, rest) = name.split_at(name.len() - f.get_name().len());
+            format!("{}[no-]{}", prefix, rest)
+        }
+        _ => name,
+    }
+}
+
+/// How the rows of generated help are arranged.
+///
+/// Only [Spec::to_help_with] and [Spec::to_long_help_with] consult the layout,
+/// and [Cli::check_spec](crate::Cli::check_spec) picks one for the width of the
+/// terminal when the help is shown. Markdown and JSON exports are written the
+/// same regardless of the terminal.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum HelpLayout {
+    /// Each name and its description share a line, with the descriptions
+    /// lined up in one column.
+    Columns,
+    /// Each name is on its own line with its description indented below it.
+    Stacked,
+}
+
+impl HelpLayout {
+    /// Terminal width, in columns, below which help is stacked.
+    pub const NARROW_WIDTH: usize = 60;
+
+    /// Chooses the layout for a terminal `width` columns wide, keeping columns
+    /// when the width is unknown.
+    pub fn for_width(width: Option<usize>) -> Self {
+        match width {
+            Some(w) if w < Self::NARROW_WIDTH => Self::Stacked,
+            _ => Self::Columns,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Spec {
+    name: String,
+    summary: Option<String>,
+    availability: Availability,
+    args: Vec<Entry>,
+    commands: Vec<Spec>,
+    switches: Option<SwitchLayout>,
+    before_help: Option<String>,
+    after_help: Option<String>,
+    help: Option<Help>,
+}
+
+impl Spec {
+    pub fn new<T: AsRef<str>>(name: T) -> Self {
+        Self {
+            name: name.as_ref().to_string(),
+            summary: None,
+            availability: Availability::default(),
+            args: Vec::new(),
+            commands: Vec::new(),
+            switches: None,
+            before_help: None,
+            after_help: None,
+            help: None,
+        }
+    }
+
+    pub fn summary<T: AsRef<str>>(mut self, text: T) -> Self {
+        self.summary = Some(text.as_ref().to_string());
+        self
+    }
+
+    pub fn availability(mut self, a: Availability) -> Self {
+        self.availability = a;
+        self
+    }
+
+    /// Displays `text` at the top of the command's generated help, wrapped as
+    /// with [Help::before_help](crate::Help::before_help).
+    pub fn before_help<T: AsRef<str>>(mut self, text: T) -> Self {
+        self.before_help = Some(text.as_ref().to_string());
+        self
+    }
+
+    /// Displays `text` at the bottom of the command's generated help, such as
+    /// examples, wrapped as with [Help::after_help](crate::Help::after_help).
+    pub fn after_help<T: AsRef<str>>(mut self, text: T) -> Self {
+        self.after_help = Some(text.as_ref().to_string());
+        self
+    }
+
+    pub fn flag<T: AsRef<str>>(self, f: Flag, about: T) -> Self {
+        self.arg(Arg::Flag(f), about)
+    }
+
+    pub fn option<T: AsRef<str>>(self, o: Optional, about: T) -> Self {
+        self.arg(Arg::Optional(o), about)
+    }
+
+    pub fn positional<T: AsRef<str>>(self, p: Positional, about: T) -> Self {
+        self.arg(Arg::Positional(p), about)
+    }
+
+    pub fn arg<T: AsRef<str>>(mut self, arg: Arg, about: T) -> Self {
+        let (required, arity) = match &arg {
+            Arg::Flag(_) => (false, (0, Some(0))),
+            Arg::Optional(_) => (false, (1, Some(1))),
+            Arg::Positional(_) => (true, (1, Some(1))),
+        };
+        self.args.push(Entry {
+            arg,
+            about: about.as_ref().to_string(),
+            details: None,
+            value_type: None,
+            required,
+            arity,
+        });
+        self
+    }
+
+    /// Describes the most recently added argument at length.
+    ///
+    /// The details are shown beneath the argument's summary in the full help
+    /// written for `--help` but not in the quick help written for `-h`.
+    pub fn details<T: AsRef<str>>(mut self, text: T) -> Self {
+        if let Some(e) = self.args.last_mut() {
+            e.details = Some(text.as_ref().to_string());
+        }
+        self
+    }
+
+    /// Names the type the most recently added argument's value is parsed into,
+    /// such as `u16` or `path`, for tools reading the exported spec.
+    pub fn value_type<T: AsRef<str>>(mut self, name: T) -> Self {
+        if let Some(e) = self.args.last_mut() {
+            e.value_type = Some(name.as_ref().to_string());
+        }
+        self
+    }
+
+    /// Sets whether the most recently added argument must be given.
+    ///
+    /// Positionals are required and flags and options are not unless set
+    /// otherwise.
+    pub fn required(mut self, required: bool) -> Self {
+        if let Some(e) = self.args.last_mut() {
+            e.required = required;
+        }
+        self
+    }
+
+    /// Sets how many values the most recently added option or positional takes,
+    /// from `min` up to `max`, or any number when `max` is `None`.
+    ///
+    /// Each takes exactly one value unless set otherwise. Flags never take a
+    /// value and are left unchanged.
+    pub fn arity(mut self, min: usize, max: Option<usize>) -> Self {
+        if let Some(e) = self.args.last_mut() {
+            if e.arg.as_positional().is_some() == true {
+                e.arity = (min, max);
+            }
+        }
+        self
+    }
+
+    /// Adds the subcommand `spec` under this command.
+    ///
+    /// The subcommand inherits this command's [switch layout](Spec::switches)
+    /// and [help](Spec::help) unless it sets its own.
+    pub fn command(mut self, mut spec: Spec) -> Self {
+        if spec.switches.is_none() == true {
+            spec.set_switches(self.switches.clone());
+        }
+        if spec.help.is_none() == true {
+            spec.set_help(self.help.clone());
+        }
+        self.commands.push(spec);
+        self
+    }
+
+    /// Builds the generated help upon `help` for this command and its
+    /// subcommands, such as to lay it out with a [template](Help::template) or
+    /// page it.
+    ///
+    /// The generated text replaces any text already set on `help`.
+    pub fn help(mut self, help: Help) -> Self {
+        self.set_help(Some(help));
+        self
+    }
+
+    fn set_help(&mut self, help: Option<Help>) -> () {
+        for c in &mut self.commands {
+            if c.help.is_none() == true || c.help == self.help {
+                c.set_help(help.clone());
+            }
+        }
+        self.help = help;
+    }
+
+    pub fn get_help(&self) -> Option<&Help> {
+        self.help.as_ref()
+    }
+
+    /// Shows each flag's switch beside its long name in the help text, arranged
+    /// by `layout`, for this command and its subcommands.
+    ///
+    /// Without a layout only the long names are shown.
+    pub fn switches(mut self, layout: SwitchLayout) -> Self {
+        self.set_switches(Some(layout));
+        self
+    }
+
+    fn set_switches(&mut self, layout: Option<SwitchLayout>) -> () {
+        for c in &mut self.commands {
+            if c.switches.is_none() == true || c.switches == self.switches {
+                c.set_switches(layout.clone());
+            }
+        }
+        self.switches = layout;
+    }
+
+    pub fn get_switches(&self) -> Option<&SwitchLayout> {
+        self.switches.as_ref()
+    }
+
+    pub fn get_name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    pub fn get_summary(&self) -> Option<&str> {
+        self.summary.as_deref()
+    }
+
+    pub fn get_availability(&self) -> &Availability {
+        &self.availability
+    }
+
+    pub fn get_args(&self) -> &Vec<Entry> {
+        &self.args
+    }
+
+    pub fn get_commands(&self) -> &Vec<Spec> {
+        &self.commands
+    }
+
+    /// Finds the flag or option named `name` in this command or any subcommand
+    /// beneath it.
+    pub fn find_flag(&self, name: &str) -> Option<&Entry> {
+        self.args
+            .iter()
+            .find(|e| e.arg.as_flag().is_some() && e.arg.get_name() == name)
+            .or_else(|| self.commands.iter().find_map(|c| c.find_flag(name)))
+    }
+
+    /// Iterates over the arguments shown in generated documentation, which
+    /// excludes unstable flags and options.
+    pub fn documented_args(&self) -> impl Iterator<Item = &Entry> {
+        self.args.iter().filter(|e| {
+            e.arg
+                .as_flag()
+                .map(|f| f.is_unstable() == false)
+                .unwrap_or(true)
+        })
+    }
+
+    /// Composes a usage line for the command reached by `path`, which lists the
+    /// names of its parent commands.
+    pub fn usage(&self, path: &[&str]) -> String {
+        let mut words: Vec<String> = path.iter().map(|p| p.to_string()).collect();
+        words.push(self.name.clone());
+        if self.documented_args().any(|e| e.arg.as_flag().is_some()) {
+            words.push(String::from("[options]"));
+        }
+        self.documented_args()
+            .filter_map(|e| match &e.arg {
+                Arg::Positional(p) => Some(p.to_string()),
+                _ => None,
+            })
+            .for_each(|p| words.push(p));
+        if self.commands.is_empty() == false {
+            words.push(String::from("<command>"));
+        }
+        words.join(" ")
+    }
+
+    /// Composes the help text for the command reached by `path`, which lists the
+    /// names of its parent commands.
+    pub fn to_help(&self, path: &[&str]) -> String {
+        self.to_help_with(path, HelpLayout::Columns)
+    }
+
+    /// Composes the help text for the command reached by `path` arranged in the
+    /// `layout`, such as the one chosen by [HelpLayout::for_width] for the
+    /// user's terminal.
+    pub fn to_help_with(&self, path: &[&str], layout: HelpLayout) -> String {
+        self.compose_help(path, layout, false)
+    }
+
+    /// Composes the full help text for the command reached by `path`, which
+    /// follows each argument with its [details](Spec::details).
+    ///
+    /// This is the text shown for `--help`, while [to_help](Spec::to_help) is
+    /// shown for `-h`.
+    pub fn to_long_help(&self, path: &[&str]) -> String {
+        self.to_long_help_with(path, HelpLayout::Columns)
+    }
+
+    /// Composes the full help text for the command reached by `path` arranged
+    /// in the `layout`.
+    pub fn to_long_help_with(&self, path: &[&str], layout: HelpLayout) -> String {
+        self.compose_help(path, layout, true)
+    }
+
+    fn compose_help(&self, path: &[&str], layout: HelpLayout, long: bool) -> String {
+        let mut text = String::new();
+        if let Some(before) = &self.before_help {
+            text.push_str(&format!("{}\n\n", help::wrap(before, help::WRAP_WIDTH)));
+        }
+        let args: Vec<Row> = self
+            .documented_args()
+            .filter(|e| e.arg.as_flag().is_none())
+            .map(|e| e.row(e.arg.to_string(), long))
+            .collect();
+        // line up the long names only when some switch sits in front of them
+        let align = self
+            .documented_args()
+            .any(|e| e.arg.as_flag().and_then(|f| f.get_switch()).is_some());
+        let label = |e: &Entry| match &self.switches {
+            Some(layout) => layout.label(&e.arg, align),
+            None => help_name(&e.arg),
+        };
+        let commands: Vec<Row> = self
+            .commands
+            .iter()
+            .map(|c| (c.name.clone(), c.summary.as_deref().unwrap_or_default(), None))
+            .collect();
+        let template = self.help.as_ref().and_then(|h| h.get_template());
+        let mut sections: Vec<(&str, Vec<Row>)> = match template {
+            // the template places flags and options in sections of their own
+            Some(_) => {
+                let rows = |flag: bool| -> Vec<Row> {
+                    self.documented_args()
+                        .filter(|e| e.arg.as_flag().and_then(|f| f.get_category()).is_none())
+                        .filter(|e| match &e.arg {
+                            Arg::Flag(_) => flag == true,
+                            Arg::Optional(_) => flag == false,
+                            Arg::Positional(_) => false,
+                        })
+                        .map(|e| e.row(label(e), long))
+                        .collect()
+                };
+                vec![("Args", args), ("Flags", rows(true)), ("Options", rows(false))]
+            }
+            None => vec![("Args", args), ("Options", Vec::new())],
+        };
+        // options with a category follow the others under its heading, in the
+        // order the categories are first declared
+        for e in self.documented_args() {
+            let heading = match (e.arg.as_flag(), template) {
+                (Some(f), None) => f.get_category().unwrap_or("Options"),
+                (Some(f), Some(_)) => match f.get_category() {
+                    Some(heading) => heading,
+                    None => continue,
+                },
+                (None, _) => continue,
+            };
+            let row = e.row(label(e), long);
+            match sections.iter_mut().find(|(title, _)| *title == heading) {
+                Some((_, rows)) => rows.push(row),
+                None => sections.push((heading, vec![row])),
+            }
+        }
+        sections.push(("Commands", commands));
+        // align the descriptions of every section in one column
+        let width = sections
+            .iter()
+            .flat_map(|(_, rows)| rows.iter())
+            .map(|(name, _, _)| name.len())
+            .max()
+            .unwrap_or(0)
+            + 3;
+        let usage = format!("Usage:\n    {}\n", self.usage(path));
+        match template {
+            Some(template) => {
+                let summary = self.summary.as_ref().map(|s| format!("{}\n", s));
+                let mut filled = vec![("summary", summary.unwrap_or_default()), ("usage", usage)];
+                let (fixed, rest) = sections.split_at(3);
+                let (grouped, commands) = rest.split_at(rest.len() - 1);
+                for (name, (title, rows)) in ["args", "flags", "options"].into_iter().zip(fixed) {
+                    filled.push((name, write_section(title, rows, layout, width)));
+                }
+                let categories = grouped
+                    .iter()
+                    .map(|(title, rows)| write_section(title, rows, layout, width))
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                // categories follow the options unless the template places them
+                match template.contains("{categories}") {
+                    true => filled.push(("categories", categories)),
+                    false => {
+                        let options = &mut filled.last_mut().unwrap().1;
+                        if options.is_empty() == false && categories.is_empty() == false {
+                            options.push('\n');
+                        }
+                        options.push_str(&categories);
+                    }
+                }
+                let (title, rows) = &commands[0];
+                filled.push(("subcommands", write_section(title, rows, layout, width)));
+                text.push_str(&help::fill(template, &filled));
+            }
+            None => {
+                if let Some(summary) = &self.summary {
+                    text.push_str(&format!("{}\n\n", summary));
+                }
+                text.push_str(&usage);
+                for (title, rows) in &sections {
+                    if rows.is_empty() == false {
+                        text.push_str(&format!("\n{}", write_section(title, rows, layout, width)));
+                    }
+                }
+            }
+        }
+        if let Some(after) = &self.after_help {
+            text.push_str(&format!("\n{}\n", help::wrap(after, help::WRAP_WIDTH)));
+        }
+        text
+    }
+
+    /// Writes a Markdown reference page with a section for this command and
+    /// every subcommand beneath it.
+    pub fn to_markdown(&self) -> String {
+        let mut page = String::new();
+        self.write_markdown(&mut page, &mut Vec::new());
+        page
+    }
+
+    fn write_markdown<'a>(&'a self, page: &mut String, path: &mut Vec<&'a str>) {
+        let level = match path.is_empty() {
+            true => "#",
+            false => "##",
+        };
+        let title = self.usage_path(path);
+        page.push_str(&format!("{} {}\n\n", level, title));
+        if let Some(badge) = self.availability.badge() {
+            page.push_str(&format!("_{}_\n\n", badge));
+        }
+        if let Some(summary) = &self.summary {
+            page.push_str(&format!("{}\n\n", summary));
+        }
+        page.push_str(&format!("```text\n{}\n```\n\n", self.usage(path)));
+
+        let positionals: Vec<&Entry> = self
+            .documented_args()
+            .filter(|e| e.arg.as_flag().is_none())
+            .collect();
+        if positionals.is_empty() == false {
+            page.push_str("### Arguments\n\n| Name | Description | Values |\n| --- | --- | --- |\n");
+            for e in positionals {
+                let p = e.arg.as_positional().unwrap();
+                page.push_str(&format!(
+                    "| `{}` | {} | {} |\n",
+                    p,
+                    escape(&e.about),
+                    escape(&values(p))
+                ));
+            }
+            page.push('\n');
+        }
+
+        let options: Vec<&Entry> = self
+            .documented_args()
+            .filter(|e| e.arg.as_flag().is_some())
+            .collect();
+        if options.is_empty() == false {
+            page.push_str("### Options\n\n| Flag | Switch | Description | Values |\n| --- | --- | --- | --- |\n");
+            for e in options {
+                let f = e.arg.as_flag().unwrap();
+                let switch = f
+                    .get_switch()
+                    .map(|c| format!("`-{}`", c))
+                    .unwrap_or_default();
+                let about = match f.get_deprecation() {
+                    Some(note) => format!("{} (deprecated: {})", e.about, note),
+                    None => e.about.clone(),
+                };
+                page.push_str(&format!(
+                    "| `{}` | {} | {} | {} |\n",
+                    e.arg,
+                    switch,
+                    escape(about.trim()),
+                    escape(&e.arg.as_positional().map(values).unwrap_or_default())
+                ));
+            }
+            page.push('\n');
+        }
+
+        path.push(self.name.as_ref());
+        if self.commands.is_empty() == false {
+            page.push_str("### Commands\n\n| Command | Description |\n| --- | --- |\n");
+            for c in &self.commands {
+                let heading = c.usage_path(path);
+                page.push_str(&format!(
+                    "| [`{}`](#{}) | {} |\n",
+                    c.name,
+                    anchor(&heading),
+                    escape(c.summary.as_deref().unwrap_or_default())
+                ));
+            }
+            page.push('\n');
+        }
+        for c in &self.commands {
+            c.write_markdown(page, path);
+        }
+        path.pop();
+    }
+
+    /// Writes a script completing this command and every subcommand beneath it
+    /// for `shell`.
+    pub fn to_completion(&self, shell: Shell) -> String {
+        complete::script(self, shell)
+    }
+
+    /// Writes a script for `shell` that completes this command by asking the
+    /// program for the candidates, which it answers once
+    /// [dynamic completion](crate::Cli::dynamic_completion) is enabled.
+    ///
+    /// Unlike [to_completion](Spec::to_completion), the candidates can be looked
+    /// up at runtime with [Positional::candidates].
+    pub fn to_dynamic_completion(&self, shell: Shell) -> String {
+        complete::dynamic_script(&self.name, shell)
+    }
+
+    /// Writes the entire command tree as a versioned JSON document for external
+    /// tools, such as editor plugins and documentation sites.
+    ///
+    /// Unlike generated documentation, unstable arguments are included and marked
+    /// as such.
+    pub fn to_json(&self) -> String {
+        Value::object(vec![
+            ("schema", Value::str("clif-spec")),
+            ("version", Value::Int(SCHEMA_VERSION)),
+            ("command", self.to_json_value()),
+        ])
+        .to_string()
+    }
+
+    fn to_json_value(&self) -> Value {
+        let (status, since, note) = match &self.availability {
+            Availability::Stable => ("stable", None, None),
+            Availability::Experimental => ("experimental", None, None),
+            Availability::Deprecated { since, note } => ("deprecated", Some(since), note.as_ref()),
+            Availability::Removed { since, note } => ("removed", Some(since), note.as_ref()),
+        };
+        Value::object(vec![
+            ("name", Value::str(&self.name)),
+            ("summary", Value::opt_str(self.summary.as_ref())),
+            (
+                "availability",
+                Value::object(vec![
+                    ("status", Value::str(status)),
+                    ("since", Value::opt_str(since)),
+                    ("note", Value::opt_str(note)),
+                ]),
+            ),
+            ("args", Value::Array(self.args.iter().map(|e| e.to_json_value()).collect())),
+            (
+                "commands",
+                Value::Array(self.commands.iter().map(|c| c.to_json_value()).collect()),
+            ),
+        ])
+    }
+
+    /// Joins the parent command names in `path` with this command's name.
+    fn usage_path(&self, path: &[&str]) -> String {
+        let mut words = path.to_vec();
+        words.push(self.name.as_ref());
+        words.join(" ")
+    }
+}
+
+/// Writes the section titled `title` listing `rows` with their descriptions
+/// starting at column `width`, or nothing when there are no rows.
+fn write_section(title: &str, rows: &[Row], layout: HelpLayout, width: usize) -> String {
+    if rows.is_empty() == true {
+        return String::new();
+    }
+    let mut text = format!("{}:\n", title);
+    for (name, about, details) in rows {
+        match layout {
+            HelpLayout::Columns => {
+                text.push_str(&format!("    {:<width$}{}\n", name, about, width = width))
+            }
+            HelpLayout::Stacked => {
+                text.push_str(&format!("    {}\n", name.trim_start()));
+                if about.is_empty() == false {
+                    text.push_str(&format!("        {}\n", about));
+                }
+            }
+        }
+        if let Some(details) = details {
+            for line in details.lines() {
+                match line.is_empty() {
+                    true => text.push('\n'),
+                    false => text.push_str(&format!("        {}\n", line)),
+                }
+            }
+        }
+    }
+    text
+}
+
+/// Lists the possible values of `p` for a table cell.
+fn values(p: &Positional) -> String {
+    p.get_possible_values()
+        .iter()
+        .map(|v| format!("`{}`", v))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Prevents `text` from breaking out of a Markdown table cell.
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Computes the fragment identifier most Markdown renderers assign to `heading`.
+fn anchor(heading: &str) -> String {
+    heading
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn orbit() -> Spec {
+        Spec::new("orbit")
+            .summary("An hdl package manager.")
+            .flag(Flag::new("version"), "print the version")
+            .flag(Flag::new("trace").unstable(), "trace internal calls")
+            .command(
+                Spec::new("new")
+                    .summary("Create a new ip.")
+                    .flag(Flag::new("force").switch('f'), "overwrite an existing directory")
+                    .option(
+                        Optional::new("lang").value("hdl").possible_values(&["vhdl", "verilog"]),
+                        "primary language",
+                    )
+                    .positional(Positional::new("ip"), "name | path of the ip"),
+            )
+            .command(
+                Spec::new("plan")
+                    .summary("Generate a blueprint.")
+                    .availability(Availability::Experimental),
+            )
+    }
+
+    #[test]
+    fn help_text() {
+        let spec = orbit();
+        assert_eq!(
+            spec.get_commands()[0].to_help(&["orbit"]),
+            "\
+Create a new ip.
+
+Usage:
+    orbit new [options] <ip>
+
+Args:
+    <ip>           name | path of the ip
+
+Options:
+    --force        overwrite an existing directory
+    --lang <hdl>   primary language
+"
+        );
+    }
+
+    #[test]
+    fn negatable_flag_help() {
+        let spec = Spec::new("orbit")
+            .flag(Flag::new("cache").negatable(), "reuse downloaded ip")
+            .flag(Flag::new("force"), "overwrite an existing directory");
+        let help = spec.to_help(&[]);
+        assert!(help.contains("    --[no-]cache   reuse downloaded ip\n"));
+        assert!(help.contains("    --force        overwrite an existing directory\n"));
+    }
+
+    #[test]
+    fn templated_help() {
+        let spec = orbit()
+            .help(Help::new().template("{usage}\n\n{flags}\n{options}\n{subcommands}\n{args}"));
+        assert_eq!(
+            spec.get_commands()[0].to_help(&["orbit"]),
+            "\
+Usage:
+    orbit new [options] <ip>
+
+Flags:
+    --force        overwrite an existing directory
+
+Options:
+    --lang <hdl>   primary language
+
+Args:
+    <ip>           name | path of the ip
+"
+        );
+        // empty sections are left out with their blank lines
+        let spec = Spec::new("orbit")
+            .help(Help::new().template("{summary}\n{usage}\n{options}\n{flags}\n{version}"))
+            .flag(Flag::new("quiet"), "print nothing");
+        assert_eq!(
+            spec.to_help(&[]),
+            "Usage:\n    orbit [options]\n\nFlags:\n    --quiet   print nothing\n\n{version}"
+        );
+        // categorized flags and options are listed under their own headings
+        let spec = Spec::new("build")
+            .flag(Flag::new("quiet").category("Output options"), "print nothing")
+            .flag(Flag::new("release"), "optimize the build")
+            .option(Optional::new("jobs").category("Build options"), "run in parallel");
+        let help = |template: &str| spec.clone().help(Help::new().template(template)).to_help(&[]);
+        assert_eq!(
+            help("{flags}\n{categories}\n{usage}"),
+            "\
+Flags:
+    --release       optimize the build
+
+Output options:
+    --quiet         print nothing
+
+Build options:
+    --jobs <jobs>   run in parallel
+
+Usage:
+    build [options]
+"
+        );
+        assert_eq!(
+            help("{options}"),
+            "Output options:\n    --quiet         print nothing\n\nBuild options:\n    --jobs <jobs>   run in parallel\n"
+        );
+    }
+
+    #[test]
+    fn categorized_help() {
+        let spec = Spec::new("build")
+            .flag(Flag::new("quiet").category("Output options"), "print nothing")
+            .flag(Flag::new("release"), "optimize the build")
+            .option(
+                Optional::new("format").category("Output options"),
+                "style of the messages",
+            )
+            .option(Optional::new("jobs").category("Build options"), "run in parallel")
+            .flag(Flag::new("offline"), "use only cached sources");
+        assert_eq!(
+            spec.to_help(&[]),
+            "\
+Usage:
+    build [options]
+
+Options:
+    --release           optimize the build
+    --offline           use only cached sources
+
+Output options:
+    --quiet             print nothing
+    --format <format>   style of the messages
+
+Build options:
+    --jobs <jobs>       run in parallel
+"
+        );
+    }
+
+    #[test]
+    fn help_preamble_and_epilogue() {
+        let spec = Spec::new("lock")
+            .before_help("Note: the lockfile is rewritten from scratch.")
+            .flag(Flag::new("force"), "ignore the existing lockfile")
+            .after_help("Environment:\n    ORBIT_HOME   where ips are installed");
+        assert_eq!(
+            spec.to_help(&[]),
+            "\
+Note: the lockfile is rewritten from scratch.
+
+Usage:
+    lock [options]
+
+Options:
+    --force   ignore the existing lockfile
+
+Environment:
+    ORBIT_HOME   where ips are installed
+"
+        );
+    }
+
+    #[test]
+    fn stacked_help() {
+        assert_eq!(HelpLayout::for_width(None), HelpLayout::Columns);
+        assert_eq!(HelpLayout::for_width(Some(80)), HelpLayout::Columns);
+        assert_eq!(HelpLayout::for_width(Some(40)), HelpLayout::Stacked);
+
+        let spec = orbit().switches(SwitchLayout::new());
+        assert_eq!(
+            spec.get_commands()[0].to_help_with(&["orbit"], HelpLayout::Stacked),
+            "\
+Create a new ip.
+
+Usage:
+    orbit new [options] <ip>
+
+Args:
+    <ip>
+        name | path of the ip
+
+Options:
+    -f, --force
+        overwrite an existing directory
+    --lang <hdl>
+        primary language
+"
+        );
+    }
+
+    #[test]
+    fn switch_layouts() {
+        let spec = orbit().switches(SwitchLayout::new());
+        assert!(spec.get_commands()[0].to_help(&["orbit"]).ends_with(
+            "\
+Options:
+    -f, --force        overwrite an existing directory
+        --lang <hdl>   primary language
+"
+        ));
+        // nothing is indented when no option has a switch
+        assert!(spec.to_help(&[]).contains("\n    --version   print the version\n"));
+
+        let spec = orbit().command(
+            Spec::new("read")
+                .switches(SwitchLayout::new().order(SwitchOrder::Last).separator(" | "))
+                .flag(Flag::new("all").switch('a'), "read every file")
+                .flag(Flag::new("quiet"), "say nothing"),
+        );
+        assert!(spec.get_commands()[2].to_help(&["orbit"]).ends_with(
+            "\
+Options:
+    --all | -a   read every file
+    --quiet      say nothing
+"
+        ));
+    }
+
+    #[test]
+    fn usage_line() {
+        let spec = orbit();
+        assert_eq!(spec.usage(&[]), "orbit [options] <command>");
+        assert_eq!(
+            spec.get_commands()[0].usage(&["orbit"]),
+            "orbit new [options] <ip>"
+        );
+        assert_eq!(spec.get_commands()[1].usage(&["orbit"]), "orbit plan");
+    }
+
+    #[test]
+    fn markdown_page() {
+        let page = orbit().to_markdown();
+        assert!(page.starts_with("# orbit\n\nAn hdl package manager.\n\n```text\norbit [options] <command>\n```\n"));
+        assert!(page.contains("| `--version` |  | print the version |  |\n"));
+        // unstable flags are left out of the reference
+        assert_eq!(page.contains("--trace"), false);
+        assert!(page.contains("| [`new`](#orbit-new) | Create a new ip. |\n"));
+        assert!(page.contains("## orbit new\n\nCreate a new ip."));
+        assert!(page.contains("| `<ip>` | name \\| path of the ip |  |\n"));
+        assert!(page.contains("| `--lang <hdl>` |  | primary language | `vhdl`, `verilog` |\n"));
+        assert!(page.contains("| `--force` | `-f` | overwrite an existing directory |  |\n"));
+        assert!(page.contains("## orbit plan\n\n_[experimental]_\n\nGenerate a blueprint."));
+    }
+
+    #[test]
+    fn json_snapshot() {
+        let spec = Spec::new("orbit")
+            .summary("An hdl package manager.")
+            .flag(Flag::new("trace").unstable(), "trace internal calls")
+            .command(
+                Spec::new("get")
+                    .availability(Availability::deprecated("0.9").note("use 'install' instead"))
+                    .option(
+                        Optional::new("format")
+                            .switch('f')
+                            .value("fmt")
+                            .possible_values(&["json", "toml"])
+                            .value_alias("js", "json")
+                            .requires("output"),
+                        "output \"format\"",
+                    )
+                    .positional(Positional::new("ip"), "name of the ip")
+                    .value_type("string"),
+            );
+        assert_eq!(spec.to_json(), JSON_SNAPSHOT);
+    }
+
+    const JSON_SNAPSHOT: &str = r#"{
+  "schema": "clif-spec",
+  "version": 1,
+  "command": {
+    "name": "orbit",
+    "summary": "An hdl package manager.",
+    "availability": {
+      "status": "stable",
+      "since": null,
+      "note": null
+    },
+    "args": [
+      {
+        "kind": "flag",
+        "name": "trace",
+        "switch": null,
+        "value": null,
+        "about": "trace internal calls",
+        "type": null,
+        "possible_values": [],
+        "aliases": [],
+        "hyphen_values": false,
+        "requires": [],
+        "unstable": true,
+        "deprecated": null,
+        "required": false,
+        "arity": {
+          "min": 0,
+          "max": 0
+        }
+      }
+    ],
+    "commands": [
+      {
+        "name": "get",
+        "summary": null,
+        "availability": {
+          "status": "deprecated",
+          "since": "0.9",
+          "note": "use 'install' instead"
+        },
+        "args": [
+          {
+            "kind": "option",
+            "name": "format",
+            "switch": "f",
+            "value": "fmt",
+            "about": "output \"format\"",
+            "type": null,
+            "possible_values": [
+              "json",
+              "toml"
+            ],
+            "aliases": [
+              {
+                "alias": "js",
+                "value": "json"
+              }
+            ],
+            "hyphen_values": false,
+            "requires": [
+              "output"
+            ],
+            "unstable": false,
+            "deprecated": null,
+            "required": false,
+            "arity": {
+              "min": 1,
+              "max": 1
+            }
+          },
+          {
+            "kind": "positional",
+            "name": "ip",
+            "switch": null,
+            "value": null,
+            "about": "name of the ip",
+            "type": "string",
+            "possible_values": [],
+            "aliases": [],
+            "hyphen_values": false,
+            "requires": [],
+            "unstable": false,
+            "deprecated": null,
+            "required": true,
+            "arity": {
+              "min": 1,
+              "max": 1
+            }
+          }
+        ],
+        "commands": []
+      }
+    ]
+  }
+}"#;
+
+    #[test]
+    fn anchors() {
+        assert_eq!(anchor("orbit new"), "orbit-new");
+        assert_eq!(anchor("Get.Info x_y"), "getinfo-x_y");
+    }
+}
@@ -0,0 +1,663 @@
+//! Styling for messages written to a terminal.
+//!
+//! A [Theme] assigns a [Paint] to each kind of text within a message, such as
+//! argument names or hints, so every error is highlighted consistently.
+
+use crate::arg::Optional;
+use crate::error::{Error, ErrorContext, Span};
+use crate::i18n::{Catalog, English, Message};
+use std::fmt::Display;
+use std::ffi::OsString;
+use std::io::IsTerminal;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Environment variable that disables colored output when set to a non-empty value.
+const NO_COLOR: &str = "NO_COLOR";
+/// Environment variable that disables colored output when set to `0`.
+const CLICOLOR: &str = "CLICOLOR";
+/// Environment variable that enables colored output even when not writing to a
+/// terminal, when set to a non-empty value other than `0`.
+const CLICOLOR_FORCE: &str = "CLICOLOR_FORCE";
+
+/// The decision set by [override_color], which is `AUTO` until one is made.
+static OVERRIDE: AtomicU8 = AtomicU8::new(AUTO);
+const AUTO: u8 = 0;
+const NEVER: u8 = 1;
+const ALWAYS: u8 = 2;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    /// Returns the ANSI select graphic rendition code for the foreground color.
+    fn code(&self) -> u8 {
+        match self {
+            Self::Black => 30,
+            Self::Red => 31,
+            Self::Green => 32,
+            Self::Yellow => 33,
+            Self::Blue => 34,
+            Self::Magenta => 35,
+            Self::Cyan => 36,
+            Self::White => 37,
+        }
+    }
+}
+
+/// A combination of a foreground color and text attributes.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Paint {
+    fg: Option<Color>,
+    bold: bool,
+    dimmed: bool,
+    underline: bool,
+}
+
+impl Paint {
+    /// Creates a paint that leaves text unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn fg(mut self, c: Color) -> Self {
+        self.fg = Some(c);
+        self
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn dimmed(mut self) -> Self {
+        self.dimmed = true;
+        self
+    }
+
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    /// Wraps `text` in the escape sequences for this paint.
+    ///
+    /// The text is returned unchanged when `enabled` is false.
+    pub fn apply<T: AsRef<str>>(&self, text: T, enabled: bool) -> String {
+        self.paint(text.as_ref(), enabled).to_string()
+    }
+
+    /// Displays `text` wrapped in the escape sequences for this paint, writing
+    /// it directly to the formatter instead of building a new string.
+    pub fn paint<T: Display>(&self, text: T, enabled: bool) -> Painted<'_, T> {
+        Painted {
+            paint: self,
+            text,
+            enabled,
+        }
+    }
+
+    /// Returns the select graphic rendition codes of the paint, in order.
+    fn codes(&self) -> impl Iterator<Item = u8> {
+        [
+            self.bold.then_some(1),
+            self.dimmed.then_some(2),
+            self.underline.then_some(4),
+            self.fg.map(|c| c.code()),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+/// Text to display with a [Paint].
+pub struct Painted<'a, T: Display> {
+    paint: &'a Paint,
+    text: T,
+    enabled: bool,
+}
+
+impl<'a, T: Display> Display for Painted<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut codes = self.paint.codes().peekable();
+        if self.enabled == false || codes.peek().is_none() == true {
+            return write!(f, "{}", self.text);
+        }
+        write!(f, "\x1b[")?;
+        for (i, c) in codes.enumerate() {
+            match i {
+                0 => write!(f, "{}", c)?,
+                _ => write!(f, ";{}", c)?,
+            }
+        }
+        write!(f, "m{}\x1b[0m", self.text)
+    }
+}
+
+/// The paints used for each kind of text within a message.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Theme {
+    error: Paint,
+    warning: Paint,
+    arg: Paint,
+    value: Paint,
+    suggestion: Paint,
+    hint: Paint,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            error: Paint::new().fg(Color::Red).bold(),
+            warning: Paint::new().fg(Color::Yellow).bold(),
+            arg: Paint::new().fg(Color::Blue).bold(),
+            value: Paint::new().fg(Color::Yellow),
+            suggestion: Paint::new().fg(Color::Green),
+            hint: Paint::new().dimmed(),
+        }
+    }
+}
+
+impl Theme {
+    /// Creates a theme that leaves all text unchanged.
+    pub fn plain() -> Self {
+        Self {
+            error: Paint::new(),
+            warning: Paint::new(),
+            arg: Paint::new(),
+            value: Paint::new(),
+            suggestion: Paint::new(),
+            hint: Paint::new(),
+        }
+    }
+
+    /// Sets the paint for the `error:` prefix.
+    pub fn error(mut self, p: Paint) -> Self {
+        self.error = p;
+        self
+    }
+
+    /// Sets the paint for the `warning:` prefix.
+    pub fn warning(mut self, p: Paint) -> Self {
+        self.warning = p;
+        self
+    }
+
+    /// Sets the paint for argument names.
+    pub fn arg(mut self, p: Paint) -> Self {
+        self.arg = p;
+        self
+    }
+
+    /// Sets the paint for values and words supplied by the user.
+    pub fn value(mut self, p: Paint) -> Self {
+        self.value = p;
+        self
+    }
+
+    /// Sets the paint for suggested replacements.
+    pub fn suggestion(mut self, p: Paint) -> Self {
+        self.suggestion = p;
+        self
+    }
+
+    /// Sets the paint for hints on where to find more information.
+    pub fn hint(mut self, p: Paint) -> Self {
+        self.hint = p;
+        self
+    }
+
+    pub fn get_error(&self) -> &Paint {
+        &self.error
+    }
+
+    pub fn get_warning(&self) -> &Paint {
+        &self.warning
+    }
+
+    pub fn get_arg(&self) -> &Paint {
+        &self.arg
+    }
+
+    pub fn get_value(&self) -> &Paint {
+        &self.value
+    }
+
+    pub fn get_suggestion(&self) -> &Paint {
+        &self.suggestion
+    }
+
+    pub fn get_hint(&self) -> &Paint {
+        &self.hint
+    }
+}
+
+/// An [Error] rendered with a [Theme].
+///
+/// When the error has a [Span] and the command-line is known, the command-line
+/// is echoed beneath the first line of the message with the offending token
+/// underlined:
+///
+/// ```text
+/// error: argument '--jobs <jobs>' failed to process '4x' due to: invalid digit found in string
+///
+///     make -vj4x
+///             ^^
+/// ```
+pub struct Styled<'a> {
+    error: &'a Error,
+    theme: &'a Theme,
+    catalog: &'a dyn Catalog,
+    source: Option<&'a [String]>,
+    enabled: bool,
+}
+
+impl<'a> Styled<'a> {
+    pub(crate) fn new(error: &'a Error, theme: &'a Theme, enabled: bool) -> Self {
+        Self {
+            error,
+            theme,
+            catalog: &English,
+            source: error.get_line(),
+            enabled,
+        }
+    }
+
+    /// Echoes `line`, the command-line beginning with the program name, beneath
+    /// the message with the offending token underlined.
+    ///
+    /// This is set for errors raised by [Cli::go](crate::Cli::go). Supply it
+    /// when reporting an error returned by a [Cli](crate::Cli) directly.
+    pub fn source(mut self, line: &'a [String]) -> Self {
+        self.source = Some(line);
+        self
+    }
+
+    /// Writes only the message, without echoing the command-line.
+    pub fn no_source(mut self) -> Self {
+        self.source = None;
+        self
+    }
+
+    /// Writes the message in the language of `catalog` instead of English.
+    pub fn catalog(mut self, catalog: &'a dyn Catalog) -> Self {
+        self.catalog = catalog;
+        self
+    }
+
+    /// Overrides the automatic decision to use colors.
+    pub fn color(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+}
+
+impl<'a> Display for Styled<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        // each collected error is labeled and echoes the command-line on its own
+        if let ErrorContext::Multiple(errors) = self.error.context() {
+            for (i, err) in errors.iter().enumerate() {
+                if i > 0 {
+                    write!(f, "{}", PARAGRAPH)?;
+                }
+                let styled = Styled {
+                    error: err,
+                    source: self.source.map(|line| err.get_line().unwrap_or(line)),
+                    ..*self
+                };
+                write!(f, "{}", styled)?;
+            }
+            return Ok(());
+        }
+        if self.error.is_informational() == false {
+            let label = self
+                .catalog
+                .template(Message::ErrorLabel)
+                .unwrap_or(Message::ErrorLabel.english());
+            write!(f, "{}: ", self.theme.get_error().paint(label, self.enabled))?;
+        }
+        let (span, line) = match (self.error.span(), self.source) {
+            (Some(span), Some(line)) if span.get_arg() < line.len() => (span, line),
+            _ => return self.error.write_message(f, self.theme, self.catalog, self.enabled),
+        };
+        // place the command-line after the first paragraph of the message
+        let text = Body(self).to_string();
+        let (head, rest) = match text.split_once(PARAGRAPH) {
+            Some((head, rest)) => (head, Some(rest)),
+            None => (text.as_str(), None),
+        };
+        write!(f, "{}{}", head, PARAGRAPH)?;
+        write_snippet(f, line, span, self.theme.get_error(), self.enabled)?;
+        match rest {
+            Some(rest) => write!(f, "{}{}", PARAGRAPH, rest),
+            None => Ok(()),
+        }
+    }
+}
+
+const PARAGRAPH: &str = "\n\n";
+
+/// The message of a [Styled] error, without its label.
+struct Body<'a, 'b>(&'b Styled<'a>);
+
+impl<'a, 'b> Display for Body<'a, 'b> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let s = self.0;
+        s.error.write_message(f, s.theme, s.catalog, s.enabled)
+    }
+}
+
+/// Writes the command-line `line` indented, with the token at `span` underlined
+/// by carets painted with `paint`.
+fn write_snippet(
+    f: &mut std::fmt::Formatter<'_>,
+    line: &[String],
+    span: &Span,
+    paint: &Paint,
+    enabled: bool,
+) -> Result<(), std::fmt::Error> {
+    let mut text = String::new();
+    let (mut column, mut width) = (0, 1);
+    for (i, word) in line.iter().enumerate() {
+        // the program name is unknown for arguments read from a line of text
+        if i == 0 && word.is_empty() == true {
+            continue;
+        }
+        if text.is_empty() == false {
+            text.push(' ');
+        }
+        if i == span.get_arg() {
+            let range = span.get_range();
+            let (start, len) = match (word.get(..range.start), word.get(range)) {
+                (Some(head), Some(token)) => (head.chars().count(), token.chars().count()),
+                // underline the whole word if it was redacted
+                _ => (0, word.chars().count()),
+            };
+            column = text.chars().count() + start;
+            width = len.max(1);
+        }
+        text.push_str(word);
+    }
+    write!(
+        f,
+        "    {}\n    {}{}",
+        text,
+        " ".repeat(column),
+        paint.paint("^".repeat(width), enabled)
+    )
+}
+
+/// Decides if text written to `stream` should be colored.
+///
+/// A decision made with [override_color] is used as is. Otherwise colors are
+/// disabled when `NO_COLOR` is set to a non-empty value, enabled when
+/// `CLICOLOR_FORCE` is set to a value other than `0`, and disabled when
+/// `CLICOLOR` is `0`. If none of these apply, colors are used only when `stream`
+/// is a terminal.
+pub fn should_color<S: IsTerminal>(stream: &S) -> bool {
+    match OVERRIDE.load(Ordering::Relaxed) {
+        NEVER => false,
+        ALWAYS => true,
+        _ => decide(|key| std::env::var_os(key), || stream.is_terminal()),
+    }
+}
+
+/// Forces colors on or off for everything written by this crate, regardless
+/// of the environment, such as when the user passes `--color=never`.
+///
+/// Passing `None` returns to deciding from the environment and terminal.
+pub fn override_color(enabled: Option<bool>) -> () {
+    let choice = match enabled {
+        None => AUTO,
+        Some(false) => NEVER,
+        Some(true) => ALWAYS,
+    };
+    OVERRIDE.store(choice, Ordering::Relaxed);
+}
+
+/// When to use colors, as chosen by the conventional `--color <when>` option.
+///
+/// ```
+/// use clif::style::ColorChoice;
+///
+/// assert_eq!("never".parse::<ColorChoice>().unwrap(), ColorChoice::Never);
+/// assert_eq!(ColorChoice::Always.to_override(), Some(true));
+/// ```
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum ColorChoice {
+    /// Decide from the environment and terminal.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Returns the `--color <when>` option accepting each choice.
+    pub fn arg() -> Optional {
+        Optional::new("color")
+            .value("when")
+            .possible_values(&["auto", "always", "never"])
+    }
+
+    /// Returns the decision passed to [override_color] for the choice.
+    pub fn to_override(&self) -> Option<bool> {
+        match self {
+            Self::Auto => None,
+            Self::Always => Some(true),
+            Self::Never => Some(false),
+        }
+    }
+
+    /// Makes the choice the color decision for everything written by this crate.
+    pub fn apply(&self) -> () {
+        override_color(self.to_override())
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = ColorChoiceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(ColorChoiceError(s.to_string())),
+        }
+    }
+}
+
+impl Display for ColorChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Auto => "auto",
+            Self::Always => "always",
+            Self::Never => "never",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A word that does not name a [ColorChoice].
+#[derive(Debug, PartialEq)]
+pub struct ColorChoiceError(String);
+
+impl std::error::Error for ColorChoiceError {}
+
+impl Display for ColorChoiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected 'auto', 'always', or 'never' but found '{}'", self.0)
+    }
+}
+
+/// Decides on colors from the environment variables read by `var`, checking
+/// `is_terminal` only when they leave it undecided.
+fn decide<V, T>(var: V, is_terminal: T) -> bool
+where
+    V: Fn(&str) -> Option<OsString>,
+    T: FnOnce() -> bool,
+{
+    let set = |key: &str| var(key).filter(|v| v.is_empty() == false);
+    if set(NO_COLOR).is_some() {
+        return false;
+    }
+    if set(CLICOLOR_FORCE).is_some_and(|v| v != "0") {
+        return true;
+    }
+    if set(CLICOLOR).is_some_and(|v| v == "0") {
+        return false;
+    }
+    is_terminal()
+}
+
+/// Writes `message` to stderr labeled as a warning in the default [Theme].
+pub(crate) fn print_warning<T: Display>(message: T) -> () {
+    let enabled = should_color(&std::io::stderr());
+    eprintln!("{}: {}", Theme::default().get_warning().apply("warning", enabled), message);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn apply_paint() {
+        let p = Paint::new().fg(Color::Red).bold();
+        assert_eq!(p.apply("error", true), "\x1b[1;31merror\x1b[0m");
+        assert_eq!(p.apply("error", false), "error");
+        assert_eq!(Paint::new().dimmed().apply("hint", true), "\x1b[2mhint\x1b[0m");
+        // a paint without any attributes adds no escape sequences
+        assert_eq!(Paint::new().apply("text", true), "text");
+        // painting writes any displayable value in place
+        let p = Paint::new().underline().fg(Color::Cyan);
+        assert_eq!(p.paint(42, true).to_string(), "\x1b[4;36m42\x1b[0m");
+        assert_eq!(p.paint(42, false).to_string(), "42");
+    }
+
+    #[test]
+    fn styled_error() {
+        use crate::cli::Cli;
+        use crate::help::Help;
+
+        let mut cli = Cli::new().tokenize(["orbit", "get"].iter().map(|s| s.to_string()));
+        cli.check_help(Help::new()).unwrap();
+        let err = cli.is_empty().unwrap_err();
+        assert_eq!(
+            err.styled(&Theme::default()).color(false).to_string(),
+            "error: invalid argument 'get'\n\nFor more information, try '--help'."
+        );
+        let text = err.styled(&Theme::default()).color(true).to_string();
+        assert!(text.starts_with("\x1b[1;31merror\x1b[0m: "));
+        assert!(text.contains("\x1b[2mFor more information, try '\x1b[0m\x1b[32m--help\x1b[0m"));
+    }
+
+    #[test]
+    fn translated_error() {
+        use crate::cli::Cli;
+        use crate::help::Help;
+        use std::collections::HashMap;
+
+        let mut catalog = HashMap::new();
+        catalog.insert(Message::ErrorLabel, String::from("erreur"));
+        catalog.insert(Message::InvalidArgument, String::from("argument invalide '{0}'"));
+        let mut cli = Cli::new().tokenize(["orbit", "get"].iter().map(|s| s.to_string()));
+        cli.check_help(Help::new()).unwrap();
+        let err = cli.is_empty().unwrap_err();
+        // the help tip is not translated so it is written in english
+        assert_eq!(
+            err.styled(&Theme::default()).catalog(&catalog).color(false).to_string(),
+            "erreur: argument invalide 'get'\n\nFor more information, try '--help'."
+        );
+    }
+
+    #[test]
+    fn caret_snippet() {
+        use crate::arg::Optional;
+        use crate::cli::Cli;
+        use crate::help::Help;
+
+        let line: Vec<String> = ["make", "-vj4x"].iter().map(|s| s.to_string()).collect();
+        let mut cli = Cli::new().tokenize(line.clone().into_iter());
+        let err = cli
+            .check_option::<u8>(Optional::new("jobs").switch('j'))
+            .unwrap_err();
+        assert_eq!(
+            err.styled(&Theme::default()).source(&line).color(false).to_string(),
+            "error: argument '--jobs <jobs>' failed to process '4x' due to: invalid digit found in string\n\n    make -vj4x\n            ^^"
+        );
+
+        // the snippet follows the first paragraph of the message
+        let line: Vec<String> = ["orbit", "--fast", "gét"].iter().map(|s| s.to_string()).collect();
+        let mut cli = Cli::new().tokenize(line.clone().into_iter());
+        cli.check_help(Help::new()).unwrap();
+        let err = cli.is_empty().unwrap_err();
+        assert_eq!(
+            err.styled(&Theme::default()).source(&line).color(false).to_string(),
+            "error: invalid argument '--fast'\n\n    orbit --fast gét\n          ^^^^^^\n\nFor more information, try '--help'."
+        );
+        assert_eq!(
+            err.styled(&Theme::default()).color(false).to_string(),
+            "error: invalid argument '--fast'\n\nFor more information, try '--help'."
+        );
+    }
+
+    #[test]
+    fn plain_theme() {
+        let theme = Theme::plain();
+        assert_eq!(theme.get_arg().apply("--verbose", true), "--verbose");
+        let theme = Theme::default().arg(Paint::new().underline());
+        assert_eq!(theme.get_arg().apply("--verbose", true), "\x1b[4m--verbose\x1b[0m");
+    }
+
+    #[test]
+    fn color_environment() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| OsString::from(v))
+            }
+        };
+        assert_eq!(decide(env(&[]), || true), true);
+        assert_eq!(decide(env(&[]), || false), false);
+        assert_eq!(decide(env(&[("NO_COLOR", "1")]), || true), false);
+        assert_eq!(decide(env(&[("NO_COLOR", "")]), || true), true);
+        assert_eq!(decide(env(&[("CLICOLOR_FORCE", "1")]), || false), true);
+        assert_eq!(decide(env(&[("CLICOLOR_FORCE", "0")]), || false), false);
+        assert_eq!(decide(env(&[("CLICOLOR", "0")]), || true), false);
+        assert_eq!(decide(env(&[("CLICOLOR", "1")]), || false), false);
+        // NO_COLOR outranks a forced color
+        assert_eq!(
+            decide(env(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")]), || true),
+            false
+        );
+        assert_eq!(
+            decide(env(&[("CLICOLOR", "0"), ("CLICOLOR_FORCE", "1")]), || false),
+            true
+        );
+    }
+
+    #[test]
+    fn parse_color_choice() {
+        assert_eq!("auto".parse::<ColorChoice>(), Ok(ColorChoice::Auto));
+        assert_eq!("always".parse::<ColorChoice>(), Ok(ColorChoice::Always));
+        assert_eq!(
+            "yes".parse::<ColorChoice>().unwrap_err().to_string(),
+            "expected 'auto', 'always', or 'never' but found 'yes'"
+        );
+        assert_eq!(ColorChoice::Never.to_string(), "never");
+        assert_eq!(ColorChoice::Never.to_override(), Some(false));
+        assert_eq!(ColorChoice::default().to_override(), None);
+        assert_eq!(ColorChoice::arg().to_string(), "--color <when>");
+    }
+}
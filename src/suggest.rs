@@ -0,0 +1,171 @@
+//! Controls for the spelling suggestions offered in errors.
+
+use crate::seqalin::Cost;
+
+/// A kind of word a spelling suggestion can be offered for.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum Class {
+    /// Subcommand names given to `match_command`.
+    Subcommand,
+    /// Flag and option names.
+    Flag,
+    /// Values restricted to a list of possible values.
+    Value,
+}
+
+/// Decides how many edits away a misspelled word may be from a known word for
+/// the known word to be suggested.
+///
+/// ```
+/// use clif::{Cli, ThresholdPolicy};
+///
+/// // `--hel` suggests `--help`, but `ad` is too short to suggest `add`
+/// let cli = Cli::new().threshold_policy(ThresholdPolicy::Auto);
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ThresholdPolicy {
+    /// Suggests known words that are less than the cost away; a cost of 0
+    /// disables suggestions.
+    Fixed(Cost),
+    /// Allows one edit for every `n` characters of the misspelled word.
+    Relative(usize),
+    /// Allows one edit for every 4 characters of each known word, so long
+    /// flags tolerate more typos than short commands.
+    Auto,
+}
+
+impl Default for ThresholdPolicy {
+    fn default() -> Self {
+        Self::Fixed(0)
+    }
+}
+
+impl ThresholdPolicy {
+    /// Number of characters of a known word per edit allowed by [Auto](Self::Auto).
+    const CHARS_PER_EDIT: usize = 4;
+
+    /// Checks if no suggestions can be made.
+    pub(crate) fn is_disabled(&self) -> bool {
+        self == &Self::Fixed(0)
+    }
+
+    /// Computes the exclusive threshold for suggesting `candidate` in place of
+    /// `word`.
+    pub(crate) fn threshold(&self, word: &str, candidate: &str) -> Cost {
+        // the threshold is exclusive so allow one more than the edits
+        match self {
+            Self::Fixed(cost) => *cost,
+            Self::Relative(n) => word.chars().count() / (*n).max(1) + 1,
+            Self::Auto => candidate.chars().count() / Self::CHARS_PER_EDIT + 1,
+        }
+    }
+}
+
+/// Selects which classes of spelling suggestions are offered.
+///
+/// Every class is enabled by default. The threshold set with
+/// [Cli::threshold](crate::Cli::threshold) still applies to the enabled classes.
+/// For example, an application whose plugins contribute subcommands at runtime
+/// can disable subcommand suggestions, because its word bank is incomplete, while
+/// keeping flag suggestions.
+///
+/// Words are compared case-sensitively unless [ignore_case](Self::ignore_case)
+/// is enabled, in which case `GET` suggests `get`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SuggestionConfig {
+    subcommands: bool,
+    flags: bool,
+    values: bool,
+    ignore_case: bool,
+}
+
+impl Default for SuggestionConfig {
+    fn default() -> Self {
+        Self {
+            subcommands: true,
+            flags: true,
+            values: true,
+            ignore_case: false,
+        }
+    }
+}
+
+impl SuggestionConfig {
+    /// Creates a configuration with every class of suggestion enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a configuration with every class of suggestion disabled.
+    pub fn none() -> Self {
+        Self {
+            subcommands: false,
+            flags: false,
+            values: false,
+            ignore_case: false,
+        }
+    }
+
+    pub fn subcommands(mut self, enabled: bool) -> Self {
+        self.subcommands = enabled;
+        self
+    }
+
+    pub fn flags(mut self, enabled: bool) -> Self {
+        self.flags = enabled;
+        self
+    }
+
+    pub fn values(mut self, enabled: bool) -> Self {
+        self.values = enabled;
+        self
+    }
+
+    /// Compares words to their suggestions without regard to case.
+    pub fn ignore_case(mut self, enabled: bool) -> Self {
+        self.ignore_case = enabled;
+        self
+    }
+
+    pub(crate) fn is_ignoring_case(&self) -> bool {
+        self.ignore_case
+    }
+
+    /// Checks if suggestions are offered for the `class` of word.
+    pub(crate) fn is_enabled(&self, class: Class) -> bool {
+        match class {
+            Class::Subcommand => self.subcommands,
+            Class::Flag => self.flags,
+            Class::Value => self.values,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn toggle_classes() {
+        let config = SuggestionConfig::new().subcommands(false);
+        assert_eq!(config.is_enabled(Class::Subcommand), false);
+        assert_eq!(config.is_enabled(Class::Flag), true);
+        assert_eq!(config.is_enabled(Class::Value), true);
+
+        let config = SuggestionConfig::none().values(true);
+        assert_eq!(config.is_enabled(Class::Flag), false);
+        assert_eq!(config.is_enabled(Class::Value), true);
+        assert_eq!(config.is_ignoring_case(), false);
+        assert_eq!(config.ignore_case(true).is_ignoring_case(), true);
+    }
+
+    #[test]
+    fn threshold_policies() {
+        assert_eq!(ThresholdPolicy::default().is_disabled(), true);
+        assert_eq!(ThresholdPolicy::Fixed(3).threshold("ad", "add"), 3);
+        assert_eq!(ThresholdPolicy::Relative(4).threshold("verbsoe", "verbose"), 2);
+        // short known words must be matched exactly
+        assert_eq!(ThresholdPolicy::Auto.threshold("ad", "add"), 1);
+        assert_eq!(ThresholdPolicy::Auto.threshold("hel", "help"), 2);
+    }
+}
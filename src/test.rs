@@ -0,0 +1,185 @@
+//! Helpers for testing how commands parse their arguments.
+//!
+//! Each helper runs the same path as [Cli::go](crate::Cli::go) up to executing
+//! the command: the arguments are tokenized, including the program name, and
+//! the command is read with [FromCli]. [run] goes on to execute it.
+//!
+//! ```
+//! use clif::arg::Positional;
+//! use clif::cmd::FromCli;
+//! use clif::{assert_parse_error, assert_parses, Cli, Error, ErrorKind};
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Add {
+//!     lhs: u8,
+//!     rhs: u8,
+//! }
+//!
+//! impl FromCli for Add {
+//!     fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+//!         let app = Add {
+//!             lhs: cli.require_positional(Positional::new("lhs"))?,
+//!             rhs: cli.require_positional(Positional::new("rhs"))?,
+//!         };
+//!         cli.is_empty()?;
+//!         Ok(app)
+//!     }
+//! }
+//!
+//! assert_parses!(Add, ["add", "1", "2"], Add { lhs: 1, rhs: 2 });
+//! assert_parse_error!(Add, ["add", "1"], ErrorKind::MissingPositional);
+//! ```
+
+pub use crate::assert_parse_error;
+pub use crate::assert_parses;
+use crate::cli::Cli;
+use crate::command::{FromCli, Runner};
+use crate::error::Error;
+use std::fmt::Debug;
+
+/// Reads `T` from `args`, which begin with the program name.
+pub fn parse<T: FromCli, S: AsRef<str>>(args: impl IntoIterator<Item = S>) -> Result<T, Error> {
+    parse_with(Cli::new(), args)
+}
+
+/// Reads `T` from `args` as with [parse], using `cli` configured as the
+/// application does, such as with a suggestion threshold.
+pub fn parse_with<T: FromCli, S: AsRef<str>>(
+    cli: Cli,
+    args: impl IntoIterator<Item = S>,
+) -> Result<T, Error> {
+    let mut cli = cli.tokenize(args.into_iter().map(|a| a.as_ref().to_string()));
+    let app = T::from_cli(&mut cli);
+    cli.gather(app)
+}
+
+/// Reads `T` from `args` as with [parse], returning the error it fails with.
+///
+/// Panics if `T` is read successfully.
+pub fn parse_err<T: FromCli + Debug, S: AsRef<str>>(args: impl IntoIterator<Item = S>) -> Error {
+    match parse::<T, S>(args) {
+        Ok(app) => panic!("expected an error but parsed {:?}", app),
+        Err(err) => err,
+    }
+}
+
+/// Reads `T` from `args`, which begin with the program name, and executes it
+/// with `context` as [Cli::launch] does, returning its status.
+///
+/// Pass a [captured](crate::cmd::Context::captured) context to check what the
+/// command writes.
+pub fn run<C, T: Runner<C>, S: AsRef<str>>(
+    args: impl IntoIterator<Item = S>,
+    context: &C,
+) -> Result<T::Status, Error> {
+    run_with::<C, T, S>(Cli::new(), args, context)
+}
+
+/// Reads and executes `T` as with [run], using `cli` configured as the
+/// application does.
+pub fn run_with<C, T: Runner<C>, S: AsRef<str>>(
+    cli: Cli,
+    args: impl IntoIterator<Item = S>,
+    context: &C,
+) -> Result<T::Status, Error> {
+    cli.tokenize(args.into_iter().map(|a| a.as_ref().to_string()))
+        .launch::<C, T>(context)
+}
+
+/// Asserts the arguments, beginning with the program name, are read as the
+/// expected command.
+///
+/// The error is displayed if the arguments fail to parse.
+#[macro_export]
+macro_rules! assert_parses {
+    ($t:ty, [$($arg:expr),+ $(,)?], $expected:expr $(,)?) => {
+        match $crate::test::parse::<$t, _>([$($arg),+]) {
+            Ok(app) => assert_eq!(app, $expected),
+            Err(err) => panic!("expected the arguments to parse but got: {}", err),
+        }
+    };
+}
+
+/// Asserts reading the command from the arguments, beginning with the program
+/// name, fails with the expected [ErrorKind](crate::ErrorKind).
+#[macro_export]
+macro_rules! assert_parse_error {
+    ($t:ty, [$($arg:expr),+ $(,)?], $kind:expr $(,)?) => {
+        assert_eq!($crate::test::parse_err::<$t, _>([$($arg),+]).kind(), $kind)
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arg::{Flag, Positional};
+    use crate::command::Command;
+    use crate::error::ErrorKind;
+    use crate::output::Context;
+
+    #[derive(Debug, PartialEq)]
+    struct Greet {
+        loud: bool,
+        name: String,
+    }
+
+    impl FromCli for Greet {
+        fn from_cli(cli: &mut Cli) -> Result<Self, Error> {
+            let app = Greet {
+                loud: cli.check_flag(Flag::new("loud"))?,
+                name: cli.require_positional(Positional::new("name"))?,
+            };
+            cli.is_empty()?;
+            Ok(app)
+        }
+    }
+
+    #[test]
+    fn harness_macros() {
+        assert_parses!(
+            Greet,
+            ["greet", "--loud", "world"],
+            Greet {
+                loud: true,
+                name: String::from("world"),
+            }
+        );
+        assert_parse_error!(Greet, ["greet"], ErrorKind::MissingPositional);
+        assert_parse_error!(Greet, ["greet", "world", "--lod"], ErrorKind::UnexpectedArg);
+
+        let err = parse_with::<Greet, _>(Cli::new().threshold(2), ["greet", "world", "--lod"]);
+        assert_eq!(err.unwrap_err().kind(), ErrorKind::SuggestArg);
+    }
+
+    impl Command<Context> for Greet {
+        type Status = u8;
+
+        fn exec(&self, context: &Context) -> Self::Status {
+            let greeting = format!("hello, {}", self.name);
+            match self.loud {
+                true => context.stdout().println(greeting.to_uppercase()).unwrap(),
+                false => context.stdout().println(greeting).unwrap(),
+            }
+            0
+        }
+    }
+
+    impl Runner<Context> for Greet {}
+
+    #[test]
+    fn run_commands() {
+        let context = Context::captured();
+        assert_eq!(run::<_, Greet, _>(["greet", "--loud", "world"], &context).unwrap(), 0);
+        assert_eq!(context.take_stdout(), "HELLO, WORLD\n");
+
+        let err = run::<_, Greet, _>(["greet"], &context).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MissingPositional);
+        assert_eq!(context.take_stdout(), "");
+    }
+
+    #[test]
+    #[should_panic = "expected an error but parsed"]
+    fn parse_err_succeeds() {
+        parse_err::<Greet, _>(["greet", "world"]);
+    }
+}
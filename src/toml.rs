@@ -0,0 +1,298 @@
+//! A minimal reader for the subset of TOML used by fixture and configuration
+//! files.
+//!
+//! Supported syntax: comments, `[table]` and `[[array-of-tables]]` headers, and
+//! `key = value` pairs where a value is a basic string, integer, float,
+//! boolean, or single-line array of those.
+
+use std::fmt::Display;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Array(Vec<Value>),
+    Table(Table),
+    Tables(Vec<Table>),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(s) => Some(s.as_ref()),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Self::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    pub fn as_tables(&self) -> Option<&Vec<Table>> {
+        match self {
+            Self::Tables(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Writes the value back as its plain string form (strings are left unquoted).
+    pub fn to_plain_string(&self) -> String {
+        match self {
+            Self::Str(s) => s.to_string(),
+            Self::Int(i) => i.to_string(),
+            Self::Float(f) => f.to_string(),
+            Self::Bool(b) => b.to_string(),
+            Self::Array(a) => a
+                .iter()
+                .map(|v| v.to_plain_string())
+                .collect::<Vec<String>>()
+                .join(","),
+            Self::Table(_) | Self::Tables(_) => String::new(),
+        }
+    }
+}
+
+/// An ordered collection of key-value pairs.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Table {
+    entries: Vec<(String, Value)>,
+}
+
+impl Table {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Iterates over the key-value pairs in the order they were written.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.entries.iter().map(|(k, v)| (k.as_ref(), v))
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.entries
+            .iter_mut()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    fn insert(&mut self, key: String, value: Value) {
+        match self.get_mut(&key) {
+            Some(v) => *v = value,
+            None => self.entries.push((key, value)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    line: usize,
+    reason: String,
+}
+
+impl ParseError {
+    pub(crate) fn new<T: AsRef<str>>(line: usize, reason: T) -> Self {
+        Self {
+            line,
+            reason: reason.as_ref().to_string(),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// Identifies which table the following key-value pairs are written into.
+enum Header {
+    Root,
+    Table(String),
+    Tables(String),
+}
+
+/// Reads the `text` into its root table.
+pub fn parse(text: &str) -> Result<Table, ParseError> {
+    let mut root = Table::default();
+    let mut header = Header::Root;
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("[[").and_then(|l| l.strip_suffix("]]")) {
+            let name = name.trim().to_string();
+            match root.get_mut(&name) {
+                Some(Value::Tables(t)) => t.push(Table::default()),
+                Some(_) => return Err(ParseError::new(line_no, format!("key '{}' is already defined", name))),
+                None => root.insert(name.clone(), Value::Tables(vec![Table::default()])),
+            }
+            header = Header::Tables(name);
+        } else if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let name = name.trim().to_string();
+            if root.get(&name).is_some() {
+                return Err(ParseError::new(line_no, format!("table '{}' is already defined", name)));
+            }
+            root.insert(name.clone(), Value::Table(Table::default()));
+            header = Header::Table(name);
+        } else if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim().trim_matches('"').to_string();
+            let value = parse_value(value.trim()).map_err(|e| ParseError::new(line_no, e))?;
+            let table = match &header {
+                Header::Root => &mut root,
+                Header::Table(name) => match root.get_mut(name) {
+                    Some(Value::Table(t)) => t,
+                    _ => unreachable!(),
+                },
+                Header::Tables(name) => match root.get_mut(name) {
+                    Some(Value::Tables(t)) => t.last_mut().unwrap(),
+                    _ => unreachable!(),
+                },
+            };
+            table.insert(key, value);
+        } else {
+            return Err(ParseError::new(line_no, format!("expected a key-value pair but found '{}'", line)));
+        }
+    }
+    Ok(root)
+}
+
+/// Removes a trailing comment from the line while respecting quoted strings.
+fn strip_comment(line: &str) -> &str {
+    let mut in_str = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_str => escaped = true,
+            '"' => in_str = !in_str,
+            '#' if !in_str => return &line[..i],
+            _ => (),
+        }
+    }
+    line
+}
+
+fn parse_value(s: &str) -> Result<Value, String> {
+    let (value, rest) = parse_next(s)?;
+    match rest.trim().is_empty() {
+        true => Ok(value),
+        false => Err(format!("unexpected trailing characters '{}'", rest.trim())),
+    }
+}
+
+/// Parses a single value from the beginning of `s`, returning the remaining text.
+fn parse_next(s: &str) -> Result<(Value, &str), String> {
+    let s = s.trim_start();
+    if let Some(rest) = s.strip_prefix('"') {
+        let mut result = String::new();
+        let mut chars = rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => return Ok((Value::Str(result), &rest[i + 1..])),
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => result.push('\n'),
+                    Some((_, 't')) => result.push('\t'),
+                    Some((_, '"')) => result.push('"'),
+                    Some((_, '\\')) => result.push('\\'),
+                    Some((_, e)) => return Err(format!("unknown escape sequence '\\{}'", e)),
+                    None => break,
+                },
+                _ => result.push(c),
+            }
+        }
+        Err(String::from("unterminated string"))
+    } else if let Some(mut rest) = s.strip_prefix('[') {
+        let mut items = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(r) = rest.strip_prefix(']') {
+                return Ok((Value::Array(items), r));
+            }
+            let (item, r) = parse_next(rest)?;
+            items.push(item);
+            rest = r.trim_start();
+            if let Some(r) = rest.strip_prefix(',') {
+                rest = r;
+            } else if !rest.starts_with(']') {
+                return Err(String::from("expected ',' or ']' in array"));
+            }
+        }
+    } else {
+        let end = s.find([',', ']']).unwrap_or(s.len());
+        let (word, rest) = s.split_at(end);
+        let word = word.trim();
+        let value = match word {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => {
+                let digits = word.replace('_', "");
+                if let Ok(i) = digits.parse::<i64>() {
+                    Value::Int(i)
+                } else if let Ok(f) = digits.parse::<f64>() {
+                    Value::Float(f)
+                } else {
+                    return Err(format!("invalid value '{}'", word));
+                }
+            }
+        };
+        Ok((value, rest))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn read_tables() {
+        let doc = parse(
+            r#"
+# leading comment
+title = "demo" # trailing comment
+[cli]
+rate = 10
+verbose = true
+files = ["a.txt", "b # c.txt"]
+
+[[case]]
+name = "first"
+[[case]]
+name = "second"
+"#,
+        )
+        .unwrap();
+        assert_eq!(doc.get("title"), Some(&Value::Str("demo".to_string())));
+        let cli = match doc.get("cli").unwrap() {
+            Value::Table(t) => t,
+            _ => panic!("expected a table"),
+        };
+        assert_eq!(cli.get("rate"), Some(&Value::Int(10)));
+        assert_eq!(cli.get("verbose"), Some(&Value::Bool(true)));
+        assert_eq!(
+            cli.get("files").unwrap().to_plain_string(),
+            "a.txt,b # c.txt"
+        );
+        let cases = doc.get("case").unwrap().as_tables().unwrap();
+        assert_eq!(cases.len(), 2);
+        assert_eq!(cases[1].get("name").unwrap().as_str(), Some("second"));
+    }
+
+    #[test]
+    fn reject_bad_values() {
+        assert!(parse("key = \"open").is_err());
+        assert!(parse("key = [1, 2").is_err());
+        assert!(parse("just words").is_err());
+    }
+}
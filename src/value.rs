@@ -0,0 +1,444 @@
+//! Typed values produced by the parser for common argument patterns.
+
+use crate::arg::Arg;
+use std::fmt::Display;
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+/// The number of times a flag was raised.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Default)]
+pub struct Count(usize);
+
+impl Count {
+    pub fn new(n: usize) -> Self {
+        Self(n)
+    }
+
+    pub fn get(&self) -> usize {
+        self.0
+    }
+
+    /// Checks if the flag was raised at least once.
+    pub fn is_raised(&self) -> bool {
+        self.0 > 0
+    }
+}
+
+impl From<Count> for usize {
+    fn from(c: Count) -> Self {
+        c.0
+    }
+}
+
+impl Display for Count {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Values read for options, kept in the order they were supplied on the command
+/// line.
+///
+/// Occurrences of different options are combined with [merge](Occurrences::merge)
+/// after mapping them into a common type, then fed to a builder one at a time
+/// with [fold_into](Occurrences::fold_into). Each value also keeps the index of
+/// the argument it was supplied with, so the interleaving of different options
+/// can be inspected directly.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Occurrences<T> {
+    items: Vec<(usize, T)>,
+}
+
+impl<T> Default for Occurrences<T> {
+    fn default() -> Self {
+        Self { items: Vec::new() }
+    }
+}
+
+impl<T> Occurrences<T> {
+    /// Creates occurrences from values paired with the index of their argument.
+    pub(crate) fn new(mut items: Vec<(usize, T)>) -> Self {
+        items.sort_by_key(|(i, _)| *i);
+        Self { items }
+    }
+
+    /// Transforms each value, keeping its position.
+    pub fn map<U, F: FnMut(T) -> U>(self, mut f: F) -> Occurrences<U> {
+        Occurrences {
+            items: self.items.into_iter().map(|(i, v)| (i, f(v))).collect(),
+        }
+    }
+
+    /// Combines the occurrences of another option, keeping command-line order.
+    pub fn merge(mut self, other: Self) -> Self {
+        self.items.extend(other.items);
+        self.items.sort_by_key(|(i, _)| *i);
+        self
+    }
+
+    /// Feeds each value to `builder` through `f` in command-line order.
+    pub fn fold_into<B, F: FnMut(B, T) -> B>(self, builder: B, mut f: F) -> B {
+        self.items.into_iter().fold(builder, |b, (_, v)| f(b, v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the values in command-line order.
+    pub fn into_vec(self) -> Vec<T> {
+        self.items.into_iter().map(|(_, v)| v).collect()
+    }
+
+    /// Iterates over the values in command-line order along with the index of
+    /// the argument each was supplied with, where the program name is at index 0.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.items.iter().map(|(i, v)| (*i, v))
+    }
+
+    /// Returns the values in command-line order paired with the index of the
+    /// argument each was supplied with.
+    pub fn into_indexed(self) -> Vec<(usize, T)> {
+        self.items
+    }
+}
+
+/// A value that may instead be `-`, the conventional name for standard input.
+///
+/// Parsing `-` produces [StdinOr::Stdin] and any other word is parsed as `T`:
+///
+/// ```
+/// use clif::value::StdinOr;
+/// use std::path::PathBuf;
+///
+/// assert_eq!("-".parse::<StdinOr<PathBuf>>().unwrap(), StdinOr::Stdin);
+/// assert_eq!(
+///     "a.txt".parse::<StdinOr<PathBuf>>().unwrap(),
+///     StdinOr::Value(PathBuf::from("a.txt"))
+/// );
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub enum StdinOr<T> {
+    Stdin,
+    Value(T),
+}
+
+impl<T> StdinOr<T> {
+    pub fn is_stdin(&self) -> bool {
+        matches!(self, Self::Stdin)
+    }
+
+    /// References the value, or returns `None` for standard input.
+    pub fn as_value(&self) -> Option<&T> {
+        match self {
+            Self::Stdin => None,
+            Self::Value(v) => Some(v),
+        }
+    }
+}
+
+impl<T: AsRef<Path>> StdinOr<T> {
+    /// Opens standard input or the file at the path.
+    pub fn open(&self) -> io::Result<Box<dyn Read>> {
+        match self {
+            Self::Stdin => Ok(Box::new(io::stdin())),
+            Self::Value(p) => Ok(Box::new(std::fs::File::open(p)?)),
+        }
+    }
+
+    /// Reads all of standard input or the file at the path.
+    pub fn read_to_string(&self) -> io::Result<String> {
+        let mut text = String::new();
+        self.open()?.read_to_string(&mut text)?;
+        Ok(text)
+    }
+}
+
+impl<T: FromStr> FromStr for StdinOr<T> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "-" => Ok(Self::Stdin),
+            _ => Ok(Self::Value(T::from_str(s)?)),
+        }
+    }
+}
+
+impl<T: Display> Display for StdinOr<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stdin => write!(f, "-"),
+            Self::Value(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// Converts the text supplied for an argument into a value.
+///
+/// Every type implementing [FromStr] with an error type that implements
+/// [std::error::Error] is parsed with it. Implement this trait directly for
+/// parsing that depends on the argument it is read for, or to report the text
+/// as invalid rather than as failing to convert.
+///
+/// ```
+/// use clif::arg::Arg;
+/// use clif::value::{ParseError, ValueParse};
+///
+/// struct Port(u16);
+///
+/// impl ValueParse for Port {
+///     fn parse(arg: &Arg, text: &str) -> Result<Self, ParseError> {
+///         match text.parse::<u16>().map_err(ParseError::new)? {
+///             0 => Err(ParseError::invalid(format!("{} cannot be 0", arg.get_name()))),
+///             n => Ok(Port(n)),
+///         }
+///     }
+/// }
+/// ```
+pub trait ValueParse: Sized {
+    /// Parses `text`, which was supplied for `arg`.
+    fn parse(arg: &Arg, text: &str) -> Result<Self, ParseError>;
+}
+
+impl<T: FromStr> ValueParse for T
+where
+    <T as FromStr>::Err: 'static + std::error::Error,
+{
+    fn parse(_: &Arg, text: &str) -> Result<Self, ParseError> {
+        text.parse::<T>().map_err(ParseError::new)
+    }
+}
+
+/// The reason the text supplied for an argument was not parsed by [ValueParse].
+#[derive(Debug)]
+pub struct ParseError(Reason);
+
+#[derive(Debug)]
+pub(crate) enum Reason {
+    /// The text could not be converted, reported as a `BadType` error.
+    Failed(Box<dyn std::error::Error>),
+    /// The text was understood but is not allowed, reported as an
+    /// `InvalidValue` error.
+    Invalid(String),
+}
+
+impl ParseError {
+    /// Creates an error for text that could not be converted due to `err`.
+    pub fn new<E: std::error::Error + 'static>(err: E) -> Self {
+        Self(Reason::Failed(Box::new(err)))
+    }
+
+    /// Creates an error for text that is not an allowed value, explained by
+    /// `reason`.
+    pub fn invalid<T: AsRef<str>>(reason: T) -> Self {
+        Self(Reason::Invalid(reason.as_ref().to_string()))
+    }
+
+    pub(crate) fn into_reason(self) -> Reason {
+        self.0
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Reason::Failed(err) => write!(f, "{}", err),
+            Reason::Invalid(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+/// The characters separating the integer and fractional parts of a number and
+/// grouping its thousands.
+///
+/// The default format is `1,234.56`. Arguments marked as
+/// [localized](crate::arg::Optional::localized) are rewritten from the format
+/// set on the [Cli](crate::Cli) into that form before they are parsed.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct NumberFormat {
+    decimal: char,
+    group: char,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::new('.', ',')
+    }
+}
+
+impl NumberFormat {
+    pub fn new(decimal: char, group: char) -> Self {
+        Self { decimal, group }
+    }
+
+    /// Selects the format of the language named by the `LC_ALL`, `LC_NUMERIC`,
+    /// or `LANG` environment variable, in that order of precedence.
+    ///
+    /// The default format is used when none of the variables name a language.
+    pub fn detect() -> Self {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|v| std::env::var(v).ok())
+            .find(|v| v.is_empty() == false)
+            .map(|v| Self::for_locale(&v))
+            .unwrap_or_default()
+    }
+
+    /// Selects the format for a locale name such as `de_DE.UTF-8`.
+    pub fn for_locale(locale: &str) -> Self {
+        let lang = locale
+            .split(|c| c == '_' || c == '-' || c == '.' || c == '@')
+            .next()
+            .unwrap_or_default();
+        match lang {
+            "de" | "es" | "it" | "nl" | "pt" | "da" | "tr" | "id" | "el" => Self::new(',', '.'),
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "uk" | "hu" => {
+                Self::new(',', ' ')
+            }
+            _ => Self::default(),
+        }
+    }
+
+    pub fn get_decimal(&self) -> char {
+        self.decimal
+    }
+
+    pub fn get_group(&self) -> char {
+        self.group
+    }
+
+    /// Rewrites a number written in this format as `1234.56`.
+    ///
+    /// Group separators are removed, including non-breaking spaces when the
+    /// format groups with spaces. Separators must split the integer part into
+    /// groups of three digits after the first, and may not follow the decimal
+    /// mark, so `1.5` is rejected rather than read as `15` when the group
+    /// separator is `.`.
+    pub fn normalize(&self, word: &str) -> Result<String, ParseError> {
+        let is_group = |c: char| {
+            c == self.group || (self.group == ' ' && (c == '\u{a0}' || c == '\u{202f}'))
+        };
+        let (int, frac) = match word.split_once(self.decimal) {
+            Some((int, frac)) => (int, Some(frac)),
+            None => (word, None),
+        };
+        let misplaced = || {
+            ParseError::invalid(format!(
+                "digits must be grouped by three, as in 1{}234{}5",
+                self.group, self.decimal
+            ))
+        };
+        if frac.map(|f| f.contains(|c| is_group(c) || c == self.decimal)) == Some(true) {
+            return Err(misplaced());
+        }
+        let digits = int.trim_start_matches(|c| c == '-' || c == '+');
+        if digits.contains(is_group) == true {
+            let mut groups = digits.split(is_group).map(|g| g.chars().count());
+            let first = groups.next().unwrap_or_default();
+            if first == 0 || first > 3 || groups.any(|n| n != 3) == true {
+                return Err(misplaced());
+            }
+        }
+        Ok(word
+            .chars()
+            .filter(|c| is_group(*c) == false)
+            .map(|c| match c == self.decimal {
+                true => '.',
+                false => c,
+            })
+            .collect())
+    }
+}
+
+/// A standard logging level derived from repeated flags such as `-vvv`.
+///
+/// Levels are ordered, so `level >= Verbosity::Debug` checks for debug output
+/// or anything more detailed.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Default)]
+pub enum Verbosity {
+    Quiet,
+    #[default]
+    Normal,
+    Verbose,
+    Debug,
+    Trace,
+}
+
+impl Verbosity {
+    /// Combines the counts of a verbose flag and a quiet flag into one level.
+    ///
+    /// Each quiet occurrence cancels a verbose occurrence. The level saturates at
+    /// [Verbosity::Quiet] and [Verbosity::Trace].
+    pub fn new(verbose: Count, quiet: Count) -> Self {
+        match verbose.get().checked_sub(quiet.get()) {
+            Some(0) => Self::Normal,
+            Some(1) => Self::Verbose,
+            Some(2) => Self::Debug,
+            Some(_) => Self::Trace,
+            None => Self::Quiet,
+        }
+    }
+}
+
+impl From<Count> for Verbosity {
+    fn from(verbose: Count) -> Self {
+        Self::new(verbose, Count::default())
+    }
+}
+
+impl Display for Verbosity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Quiet => "quiet",
+            Self::Normal => "normal",
+            Self::Verbose => "verbose",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn number_formats() {
+        let de = NumberFormat::for_locale("de_DE.UTF-8");
+        assert_eq!(de, NumberFormat::new(',', '.'));
+        assert_eq!(de.normalize("1.234,56").unwrap(), "1234.56");
+        assert_eq!(de.normalize("-12.345.678").unwrap(), "-12345678");
+        assert_eq!(
+            NumberFormat::for_locale("fr_FR").normalize("1\u{a0}234,5").unwrap(),
+            "1234.5"
+        );
+        assert_eq!(NumberFormat::for_locale("C").normalize("-1,234.5").unwrap(), "-1234.5");
+        // separators out of place are rejected instead of dropped
+        assert!(de.normalize("1.5").is_err());
+        assert!(de.normalize("1234.567").is_err());
+        assert!(de.normalize(".234").is_err());
+        assert!(de.normalize("1,234.5").is_err());
+        assert!(de.normalize("1,5,0").is_err());
+        assert!(NumberFormat::default().normalize("1,5").is_err());
+        assert_eq!(NumberFormat::for_locale("en_US"), NumberFormat::default());
+    }
+
+    #[test]
+    fn verbosity_from_counts() {
+        assert_eq!(Verbosity::from(Count::new(0)), Verbosity::Normal);
+        assert_eq!(Verbosity::from(Count::new(2)), Verbosity::Debug);
+        assert_eq!(Verbosity::from(Count::new(9)), Verbosity::Trace);
+        assert_eq!(Verbosity::new(Count::new(1), Count::new(2)), Verbosity::Quiet);
+        assert_eq!(Verbosity::new(Count::new(2), Count::new(1)), Verbosity::Verbose);
+        assert!(Verbosity::Trace > Verbosity::Debug);
+    }
+}
@@ -0,0 +1,103 @@
+//! Discovery of the directory a command must run within.
+
+use crate::error::{Error, ErrorContext, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// Requires a command to run inside a project, whose root is the nearest
+/// directory containing a marker file.
+///
+/// A command declares its workspace with
+/// [Cli::check_workspace](crate::Cli::check_workspace) while it is parsed, so
+/// the root is discovered for the subcommand that was actually matched and a
+/// missing project is reported with the same error across every command.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Workspace {
+    marker: String,
+    enter: bool,
+}
+
+impl Workspace {
+    /// Requires the file or directory `name` to exist in the current directory or
+    /// one of its parents.
+    ///
+    /// The current directory is left unchanged unless the workspace is
+    /// [entered](Workspace::enter).
+    pub fn marker<T: AsRef<str>>(name: T) -> Self {
+        Self {
+            marker: name.as_ref().to_string(),
+            enter: false,
+        }
+    }
+
+    /// Sets whether the current directory is changed to the discovered root
+    /// after parsing and before the command executes.
+    ///
+    /// Relative paths read from the command line name files in the directory
+    /// the program was started in, so a command entering its workspace must
+    /// resolve them against the original directory itself.
+    pub fn enter(mut self, enabled: bool) -> Self {
+        self.enter = enabled;
+        self
+    }
+
+    pub fn get_marker(&self) -> &str {
+        self.marker.as_ref()
+    }
+
+    pub fn is_entered(&self) -> bool {
+        self.enter
+    }
+
+    /// Walks up from `start` to find the nearest directory containing the marker.
+    pub fn discover(&self, start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .find(|dir| dir.join(&self.marker).exists())
+            .map(|dir| dir.to_path_buf())
+    }
+
+    /// Discovers the root from the current directory.
+    ///
+    /// Errors with the context of a missing workspace if there is none.
+    pub(crate) fn resolve(&self) -> Result<PathBuf, ErrorContext> {
+        let missing = |dir: &Path| {
+            ErrorContext::MissingWorkspace(self.marker.clone(), dir.display().to_string())
+        };
+        let cwd = std::env::current_dir().map_err(|_| missing(Path::new(".")))?;
+        self.discover(&cwd).ok_or_else(|| missing(&cwd))
+    }
+
+    /// Changes the current directory to the discovered `root`.
+    pub(crate) fn enter_root(&self, root: &Path) -> Result<(), Error> {
+        std::env::set_current_dir(root).map_err(|_| {
+            Error::new(
+                None,
+                ErrorKind::MissingWorkspace,
+                ErrorContext::MissingWorkspace(self.marker.clone(), root.display().to_string()),
+                false,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn discover_root() {
+        let root = std::env::temp_dir().join(format!("clif-workspace-{}", std::process::id()));
+        let nested = root.join("src").join("rtl");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join("Orbit.toml"), "").unwrap();
+
+        let ws = Workspace::marker("Orbit.toml");
+        assert_eq!(ws.discover(&nested), Some(root.clone()));
+        assert_eq!(ws.discover(&root), Some(root.clone()));
+        assert_eq!(Workspace::marker("clif-missing.toml").discover(&nested), None);
+
+        assert_eq!(Workspace::marker("Orbit.toml").is_entered(), false);
+        assert_eq!(Workspace::marker("Orbit.toml").enter(true).is_entered(), true);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}